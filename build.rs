@@ -0,0 +1,23 @@
+/*
+ * Build script, refreshes the C header of the `capi` feature on request
+ *
+ * The committed 'include/ltspice.h' is used as is by default: cbindgen runs `cargo metadata`,
+ * which fails offline and in vendored builds. After changing 'src/capi.rs', the header is
+ * regenerated with `LTSPICE_UPDATE_HEADER=1 cargo build --features capi`.
+ */
+
+fn main() {
+    #[cfg(feature = "capi")]
+    if std::env::var_os("LTSPICE_UPDATE_HEADER").is_some() {
+        let directory = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        cbindgen::Builder::new()
+            .with_crate(&directory)
+            .with_config(cbindgen::Config::from_file(format!("{}/cbindgen.toml", directory)).unwrap())
+            .generate()
+            .expect("Unable to generate the C header.")
+            .write_to_file(format!("{}/include/ltspice.h", directory));
+    }
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=LTSPICE_UPDATE_HEADER");
+}
@@ -0,0 +1,477 @@
+/*
+ * This crate contains the decoder of the binary section of raw files
+ *
+ * It only depends on `core` and `alloc`, so the samples of a raw file can be decoded
+ * on test instruments and firmware without the standard library. The header is not parsed:
+ * the caller provides the layout of a point, and a rule to detect the start of a new step.
+ */
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/* #### Enums #### */
+
+/// The encoding of a single sample.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DataType {
+    Float32,
+    Float64,
+    Complex128,
+}
+
+// Whether `decode_points` decodes the samples of the variables, and their statistics.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Variables {
+    Skip,
+    Decode { statistics: bool },
+}
+
+/* #### Structs #### */
+
+/// The layout of a point: the x sample followed by one sample per variable, in header order.
+/// Every variable has its own data type, as some simulators mix precisions within a point.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Layout {
+    pub x: DataType,
+    /// The data type of every variable, excluding the x axis.
+    pub y: Vec<DataType>,
+}
+
+/// The decoded samples, split in steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded<T> {
+    /// The x axis of every step.
+    pub x: Vec<Vec<T>>,
+    /// The samples of every variable (in header order) and step.
+    pub y: Vec<Vec<Vec<T>>>,
+    /// Number of decoded points, over every step.
+    pub points: usize,
+    /// The statistics of every variable (in header order) and step, if requested.
+    pub statistics: Vec<Vec<Statistics>>,
+}
+
+/// The decoded samples of every variable stored back to back in a single vector (a slab),
+/// instead of one vector per step, see `decode_slabs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slabs<T> {
+    /// The x axis of every step, back to back.
+    pub x: Vec<T>,
+    /// The samples of every variable (in header order), every step back to back.
+    pub y: Vec<Vec<T>>,
+    /// The number of points of every step.
+    pub steps: Vec<usize>,
+    /// The statistics of every variable (in header order) and step, if requested.
+    pub statistics: Vec<Vec<Statistics>>,
+}
+
+/// Statistics of the real parts of the samples of a variable in a step, accumulated while decoding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub sum_squares: f64,
+    pub count: usize,
+}
+
+/* #### Traits #### */
+
+/// A decoded sample, built from its real and imaginary parts.
+pub trait Sample: Copy {
+    fn from_parts(real: f64, imaginary: f64) -> Self;
+}
+
+/* #### Constants #### */
+
+/// Number of points decoded between two calls of the `cancelled` callback of `decode`.
+pub const CHECK_POINTS: usize = 4096;
+
+/* #### Functions #### */
+
+/// Reads a single sample from the start of `bytes`, in little-endian byte order, as raw files are written.
+/// Panics if `bytes` is shorter than the size of the data type.
+pub fn read_sample<T: Sample>(data_type: DataType, bytes: &[u8]) -> T {
+    return match data_type {
+        DataType::Float32 => T::from_parts(f32::from_le_bytes(bytes[0..4].try_into().unwrap()) as f64, 0.0),
+        DataType::Float64 => T::from_parts(f64::from_le_bytes(bytes[0..8].try_into().unwrap()), 0.0),
+        DataType::Complex128 => T::from_parts(
+            f64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            f64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        ),
+    };
+}
+
+/// Decodes the complete points of `bytes`, a trailing partial point is ignored.
+/// `is_new_step(first, previous, current)` is called with x samples of the current step,
+/// and starts a new step when it returns true. `cancelled` is called every `CHECK_POINTS`
+/// points, the decoding stops and returns None once it returns true.
+pub fn decode<T, N, C>(bytes: &[u8], layout: &Layout, is_new_step: N, cancelled: C) -> Option<Decoded<T>>
+where
+    T: Sample,
+    N: FnMut(&T, &T, &T) -> bool,
+    C: FnMut() -> bool,
+{
+    return decode_points(bytes, layout, Variables::Decode { statistics: false }, &[], is_new_step, cancelled);
+}
+
+/// Decodes the complete points of `bytes` like `decode`, allocating every step with the capacity
+/// given in `steps` (e.g. the step lengths of a previous `decode_x`), so the vectors of a sweep
+/// are allocated once instead of growing. Once a step ends with another length than given,
+/// `steps` is assumed stale (e.g. estimated from an outdated log): that step is shrunk to fit,
+/// and the following steps are allocated with its length instead.
+/// The `statistics` of the result are accumulated in the same pass if `statistics` is true.
+pub fn decode_sized<T, N, C>(
+    bytes: &[u8],
+    layout: &Layout,
+    steps: &[usize],
+    statistics: bool,
+    is_new_step: N,
+    cancelled: C,
+) -> Option<Decoded<T>>
+where
+    T: Sample,
+    N: FnMut(&T, &T, &T) -> bool,
+    C: FnMut() -> bool,
+{
+    return decode_points(bytes, layout, Variables::Decode { statistics }, steps, is_new_step, cancelled);
+}
+
+/// Decodes only the x axis of the complete points of `bytes`, skipping the samples of the
+/// variables, see `decode`. The `y` field of the result is empty.
+pub fn decode_x<T, N, C>(bytes: &[u8], layout: &Layout, is_new_step: N, cancelled: C) -> Option<Decoded<T>>
+where
+    T: Sample,
+    N: FnMut(&T, &T, &T) -> bool,
+    C: FnMut() -> bool,
+{
+    return decode_points(bytes, layout, Variables::Skip, &[], is_new_step, cancelled);
+}
+
+/// Decodes the samples of the variable `index` (in header order) of the complete points of
+/// `bytes`, split in steps of the lengths given in `steps` (e.g. the step lengths of a previous
+/// `decode_x`), so a file can be decoded one variable at a time. Points beyond `steps` are
+/// ignored. The `x` field of the result is empty, its `y` and `statistics` fields hold the
+/// variable only. Returns None if `index` is out of the layout or once `cancelled` returns true.
+pub fn decode_variable<T, C>(
+    bytes: &[u8],
+    layout: &Layout,
+    index: usize,
+    steps: &[usize],
+    statistics: bool,
+    mut cancelled: C,
+) -> Option<Decoded<T>>
+where
+    T: Sample,
+    C: FnMut() -> bool,
+{
+    let data_type = *layout.y.get(index)?;
+    let offset = layout.offsets()[index];
+    let mut points = bytes.chunks_exact(layout.point_size());
+    let mut decoded = Decoded {
+        x: Vec::new(),
+        y: vec![Vec::with_capacity(steps.len())],
+        points: 0,
+        statistics: match statistics {
+            true => vec![Vec::with_capacity(steps.len())],
+            false => Vec::new(),
+        },
+    };
+    for length in steps.iter() {
+        let mut samples = Vec::with_capacity(*length);
+        let mut step_statistics = Statistics::default();
+        for point in points.by_ref().take(*length) {
+            if decoded.points % CHECK_POINTS == 0 && cancelled() {
+                return None;
+            }
+            decoded.points += 1;
+
+            let (real, imaginary): (f64, f64) = read_sample(data_type, &point[offset..]);
+            if statistics {
+                step_statistics.add(real);
+            }
+            samples.push(T::from_parts(real, imaginary));
+        }
+        decoded.y[0].push(samples);
+        if let Some(variable) = decoded.statistics.first_mut() {
+            variable.push(step_statistics);
+        }
+    }
+    return Some(decoded);
+}
+
+/// Calls `visit(step, samples)` for every complete point of `bytes`, split in steps of the
+/// lengths given in `steps` (e.g. the step lengths of a previous `decode_x`), with the samples of
+/// the variables in header order. Every variable can then be streamed elsewhere (e.g. to a file)
+/// in a single pass, without decoding the file in memory. Points beyond `steps` are ignored.
+/// Returns the number of visited points, or None once `cancelled` returns true.
+pub fn visit_points<T, C, V>(bytes: &[u8], layout: &Layout, steps: &[usize], mut cancelled: C, mut visit: V) -> Option<usize>
+where
+    T: Sample,
+    C: FnMut() -> bool,
+    V: FnMut(usize, &[T]),
+{
+    let offsets = layout.offsets();
+    let mut points = bytes.chunks_exact(layout.point_size());
+    let mut samples: Vec<T> = Vec::with_capacity(layout.y.len());
+    let mut count = 0;
+    for (step, length) in steps.iter().enumerate() {
+        for point in points.by_ref().take(*length) {
+            if count % CHECK_POINTS == 0 && cancelled() {
+                return None;
+            }
+            count += 1;
+
+            samples.clear();
+            for (data_type, offset) in layout.y.iter().zip(offsets.iter()) {
+                samples.push(read_sample(*data_type, &point[*offset..]));
+            }
+            visit(step, &samples);
+        }
+    }
+    return Some(count);
+}
+
+/// Decodes the complete points of `bytes` like `decode_sized`, into a single vector per variable
+/// (and for the x axis) holding every step back to back. Every vector is allocated once with the
+/// number of points of `bytes`, so a sweep of many steps makes one allocation per variable
+/// instead of one per step and variable. The steps are split by `is_new_step`, see `decode`.
+pub fn decode_slabs<T, N, C>(
+    bytes: &[u8],
+    layout: &Layout,
+    statistics: bool,
+    mut is_new_step: N,
+    mut cancelled: C,
+) -> Option<Slabs<T>>
+where
+    T: Sample,
+    N: FnMut(&T, &T, &T) -> bool,
+    C: FnMut() -> bool,
+{
+    let offsets = layout.offsets();
+    let points = bytes.len() / layout.point_size();
+    let mut slabs = Slabs {
+        x: Vec::with_capacity(points),
+        y: (0..layout.y.len()).map(|_| Vec::with_capacity(points)).collect(),
+        steps: vec![0],
+        statistics: match statistics {
+            true => vec![vec![Statistics::default()]; layout.y.len()],
+            false => Vec::new(),
+        },
+    };
+    // Index of the first point of the current step
+    let mut start = 0;
+    for (index, point) in bytes.chunks_exact(layout.point_size()).enumerate() {
+        if index % CHECK_POINTS == 0 && cancelled() {
+            return None;
+        }
+
+        let x: T = read_sample(layout.x, point);
+        if index > start && is_new_step(&slabs.x[start], &slabs.x[index - 1], &x) {
+            start = index;
+            slabs.steps.push(0);
+            for steps in slabs.statistics.iter_mut() {
+                steps.push(Statistics::default());
+            }
+        }
+        slabs.x.push(x);
+        *slabs.steps.last_mut().unwrap() += 1;
+
+        for (variable, (data_type, offset)) in layout.y.iter().zip(offsets.iter()).enumerate() {
+            let (real, imaginary): (f64, f64) = read_sample(*data_type, &point[*offset..]);
+            if let Some(steps) = slabs.statistics.get_mut(variable) {
+                steps.last_mut().unwrap().add(real);
+            }
+            slabs.y[variable].push(T::from_parts(real, imaginary));
+        }
+    }
+    return Some(slabs);
+}
+
+// Decodes the points of `bytes`, with the samples of the variables (and their statistics) as requested.
+// Step vectors are allocated with the capacity of `capacities`, if known.
+fn decode_points<T, N, C>(
+    bytes: &[u8],
+    layout: &Layout,
+    variables: Variables,
+    capacities: &[usize],
+    mut is_new_step: N,
+    mut cancelled: C,
+) -> Option<Decoded<T>>
+where
+    T: Sample,
+    N: FnMut(&T, &T, &T) -> bool,
+    C: FnMut() -> bool,
+{
+    let offsets = layout.offsets();
+    let capacity = |step: usize| capacities.get(step).copied().unwrap_or(0);
+    // Length of the first step whose capacity was wrong, used for the following steps
+    let mut actual: Option<usize> = None;
+    let mut first = Vec::with_capacity(capacities.len().max(1));
+    first.push(Vec::with_capacity(capacity(0)));
+    let mut decoded = Decoded {
+        x: first,
+        y: match variables {
+            Variables::Decode { .. } => (0..layout.y.len())
+                .map(|_| {
+                    let mut steps = Vec::with_capacity(capacities.len().max(1));
+                    steps.push(Vec::with_capacity(capacity(0)));
+                    steps
+                })
+                .collect(),
+            Variables::Skip => Vec::new(),
+        },
+        points: 0,
+        statistics: match variables {
+            Variables::Decode { statistics: true } => vec![vec![Statistics::default()]; layout.y.len()],
+            _ => Vec::new(),
+        },
+    };
+    for point in bytes.chunks_exact(layout.point_size()) {
+        if decoded.points % CHECK_POINTS == 0 && cancelled() {
+            return None;
+        }
+        decoded.points += 1;
+
+        let x: T = read_sample(layout.x, point);
+
+        // When the sweep restarts, we know we have a new step
+        let index = decoded.x.len() - 1;
+        let step = decoded.x.last_mut().unwrap();
+        let new_step = match (step.first(), step.last()) {
+            (Some(first), Some(previous)) => is_new_step(first, previous, &x),
+            _ => false,
+        };
+        if new_step {
+            let length = step.len();
+            if !capacities.is_empty() && actual.is_none() && length != capacity(index) {
+                actual = Some(length);
+                step.shrink_to_fit();
+                for steps in decoded.y.iter_mut() {
+                    steps.last_mut().unwrap().shrink_to_fit();
+                }
+            }
+            let capacity = actual.unwrap_or_else(|| capacity(index + 1));
+            decoded.x.push(Vec::with_capacity(capacity));
+            for steps in decoded.y.iter_mut() {
+                steps.push(Vec::with_capacity(capacity));
+            }
+            for steps in decoded.statistics.iter_mut() {
+                steps.push(Statistics::default());
+            }
+        }
+        decoded.x.last_mut().unwrap().push(x);
+
+        // After the x sample, the samples of the variables follow in header order
+        match decoded.statistics.is_empty() {
+            true => {
+                for ((steps, data_type), offset) in decoded.y.iter_mut().zip(layout.y.iter()).zip(offsets.iter()) {
+                    steps.last_mut().unwrap().push(read_sample(*data_type, &point[*offset..]));
+                }
+            }
+            false => {
+                let variables = decoded.y.iter_mut().zip(decoded.statistics.iter_mut());
+                for ((steps, statistics), (data_type, offset)) in variables.zip(layout.y.iter().zip(offsets.iter())) {
+                    let (real, imaginary): (f64, f64) = read_sample(*data_type, &point[*offset..]);
+                    statistics.last_mut().unwrap().add(real);
+                    steps.last_mut().unwrap().push(T::from_parts(real, imaginary));
+                }
+            }
+        }
+    }
+
+    return Some(decoded);
+}
+
+/* #### Implementations #### */
+
+impl DataType {
+    /// Returns the size of a sample, in bytes.
+    pub fn size(&self) -> usize {
+        return match self {
+            DataType::Float32 => 4,
+            DataType::Float64 => 8,
+            DataType::Complex128 => 16,
+        };
+    }
+}
+
+impl Statistics {
+    /// Adds a sample, NaN samples are skipped.
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.sum_squares += value * value;
+        self.count += 1;
+    }
+
+    /// Returns the arithmetic mean of the samples (not weighted by the x axis), None without samples.
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        return Some(self.sum / self.count as f64);
+    }
+
+    /// Returns the mean of the squared samples (not weighted by the x axis), None without samples.
+    /// Its square root is the RMS.
+    pub fn mean_square(&self) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        return Some(self.sum_squares / self.count as f64);
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        return Statistics {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            sum_squares: 0.0,
+            count: 0,
+        };
+    }
+}
+
+impl Layout {
+    /// Returns the layout of a point whose variables all share the same data type.
+    pub fn uniform(x: DataType, y: DataType, variables: usize) -> Self {
+        return Layout { x, y: vec![y; variables] };
+    }
+
+    /// Returns the number of variables, excluding the x axis.
+    pub fn variables(&self) -> usize {
+        return self.y.len();
+    }
+
+    /// Returns the offset of every variable within a point, in bytes.
+    pub fn offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(self.y.len());
+        let mut offset = self.x.size();
+        for data_type in self.y.iter() {
+            offsets.push(offset);
+            offset += data_type.size();
+        }
+        return offsets;
+    }
+
+    /// Returns the size of a point, in bytes.
+    pub fn point_size(&self) -> usize {
+        return self.x.size() + self.y.iter().map(|data_type| data_type.size()).sum::<usize>();
+    }
+}
+
+impl Sample for (f64, f64) {
+    fn from_parts(real: f64, imaginary: f64) -> Self {
+        return (real, imaginary);
+    }
+}
@@ -0,0 +1,26 @@
+/*
+ * Fuzz target for the no_std sample decoder, with a layout taken from the first input bytes
+ */
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ltspice_decode::{decode, DataType, Layout};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, bytes)) = data.split_first() else {
+        return;
+    };
+    // The x type and number of variables, then the type of every variable
+    let types = [DataType::Float32, DataType::Float64, DataType::Complex128];
+    let variables = (selector / 3) as usize % 32;
+    if bytes.len() < variables {
+        return;
+    }
+    let (selectors, bytes) = bytes.split_at(variables);
+    let layout = Layout {
+        x: types[(selector % 3) as usize],
+        y: selectors.iter().map(|selector| types[(selector % 3) as usize]).collect(),
+    };
+    let _ = decode::<(f64, f64), _, _>(bytes, &layout, |first, _, current| current.0 == first.0, || false);
+});
@@ -0,0 +1,16 @@
+/*
+ * Fuzz target for `SteppedSimulation::parse_untrusted`, which must never panic
+ */
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ltspice::untrusted::Limits;
+use ltspice::options::LoadOptions;
+use ltspice::SteppedSimulation;
+
+fuzz_target!(|data: &[u8]| {
+    let limits = Limits::new().max_bytes(1024 * 1024);
+    let _ = SteppedSimulation::parse_untrusted_with_limits(data, &limits, LoadOptions::new());
+    let _ = SteppedSimulation::parse_untrusted_with_limits(data, &limits, LoadOptions::new().strict(true));
+});
@@ -0,0 +1,79 @@
+#ifndef LTSPICE_H
+#define LTSPICE_H
+
+/* This file is generated by cbindgen from src/capi.rs, do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct ltspice_simulation ltspice_simulation;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Opens and parses a '.raw' file.
+ * Returns NULL on failure, the handle must be released with `ltspice_free`.
+ */
+ltspice_simulation *ltspice_open(const char *path);
+
+/**
+ * Parses a '.raw' file from memory.
+ * Returns NULL on failure, the handle must be released with `ltspice_free`.
+ */
+ltspice_simulation *ltspice_open_bytes(const uint8_t *data, size_t length);
+
+/**
+ * Releases a handle returned by `ltspice_open` or `ltspice_open_bytes`.
+ */
+void ltspice_free(ltspice_simulation *simulation);
+
+/**
+ * Returns the number of variables (excluding the x axis), or 0 for an invalid handle.
+ */
+size_t ltspice_get_variable_count(const ltspice_simulation *simulation);
+
+/**
+ * Returns the number of steps, or 0 for an invalid handle.
+ */
+size_t ltspice_get_step_count(const ltspice_simulation *simulation);
+
+/**
+ * Copies the NUL-terminated name of the variable at `index` into `buffer`.
+ * Returns the length of the name (excluding the terminator), or -1 if the index is invalid
+ * or the buffer is too small.
+ */
+ptrdiff_t ltspice_get_variable_name(const ltspice_simulation *simulation,
+                                    size_t index,
+                                    char *buffer,
+                                    size_t capacity);
+
+/**
+ * Returns the number of points of a trace, or -1 if it does not exist.
+ * Use the name "x" for the abscissa.
+ */
+ptrdiff_t ltspice_get_trace_length(const ltspice_simulation *simulation,
+                                   const char *name,
+                                   uint16_t step);
+
+/**
+ * Copies up to `capacity` points of a trace into the caller-provided buffers.
+ * `imaginary` may be NULL for real-valued simulations.
+ * Returns the number of copied points, or -1 if the trace does not exist.
+ */
+ptrdiff_t ltspice_get_trace_data(const ltspice_simulation *simulation,
+                                 const char *name,
+                                 uint16_t step,
+                                 double *real,
+                                 double *imaginary,
+                                 size_t capacity);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* LTSPICE_H */
@@ -0,0 +1,78 @@
+/*
+ * This file contains the small-signal (AC) analysis helpers
+ */
+
+use std::f64::consts::PI;
+
+use crate::trace::TraceBuf;
+use crate::{SteppedSimulation, Unit, Value};
+
+/* #### Functions #### */
+
+/// Removes the 2π discontinuities from a sequence of phases, in radians.
+/// A jump between consecutive points larger than `tolerance` is considered a wrap and
+/// corrected by the multiple of 2π bringing it closest to zero. Tolerances below π are
+/// treated as π, since smaller jumps cannot be told apart from a real phase change.
+pub fn unwrap_phase(phases: &[f64], tolerance: f64) -> Vec<f64> {
+    let tolerance = tolerance.max(PI);
+    let mut result = Vec::with_capacity(phases.len());
+    let mut correction = 0.0;
+
+    for (index, phase) in phases.iter().enumerate() {
+        if index > 0 {
+            let jump = phase - phases[index - 1];
+            if jump.abs() > tolerance {
+                correction -= (jump / (2.0 * PI)).round() * 2.0 * PI;
+            }
+        }
+        result.push(phase + correction);
+    }
+
+    return result;
+}
+
+// LTSpice measures the current of a voltage source flowing into its positive terminal, so a
+// source delivering power has a negative current. Its sign is inverted to get the impedance
+// seen by the source instead of a negative one.
+pub(crate) fn current_sign(current: &str) -> f64 {
+    let name = current.to_ascii_uppercase();
+    return match name.starts_with("I(V") {
+        true => -1.0,
+        false => 1.0,
+    };
+}
+
+/// Computes the complex impedance Z = V / I of a node for one step, e.g. the input impedance
+/// with `impedance(&sim, "V(in)", "I(V1)", 0)`. The current of a voltage source is inverted
+/// so that a source driving a passive load sees a positive resistance.
+/// Returns None if either variable or the step does not exist.
+pub fn impedance(simulation: &SteppedSimulation, voltage: &str, current: &str, step: u16) -> Option<TraceBuf> {
+    let v = simulation.trace(voltage, step)?;
+    let i = simulation.trace(current, step)?;
+    let sign = current_sign(i.name());
+
+    let values = v
+        .values()
+        .iter()
+        .zip(i.values().iter())
+        .map(|(v, i)| *v / (*i * sign))
+        .collect::<Vec<Value>>();
+    let name = format!("Z({},{})", v.name(), i.name());
+    return Some(v.with_data(name, v.x()[..values.len()].to_vec(), values).with_unit(Unit::Ohm));
+}
+
+/// Computes the complex admittance Y = I / V of a node for one step, see `impedance`.
+pub fn admittance(simulation: &SteppedSimulation, voltage: &str, current: &str, step: u16) -> Option<TraceBuf> {
+    let v = simulation.trace(voltage, step)?;
+    let i = simulation.trace(current, step)?;
+    let sign = current_sign(i.name());
+
+    let values: Vec<Value> = v
+        .values()
+        .iter()
+        .zip(i.values().iter())
+        .map(|(v, i)| *i * sign / *v)
+        .collect();
+    let name = format!("Y({},{})", v.name(), i.name());
+    return Some(v.with_data(name, v.x()[..values.len()].to_vec(), values).with_unit(Unit::Siemens));
+}
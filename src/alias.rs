@@ -0,0 +1,102 @@
+/*
+ * This file contains the variable aliasing and renaming support
+ *
+ * Aliases are additional lookup names for an existing variable (the data is not
+ * copied and exports keep the original name), while renames change the variable
+ * name itself, so exports use the new name.
+ */
+
+use std::error::Error;
+
+use crate::ordered::OrderedMap;
+use crate::SteppedSimulation;
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Resolves an alias to the name of the variable it refers to.
+    pub(crate) fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        if self.has_column(name) {
+            return name;
+        }
+        return match self.aliases.get(name) {
+            Some(target) => target.as_str(),
+            None => name,
+        };
+    }
+
+    /// Makes the variable `target` also accessible as `alias`, e.g. `alias("V(n003)", "V(out)")`.
+    pub fn alias(&mut self, target: &str, alias: &str) -> Result<(), Box<dyn Error>> {
+        let target = self.resolve(target).to_string();
+        if !self.has_column(&target) {
+            Err(format!("Unknown variable '{}'.", target))?;
+        }
+        if self.has_column(alias) {
+            Err(format!("'{}' is already a variable name.", alias))?;
+        }
+        self.aliases.insert(alias.to_string(), target);
+        return Ok(());
+    }
+
+    /// Returns the aliases, as alias -> variable name.
+    pub fn aliases(&self) -> &OrderedMap<String> {
+        return &self.aliases;
+    }
+
+    /// Renames a variable, existing aliases follow the new name.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+        if from == "x" || to == "x" {
+            Err("The x axis cannot be renamed.")?;
+        }
+        if from == to {
+            return Ok(());
+        }
+        if self.has_column(to) || self.aliases.contains_key(to) {
+            Err(format!("'{}' is already in use.", to))?;
+        }
+        if let Some(data) = self.data.remove(from) {
+            self.data.insert(to.to_string(), data);
+        } else if let Some(column) = self.spilled.remove(from) {
+            self.spilled.insert(to.to_string(), column);
+        } else if let Some(column) = self.compacted.remove(from) {
+            self.compacted.insert(to.to_string(), column);
+        } else if let Some(column) = self.slabs.remove(from) {
+            self.slabs.insert(to.to_string(), column);
+        } else {
+            Err(format!("Unknown variable '{}'.", from))?;
+        }
+        if let Some(statistics) = self.column_stats.remove(from) {
+            self.column_stats.insert(to.to_string(), statistics);
+        }
+        for variable in self.variables.iter_mut().filter(|variable| variable.name == from) {
+            variable.name = to.to_string();
+        }
+        for target in self.aliases.values_mut().filter(|target| *target == from) {
+            *target = to.to_string();
+        }
+        for marker in self.markers.iter_mut().filter(|marker| marker.variable == from) {
+            marker.variable = to.to_string();
+        }
+        return Ok(());
+    }
+
+    /// Renames several variables at once (old name -> new name), from any map or list of pairs.
+    /// Variables missing from the simulation are ignored, so one map can serve many files.
+    /// Renames are applied in iteration order, so use an ordered collection for chained renames.
+    pub fn rename_map<K: AsRef<str>, V: AsRef<str>>(
+        &mut self,
+        renames: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<(), Box<dyn Error>> {
+        for (from, to) in renames {
+            if self.has_column(from.as_ref()) {
+                self.rename(from.as_ref(), to.as_ref())?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Configures renames applied automatically every time the simulation is (re)loaded.
+    pub fn set_rename_map(&mut self, renames: OrderedMap<String>) {
+        self.renames = renames;
+    }
+}
@@ -0,0 +1,279 @@
+/*
+ * This file contains the analyses of measurements across the steps of a sweep
+ *
+ * Sensitivities fit the measurement as a linear function of every swept parameter at once,
+ * metric ≈ c + Σ slope_i · p_i, so that nested multi-parameter sweeps give each
+ * parameter its own partial derivative. Corners summarize several measurements,
+ * with the best, worst and typical step of each and the Pareto front of the sweep.
+ * Rejection ratios combine the AC responses of two runs, from two simulations or two steps.
+ */
+
+use crate::convert::db;
+use crate::fit::least_squares;
+use crate::trace::{Trace, TraceBuf};
+use crate::transform::interpolate;
+use crate::{SteppedSimulation, Unit, Value};
+
+/* #### Enums #### */
+
+/// Whether lower or higher values of a metric are better.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Goal {
+    Minimize,
+    Maximize,
+}
+
+/* #### Structs #### */
+
+/// The sensitivity of a measurement to a '.step' parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    pub parameter: String,
+    /// d(metric)/d(parameter), in metric units per parameter unit.
+    pub slope: f64,
+    /// (d(metric)/metric) / (d(parameter)/parameter) at the mean of the sweep,
+    /// e.g. 0.5 if a 1% change of the parameter changes the metric by 0.5%.
+    pub normalized: f64,
+}
+
+/// A named measurement evaluated on every step, see `corners`.
+pub struct Metric<'a> {
+    pub name: String,
+    pub goal: Goal,
+    evaluate: Box<dyn Fn(u16) -> Option<f64> + 'a>,
+}
+
+/// A step of the sweep with the value of a metric and its '.step' parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corner {
+    pub step: u16,
+    pub value: f64,
+    pub parameters: Vec<(String, f64)>,
+}
+
+/// The corners of a single metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricCorners {
+    pub metric: String,
+    pub best: Corner,
+    pub worst: Corner,
+    /// The step closest to the median value.
+    pub typical: Corner,
+}
+
+/// The result of `corners`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corners {
+    /// The corners of every metric with at least one value, in the order of the metrics.
+    pub metrics: Vec<MetricCorners>,
+    /// The steps no other step improves on for every metric at once, in step order.
+    /// Only steps for which every metric has a value are considered.
+    pub pareto: Vec<u16>,
+}
+
+/* #### Functions #### */
+
+/// Fits the sensitivity of a measurement to a single parameter, see `sensitivities`.
+pub fn sensitivity<F>(simulation: &SteppedSimulation, metric: F, parameter: &str) -> Option<Sensitivity>
+where
+    F: Fn(u16) -> Option<f64>,
+{
+    return sensitivities(simulation, metric)
+        .into_iter()
+        .find(|sensitivity| sensitivity.parameter.eq_ignore_ascii_case(parameter));
+}
+
+/// Evaluates the measurement on every step and fits its sensitivity to every swept parameter.
+/// Steps for which the metric returns None are skipped, and parameters with a single value
+/// are not reported. Returns an empty list if the fit is underdetermined.
+pub fn sensitivities<F>(simulation: &SteppedSimulation, metric: F) -> Vec<Sensitivity>
+where
+    F: Fn(u16) -> Option<f64>,
+{
+    // (parameters, metric) of every usable step
+    let rows: Vec<(&[(String, f64)], f64)> = (0..simulation.get_step_count() as u16)
+        .filter_map(|step| Some((simulation.get_step_parameters(step)?, metric(step)?)))
+        .filter(|(_, value)| value.is_finite())
+        .collect();
+    let Some((first, _)) = rows.first() else {
+        return Vec::new();
+    };
+
+    // The parameters swept over more than one value, known for every step
+    let value = |parameters: &[(String, f64)], name: &str| {
+        parameters.iter().find(|(parameter, _)| parameter == name).map(|(_, value)| *value)
+    };
+    let names: Vec<&str> = first
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| rows.iter().all(|(parameters, _)| value(parameters, name).is_some()))
+        .filter(|name| rows.iter().any(|(parameters, _)| value(parameters, name) != value(first, name)))
+        .collect();
+
+    let mut columns: Vec<Vec<f64>> = vec![vec![1.0; rows.len()]];
+    for name in names.iter() {
+        columns.push(rows.iter().map(|(parameters, _)| value(parameters, name).unwrap_or(0.0)).collect());
+    }
+    let mean_metric = rows.iter().map(|(_, value)| *value).sum::<f64>() / rows.len() as f64;
+    let means: Vec<f64> = columns.iter().map(|column| column.iter().sum::<f64>() / rows.len() as f64).collect();
+
+    let Some(solution) = least_squares(columns, rows.iter().map(|(_, value)| *value).collect()) else {
+        return Vec::new();
+    };
+
+    return names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let slope = solution[index + 1];
+            return Sensitivity {
+                parameter: name.to_string(),
+                slope,
+                normalized: slope * means[index + 1] / mean_metric,
+            };
+        })
+        .collect();
+}
+
+/// Evaluates every metric on every step and summarizes the sweep: best, worst and typical
+/// step of each metric, and the Pareto front over all of them.
+///
+/// ```ignore
+/// let summary = analysis::corners(&sim, vec![
+///     Metric::maximize("gain", |step| gain(&sim, step)),
+///     Metric::minimize("power", |step| power(&sim, step)),
+/// ]);
+/// ```
+pub fn corners(simulation: &SteppedSimulation, metrics: Vec<Metric<'_>>) -> Corners {
+    let steps = simulation.get_step_count() as u16;
+
+    // values[metric][step]
+    let values: Vec<Vec<Option<f64>>> = metrics
+        .iter()
+        .map(|metric| {
+            (0..steps)
+                .map(|step| (metric.evaluate)(step).filter(|value| value.is_finite()))
+                .collect()
+        })
+        .collect();
+    let corner = |step: u16, value: f64| Corner {
+        step,
+        value,
+        parameters: simulation.get_step_parameters(step).map_or(Vec::new(), |parameters| parameters.to_vec()),
+    };
+
+    let mut summaries = Vec::new();
+    for (metric, values) in metrics.iter().zip(values.iter()) {
+        // (value, step) sorted from best to worst
+        let mut sorted: Vec<(f64, u16)> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(step, value)| Some(((*value)?, step as u16)))
+            .collect();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if metric.goal == Goal::Maximize {
+            sorted.reverse();
+        }
+        let (Some(best), Some(worst)) = (sorted.first(), sorted.last()) else {
+            continue;
+        };
+        let typical = sorted[sorted.len() / 2];
+
+        summaries.push(MetricCorners {
+            metric: metric.name.clone(),
+            best: corner(best.1, best.0),
+            worst: corner(worst.1, worst.0),
+            typical: corner(typical.1, typical.0),
+        });
+    }
+
+    // Values oriented so that lower is better, for the steps with every metric
+    let candidates: Vec<(u16, Vec<f64>)> = (0..steps)
+        .filter_map(|step| {
+            let oriented = metrics
+                .iter()
+                .zip(values.iter())
+                .map(|(metric, values)| {
+                    let value = values[step as usize]?;
+                    return Some(match metric.goal {
+                        Goal::Minimize => value,
+                        Goal::Maximize => -value,
+                    });
+                })
+                .collect::<Option<Vec<f64>>>()?;
+            return Some((step, oriented));
+        })
+        .collect();
+    let dominates = |a: &[f64], b: &[f64]| {
+        a.iter().zip(b.iter()).all(|(a, b)| a <= b) && a.iter().zip(b.iter()).any(|(a, b)| a < b)
+    };
+    let pareto = candidates
+        .iter()
+        .filter(|(_, values)| !candidates.iter().any(|(_, other)| dominates(other, values)))
+        .map(|(step, _)| *step)
+        .collect();
+
+    return Corners {
+        metrics: summaries,
+        pareto,
+    };
+}
+
+// Returns 20·log10(|numerator| / |denominator|) at the frequencies of the numerator, with the
+// denominator interpolated onto them. Frequencies outside the denominator are dropped.
+fn rejection(numerator: &Trace<'_>, denominator: &Trace<'_>, name: &str) -> Option<TraceBuf> {
+    let mut x = Vec::with_capacity(numerator.len());
+    let mut values = Vec::with_capacity(numerator.len());
+    for (frequency, value) in numerator.x().iter().zip(numerator.values()) {
+        if let Some(reference) = interpolate(denominator.x(), denominator.values(), frequency.real()) {
+            x.push(*frequency);
+            values.push(Value::from(db(value.abs() / reference.abs())));
+        }
+    }
+    if x.is_empty() {
+        return None;
+    }
+    return Some(numerator.with_data(name.to_string(), x, values).with_unit(Unit::Decibel));
+}
+
+/// Computes the common-mode rejection ratio vs frequency in dB, |Adm| / |Acm|, from the output
+/// of a differential-mode run and of a common-mode run with the same stimulus amplitude.
+/// Returns None if the runs share no frequency range.
+pub fn cmrr(run_dm: &Trace<'_>, run_cm: &Trace<'_>) -> Option<TraceBuf> {
+    return rejection(run_dm, run_cm, "CMRR");
+}
+
+/// Computes the power supply rejection ratio vs frequency in dB, |Afwd| / |Asupply|, from the
+/// output of a run driving the input and of a run driving the supply with the same amplitude.
+/// Returns None if the runs share no frequency range.
+pub fn psrr(run_fwd: &Trace<'_>, run_supply: &Trace<'_>) -> Option<TraceBuf> {
+    return rejection(run_fwd, run_supply, "PSRR");
+}
+
+/* #### Implementations #### */
+
+impl<'a> Metric<'a> {
+    /// A metric for which lower values are better, e.g. power or distortion.
+    pub fn minimize<F>(name: &str, evaluate: F) -> Self
+    where
+        F: Fn(u16) -> Option<f64> + 'a,
+    {
+        return Metric {
+            name: name.to_string(),
+            goal: Goal::Minimize,
+            evaluate: Box::new(evaluate),
+        };
+    }
+
+    /// A metric for which higher values are better, e.g. gain or phase margin.
+    pub fn maximize<F>(name: &str, evaluate: F) -> Self
+    where
+        F: Fn(u16) -> Option<f64> + 'a,
+    {
+        return Metric {
+            name: name.to_string(),
+            goal: Goal::Maximize,
+            evaluate: Box::new(evaluate),
+        };
+    }
+}
@@ -0,0 +1,52 @@
+/*
+ * This file contains the conversions to ndarray arrays
+ */
+
+use ndarray::{Array2, Array3};
+
+use crate::SteppedSimulation;
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Returns the real part of the specified step as a (points × columns) array.
+    /// Column 0 is the x axis, followed by the variables in header order.
+    /// Returns None if the step does not exist.
+    pub fn to_ndarray(&self, step: u16) -> Option<Array2<f64>> {
+        let columns = self.get_columns(step)?;
+        let points = columns.iter().map(|(_, values)| values.len()).min().unwrap_or(0);
+
+        return Some(Array2::from_shape_fn((points, columns.len()), |(point, column)| {
+            columns[column].1[point].real()
+        }));
+    }
+
+    /// Returns the real part of every step as a (steps × points × columns) array, with the
+    /// same column layout as `to_ndarray`.
+    /// Returns None if there are no steps or the steps have different lengths.
+    pub fn to_ndarray3(&self) -> Option<Array3<f64>> {
+        let steps = (0..self.get_step_count() as u16)
+            .map(|step| self.get_columns(step))
+            .collect::<Option<Vec<_>>>()?;
+
+        let first = steps.first()?;
+        let points = first.first()?.1.len();
+        let width = first.len();
+        for columns in steps.iter() {
+            if columns.len() != width || columns.iter().any(|(_, values)| values.len() != points) {
+                return None;
+            }
+        }
+
+        return Some(Array3::from_shape_fn((steps.len(), points, width), |(step, point, column)| {
+            steps[step][column].1[point].real()
+        }));
+    }
+
+    /// Returns the names of the columns produced by the array conversions.
+    pub fn column_names(&self) -> Vec<&str> {
+        let mut names = vec!["x"];
+        names.extend(self.get_variables().iter().map(|variable| variable.name()));
+        return names;
+    }
+}
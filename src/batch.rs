@@ -0,0 +1,320 @@
+/*
+ * This file contains the parallel conversion of many raw files
+ *
+ * The files are selected with a glob pattern ('*' and '?' within a name, '**' for any number
+ * of directories), then parsed and exported by a bounded number of worker threads, each one
+ * picking the next file once done. Every file is reported as converted or failed, one failure
+ * (even a panic of the parser) never stops the batch.
+ */
+
+use std::error::Error;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::options::LoadOptions;
+use crate::SteppedSimulation;
+
+/* #### Enums #### */
+
+/// The output format of a batch conversion.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BatchFormat {
+    Json,
+    /// LTSpice binary '.raw', e.g. to normalize files written by other simulators.
+    Raw,
+    /// InfluxDB line protocol, with the file stem as measurement.
+    LineProtocol,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+    #[cfg(feature = "feather")]
+    Feather,
+}
+
+/* #### Structs #### */
+
+/// The progress of a batch, reported after every file.
+#[derive(Debug, Clone)]
+pub struct Progress<'a> {
+    /// Number of files processed so far, including this one.
+    pub done: usize,
+    pub total: usize,
+    pub path: &'a Path,
+    /// The written file, or the error message.
+    pub result: &'a Result<PathBuf, String>,
+}
+
+/// The outcome of a batch conversion.
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    /// (input, output) of every converted file.
+    pub converted: Vec<(PathBuf, PathBuf)>,
+    /// (input, error) of every failed file.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+type ProgressCallback = Box<dyn Fn(&Progress<'_>) + Send + Sync>;
+
+/// A parallel conversion of raw files, see `Batch::convert`.
+pub struct Batch {
+    threads: usize,
+    options: LoadOptions,
+    progress: Option<ProgressCallback>,
+}
+
+/* #### Functions #### */
+
+// Matches a file or directory name against a pattern with '*' and '?' wildcards.
+fn matches_name(pattern: &[char], name: &[char]) -> bool {
+    return match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => matches_name(&pattern[1..], name) || (!name.is_empty() && matches_name(pattern, &name[1..])),
+        (Some('?'), Some(_)) => matches_name(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => matches_name(&pattern[1..], &name[1..]),
+        _ => false,
+    };
+}
+
+// Collects the files under `directory` matching the remaining components of a pattern.
+fn expand(directory: &Path, components: &[String], files: &mut Vec<PathBuf>) {
+    let Some((component, rest)) = components.split_first() else {
+        return;
+    };
+    if component == "**" {
+        // Zero directories, then one more level with the same '**'
+        expand(directory, rest, files);
+        let Ok(entries) = fs::read_dir(directory) else {
+            return;
+        };
+        // The file type does not follow symlinks, so linked directories (which may loop back to
+        // a parent) are not descended into
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                expand(&entry.path(), components, files);
+            }
+        }
+        return;
+    }
+
+    let pattern: Vec<char> = component.chars().collect();
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name: Vec<char> = entry.file_name().to_string_lossy().chars().collect();
+        if !matches_name(&pattern, &name) {
+            continue;
+        }
+        let path = entry.path();
+        match rest.is_empty() {
+            true if path.is_file() => files.push(path),
+            false if path.is_dir() => expand(&path, rest, files),
+            _ => {}
+        }
+    }
+}
+
+// Splits a glob pattern in the directory to start from, made of the components before the
+// first wildcard, and the remaining components.
+fn split_pattern(pattern: &str) -> (PathBuf, Vec<String>) {
+    let mut root = PathBuf::new();
+    let mut components = Vec::new();
+    for component in Path::new(pattern).components() {
+        let text = component.as_os_str().to_string_lossy().to_string();
+        if components.is_empty() && !text.contains(['*', '?']) {
+            root.push(component);
+        } else if !matches!(component, Component::CurDir) {
+            components.push(text);
+        }
+    }
+    return (root, components);
+}
+
+/// Returns the files matching a glob pattern, e.g. "archive/**/*.raw", sorted by path.
+pub fn glob(pattern: &str) -> Vec<PathBuf> {
+    let (root, components) = split_pattern(pattern);
+    let mut files = Vec::new();
+    match components.is_empty() {
+        true if root.is_file() => files.push(root),
+        true => {}
+        false => {
+            let root = if root.as_os_str().is_empty() { PathBuf::from(".") } else { root };
+            expand(&root, &components, &mut files);
+        }
+    }
+    files.sort();
+    files.dedup();
+    return files;
+}
+
+// Returns the output of an input file, at its path relative to `root` (or its whole path if
+// outside of it, without '.' or '..') under `out_dir`, so files with the same name in different
+// directories do not collide. Only the last extension is replaced, "tran.v2.raw" becomes "tran.v2.json".
+pub(crate) fn output_path(out_dir: &Path, root: &Path, path: &Path, format: BatchFormat) -> PathBuf {
+    let relative = match path.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => Path::new(path.file_name().unwrap_or_default()),
+        Ok(relative) => relative,
+        Err(_) => path,
+    };
+    let mut output = out_dir.to_path_buf();
+    for component in relative.components() {
+        if let Component::Normal(name) = component {
+            output.push(name);
+        }
+    }
+    return output.with_extension(format.extension());
+}
+
+/* #### Implementations #### */
+
+impl BatchFormat {
+    /// Returns the extension of the output files.
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            BatchFormat::Json => "json",
+            BatchFormat::Raw => "raw",
+            BatchFormat::LineProtocol => "lp",
+            #[cfg(feature = "sqlite")]
+            BatchFormat::Sqlite => "sqlite",
+            #[cfg(feature = "feather")]
+            BatchFormat::Feather => "feather",
+        };
+    }
+
+    // Writes a simulation to a file in this format.
+    pub(crate) fn write(&self, simulation: &SteppedSimulation, path: &Path) -> Result<(), Box<dyn Error>> {
+        match self {
+            BatchFormat::Json => fs::write(path, simulation.to_json())?,
+            BatchFormat::Raw => simulation.write_raw(path)?,
+            BatchFormat::LineProtocol => {
+                let measurement = simulation.path.file_stem().map_or("simulation".into(), |stem| stem.to_string_lossy());
+                fs::write(path, simulation.to_line_protocol(&measurement)?)?;
+            }
+            #[cfg(feature = "sqlite")]
+            BatchFormat::Sqlite => simulation.to_sqlite(path)?,
+            #[cfg(feature = "feather")]
+            BatchFormat::Feather => simulation.to_feather(path)?,
+        }
+        return Ok(());
+    }
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        return Batch {
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            options: LoadOptions::default(),
+            progress: None,
+        };
+    }
+}
+
+impl Batch {
+    /// Creates a batch using one thread per available core.
+    pub fn new() -> Self {
+        return Batch::default();
+    }
+
+    /// Sets the number of worker threads, at least one.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        return self;
+    }
+
+    /// Sets the options used to load every file.
+    pub fn load_options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        return self;
+    }
+
+    /// Calls `progress` after every file, from the worker thread which processed it. A panic of
+    /// `progress` stops its worker, and is resumed by `convert` once the other workers are done.
+    pub fn on_progress<F>(mut self, progress: F) -> Self
+    where
+        F: Fn(&Progress<'_>) + Send + Sync + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        return self;
+    }
+
+    /// Parses every file matching a glob pattern and exports it to `out_dir`, at its path
+    /// relative to the directory before the first wildcard, with the extension of the format
+    /// ("runs/**/*.raw" writes "runs/a/tran.raw" to "<out_dir>/a/tran.json"). Files whose
+    /// output would overwrite the one of a previous file fail. Fails only if no file matches
+    /// or the output directory cannot be created.
+    pub fn convert(&self, pattern: &str, format: BatchFormat, out_dir: &Path) -> Result<BatchReport, Box<dyn Error>> {
+        let files = glob(pattern);
+        if files.is_empty() {
+            Err(format!("No file matches '{}'.", pattern))?;
+        }
+        fs::create_dir_all(out_dir)?;
+
+        // e.g. "tran.raw" and "tran.txt" are both written to "tran.json"
+        let (root, _) = split_pattern(pattern);
+        let root = if root.as_os_str().is_empty() { PathBuf::from(".") } else { root };
+        let mut outputs: Vec<Result<PathBuf, String>> = Vec::with_capacity(files.len());
+        for path in files.iter() {
+            let output = output_path(out_dir, &root, path, format);
+            let previous = outputs.iter().zip(files.iter()).find(|(other, _)| other.as_ref() == Ok(&output));
+            outputs.push(match previous {
+                Some((_, other)) => Err(format!("Its output {} is already written for {}.", output.display(), other.display())),
+                None => Ok(output),
+            });
+        }
+
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<PathBuf, String>>>> = Mutex::new(vec![None; files.len()]);
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads.min(files.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = files.get(index) else {
+                        break;
+                    };
+                    let result = outputs[index].clone().and_then(|output| {
+                        let converted = panic::catch_unwind(AssertUnwindSafe(|| self.convert_file(path, format, &output)));
+                        return match converted {
+                            Ok(converted) => converted.map(|_| output).map_err(|e| e.to_string()),
+                            Err(_) => Err("The conversion panicked.".to_string()),
+                        };
+                    });
+
+                    let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    if let Some(progress) = &self.progress {
+                        progress(&Progress {
+                            done,
+                            total: files.len(),
+                            path,
+                            result: &result,
+                        });
+                    }
+                    results.lock().unwrap_or_else(PoisonError::into_inner)[index] = Some(result);
+                });
+            }
+        });
+
+        let mut report = BatchReport::default();
+        for (path, result) in files.into_iter().zip(results.into_inner().unwrap_or_else(PoisonError::into_inner)) {
+            match result {
+                Some(Ok(output)) => report.converted.push((path, output)),
+                Some(Err(e)) => report.failed.push((path, e)),
+                None => report.failed.push((path, "Not processed.".to_string())),
+            }
+        }
+        return Ok(report);
+    }
+
+    // Parses and exports a single file.
+    fn convert_file(&self, path: &Path, format: BatchFormat, output: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut simulation = SteppedSimulation::new(path.to_path_buf());
+        simulation.set_load_options(self.options.clone());
+        simulation.reload()?;
+        return format.write(&simulation, output);
+    }
+}
@@ -0,0 +1,25 @@
+/*
+ * HTTP waveform server for LTSpice '.raw' files
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use ltspice::serve::WaveformServer;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => {
+            eprintln!("Usage: ltspice-serve <file.raw> [address] [allowed origin]");
+            std::process::exit(1);
+        }
+    };
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+    let origin = args.next();
+
+    let mut server = WaveformServer::new(&path)?;
+    server.set_allowed_origin(origin.as_deref());
+    server.run(&address)
+}
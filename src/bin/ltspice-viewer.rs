@@ -0,0 +1,24 @@
+/*
+ * Terminal waveform viewer for LTSpice '.raw' files
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use ltspice::SteppedSimulation;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let path = match std::env::args().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            eprintln!("Usage: ltspice-viewer <file.raw>");
+            std::process::exit(1);
+        }
+    };
+
+    let mut simulation = SteppedSimulation::new(path);
+    simulation.reload()?;
+
+    ltspice::tui::run(&simulation)?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+/*
+ * Command line interface for LTSpice '.raw' files
+ *
+ * ltspice run-checks [--config ltspice.toml] [--format text|junit|github] <files...>
+ *
+ * Runs the verification suite of the config file on every file (wildcards are expanded),
+ * and exits with a non-zero status if any file fails to load or any check fails.
+ * The junit format prints a JUnit XML report, the github format prints annotations.
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use ltspice::batch::glob;
+use ltspice::checks::{render, Config, OutputFormat};
+
+fn usage() -> ! {
+    eprintln!("Usage: ltspice run-checks [--config ltspice.toml] [--format text|junit|github] <files...>");
+    std::process::exit(1);
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some("run-checks") {
+        usage();
+    }
+
+    let mut config = PathBuf::from("ltspice.toml");
+    let mut format = OutputFormat::Text;
+    let mut files = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config = args.next().map(PathBuf::from).unwrap_or_else(|| usage()),
+            "--format" => format = args.next().and_then(|name| OutputFormat::from_name(&name)).unwrap_or_else(|| usage()),
+            pattern if pattern.contains(['*', '?']) => files.extend(glob(pattern)),
+            path => files.push(PathBuf::from(path)),
+        }
+    }
+    if files.is_empty() {
+        usage();
+    }
+    let config = Config::load(&config)?;
+
+    let reports: Vec<_> = files.iter().map(|file| config.check_file(file)).collect();
+    print!("{}", render(&reports, format));
+
+    let failed = reports.iter().any(|report| !report.passed());
+    if failed {
+        std::process::exit(1);
+    }
+    return Ok(());
+}
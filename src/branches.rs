@@ -0,0 +1,80 @@
+/*
+ * This file contains the splitting of swept traces into monotonic branches
+ *
+ * A '.dc' sweep of a hysteretic circuit intentionally runs the swept source up and back down
+ * (or over several loops). Every reversal of the x axis starts a new branch, the turning point
+ * belonging to both branches so that each one spans its full range.
+ */
+
+use std::ops::Range;
+
+use crate::trace::{Trace, TraceBuf};
+use crate::Value;
+
+/* #### Enums #### */
+
+/// The direction of the x axis along a branch.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+/* #### Structs #### */
+
+/// A monotonic section of a trace, see `Trace::branches`.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub direction: Direction,
+    pub trace: TraceBuf,
+}
+
+/* #### Functions #### */
+
+// Returns the index range and direction of every monotonic branch of the x axis.
+// Repeated abscissas continue the current branch.
+pub(crate) fn branch_ranges(x: &[Value]) -> Vec<(Range<usize>, Direction)> {
+    let mut branches = Vec::new();
+    let mut start = 0;
+    let mut direction: Option<Direction> = None;
+    for index in 1..x.len() {
+        let next = match x[index].real().partial_cmp(&x[index - 1].real()) {
+            Some(std::cmp::Ordering::Greater) => Direction::Forward,
+            Some(std::cmp::Ordering::Less) => Direction::Backward,
+            _ => continue,
+        };
+        match direction {
+            Some(current) if current != next => {
+                branches.push((start..index, current));
+                start = index - 1;
+                direction = Some(next);
+            }
+            _ => direction = Some(next),
+        }
+    }
+    if !x.is_empty() {
+        branches.push((start..x.len(), direction.unwrap_or(Direction::Forward)));
+    }
+    return branches;
+}
+
+/* #### Implementations #### */
+
+impl<'a> Trace<'a> {
+    /// Splits the trace at every reversal of the x axis, e.g. the up and down branches of a
+    /// '.dc' hysteresis sweep. A monotonic trace has a single branch.
+    pub fn branches(&self) -> Vec<Branch> {
+        let length = self.len();
+        return branch_ranges(&self.x()[..length])
+            .into_iter()
+            .map(|(range, direction)| Branch {
+                direction,
+                trace: self.with_data(
+                    self.name().to_string(),
+                    self.x()[range.clone()].to_vec(),
+                    self.values()[range].to_vec(),
+                ),
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,456 @@
+/*
+ * This file contains the on-disk cache of parsed simulations
+ *
+ * A snapshot is stored next to the raw file ('<name>.raw.cache') and is only
+ * reused while the size, modification time and hash of the whole raw file match.
+ * It holds everything a parse restores (flags, source, per-variable precision, derived signals,
+ * aliases, renames, column statistics, warnings and markers included), bump VERSION whenever
+ * its layout changes.
+ */
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::UNIX_EPOCH;
+
+use tracing::{debug, warn};
+
+#[cfg(feature = "chrono")]
+use crate::parse_date;
+use crate::ordered::OrderedMap;
+use crate::provenance::{fnv1a, fnv1a_continue, Provenance};
+use crate::marker::Marker;
+use crate::summary::ColumnStats;
+use crate::{DataType, Flags, Mode, Source, SteppedSimulation, SteppedVariable, Value, VariableClass};
+
+/* #### Constants #### */
+
+const MAGIC: &[u8; 4] = b"LTSC";
+const VERSION: u32 = 6;
+
+// Size of the reads while hashing the raw file
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+// Distinguishes the temporary files of the writers of a process
+static WRITERS: AtomicUsize = AtomicUsize::new(0);
+
+/* #### Structs #### */
+
+pub struct Cache;
+
+// Identifies a specific version of a raw file.
+#[derive(Debug, PartialEq, Eq)]
+struct Fingerprint {
+    size: u64,
+    modified: u128,
+    hash: u64,
+}
+
+// Little-endian reader over a snapshot, with bounds checks.
+struct Reader<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+/* #### Functions #### */
+
+// The whole file is hashed, as an in-place rewrite can keep both the size and the modification
+// time (e.g. on filesystems with a coarse timestamp resolution).
+fn fingerprint(path: &Path) -> Result<Fingerprint, Box<dyn Error>> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_nanos();
+
+    let mut file = File::open(path)?;
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut hash = fnv1a(&[]);
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        hash = fnv1a_continue(hash, &chunk[..read]);
+    }
+
+    return Ok(Fingerprint {
+        size: metadata.len(),
+        modified,
+        hash,
+    });
+}
+
+fn put_str(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+fn put_map(buffer: &mut Vec<u8>, map: &OrderedMap<String>) {
+    buffer.extend_from_slice(&(map.len() as u32).to_le_bytes());
+    for (key, value) in map.iter() {
+        put_str(buffer, key);
+        put_str(buffer, value);
+    }
+}
+
+// The enums below are stored by their debug name, like the variable classes and flags.
+fn flag_from_name(name: &str) -> Result<Flags, Box<dyn Error>> {
+    return match Flags::from_name(name) {
+        Some(flag) => Ok(flag),
+        None => Err(format!("Unknown flag '{}' in cache snapshot.", name))?,
+    };
+}
+
+fn source_from_name(name: &str) -> Source {
+    return match name {
+        "LtspiceXVII" => Source::LtspiceXVII,
+        "Ltspice24" => Source::Ltspice24,
+        "Ngspice" => Source::Ngspice,
+        "Qspice" => Source::Qspice,
+        _ => Source::Unknown,
+    };
+}
+
+// An empty name stands for an unknown precision.
+fn data_type_from_name(name: &str) -> Result<Option<DataType>, Box<dyn Error>> {
+    return match name {
+        "" => Ok(None),
+        "Float32" => Ok(Some(DataType::Float32)),
+        "Float64" => Ok(Some(DataType::Float64)),
+        "Complex128" => Ok(Some(DataType::Complex128)),
+        _ => Err(format!("Unknown data type '{}' in cache snapshot.", name))?,
+    };
+}
+
+/* #### Implementations #### */
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = match self.offset.checked_add(length) {
+            Some(end) if end <= self.buffer.len() => end,
+            _ => Err("Truncated cache snapshot.")?,
+        };
+        let slice = &self.buffer[self.offset..end];
+        self.offset = end;
+        return Ok(slice);
+    }
+
+    fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        return Ok(u32::from_le_bytes(self.take(4)?.try_into()?));
+    }
+
+    fn u64(&mut self) -> Result<u64, Box<dyn Error>> {
+        return Ok(u64::from_le_bytes(self.take(8)?.try_into()?));
+    }
+
+    fn u128(&mut self) -> Result<u128, Box<dyn Error>> {
+        return Ok(u128::from_le_bytes(self.take(16)?.try_into()?));
+    }
+
+    fn f64(&mut self) -> Result<f64, Box<dyn Error>> {
+        return Ok(f64::from_le_bytes(self.take(8)?.try_into()?));
+    }
+
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        let length = self.u32()? as usize;
+        return Ok(String::from_utf8(self.take(length)?.to_vec())?);
+    }
+
+    fn map(&mut self) -> Result<OrderedMap<String>, Box<dyn Error>> {
+        let mut map = OrderedMap::new();
+        for _ in 0..self.u32()? {
+            map.insert(self.string()?, self.string()?);
+        }
+        return Ok(map);
+    }
+
+    fn fingerprint(&mut self) -> Result<Fingerprint, Box<dyn Error>> {
+        return Ok(Fingerprint {
+            size: self.u64()?,
+            modified: self.u128()?,
+            hash: self.u64()?,
+        });
+    }
+}
+
+impl Cache {
+    /// Returns the path of the snapshot associated with a raw file.
+    pub fn snapshot_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".cache");
+        return PathBuf::from(name);
+    }
+
+    /// Loads the simulation from its snapshot if it is still up to date, otherwise parses the
+    /// raw file and stores a new snapshot. Failing to write the snapshot is not an error.
+    pub fn load_or_parse(path: &Path) -> Result<SteppedSimulation, Box<dyn Error>> {
+        match Cache::load(path) {
+            Ok(Some(simulation)) => {
+                debug!("Loaded {:?} from its cache snapshot.", path);
+                return Ok(simulation);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Ignoring unreadable cache snapshot for {:?}: {}", path, e),
+        }
+
+        let mut simulation = SteppedSimulation::new(path.to_path_buf());
+        simulation.reload()?;
+
+        if let Err(e) = Cache::store(&simulation) {
+            warn!("Could not write the cache snapshot for {:?}: {}", path, e);
+        }
+        return Ok(simulation);
+    }
+
+    /// Loads the snapshot of a raw file, returns None if there is none or it is out of date.
+    pub fn load(path: &Path) -> Result<Option<SteppedSimulation>, Box<dyn Error>> {
+        let snapshot = Cache::snapshot_path(path);
+        if !snapshot.is_file() {
+            return Ok(None);
+        }
+
+        let buffer = fs::read(&snapshot)?;
+        let mut reader = Reader { buffer: &buffer, offset: 0 };
+        if reader.take(4)? != MAGIC || reader.u32()? != VERSION {
+            return Ok(None);
+        }
+        if reader.fingerprint()? != fingerprint(path)? {
+            return Ok(None);
+        }
+
+        // Header
+        let mut simulation = SteppedSimulation::new(path.to_path_buf());
+        simulation.mode = Mode::from_name(&reader.string()?);
+        for _ in 0..reader.u32()? {
+            simulation.flags.push(flag_from_name(&reader.string()?)?);
+        }
+        simulation.source = source_from_name(&reader.string()?);
+        let date = reader.string()?;
+        if !date.is_empty() {
+            #[cfg(feature = "chrono")]
+            {
+                simulation.date = parse_date(&date);
+            }
+            simulation.date_string = Some(date);
+        }
+        simulation.offset = reader.f64()?;
+        simulation.stats.variables = reader.u32()?;
+        simulation.stats.points = reader.u32()?;
+        simulation.stats.steps = reader.u32()? as u16;
+        simulation.stats.step_size = reader.u32()?;
+        simulation.provenance = Provenance {
+            path: path.to_path_buf(),
+            size: reader.u64()?,
+            modified: fs::metadata(path)?.modified()?.duration_since(UNIX_EPOCH).ok().map(|modified| modified.as_secs()),
+            hash: reader.u64()?,
+            parser_version: reader.string()?,
+        };
+
+        // Variables
+        for _ in 0..reader.u32()? {
+            let name = reader.string()?;
+            let class = VariableClass::from_name(&reader.string()?);
+            // Header indices start at 1, 0 stands for a variable without one (e.g. a derived signal)
+            let index = Some(reader.u32()?).filter(|index| *index > 0);
+            let derived = reader.take(1)?[0] != 0;
+            let data_type = data_type_from_name(&reader.string()?)?;
+            simulation.variables.push(SteppedVariable {
+                class,
+                name,
+                index,
+                derived,
+                data_type,
+            });
+        }
+
+        // Step Parameters
+        for _ in 0..reader.u32()? {
+            let mut parameters = Vec::new();
+            for _ in 0..reader.u32()? {
+                parameters.push((reader.string()?, reader.f64()?));
+            }
+            simulation.parameters.push(parameters);
+        }
+
+        // Data
+        let mut data: OrderedMap<Vec<Vec<Value>>> = OrderedMap::new();
+        for _ in 0..reader.u32()? {
+            let name = reader.string()?;
+            let mut steps = Vec::new();
+            for _ in 0..reader.u32()? {
+                let length = reader.u64()? as usize;
+                let bytes = reader.take(length.checked_mul(16).ok_or("Invalid cache snapshot.")?)?;
+                steps.push(
+                    bytes
+                        .chunks_exact(16)
+                        .map(|a| Value {
+                            real: f64::from_le_bytes(a[0..8].try_into().unwrap()),
+                            imaginary: f64::from_le_bytes(a[8..16].try_into().unwrap()),
+                        })
+                        .collect(),
+                );
+            }
+            data.insert(name, steps);
+        }
+        simulation.data = data;
+
+        // Markers
+        for _ in 0..reader.u32()? {
+            simulation.markers.push(Marker {
+                variable: reader.string()?,
+                step: reader.u32()? as u16,
+                x: reader.f64()?,
+                value: reader.f64()?,
+                label: reader.string()?,
+            });
+        }
+
+        // Aliases And Renames
+        simulation.aliases = reader.map()?;
+        simulation.renames = reader.map()?;
+
+        // Column Statistics
+        for _ in 0..reader.u32()? {
+            let name = reader.string()?;
+            let mut steps = Vec::new();
+            for _ in 0..reader.u32()? {
+                steps.push(ColumnStats {
+                    min: reader.f64()?,
+                    max: reader.f64()?,
+                    mean: reader.f64()?,
+                    rms: reader.f64()?,
+                    count: reader.u64()? as usize,
+                });
+            }
+            simulation.column_stats.insert(name, steps);
+        }
+
+        // Warnings
+        for _ in 0..reader.u32()? {
+            simulation.warnings.push(reader.string()?);
+        }
+
+        return Ok(Some(simulation));
+    }
+
+    /// Stores a snapshot of a parsed simulation next to its raw file.
+    pub fn store(simulation: &SteppedSimulation) -> Result<(), Box<dyn Error>> {
+        let fingerprint = fingerprint(&simulation.path)?;
+
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(MAGIC);
+        buffer.extend_from_slice(&VERSION.to_le_bytes());
+        buffer.extend_from_slice(&fingerprint.size.to_le_bytes());
+        buffer.extend_from_slice(&fingerprint.modified.to_le_bytes());
+        buffer.extend_from_slice(&fingerprint.hash.to_le_bytes());
+
+        // Header
+        put_str(&mut buffer, &simulation.mode.to_string());
+        buffer.extend_from_slice(&(simulation.flags.len() as u32).to_le_bytes());
+        for flag in simulation.flags.iter() {
+            put_str(&mut buffer, &format!("{:?}", flag));
+        }
+        put_str(&mut buffer, &format!("{:?}", simulation.source));
+        put_str(&mut buffer, simulation.date_string.as_deref().unwrap_or(""));
+        buffer.extend_from_slice(&simulation.offset.to_le_bytes());
+        buffer.extend_from_slice(&simulation.stats.variables.to_le_bytes());
+        buffer.extend_from_slice(&simulation.stats.points.to_le_bytes());
+        buffer.extend_from_slice(&(simulation.stats.steps as u32).to_le_bytes());
+        buffer.extend_from_slice(&simulation.stats.step_size.to_le_bytes());
+        buffer.extend_from_slice(&simulation.provenance.size.to_le_bytes());
+        buffer.extend_from_slice(&simulation.provenance.hash.to_le_bytes());
+        put_str(&mut buffer, &simulation.provenance.parser_version);
+
+        // Variables
+        buffer.extend_from_slice(&(simulation.variables.len() as u32).to_le_bytes());
+        for variable in simulation.variables.iter() {
+            put_str(&mut buffer, &variable.name);
+            put_str(&mut buffer, &format!("{:?}", variable.class));
+            buffer.extend_from_slice(&variable.index.unwrap_or(0).to_le_bytes());
+            buffer.push(variable.derived as u8);
+            put_str(&mut buffer, &variable.data_type.map_or(String::new(), |data_type| format!("{:?}", data_type)));
+        }
+
+        // Step Parameters
+        buffer.extend_from_slice(&(simulation.parameters.len() as u32).to_le_bytes());
+        for parameters in simulation.parameters.iter() {
+            buffer.extend_from_slice(&(parameters.len() as u32).to_le_bytes());
+            for (name, value) in parameters.iter() {
+                put_str(&mut buffer, name);
+                buffer.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        // Data
+        let mut columns: Vec<(&str, &Vec<Vec<Value>>)> = Vec::new();
+        for name in std::iter::once("x").chain(simulation.variables.iter().map(|variable| variable.name())) {
+            if let Some(steps) = simulation.try_column(name)? {
+                columns.push((name, steps));
+            }
+        }
+        buffer.extend_from_slice(&(columns.len() as u32).to_le_bytes());
+        for (name, steps) in columns.into_iter() {
+            put_str(&mut buffer, name);
+            buffer.extend_from_slice(&(steps.len() as u32).to_le_bytes());
+            for values in steps.iter() {
+                buffer.extend_from_slice(&(values.len() as u64).to_le_bytes());
+                for value in values.iter() {
+                    buffer.extend_from_slice(&value.real.to_le_bytes());
+                    buffer.extend_from_slice(&value.imaginary.to_le_bytes());
+                }
+            }
+        }
+
+        // Markers
+        buffer.extend_from_slice(&(simulation.markers.len() as u32).to_le_bytes());
+        for marker in simulation.markers.iter() {
+            put_str(&mut buffer, &marker.variable);
+            buffer.extend_from_slice(&(marker.step as u32).to_le_bytes());
+            buffer.extend_from_slice(&marker.x.to_le_bytes());
+            buffer.extend_from_slice(&marker.value.to_le_bytes());
+            put_str(&mut buffer, &marker.label);
+        }
+
+        // Aliases And Renames
+        put_map(&mut buffer, &simulation.aliases);
+        put_map(&mut buffer, &simulation.renames);
+
+        // Column Statistics
+        buffer.extend_from_slice(&(simulation.column_stats.len() as u32).to_le_bytes());
+        for (name, steps) in simulation.column_stats.iter() {
+            put_str(&mut buffer, name);
+            buffer.extend_from_slice(&(steps.len() as u32).to_le_bytes());
+            for statistics in steps.iter() {
+                for value in [statistics.min, statistics.max, statistics.mean, statistics.rms] {
+                    buffer.extend_from_slice(&value.to_le_bytes());
+                }
+                buffer.extend_from_slice(&(statistics.count as u64).to_le_bytes());
+            }
+        }
+
+        // Warnings
+        buffer.extend_from_slice(&(simulation.warnings.len() as u32).to_le_bytes());
+        for warning in simulation.warnings.iter() {
+            put_str(&mut buffer, warning);
+        }
+
+        // Write to a temporary file first, so readers never see a partial snapshot. Every writer
+        // (process and call) uses its own, so that concurrent stores cannot interleave.
+        let snapshot = Cache::snapshot_path(&simulation.path);
+        let mut temporary = snapshot.as_os_str().to_owned();
+        temporary.push(format!(".{}-{}.tmp", std::process::id(), WRITERS.fetch_add(1, Ordering::Relaxed)));
+        let temporary = PathBuf::from(temporary);
+        let written = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&temporary)
+            .and_then(|mut file| file.write_all(&buffer))
+            .and_then(|_| fs::rename(&temporary, &snapshot));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temporary);
+            Err(e)?;
+        }
+
+        return Ok(());
+    }
+}
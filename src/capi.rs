@@ -0,0 +1,199 @@
+/*
+ * This file contains the C ABI, used by C/C++ and LabVIEW test software
+ *
+ * All functions are null-safe: invalid handles or arguments are reported
+ * through the return value instead of crashing the host application. Panics are caught
+ * at the boundary and reported the same way, as unwinding into C is undefined behaviour.
+ */
+
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+
+use crate::{SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Runs the body of an exported function, returning `failure` if it panics.
+fn guard<T>(failure: T, body: impl FnOnce() -> T) -> T {
+    return catch_unwind(AssertUnwindSafe(body)).unwrap_or(failure);
+}
+
+// Resolves a variable name and step into the matching trace.
+unsafe fn trace<'a>(simulation: *const SteppedSimulation, name: *const c_char, step: u16) -> Option<&'a [Value]> {
+    if simulation.is_null() || name.is_null() {
+        return None;
+    }
+    let name = CStr::from_ptr(name).to_str().ok()?;
+    return (*simulation).get(name, Some(step));
+}
+
+/// Opens and parses a '.raw' file.
+/// Returns NULL on failure, the handle must be released with `ltspice_free`.
+///
+/// # Safety
+/// `path` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_open(path: *const c_char) -> *mut SteppedSimulation {
+    return guard(ptr::null_mut(), || {
+        if path.is_null() {
+            return ptr::null_mut();
+        }
+        let path = match CStr::from_ptr(path).to_str() {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => return ptr::null_mut(),
+        };
+
+        let mut simulation = SteppedSimulation::new(path);
+        if simulation.reload().is_err() {
+            return ptr::null_mut();
+        }
+        return Box::into_raw(Box::new(simulation));
+    });
+}
+
+/// Parses a '.raw' file from memory.
+/// Returns NULL on failure, the handle must be released with `ltspice_free`.
+///
+/// # Safety
+/// `data` must be NULL or valid for reads of `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_open_bytes(data: *const u8, length: usize) -> *mut SteppedSimulation {
+    return guard(ptr::null_mut(), || {
+        if data.is_null() {
+            return ptr::null_mut();
+        }
+        return match SteppedSimulation::from_bytes(std::slice::from_raw_parts(data, length)) {
+            Ok(simulation) => Box::into_raw(Box::new(simulation)),
+            Err(_) => ptr::null_mut(),
+        };
+    });
+}
+
+/// Releases a handle returned by `ltspice_open` or `ltspice_open_bytes`.
+///
+/// # Safety
+/// `simulation` must be NULL or a handle which has not been freed yet, it is invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_free(simulation: *mut SteppedSimulation) {
+    guard((), || {
+        if !simulation.is_null() {
+            drop(Box::from_raw(simulation));
+        }
+    });
+}
+
+/// Returns the number of variables (excluding the x axis), or 0 for an invalid handle.
+///
+/// # Safety
+/// `simulation` must be NULL or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_get_variable_count(simulation: *const SteppedSimulation) -> usize {
+    return guard(0, || {
+        if simulation.is_null() {
+            return 0;
+        }
+        return (*simulation).get_variables().len();
+    });
+}
+
+/// Returns the number of steps, or 0 for an invalid handle.
+///
+/// # Safety
+/// `simulation` must be NULL or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_get_step_count(simulation: *const SteppedSimulation) -> usize {
+    return guard(0, || {
+        if simulation.is_null() {
+            return 0;
+        }
+        return (*simulation).get_step_count();
+    });
+}
+
+/// Copies the NUL-terminated name of the variable at `index` into `buffer`.
+/// Returns the length of the name (excluding the terminator), or -1 if the index is invalid
+/// or the buffer is too small.
+///
+/// # Safety
+/// `simulation` must be NULL or a valid handle, `buffer` NULL or valid for writes of
+/// `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_get_variable_name(
+    simulation: *const SteppedSimulation,
+    index: usize,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> isize {
+    return guard(-1, || {
+        if simulation.is_null() || buffer.is_null() {
+            return -1;
+        }
+        let name = match (*simulation).get_variables().get(index) {
+            Some(variable) => variable.name().as_bytes(),
+            None => return -1,
+        };
+        if name.len() + 1 > capacity {
+            return -1;
+        }
+        ptr::copy_nonoverlapping(name.as_ptr() as *const c_char, buffer, name.len());
+        *buffer.add(name.len()) = 0;
+        return name.len() as isize;
+    });
+}
+
+/// Returns the number of points of a trace, or -1 if it does not exist.
+/// Use the name "x" for the abscissa.
+///
+/// # Safety
+/// `simulation` must be NULL or a valid handle, `name` NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_get_trace_length(
+    simulation: *const SteppedSimulation,
+    name: *const c_char,
+    step: u16,
+) -> isize {
+    return guard(-1, || {
+        return match trace(simulation, name, step) {
+            Some(values) => values.len() as isize,
+            None => -1,
+        };
+    });
+}
+
+/// Copies up to `capacity` points of a trace into the caller-provided buffers.
+/// `imaginary` may be NULL for real-valued simulations.
+/// Returns the number of copied points, or -1 if the trace does not exist.
+///
+/// # Safety
+/// `simulation` must be NULL or a valid handle, `name` NULL or a valid NUL-terminated string,
+/// `real` and `imaginary` NULL or valid for writes of `capacity` values.
+#[no_mangle]
+pub unsafe extern "C" fn ltspice_get_trace_data(
+    simulation: *const SteppedSimulation,
+    name: *const c_char,
+    step: u16,
+    real: *mut f64,
+    imaginary: *mut f64,
+    capacity: usize,
+) -> isize {
+    return guard(-1, || {
+        let values = match trace(simulation, name, step) {
+            Some(values) => values,
+            None => return -1,
+        };
+        if real.is_null() {
+            return -1;
+        }
+
+        let count = values.len().min(capacity);
+        for (index, value) in values.iter().take(count).enumerate() {
+            *real.add(index) = value.real();
+            if !imaginary.is_null() {
+                *imaginary.add(index) = value.imaginary();
+            }
+        }
+        return count as isize;
+    });
+}
@@ -0,0 +1,427 @@
+/*
+ * This file contains the declarative verification suites of the `ltspice` CLI
+ *
+ * A suite is read from a TOML file ('ltspice.toml'):
+ *
+ *   variables = ["V(out)", "I(L1)"]        # required in every file
+ *
+ *   [[check]]
+ *   name = "output ripple"
+ *   variable = "V(out)"
+ *   measure = "peak_to_peak"               # min, max, mean, rms, peak_to_peak, final,
+ *   from = 1e-3                            # crest_factor, frequency or duty
+ *   max = 0.05                             # min and/or max, both inclusive
+ *
+ *   [[export]]
+ *   format = "json"                        # json, raw or lp
+ *   dir = "exports"                        # mirrors the path of the raw files
+ *
+ * Every check is evaluated on every step (or only `step`), within the optional [from, to] range.
+ * The results are rendered as plain text, JUnit XML or GitHub Actions annotations, so that CI
+ * systems display failing waveform checks natively.
+ */
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::batch::{output_path, BatchFormat};
+use crate::svg::escape;
+use crate::mc::SpecLimits;
+use crate::measure::{crest_factor, integrate};
+use crate::pwm::{self, PwmCycle};
+use crate::trace::Trace;
+use crate::{SteppedSimulation, Value};
+
+/* #### Enums #### */
+
+/// A scalar measurement of a trace.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Measure {
+    Min,
+    Max,
+    /// Average over the abscissa.
+    Mean,
+    /// RMS over the abscissa.
+    Rms,
+    PeakToPeak,
+    /// The last sample of the range.
+    Final,
+    CrestFactor,
+    /// Average switching frequency, see `pwm::analyze`.
+    Frequency,
+    /// Average duty, see `pwm::analyze`.
+    Duty,
+}
+
+/// How `render` formats the results of a suite.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum OutputFormat {
+    /// One PASS / FAIL line per result.
+    #[default]
+    Text,
+    /// A JUnit XML document, with one test suite per file and one test case per result.
+    Junit,
+    /// GitHub Actions workflow commands, annotating the failures only.
+    Github,
+}
+
+/* #### Structs #### */
+
+/// A verification suite, see the file header for the format.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    /// Variables which must exist in every file.
+    #[serde(default)]
+    pub variables: Vec<String>,
+    #[serde(default, rename = "check")]
+    pub checks: Vec<Check>,
+    #[serde(default, rename = "export")]
+    pub exports: Vec<Export>,
+}
+
+/// A measurement and its limits.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Check {
+    pub name: String,
+    pub variable: String,
+    pub measure: Measure,
+    /// Only evaluate this step, every step if None.
+    pub step: Option<u16>,
+    pub from: Option<f64>,
+    pub to: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// An export of every checked file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Export {
+    /// "json", "raw" or "lp" (InfluxDB line protocol).
+    pub format: String,
+    pub dir: PathBuf,
+}
+
+/// The outcome of a check on one step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub check: String,
+    pub step: u16,
+    /// The measured value, None if it could not be measured (which fails the check).
+    pub value: Option<f64>,
+    pub limits: SpecLimits,
+    pub passed: bool,
+}
+
+/// The outcome of a suite on one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /// Failures outside of the checks: loading, missing variables and exports.
+    pub errors: Vec<String>,
+    pub results: Vec<CheckResult>,
+}
+
+/* #### Functions #### */
+
+// Evaluates a measurement on the part of a trace within [from, to].
+fn evaluate(trace: &Trace<'_>, measure: Measure, from: f64, to: f64) -> Option<f64> {
+    let (x, values): (Vec<Value>, Vec<Value>) = trace
+        .x()
+        .iter()
+        .zip(trace.values().iter())
+        .filter(|(x, _)| x.real() >= from && x.real() <= to)
+        .map(|(x, value)| (*x, *value))
+        .unzip();
+    let cropped = trace.with_data(trace.name().to_string(), x, values);
+    let cropped = cropped.as_trace();
+    let (x, y): (Vec<f64>, Vec<f64>) = cropped.points().unzip();
+    let span = x.last()? - x.first()?;
+    let average = |y: &[f64]| -> Option<f64> {
+        if span <= 0.0 {
+            return None;
+        }
+        return Some(integrate(&x, y, None) / span);
+    };
+    // Average of a property of the complete PWM cycles within the range
+    let cycles = |property: fn(&PwmCycle) -> f64| -> Option<f64> {
+        let cycles = pwm::analyze(&cropped);
+        if cycles.is_empty() {
+            return None;
+        }
+        return Some(cycles.iter().map(property).sum::<f64>() / cycles.len() as f64);
+    };
+
+    return match measure {
+        Measure::Min => y.iter().copied().reduce(f64::min),
+        Measure::Max => y.iter().copied().reduce(f64::max),
+        Measure::Mean => average(&y),
+        Measure::Rms => average(&y.iter().map(|y| y * y).collect::<Vec<f64>>()).map(f64::sqrt),
+        Measure::PeakToPeak => Some(y.iter().copied().reduce(f64::max)? - y.iter().copied().reduce(f64::min)?),
+        Measure::Final => y.last().copied(),
+        Measure::CrestFactor => crest_factor(&cropped),
+        Measure::Frequency => cycles(|cycle| 1.0 / cycle.period),
+        Measure::Duty => cycles(|cycle| cycle.duty),
+    };
+}
+
+// Formats an optional value, "n/a" if it is None.
+fn format_value(value: Option<f64>) -> String {
+    return value.map_or("n/a".to_string(), |value| format!("{:.6e}", value));
+}
+
+// Escapes the message of a GitHub Actions workflow command.
+fn escape_annotation(value: &str) -> String {
+    return value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+}
+
+// Escapes a property (e.g. the file) of a GitHub Actions workflow command.
+fn escape_property(value: &str) -> String {
+    return escape_annotation(value).replace(':', "%3A").replace(',', "%2C");
+}
+
+fn render_text(reports: &[FileReport]) -> String {
+    let mut output = String::new();
+    for report in reports.iter() {
+        let path = report.path.display();
+        for error in report.errors.iter() {
+            output.push_str(&format!("FAIL {}: {}\n", path, error));
+        }
+        for result in report.results.iter() {
+            let status = if result.passed { "PASS" } else { "FAIL" };
+            output.push_str(&format!("{} {} {} = {}\n", status, path, result.name(), format_value(result.value)));
+        }
+    }
+    return output;
+}
+
+fn render_junit(reports: &[FileReport]) -> String {
+    let tests: usize = reports.iter().map(|report| report.errors.len() + report.results.len()).sum();
+    let failures: usize = reports.iter().map(|report| report.failures()).sum();
+    let errors: usize = reports.iter().map(|report| report.errors.len()).sum();
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str(&format!(
+        "<testsuites name=\"ltspice\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        tests, failures, errors
+    ));
+    for report in reports.iter() {
+        let path = escape(&report.path.display().to_string());
+        output.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+            path,
+            report.errors.len() + report.results.len(),
+            report.failures(),
+            report.errors.len()
+        ));
+        for error in report.errors.iter() {
+            output.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\">\n", escape(error), path));
+            output.push_str(&format!("      <error message=\"{}\"/>\n", escape(error)));
+            output.push_str("    </testcase>\n");
+        }
+        for result in report.results.iter() {
+            let name = escape(&result.name());
+            match result.passed {
+                true => output.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\"/>\n", name, path)),
+                false => {
+                    output.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\">\n", name, path));
+                    output.push_str(&format!("      <failure message=\"{}\"/>\n", escape(&result.message())));
+                    output.push_str("    </testcase>\n");
+                }
+            }
+        }
+        output.push_str("  </testsuite>\n");
+    }
+    output.push_str("</testsuites>\n");
+    return output;
+}
+
+fn render_github(reports: &[FileReport]) -> String {
+    let mut output = String::new();
+    for report in reports.iter() {
+        let path = escape_property(&report.path.display().to_string());
+        for error in report.errors.iter() {
+            output.push_str(&format!("::error file={}::{}\n", path, escape_annotation(error)));
+        }
+        for result in report.results.iter().filter(|result| !result.passed) {
+            output.push_str(&format!(
+                "::error file={},title={}::{}\n",
+                path,
+                escape_property(&result.name()),
+                escape_annotation(&result.message())
+            ));
+        }
+    }
+    return output;
+}
+
+/// Renders the reports of a suite in the specified format.
+pub fn render(reports: &[FileReport], format: OutputFormat) -> String {
+    return match format {
+        OutputFormat::Text => render_text(reports),
+        OutputFormat::Junit => render_junit(reports),
+        OutputFormat::Github => render_github(reports),
+    };
+}
+
+/* #### Implementations #### */
+
+impl OutputFormat {
+    /// Parses "text", "junit" or "github", None for an unknown format.
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        return match name.to_ascii_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "junit" | "xml" => Some(OutputFormat::Junit),
+            "github" => Some(OutputFormat::Github),
+            _ => None,
+        };
+    }
+}
+
+impl CheckResult {
+    /// Returns the name of the check and its step, e.g. "ripple [step 2]".
+    pub fn name(&self) -> String {
+        return format!("{} [step {}]", self.check, self.step);
+    }
+
+    /// Returns a description of the outcome, e.g. "1.2e-1 is outside [-, 5e-2]".
+    pub fn message(&self) -> String {
+        let limit = |limit: Option<f64>| limit.map_or("-".to_string(), |limit| format!("{:e}", limit));
+        let limits = format!("[{}, {}]", limit(self.limits.lower), limit(self.limits.upper));
+        return match (self.value, self.passed) {
+            (None, _) => format!("could not be measured, expected within {}", limits),
+            (Some(value), true) => format!("{} is within {}", format_value(Some(value)), limits),
+            (Some(value), false) => format!("{} is outside {}", format_value(Some(value)), limits),
+        };
+    }
+}
+
+impl FileReport {
+    /// Returns the number of failed checks, excluding the errors.
+    pub fn failures(&self) -> usize {
+        return self.results.iter().filter(|result| !result.passed).count();
+    }
+
+    /// Returns whether there is no error and every check passed.
+    pub fn passed(&self) -> bool {
+        return self.errors.is_empty() && self.failures() == 0;
+    }
+}
+
+impl Check {
+    /// Returns the limits of the check.
+    pub fn limits(&self) -> SpecLimits {
+        return SpecLimits::new(self.min, self.max);
+    }
+
+    /// Evaluates the check on every selected step. A missing variable or step fails the check.
+    pub fn run(&self, simulation: &SteppedSimulation) -> Vec<CheckResult> {
+        let steps: Vec<u16> = match self.step {
+            Some(step) => vec![step],
+            None => (0..simulation.get_step_count().max(1) as u16).collect(),
+        };
+        let (from, to) = (self.from.unwrap_or(f64::NEG_INFINITY), self.to.unwrap_or(f64::INFINITY));
+        return steps
+            .into_iter()
+            .map(|step| {
+                let value = simulation
+                    .trace(&self.variable, step)
+                    .and_then(|trace| evaluate(&trace, self.measure, from, to));
+                CheckResult {
+                    check: self.name.clone(),
+                    step,
+                    value,
+                    limits: self.limits(),
+                    passed: value.is_some_and(|value| self.limits().contains(value)),
+                }
+            })
+            .collect();
+    }
+}
+
+impl Export {
+    /// Returns the format of the export, None for an unknown format.
+    pub fn batch_format(&self) -> Option<BatchFormat> {
+        return match self.format.to_ascii_lowercase().as_str() {
+            "json" => Some(BatchFormat::Json),
+            "raw" => Some(BatchFormat::Raw),
+            "lp" | "line_protocol" | "influx" => Some(BatchFormat::LineProtocol),
+            _ => None,
+        };
+    }
+}
+
+impl Config {
+    /// Parses a suite from TOML.
+    pub fn parse(text: &str) -> Result<Config, Box<dyn Error>> {
+        let config: Config = toml::from_str(text)?;
+        for export in config.exports.iter() {
+            if export.batch_format().is_none() {
+                Err(format!("Unknown export format '{}'.", export.format))?;
+            }
+        }
+        return Ok(config);
+    }
+
+    /// Reads a suite from a TOML file.
+    pub fn load(path: &Path) -> Result<Config, Box<dyn Error>> {
+        return Config::parse(&std::fs::read_to_string(path)?);
+    }
+
+    /// Returns the required variables missing from a simulation.
+    pub fn missing_variables(&self, simulation: &SteppedSimulation) -> Vec<String> {
+        return self
+            .variables
+            .iter()
+            .filter(|name| simulation.trace(name, 0).is_none())
+            .cloned()
+            .collect();
+    }
+
+    /// Evaluates every check on a simulation.
+    pub fn run(&self, simulation: &SteppedSimulation) -> Vec<CheckResult> {
+        return self.checks.iter().flat_map(|check| check.run(simulation)).collect();
+    }
+
+    /// Writes a simulation to every export directory, at the path of its raw file relative to
+    /// the working directory (e.g. "runs/a/tran.raw" to "<dir>/runs/a/tran.json"), so files with
+    /// the same name in different directories do not overwrite each other.
+    pub fn export(&self, simulation: &SteppedSimulation) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let root = std::env::current_dir().unwrap_or_default();
+        let mut written = Vec::new();
+        for export in self.exports.iter() {
+            let format = export.batch_format().ok_or(format!("Unknown export format '{}'.", export.format))?;
+            let path = output_path(&export.dir, &root, &simulation.provenance().path, format);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            format.write(simulation, &path)?;
+            written.push(path);
+        }
+        return Ok(written);
+    }
+
+    /// Loads a raw file and runs the whole suite on it: required variables, checks and exports.
+    pub fn check_file(&self, path: &Path) -> FileReport {
+        let mut report = FileReport {
+            path: path.to_path_buf(),
+            errors: Vec::new(),
+            results: Vec::new(),
+        };
+        let mut simulation = SteppedSimulation::new(path.to_path_buf());
+        if let Err(e) = simulation.reload() {
+            report.errors.push(format!("could not be loaded: {}", e));
+            return report;
+        }
+        for name in self.missing_variables(&simulation) {
+            report.errors.push(format!("missing variable {}", name));
+        }
+        report.results = self.run(&simulation);
+        if let Err(e) = self.export(&simulation) {
+            report.errors.push(format!("export failed: {}", e));
+        }
+        return report;
+    }
+}
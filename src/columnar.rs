@@ -0,0 +1,205 @@
+/*
+ * This file contains the compact columnar schema used for MessagePack / CBOR serialization
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+
+use crate::ordered::OrderedMap;
+use crate::provenance::Provenance;
+use crate::{Flags, Mode, SteppedSimulation, SteppedVariable, Value, VariableClass};
+
+/* #### Structs #### */
+
+/// A variable declaration of the columnar schema.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnarVariable {
+    pub name: String,
+    pub class: String,
+}
+
+/// One step of the columnar schema. Each column is stored as packed little-endian f64 bytes,
+/// the x axis first followed by the variables; `imaginary` is only present for complex data.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnarStep {
+    pub parameters: Vec<(String, f64)>,
+    pub real: Vec<ByteBuf>,
+    pub imaginary: Option<Vec<ByteBuf>>,
+}
+
+/// A compact, columnar representation of a whole simulation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Columnar {
+    pub mode: String,
+    pub date: Option<i64>,
+    pub variables: Vec<ColumnarVariable>,
+    pub steps: Vec<ColumnarStep>,
+    /// Absent from documents written before it was introduced.
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+    /// The date as written in the header, which `date` only keeps to the second.
+    #[serde(default)]
+    pub date_string: Option<String>,
+    /// The header flags, by their debug name.
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// The header 'Offset' field.
+    #[serde(default)]
+    pub offset: f64,
+}
+
+/* #### Functions #### */
+
+fn pack(values: impl Iterator<Item = f64>) -> ByteBuf {
+    return ByteBuf::from(values.flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>());
+}
+
+fn unpack(bytes: &[u8]) -> Vec<f64> {
+    return bytes
+        .chunks_exact(8)
+        .map(|a| f64::from_le_bytes(a.try_into().unwrap()))
+        .collect();
+}
+
+/* #### Implementations #### */
+
+impl From<&SteppedSimulation> for Columnar {
+    fn from(simulation: &SteppedSimulation) -> Self {
+        let complex = *simulation.get_mode() == Mode::AC || *simulation.get_mode() == Mode::FFT;
+
+        let mut steps = Vec::new();
+        for step in 0..simulation.get_step_count() as u16 {
+            let columns = simulation.get_columns(step).unwrap_or_default();
+            steps.push(ColumnarStep {
+                parameters: simulation.get_step_parameters(step).unwrap_or(&[]).to_vec(),
+                real: columns.iter().map(|(_, values)| pack(values.iter().map(|v| v.real()))).collect(),
+                imaginary: match complex {
+                    true => Some(columns.iter().map(|(_, values)| pack(values.iter().map(|v| v.imaginary()))).collect()),
+                    false => None,
+                },
+            });
+        }
+
+        return Columnar {
+            mode: simulation.get_mode().to_string(),
+            date: simulation.get_date().map(|date| date.timestamp()),
+            variables: simulation
+                .get_variables()
+                .iter()
+                .map(|variable| ColumnarVariable {
+                    name: variable.name().to_string(),
+                    class: format!("{:?}", variable.class()),
+                })
+                .collect(),
+            steps,
+            provenance: Some(simulation.provenance().clone()),
+            date_string: simulation.date_string.clone(),
+            flags: simulation.flags.iter().map(|flag| format!("{:?}", flag)).collect(),
+            offset: simulation.offset,
+        };
+    }
+}
+
+impl Columnar {
+    /// Rebuilds a simulation from its columnar representation.
+    pub fn into_simulation(self) -> Result<SteppedSimulation, Box<dyn Error>> {
+        let mut simulation = SteppedSimulation::new(PathBuf::new());
+        simulation.mode = Mode::from_name(&self.mode);
+        simulation.date = self.date.and_then(|date| DateTime::<Utc>::from_timestamp(date, 0));
+        simulation.date_string = self.date_string;
+        for flag in self.flags.iter() {
+            match Flags::from_name(flag) {
+                Some(flag) => simulation.flags.push(flag),
+                None => Err(format!("Unknown flag '{}'.", flag))?,
+            }
+        }
+        simulation.offset = self.offset;
+        if let Some(provenance) = self.provenance {
+            simulation.provenance = provenance;
+        }
+        simulation.variables = self
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| SteppedVariable {
+                class: VariableClass::from_name(&variable.class),
+                name: variable.name.clone(),
+                index: Some(index as u32 + 1),
+                derived: false,
+                data_type: None,
+            })
+            .collect();
+
+        let names: Vec<String> = std::iter::once("x".to_string())
+            .chain(self.variables.into_iter().map(|variable| variable.name))
+            .collect();
+        let mut data: OrderedMap<Vec<Vec<Value>>> = OrderedMap::new();
+        for (number, step) in self.steps.into_iter().enumerate() {
+            if step.real.len() != names.len() {
+                Err("The number of columns does not match the number of variables.")?;
+            }
+            if step.imaginary.as_ref().is_some_and(|imaginary| imaginary.len() != names.len()) {
+                Err("The number of imaginary columns does not match the number of variables.")?;
+            }
+            let points = step.real.first().map_or(0, |x| x.len() / 8);
+            for (index, (name, real)) in names.iter().zip(step.real.iter()).enumerate() {
+                let imaginary = step.imaginary.as_ref().map(|imaginary| &imaginary[index]);
+                if real.len() != points * 8 || imaginary.is_some_and(|imaginary| imaginary.len() != real.len()) {
+                    Err(format!("Column '{}' of step {} does not have {} points.", name, number, points))?;
+                }
+                let real = unpack(real);
+                let imaginary = match imaginary {
+                    Some(imaginary) => unpack(imaginary),
+                    None => vec![0.0; real.len()],
+                };
+                let values = real
+                    .into_iter()
+                    .zip(imaginary)
+                    .map(|(real, imaginary)| Value { real, imaginary })
+                    .collect();
+                data.get_or_default(name).push(values);
+            }
+            simulation.parameters.push(step.parameters);
+        }
+        simulation.data = data;
+        simulation.stats.variables = names.len() as u32;
+        let lengths: Vec<usize> = simulation.data.get("x").map_or(Vec::new(), |x| x.iter().map(|x| x.len()).collect());
+        simulation.stats.steps = lengths.len() as u16;
+        simulation.stats.points = lengths.iter().sum::<usize>() as u32;
+        simulation.stats.step_size = lengths.first().map_or(0, |length| *length as u32);
+
+        return Ok(simulation);
+    }
+}
+
+impl SteppedSimulation {
+    /// Serializes the simulation to MessagePack, using the compact columnar schema.
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        return Ok(rmp_serde::to_vec_named(&Columnar::from(self))?);
+    }
+
+    /// Deserializes a simulation written by `to_msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        return rmp_serde::from_slice::<Columnar>(bytes)?.into_simulation();
+    }
+
+    /// Serializes the simulation to CBOR, using the compact columnar schema.
+    #[cfg(feature = "cbor")]
+    pub fn to_cbor(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buffer = Vec::new();
+        ciborium::ser::into_writer(&Columnar::from(self), &mut buffer)?;
+        return Ok(buffer);
+    }
+
+    /// Deserializes a simulation written by `to_cbor`.
+    #[cfg(feature = "cbor")]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        return ciborium::de::from_reader::<Columnar, _>(bytes)?.into_simulation();
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * This file contains the comparison of a variable across several simulations
+ *
+ * The variable of every simulation is resampled onto the abscissa of the first one, clipped to
+ * the range covered by all of them, so that before/after design changes can be subtracted,
+ * quantified and plotted together.
+ */
+
+use crate::marker::Marker;
+use crate::svg::{self, Series};
+use crate::transform::interpolate;
+use crate::SteppedSimulation;
+
+/* #### Structs #### */
+
+/// A variable of several simulations resampled onto a common abscissa, see `overlay`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlay {
+    pub name: String,
+    /// Label of every simulation, its file name or "simulation <index>".
+    pub labels: Vec<String>,
+    /// The common abscissa.
+    pub x: Vec<f64>,
+    /// The real parts of the variable of every simulation, on the common abscissa.
+    pub values: Vec<Vec<f64>>,
+    /// The markers of the variable of every simulation within the common range, labelled with
+    /// the label of their simulation.
+    pub markers: Vec<Marker>,
+}
+
+/// The deviation of a simulation from the first one of an overlay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difference {
+    /// Largest absolute difference, and the abscissa at which it occurs.
+    pub max: (f64, f64),
+    /// RMS of the difference over the points of the common abscissa.
+    pub rms: f64,
+}
+
+/* #### Functions #### */
+
+/// Resamples the first step of a variable of every simulation onto the abscissa of the first
+/// simulation, within the range covered by all of them.
+/// Returns None if a simulation lacks the variable or the ranges do not overlap.
+pub fn overlay(simulations: &[SteppedSimulation], name: &str) -> Option<Overlay> {
+    let traces = simulations
+        .iter()
+        .map(|simulation| simulation.trace(name, 0))
+        .collect::<Option<Vec<_>>>()?;
+    let start = traces.iter().map(|trace| trace.x().first().map(|x| x.real())).collect::<Option<Vec<f64>>>()?;
+    let end = traces.iter().map(|trace| trace.x().last().map(|x| x.real())).collect::<Option<Vec<f64>>>()?;
+    let (start, end) = (start.into_iter().fold(f64::MIN, f64::max), end.into_iter().fold(f64::MAX, f64::min));
+    if start > end {
+        return None;
+    }
+
+    let x: Vec<f64> = traces[0]
+        .x()
+        .iter()
+        .map(|x| x.real())
+        .filter(|x| *x >= start && *x <= end)
+        .collect();
+    let values = traces
+        .iter()
+        .map(|trace| {
+            x.iter()
+                .map(|at| interpolate(trace.x(), trace.values(), *at).map_or(f64::NAN, |value| value.real()))
+                .collect()
+        })
+        .collect();
+    let labels: Vec<String> = simulations
+        .iter()
+        .enumerate()
+        .map(|(index, simulation)| match simulation.path.file_name() {
+            Some(file) => file.to_string_lossy().to_string(),
+            None => format!("simulation {}", index),
+        })
+        .collect();
+    let mut markers = Vec::new();
+    for (trace, label) in traces.iter().zip(labels.iter()) {
+        for marker in trace.markers().filter(|marker| marker.x >= start && marker.x <= end) {
+            markers.push(Marker {
+                label: format!("{} ({})", marker.label, label),
+                ..marker.clone()
+            });
+        }
+    }
+
+    return Some(Overlay {
+        name: name.to_string(),
+        labels,
+        x,
+        values,
+        markers,
+    });
+}
+
+/* #### Implementations #### */
+
+impl Overlay {
+    /// Returns the deviation of a simulation from the first one, None if the index is out of
+    /// range or the overlay is empty.
+    pub fn difference(&self, index: usize) -> Option<Difference> {
+        let (reference, values) = (self.values.first()?, self.values.get(index)?);
+        if self.x.is_empty() {
+            return None;
+        }
+        let mut max = (0.0, self.x[0]);
+        let mut sum = 0.0;
+        for ((x, a), b) in self.x.iter().zip(reference.iter()).zip(values.iter()) {
+            let delta = (b - a).abs();
+            if delta > max.0 {
+                max = (delta, *x);
+            }
+            sum += delta * delta;
+        }
+        return Some(Difference {
+            max,
+            rms: (sum / self.x.len() as f64).sqrt(),
+        });
+    }
+
+    /// Renders the overlaid variables as an SVG line plot, with their markers.
+    pub fn svg(&self, width: u32, height: u32) -> String {
+        let series: Vec<Series> = self
+            .labels
+            .iter()
+            .zip(self.values.iter())
+            .map(|(label, values)| Series {
+                label: format!("{} ({})", self.name, label),
+                points: self.x.iter().copied().zip(values.iter().copied()).collect(),
+            })
+            .collect();
+        return svg::plot_with_markers(&series, &self.markers, width, height);
+    }
+}
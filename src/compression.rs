@@ -0,0 +1,92 @@
+/*
+ * This file contains the transparent decompression of archived raw files
+ *
+ * Gzip files ('.raw.gz', feature "gzip") and zip archives (feature "zip") are recognized
+ * from their magic bytes. A zip archive must contain a raw file, and may contain its log file.
+ */
+
+use std::error::Error;
+#[cfg(any(feature = "gzip", feature = "zip"))]
+use std::io::Read;
+
+/* #### Constants #### */
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/* #### Structs #### */
+
+/// The raw file (and log file, if archived with it) extracted from a compressed input.
+pub(crate) struct Decompressed {
+    pub raw: Vec<u8>,
+    pub log: Option<Vec<u8>>,
+}
+
+/* #### Functions #### */
+
+/// Returns whether the name designates a compressed raw file, e.g. "tran.raw.gz" or "results.zip".
+pub(crate) fn is_compressed_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    return name.ends_with(".raw.gz") || name.ends_with(".qraw.gz") || name.ends_with(".zip");
+}
+
+/// Returns whether the contents are compressed, from their magic bytes.
+pub(crate) fn is_compressed(bytes: &[u8]) -> bool {
+    return bytes.starts_with(GZIP_MAGIC) || bytes.starts_with(ZIP_MAGIC);
+}
+
+/// Decompresses the input if it is compressed, otherwise returns it unchanged.
+pub(crate) fn decompress(bytes: Vec<u8>) -> Result<Decompressed, Box<dyn Error>> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        return gunzip(&bytes);
+    }
+    if bytes.starts_with(ZIP_MAGIC) {
+        return unzip(bytes);
+    }
+    return Ok(Decompressed { raw: bytes, log: None });
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> Result<Decompressed, Box<dyn Error>> {
+    let mut raw = Vec::new();
+    flate2::read::GzDecoder::new(bytes).read_to_end(&mut raw)?;
+    return Ok(Decompressed { raw, log: None });
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_: &[u8]) -> Result<Decompressed, Box<dyn Error>> {
+    Err("Gzip compressed raw files require the 'gzip' feature.")?
+}
+
+#[cfg(feature = "zip")]
+fn unzip(bytes: Vec<u8>) -> Result<Decompressed, Box<dyn Error>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+    let names: Vec<String> = archive.file_names().map(|name| name.to_string()).collect();
+
+    let raw_name = names
+        .iter()
+        .find(|name| {
+            let name = name.to_lowercase();
+            name.ends_with(".raw") || name.ends_with(".qraw")
+        })
+        .ok_or("The zip archive does not contain a raw file.")?;
+    let mut raw = Vec::new();
+    archive.by_name(raw_name)?.read_to_end(&mut raw)?;
+
+    // The log file has the same name as the raw file
+    let stem = &raw_name[..raw_name.rfind('.').unwrap_or(raw_name.len())];
+    let log_name = format!("{}.log", stem);
+    let mut log = None;
+    if let Some(log_name) = names.iter().find(|name| name.eq_ignore_ascii_case(&log_name)) {
+        let mut contents = Vec::new();
+        archive.by_name(log_name)?.read_to_end(&mut contents)?;
+        log = Some(contents);
+    }
+
+    return Ok(Decompressed { raw, log });
+}
+
+#[cfg(not(feature = "zip"))]
+fn unzip(_: Vec<u8>) -> Result<Decompressed, Box<dyn Error>> {
+    Err("Zip archived raw files require the 'zip' feature.")?
+}
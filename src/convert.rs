@@ -0,0 +1,52 @@
+/*
+ * This file contains the logarithmic unit conversions
+ *
+ * Voltages are RMS values for the power based units (dBm), magnitudes otherwise.
+ */
+
+use crate::trace::{Trace, TraceBuf};
+use crate::{Unit, Value};
+
+/* #### Functions #### */
+
+/// Converts an amplitude ratio to decibels (20·log10).
+pub fn db(ratio: f64) -> f64 {
+    return 20.0 * ratio.abs().log10();
+}
+
+/// Converts decibels to an amplitude ratio.
+pub fn from_db(db: f64) -> f64 {
+    return 10f64.powf(db / 20.0);
+}
+
+/// Converts an RMS voltage across the reference impedance `z0` (in ohms) to dBm.
+pub fn dbm(voltage: f64, z0: f64) -> f64 {
+    return 10.0 * (voltage * voltage / z0 / 1e-3).log10();
+}
+
+/// Converts dBm to the RMS voltage across the reference impedance `z0` (in ohms).
+pub fn from_dbm(dbm: f64, z0: f64) -> f64 {
+    return (10f64.powf(dbm / 10.0) * 1e-3 * z0).sqrt();
+}
+
+/// Converts a voltage to decibels relative to 1 V.
+pub fn dbv(voltage: f64) -> f64 {
+    return db(voltage);
+}
+
+/// Converts decibels relative to 1 V to a voltage.
+pub fn from_dbv(dbv: f64) -> f64 {
+    return from_db(dbv);
+}
+
+/* #### Implementations #### */
+
+impl<'a> Trace<'a> {
+    /// Returns the magnitude of the samples in decibels (20·log10, relative to 1 of the unit).
+    pub fn to_db(&self) -> TraceBuf {
+        let values = self.values().iter().map(|value| Value::from(db(value.abs()))).collect();
+        return self
+            .with_data(self.name().to_string(), self.x().to_vec(), values)
+            .with_unit(Unit::Decibel);
+    }
+}
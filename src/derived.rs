@@ -0,0 +1,122 @@
+/*
+ * This file contains the support for derived signals stored alongside the parsed variables
+ *
+ * Derived signals are regular variables of the dataset, so they are returned by
+ * `get`, `trace` and `query` and written by every exporter. Parsed variables are
+ * never overwritten: only derived signals can be replaced or removed.
+ */
+
+use std::error::Error;
+
+use crate::{SteppedSimulation, SteppedVariable, Value, VariableClass};
+
+/* #### Functions #### */
+
+// Infers the class of a derived signal from the LTSpice naming convention.
+fn class_from_name(name: &str) -> VariableClass {
+    if name.starts_with("V(") || name.starts_with("Vcm(") {
+        return VariableClass::Voltage;
+    }
+    if name.starts_with("I(") || name.starts_with("Ix(") {
+        return VariableClass::Current;
+    }
+    return VariableClass::Unknown;
+}
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Stores a computed signal in the dataset, one vector of values per step.
+    /// Every step must have as many points as the matching x axis.
+    /// A previously inserted derived signal with the same name is replaced.
+    pub fn insert_derived(&mut self, name: &str, steps: Vec<Vec<Value>>) -> Result<(), Box<dyn Error>> {
+        if name == "x" {
+            Err("The x axis cannot be replaced by a derived signal.")?;
+        }
+        if let Some(variable) = self.variables.iter().find(|variable| variable.name == name) {
+            if !variable.derived {
+                Err(format!("'{}' is a parsed variable and cannot be replaced.", name))?;
+            }
+        }
+        if steps.len() != self.get_step_count() {
+            Err(format!(
+                "Expected {} steps for '{}', got {}.",
+                self.get_step_count(),
+                name,
+                steps.len()
+            ))?;
+        }
+        for (step, values) in steps.iter().enumerate() {
+            let points = self.get("x", Some(step as u16)).map(|x| x.len()).unwrap_or(0);
+            if values.len() != points {
+                Err(format!(
+                    "Step {} of '{}' has {} points, the x axis has {}.",
+                    step,
+                    name,
+                    values.len(),
+                    points
+                ))?;
+            }
+        }
+
+        if !self.variables.iter().any(|variable| variable.name == name) {
+            self.variables.push(SteppedVariable {
+                class: class_from_name(name),
+                name: name.to_string(),
+                index: None,
+                derived: true,
+                data_type: None,
+            });
+        }
+        self.data.insert(name.to_string(), steps);
+
+        return Ok(());
+    }
+
+    /// Computes a derived signal point by point from existing variables and stores it.
+    /// The closure receives the values of the `inputs` at each point, in the same order.
+    ///
+    /// ```ignore
+    /// sim.derive("P(load)", &["V(out)", "I(Rload)"], |v| v[0] * v[1])?;
+    /// ```
+    pub fn derive<F>(&mut self, name: &str, inputs: &[&str], function: F) -> Result<(), Box<dyn Error>>
+    where
+        F: Fn(&[Value]) -> Value,
+    {
+        let mut steps = Vec::with_capacity(self.get_step_count());
+        let mut point = vec![Value::default(); inputs.len()];
+        for step in 0..self.get_step_count() as u16 {
+            let columns = inputs
+                .iter()
+                .map(|input| self.get(input, Some(step)).ok_or(format!("Unknown variable '{}'.", input)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let points = self.get("x", Some(step)).map(|x| x.len()).unwrap_or(0);
+
+            let mut values = Vec::with_capacity(points);
+            for index in 0..points {
+                for (value, column) in point.iter_mut().zip(columns.iter()) {
+                    *value = column.get(index).copied().unwrap_or_default();
+                }
+                values.push(function(&point));
+            }
+            steps.push(values);
+        }
+
+        return self.insert_derived(name, steps);
+    }
+
+    /// Removes a derived signal, returns false if there is no derived signal with that name.
+    pub fn remove_derived(&mut self, name: &str) -> bool {
+        match self.variables.iter().position(|variable| variable.derived && variable.name == name) {
+            Some(index) => {
+                self.variables.remove(index);
+                self.data.remove(name);
+                self.spilled.remove(name);
+                self.compacted.remove(name);
+                self.slabs.remove(name);
+                return true;
+            }
+            None => return false,
+        }
+    }
+}
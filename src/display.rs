@@ -0,0 +1,130 @@
+/*
+ * This file contains the rich display support for evcxr (Rust Jupyter kernel)
+ */
+
+use std::fmt::Write;
+
+use crate::marker::Marker;
+use crate::svg::{self, escape, Series};
+use crate::{downsample, SteppedSimulation};
+
+/* #### Constants #### */
+
+const WIDTH: u32 = 720;
+const HEIGHT: u32 = 320;
+const BUCKETS: usize = 600;
+const PREVIEW_VARIABLES: usize = 4;
+
+/* #### Structs #### */
+
+/// A plot of one variable across a set of steps, rendered inline by evcxr.
+pub struct TracePlot<'a> {
+    simulation: &'a SteppedSimulation,
+    name: String,
+    steps: Vec<u16>,
+}
+
+/* #### Functions #### */
+
+// Wraps HTML content in the evcxr display markers and prints it.
+fn emit(html: &str) {
+    println!("EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT", html);
+}
+
+// Downsamples a variable for the given steps into plot series.
+fn series(simulation: &SteppedSimulation, name: &str, steps: &[u16]) -> Vec<Series> {
+    let mut series = Vec::new();
+    for step in steps.iter() {
+        let x = simulation.get("x", Some(*step));
+        let y = simulation.get(name, Some(*step));
+        if let (Some(x), Some(y)) = (x, y) {
+            let label = if steps.len() > 1 {
+                simulation.get_unit(name).label(&format!("{} (step {})", name, step))
+            } else {
+                simulation.get_unit(name).label(name)
+            };
+            series.push(Series {
+                label,
+                points: downsample::min_max(x, y, None, BUCKETS),
+            });
+        }
+    }
+    return series;
+}
+
+/* #### Implementations #### */
+
+impl<'a> TracePlot<'a> {
+    /// Renders the plot as HTML for evcxr.
+    pub fn evcxr_display(&self) {
+        let markers: Vec<Marker> = self
+            .steps
+            .iter()
+            .flat_map(|step| self.simulation.markers(&self.name, *step))
+            .cloned()
+            .collect();
+        emit(&svg::plot_with_markers(
+            &series(self.simulation, &self.name, &self.steps),
+            &markers,
+            WIDTH,
+            HEIGHT,
+        ));
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns a plot of the specified variable across all steps, displayed inline by evcxr.
+    pub fn plot(&self, name: &str) -> TracePlot<'_> {
+        return TracePlot {
+            simulation: self,
+            name: name.to_string(),
+            steps: (0..self.get_step_count() as u16).collect(),
+        };
+    }
+
+    /// Returns a plot of the specified variable for a single step, displayed inline by evcxr.
+    pub fn plot_step(&self, name: &str, step: u16) -> TracePlot<'_> {
+        return TracePlot {
+            simulation: self,
+            name: name.to_string(),
+            steps: vec![step],
+        };
+    }
+
+    /// Renders an HTML summary table and a preview plot of the first variables for evcxr.
+    pub fn evcxr_display(&self) {
+        let mut html = String::new();
+        html.push_str("<table>");
+        write!(html, "<tr><th>Mode</th><td>{}</td></tr>", self.get_mode()).unwrap();
+        if let Some(date) = self.get_date_string() {
+            write!(html, "<tr><th>Date</th><td>{}</td></tr>", escape(date)).unwrap();
+        }
+        write!(html, "<tr><th>Steps</th><td>{}</td></tr>", self.get_step_count()).unwrap();
+        write!(
+            html,
+            "<tr><th>Points</th><td>{}</td></tr>",
+            self.get_x().map(|x| x.len()).unwrap_or(0)
+        )
+        .unwrap();
+        write!(html, "<tr><th>Variables</th><td>{}</td></tr>", self.get_variables().len()).unwrap();
+        for variable in self.get_variables().iter() {
+            write!(
+                html,
+                "<tr><td>{}</td><td>{:?}</td></tr>",
+                escape(variable.name()),
+                variable.class()
+            )
+            .unwrap();
+        }
+        html.push_str("</table>");
+
+        // Preview the first variables of the first step
+        let mut preview = Vec::new();
+        for variable in self.get_variables().iter().take(PREVIEW_VARIABLES) {
+            preview.extend(series(self, variable.name(), &[0]));
+        }
+        html.push_str(&svg::plot(&preview, WIDTH, HEIGHT));
+
+        emit(&html);
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * This file contains the downsampling routines used to display large traces
+ */
+
+use crate::Value;
+
+/* #### Functions #### */
+
+/// Reduces a trace to at most `buckets` min/max pairs, so that peaks survive the reduction.
+/// Only the points whose abscissa lies inside `range` are considered, if one is specified.
+/// Returns the decimated (x, y) pairs using the real part of the values.
+pub fn min_max(x: &[Value], y: &[Value], range: Option<(f64, f64)>, buckets: usize) -> Vec<(f64, f64)> {
+    return min_max_indices(x, y, range, buckets)
+        .into_iter()
+        .map(|index| (x[index].real(), y[index].real()))
+        .collect();
+}
+
+/// Same as `min_max`, but returns the indices of the kept points, in increasing order.
+pub fn min_max_indices(x: &[Value], y: &[Value], range: Option<(f64, f64)>, buckets: usize) -> Vec<usize> {
+
+    // Collect The Visible Points
+    let points: Vec<usize> = (0..x.len().min(y.len()))
+        .filter(|index| match range {
+            Some((start, end)) => x[*index].real() >= start && x[*index].real() <= end,
+            None => true,
+        })
+        .collect();
+
+    if buckets == 0 || points.len() <= buckets * 2 {
+        return points;
+    }
+
+    // Split the points in equally sized buckets and keep the extremes of each
+    let mut result: Vec<usize> = Vec::with_capacity(buckets * 2);
+    let bucket_size = (points.len() as f64 / buckets as f64).ceil() as usize;
+    for bucket in points.chunks(bucket_size) {
+        let mut min = bucket[0];
+        let mut max = bucket[0];
+        for index in bucket.iter() {
+            if y[*index].real() < y[min].real() {
+                min = *index;
+            }
+            if y[*index].real() > y[max].real() {
+                max = *index;
+            }
+        }
+
+        // Keep the abscissa order inside the bucket
+        result.push(min.min(max));
+        if max != min {
+            result.push(min.max(max));
+        }
+    }
+
+    return result;
+}
+
+/// Returns the (min, max) abscissa span of a trace, or None if the trace is empty.
+pub fn span(x: &[Value]) -> Option<(f64, f64)> {
+    let first = x.first()?.real();
+    let last = x.last()?.real();
+    return Some((first.min(last), first.max(last)));
+}
@@ -0,0 +1,76 @@
+/*
+ * This file contains the Arrow IPC (Feather v2) exporter
+ */
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, UInt16Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+
+use crate::{Mode, SteppedSimulation};
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Writes the simulation to an uncompressed Arrow IPC (Feather v2) file, one record batch per step.
+    /// The columns are `step`, `x` and one column per variable (real part); complex simulations
+    /// get an additional `<name>.imag` column per variable.
+    /// The analysis mode, date and provenance are stored in the schema metadata, units in the field metadata.
+    pub fn to_feather(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let complex = *self.get_mode() == Mode::AC || *self.get_mode() == Mode::FFT;
+
+        // Schema
+        let mut fields = vec![
+            Field::new("step", DataType::UInt16, false),
+            Field::new("x", DataType::Float64, false)
+                .with_metadata(HashMap::from([("unit".to_string(), self.get_unit("x").symbol().to_string())])),
+        ];
+        for variable in self.get_variables().iter() {
+            let unit = HashMap::from([("unit".to_string(), self.get_unit(variable.name()).symbol().to_string())]);
+            fields.push(Field::new(variable.name(), DataType::Float64, false).with_metadata(unit.clone()));
+            if complex {
+                fields.push(Field::new(format!("{}.imag", variable.name()), DataType::Float64, false).with_metadata(unit));
+            }
+        }
+        let metadata = HashMap::from([
+            ("mode".to_string(), self.get_mode().to_string()),
+            ("date".to_string(), self.get_date().map(|date| date.to_rfc3339()).unwrap_or_default()),
+            ("source".to_string(), self.provenance().path.to_string_lossy().to_string()),
+            ("source_hash".to_string(), self.provenance().hash_hex()),
+            ("parser_version".to_string(), self.provenance().parser_version.clone()),
+        ]);
+        let schema = Arc::new(Schema::new_with_metadata(fields, metadata));
+
+        // Record Batches
+        let mut writer = FileWriter::try_new(File::create(path)?, &schema)?;
+        for step in 0..self.get_step_count() as u16 {
+            let columns = match self.get_columns(step) {
+                Some(columns) => columns,
+                None => continue,
+            };
+            let points = columns[0].1.len();
+
+            let mut arrays: Vec<ArrayRef> = vec![Arc::new(UInt16Array::from(vec![step; points]))];
+            for (index, (_, values)) in columns.iter().enumerate() {
+                if values.len() != points {
+                    Err(format!("Step {} has columns of different lengths.", step))?;
+                }
+                arrays.push(Arc::new(Float64Array::from_iter_values(values.iter().map(|v| v.real()))));
+                if complex && index > 0 {
+                    arrays.push(Arc::new(Float64Array::from_iter_values(values.iter().map(|v| v.imaginary()))));
+                }
+            }
+
+            writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+        }
+        writer.finish()?;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,232 @@
+/*
+ * This file contains the rational approximation of frequency responses
+ *
+ * The response is fitted with H(s) = N(s) / D(s) using the Sanathanan-Koerner iteration,
+ * a linearized least squares fit reweighted by the previous denominator, and the poles and
+ * zeros are the roots of the fitted polynomials. Frequencies are scaled by the highest
+ * frequency of the trace to keep the problem well conditioned.
+ */
+
+use std::f64::consts::PI;
+
+use crate::trace::Trace;
+use crate::Value;
+
+/* #### Constants #### */
+
+const ITERATIONS: usize = 20;
+const ROOT_ITERATIONS: usize = 1000;
+
+/* #### Structs #### */
+
+/// A rational approximation of a frequency response, with its poles and zeros in rad/s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RationalFit {
+    /// Numerator coefficients, by increasing power of the scaled Laplace variable.
+    numerator: Vec<f64>,
+    /// Denominator coefficients, by increasing power of the scaled Laplace variable.
+    denominator: Vec<f64>,
+    /// Angular frequency used to scale the Laplace variable, in rad/s.
+    scale: f64,
+    poles: Vec<Value>,
+    zeros: Vec<Value>,
+}
+
+/* #### Functions #### */
+
+// Evaluates a real polynomial (increasing powers) at a complex point.
+fn polynomial(coefficients: &[f64], s: Value) -> Value {
+    return coefficients
+        .iter()
+        .rev()
+        .fold(Value::default(), |sum, coefficient| sum * s + Value::from(*coefficient));
+}
+
+// Solves the least squares problem min |A·x - b| with Householder reflections.
+// The matrix is stored by columns. Returns None if it is rank deficient.
+pub(crate) fn least_squares(mut columns: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let rows = b.len();
+    let count = columns.len();
+    if rows < count {
+        return None;
+    }
+
+    for j in 0..count {
+        let norm = columns[j][j..].iter().map(|value| value * value).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        let alpha = match columns[j][j] > 0.0 {
+            true => -norm,
+            false => norm,
+        };
+        let mut v = columns[j][j..].to_vec();
+        v[0] -= alpha;
+        let length = v.iter().map(|value| value * value).sum::<f64>();
+
+        // Reflect the remaining columns and the right hand side
+        for column in columns.iter_mut().skip(j).chain(std::iter::once(&mut b)) {
+            let dot: f64 = v.iter().zip(column[j..].iter()).map(|(v, c)| v * c).sum();
+            let factor = 2.0 * dot / length;
+            for (value, v) in column[j..].iter_mut().zip(v.iter()) {
+                *value -= factor * v;
+            }
+        }
+    }
+
+    // Back substitution
+    let mut x = vec![0.0; count];
+    for j in (0..count).rev() {
+        let sum: f64 = (j + 1..count).map(|k| columns[k][j] * x[k]).sum();
+        x[j] = (b[j] - sum) / columns[j][j];
+    }
+    if x.iter().any(|value| !value.is_finite()) {
+        return None;
+    }
+    return Some(x);
+}
+
+// Finds the complex roots of a real polynomial (increasing powers) with the Durand-Kerner method.
+// Multiple roots are found too, converging linearly instead of quadratically.
+fn roots(coefficients: &[f64]) -> Vec<Value> {
+    // Ignore the negligible leading coefficients, they only add roots at infinity
+    let largest = coefficients.iter().fold(0.0f64, |largest, c| largest.max(c.abs()));
+    let degree = match coefficients.iter().rposition(|c| c.abs() > largest * 1e-12) {
+        Some(degree) => degree,
+        None => return Vec::new(),
+    };
+    let monic: Vec<f64> = coefficients[..=degree].iter().map(|c| c / coefficients[degree]).collect();
+
+    let seed = Value::new(0.4, 0.9);
+    let mut roots: Vec<Value> = Vec::with_capacity(degree);
+    let mut root = Value::from(1.0);
+    for _ in 0..degree {
+        roots.push(root);
+        root = root * seed;
+    }
+
+    for _ in 0..ROOT_ITERATIONS {
+        let mut change: f64 = 0.0;
+        for i in 0..degree {
+            let mut product = Value::from(1.0);
+            for j in 0..degree {
+                if i != j {
+                    product = product * (roots[i] - roots[j]);
+                }
+            }
+            // Coinciding estimates would divide by zero: nudge this one apart, relative to its
+            // magnitude, and let the next iterations separate them
+            if product.abs() < f64::MIN_POSITIVE {
+                roots[i] = roots[i] + seed * (1e-6 * (1.0 + roots[i].abs()));
+                change = f64::INFINITY;
+                continue;
+            }
+            let step = polynomial(&monic, roots[i]) / product;
+            roots[i] = roots[i] - step;
+            change = change.max(step.abs());
+        }
+        if change < 1e-15 {
+            break;
+        }
+    }
+    return roots;
+}
+
+/// Fits a rational function of the specified order (number of poles) to an AC response,
+/// with the abscissa in Hz. The numerator has the same order, so direct feedthrough is allowed.
+/// Returns None if the trace has too few points for the order or the fit is degenerate.
+pub fn vector_fit(trace: &Trace<'_>, order: usize) -> Option<RationalFit> {
+    let points = trace.len();
+    let unknowns = 2 * order + 1;
+    if order == 0 || 2 * points < unknowns {
+        return None;
+    }
+
+    let scale = 2.0 * PI * trace.x()[..points].iter().fold(0.0f64, |max, f| max.max(f.real().abs()));
+    if scale == 0.0 {
+        return None;
+    }
+    let s: Vec<Value> = trace.x()[..points].iter().map(|f| Value::new(0.0, 2.0 * PI * f.real() / scale)).collect();
+    let h = &trace.values()[..points];
+
+    let mut numerator = vec![0.0; order + 1];
+    let mut denominator = vec![0.0; order + 1];
+    denominator[0] = 1.0;
+
+    for _ in 0..ITERATIONS {
+        // N(s) - H·(D(s) - 1) = H, weighted by the previous denominator
+        let mut columns = vec![Vec::with_capacity(2 * points); unknowns];
+        let mut b = Vec::with_capacity(2 * points);
+        for k in 0..points {
+            let weight = 1.0 / polynomial(&denominator, s[k]).abs();
+            let mut power = Value::from(weight);
+            for i in 0..=order {
+                columns[i].push(power.real());
+                columns[i].push(power.imaginary());
+                if i > 0 {
+                    let term = -(h[k] * power);
+                    columns[order + i].push(term.real());
+                    columns[order + i].push(term.imaginary());
+                }
+                power = power * s[k];
+            }
+            b.push(h[k].real() * weight);
+            b.push(h[k].imaginary() * weight);
+        }
+
+        let solution = least_squares(columns, b)?;
+        numerator.copy_from_slice(&solution[..=order]);
+        denominator[1..].copy_from_slice(&solution[order + 1..]);
+    }
+
+    let to_rad = |root: Value| root * scale;
+    return Some(RationalFit {
+        poles: roots(&denominator).into_iter().map(to_rad).collect(),
+        zeros: roots(&numerator).into_iter().map(to_rad).collect(),
+        numerator,
+        denominator,
+        scale,
+    });
+}
+
+/* #### Implementations #### */
+
+impl RationalFit {
+    /// Returns the poles of the fit, in rad/s.
+    pub fn poles(&self) -> &[Value] {
+        return &self.poles;
+    }
+
+    /// Returns the zeros of the fit, in rad/s.
+    pub fn zeros(&self) -> &[Value] {
+        return &self.zeros;
+    }
+
+    /// Returns the poles sorted by increasing magnitude, the dominant ones first.
+    pub fn dominant_poles(&self) -> Vec<Value> {
+        let mut poles = self.poles.clone();
+        poles.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+        return poles;
+    }
+
+    /// Evaluates the fitted response at a frequency, in Hz.
+    pub fn evaluate(&self, frequency: f64) -> Value {
+        let s = Value::new(0.0, 2.0 * PI * frequency / self.scale);
+        return polynomial(&self.numerator, s) / polynomial(&self.denominator, s);
+    }
+
+    /// Returns the RMS magnitude of the error between the fit and a trace, to judge the order.
+    pub fn rms_error(&self, trace: &Trace<'_>) -> f64 {
+        let points = trace.len();
+        if points == 0 {
+            return 0.0;
+        }
+        let sum: f64 = trace
+            .x()
+            .iter()
+            .zip(trace.values().iter())
+            .map(|(f, h)| (self.evaluate(f.real()) - *h).abs().powi(2))
+            .sum();
+        return (sum / points as f64).sqrt();
+    }
+}
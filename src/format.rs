@@ -0,0 +1,96 @@
+/*
+ * This file contains the engineering-notation formatting and parsing helpers
+ */
+
+use crate::Unit;
+
+/* #### Constants #### */
+
+const PREFIXES: [(i32, &str); 9] = [
+    (-15, "f"),
+    (-12, "p"),
+    (-9, "n"),
+    (-6, "µ"),
+    (-3, "m"),
+    (0, ""),
+    (3, "k"),
+    (6, "M"),
+    (9, "G"),
+];
+
+/* #### Functions #### */
+
+/// Formats a value with an SI prefix and 3 significant digits, e.g. "3.30 mV", "1.20 µs", "4.70 kΩ".
+pub fn engineering(value: f64, unit: Unit) -> String {
+    return engineering_digits(value, unit, 3);
+}
+
+/// Formats a value with an SI prefix and the specified number of significant digits.
+pub fn engineering_digits(value: f64, unit: Unit, digits: usize) -> String {
+    if !value.is_finite() || value == 0.0 {
+        return join(&format!("{:.*}", digits.saturating_sub(1), value), "", unit);
+    }
+
+    // Pick the largest prefix not above the value's order of magnitude
+    let exponent = value.abs().log10().floor() as i32;
+    let (prefix_exponent, prefix) = match PREFIXES.iter().rev().find(|(prefix_exponent, _)| *prefix_exponent <= exponent) {
+        Some(_) if exponent >= 12 => (12, "T"),
+        Some((prefix_exponent, prefix)) => (*prefix_exponent, *prefix),
+        None => PREFIXES[0],
+    };
+
+    let scaled = value / 10f64.powi(prefix_exponent);
+    let integer_digits = scaled.abs().log10().floor() as i32 + 1;
+    let mut decimals = (digits as i32 - integer_digits).max(0) as usize;
+    let mut number = format!("{:.*}", decimals, scaled);
+
+    // Rounding may carry into the next decade (e.g. 9.9996 -> 10.000), which takes one decimal
+    if decimals > 0 && number.parse::<f64>().is_ok_and(|rounded| rounded.abs() >= 10f64.powi(integer_digits)) {
+        decimals -= 1;
+        number = format!("{:.*}", decimals, scaled);
+    }
+
+    // Rounding may carry into the next prefix (e.g. 999.9 mV -> 1000 mV -> 1.00 V)
+    if number.trim_start_matches('-').starts_with("1000") && prefix_exponent < 12 {
+        return engineering_digits(value.signum() * 10f64.powi(prefix_exponent + 3), unit, digits);
+    }
+    return join(&number, prefix, unit);
+}
+
+fn join(number: &str, prefix: &str, unit: Unit) -> String {
+    let suffix = format!("{}{}", prefix, unit.symbol());
+    if suffix.is_empty() {
+        return number.to_string();
+    }
+    return format!("{} {}", number, suffix);
+}
+
+/// Parses a number with an optional SI suffix, following the SPICE conventions:
+/// suffixes are case insensitive, "m" is milli and "meg" is mega, trailing unit letters are
+/// ignored ("10kohm" -> 10000). e.g. "10k", "100n", "4.7u", "4.7µ", "1meg", "25".
+pub fn parse(value: &str) -> Option<f64> {
+    let value = value.trim().to_lowercase();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e'))
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier = if suffix.starts_with("meg") {
+        1e6
+    } else {
+        match suffix.chars().next() {
+            None => 1.0,
+            Some('t') => 1e12,
+            Some('g') => 1e9,
+            Some('k') => 1e3,
+            Some('m') => 1e-3,
+            Some('u') | Some('µ') | Some('μ') => 1e-6,
+            Some('n') => 1e-9,
+            Some('p') => 1e-12,
+            Some('f') => 1e-15,
+            Some(_) => 1.0,
+        }
+    };
+    return Some(number * multiplier);
+}
@@ -0,0 +1,127 @@
+/*
+ * This file contains the 2D view of simulations stepping two parameters
+ */
+
+use crate::trace::Trace;
+use crate::SteppedSimulation;
+
+/* #### Structs #### */
+
+/// The steps of a simulation arranged by the values of two '.step' parameters,
+/// e.g. a load resistance × temperature sweep. Axis values are sorted in increasing order.
+#[derive(Debug, Clone)]
+pub struct Grid<'a> {
+    simulation: &'a SteppedSimulation,
+    names: [String; 2],
+    first: Vec<f64>,
+    second: Vec<f64>,
+    // Step of every (first, second) cell, by rows of the first axis
+    steps: Vec<Option<u16>>,
+}
+
+/* #### Functions #### */
+
+fn same(a: f64, b: f64) -> bool {
+    return (a - b).abs() <= 1e-12 * a.abs().max(b.abs());
+}
+
+// Sorted unique values of an axis.
+fn axis(values: &[f64]) -> Vec<f64> {
+    let mut axis = values.to_vec();
+    axis.sort_by(|a, b| a.total_cmp(b));
+    axis.dedup_by(|a, b| same(*a, *b));
+    return axis;
+}
+
+fn position(axis: &[f64], value: f64) -> Option<usize> {
+    return axis.iter().position(|a| same(*a, value));
+}
+
+/* #### Implementations #### */
+
+impl<'a> Grid<'a> {
+    /// Returns the names of the two parameters, as written in the '.step' directives.
+    pub fn names(&self) -> (&str, &str) {
+        return (&self.names[0], &self.names[1]);
+    }
+
+    /// Returns the values of the first parameter.
+    pub fn first(&self) -> &[f64] {
+        return &self.first;
+    }
+
+    /// Returns the values of the second parameter.
+    pub fn second(&self) -> &[f64] {
+        return &self.second;
+    }
+
+    /// Returns the step at the specified indices of both axes, None if it was not simulated.
+    pub fn step(&self, first: usize, second: usize) -> Option<u16> {
+        if first >= self.first.len() || second >= self.second.len() {
+            return None;
+        }
+        return self.steps[first * self.second.len() + second];
+    }
+
+    /// Returns the step simulated with the specified parameter values.
+    pub fn step_at(&self, first: f64, second: f64) -> Option<u16> {
+        return self.step(position(&self.first, first)?, position(&self.second, second)?);
+    }
+
+    /// Returns a view of a variable at the specified indices of both axes.
+    pub fn trace(&self, name: &str, first: usize, second: usize) -> Option<Trace<'a>> {
+        return self.simulation.trace(name, self.step(first, second)?);
+    }
+
+    /// Returns the (first parameter value, step) pairs with the second parameter fixed at `second`.
+    pub fn along_first(&self, second: usize) -> Vec<(f64, u16)> {
+        return (0..self.first.len())
+            .filter_map(|first| Some((self.first[first], self.step(first, second)?)))
+            .collect();
+    }
+
+    /// Returns the (second parameter value, step) pairs with the first parameter fixed at `first`.
+    pub fn along_second(&self, first: usize) -> Vec<(f64, u16)> {
+        return (0..self.second.len())
+            .filter_map(|second| Some((self.second[second], self.step(first, second)?)))
+            .collect();
+    }
+}
+
+impl SteppedSimulation {
+    /// Arranges the steps by the values of two '.step' parameters (case insensitive).
+    /// Returns None if a step misses either parameter, or two steps share the same values
+    /// (i.e. a third parameter is stepped as well).
+    pub fn grid(&self, first: &str, second: &str) -> Option<Grid<'_>> {
+        let steps = self.get_step_count();
+        let mut names = [String::new(), String::new()];
+        let mut values = (Vec::with_capacity(steps), Vec::with_capacity(steps));
+
+        for step in 0..steps {
+            let parameters = self.get_step_parameters(step as u16)?;
+            let find = |name: &str| parameters.iter().find(|(parameter, _)| parameter.eq_ignore_ascii_case(name));
+            let (a, b) = (find(first)?, find(second)?);
+            names = [a.0.clone(), b.0.clone()];
+            values.0.push(a.1);
+            values.1.push(b.1);
+        }
+
+        let (first, second) = (axis(&values.0), axis(&values.1));
+        let mut cells = vec![None; first.len() * second.len()];
+        for step in 0..steps {
+            let cell = position(&first, values.0[step])? * second.len() + position(&second, values.1[step])?;
+            if cells[cell].is_some() {
+                return None;
+            }
+            cells[cell] = Some(step as u16);
+        }
+
+        return Some(Grid {
+            simulation: self,
+            names,
+            first,
+            second,
+            steps: cells,
+        });
+    }
+}
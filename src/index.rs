@@ -0,0 +1,365 @@
+/*
+ * This file contains the simulation corpus indexer
+ *
+ * Only the headers of the raw files (and the '.step' lines of their log files)
+ * are read, so that large archives can be catalogued quickly.
+ */
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::{log, parse_date, Mode};
+
+/* #### Constants #### */
+
+const CHUNK_SIZE: usize = 64 * 1024;
+const MAX_HEADER_SIZE: usize = 16 * 1024 * 1024;
+
+// Separators of the persisted catalog
+const FIELD_SEPARATOR: char = '\t';
+const LIST_SEPARATOR: char = '\u{1f}';
+
+/* #### Structs #### */
+
+/// The header information of one raw file.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub title: String,
+    pub date: Option<DateTime<Utc>>,
+    pub mode: Option<Mode>,
+    pub plotname: String,
+    pub variables: Vec<String>,
+    pub parameters: Vec<String>,
+    pub steps: usize,
+}
+
+/// A searchable catalog of raw files.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    entries: Vec<Entry>,
+}
+
+/// A catalog query, all the specified conditions must match.
+#[derive(Debug, Default)]
+pub struct Query {
+    mode: Option<Mode>,
+    variables: Vec<String>,
+    parameters: Vec<String>,
+    title: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+/* #### Functions #### */
+
+/// Reads the header of a raw file, stopping at the start of the data section.
+pub fn read_header(path: &Path) -> Result<String, Box<dyn Error>> {
+    // The header is either UTF8 or UTF16, with the data section starting after the marker
+    let mut markers: Vec<(Vec<u8>, usize)> = Vec::new();
+    for marker in ["Binary:", "Values:"] {
+        markers.push((marker.as_bytes().to_vec(), 1));
+        markers.push((marker.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect(), 2));
+    }
+    let overlap = markers.iter().map(|(marker, _)| marker.len() - 1).max().unwrap_or(0);
+
+    let mut file = File::open(path)?;
+    let mut buffer = Vec::new();
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+
+    loop {
+        let read = file.read(&mut chunk)?;
+        // Only the new bytes are searched, along with enough of the previous ones to catch a
+        // marker split across two reads
+        let start = buffer.len().saturating_sub(overlap);
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let end = markers
+            .iter()
+            .filter_map(|(marker, alignment)| {
+                return buffer[start..]
+                    .windows(marker.len())
+                    .enumerate()
+                    .map(|(index, window)| (start + index, window))
+                    .find(|(index, window)| index % alignment == 0 && window == marker)
+                    .map(|(index, _)| index);
+            })
+            .min();
+        if let Some(end) = end {
+            return Ok(log::decode(&buffer[..end]));
+        }
+
+        if read == 0 || buffer.len() > MAX_HEADER_SIZE {
+            Err("Could not find the end of the raw file header.")?;
+        }
+    }
+}
+
+fn parse_entry(path: &Path) -> Result<Entry, Box<dyn Error>> {
+    let header = read_header(path)?;
+    let mut entry = Entry {
+        path: path.to_path_buf(),
+        title: String::new(),
+        date: None,
+        mode: None,
+        plotname: String::new(),
+        variables: Vec::new(),
+        parameters: Vec::new(),
+        steps: 1,
+    };
+
+    let mut in_variables = false;
+    for line in header.lines() {
+        if in_variables && line.starts_with(char::is_whitespace) {
+            // Variable lines: "<index> <name> <type>"
+            if let Some(name) = line.split_whitespace().nth(1) {
+                entry.variables.push(name.to_string());
+            }
+            continue;
+        }
+        in_variables = false;
+
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        match key {
+            "Title" => entry.title = value.to_string(),
+            "Date" => entry.date = parse_date(value),
+            "Plotname" => {
+                entry.plotname = value.to_string();
+                entry.mode = Some(Mode::from_plotname(value));
+            }
+            "Variables" => in_variables = true,
+            _ => {}
+        }
+    }
+
+    // The step parameters are only available in the log file
+    let log_path = path.with_extension("log");
+    if let Ok(bytes) = fs::read(log_path) {
+        let steps = log::step_parameters(&log::decode(&bytes));
+        if !steps.is_empty() {
+            entry.steps = steps.len();
+            for (name, _) in steps[0].iter() {
+                entry.parameters.push(name.clone());
+            }
+        }
+    }
+
+    return Ok(entry);
+}
+
+// Escapes the separators (and line breaks) of a catalog field, reversed by `unescape`.
+fn escape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            FIELD_SEPARATOR => output.push_str("\\t"),
+            LIST_SEPARATOR => output.push_str("\\u"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            c => output.push(c),
+        }
+    }
+    return output;
+}
+
+fn unescape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => output.push(FIELD_SEPARATOR),
+            Some('u') => output.push(LIST_SEPARATOR),
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some(c) => output.push(c),
+            None => output.push('\\'),
+        }
+    }
+    return output;
+}
+
+/* #### Implementations #### */
+
+impl Catalog {
+    /// Recursively scans a directory for '.raw' and '.qraw' files and catalogs their headers.
+    /// Unreadable files are skipped with a warning.
+    pub fn scan(directory: &Path) -> Result<Catalog, Box<dyn Error>> {
+        let mut catalog = Catalog::default();
+        let mut pending = vec![directory.to_path_buf()];
+
+        while let Some(directory) = pending.pop() {
+            for item in fs::read_dir(&directory)? {
+                let item = item?;
+                let path = item.path();
+                // The file type does not follow symlinks, so linked directories (which may loop
+                // back to a parent) are not descended into
+                let file_type = item.file_type()?;
+                if file_type.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                if file_type.is_symlink() && path.is_dir() {
+                    debug!("Skipping the symlinked directory {:?}", path);
+                    continue;
+                }
+                let raw = |extension: &std::ffi::OsStr| {
+                    extension.eq_ignore_ascii_case("raw") || extension.eq_ignore_ascii_case("qraw")
+                };
+                if !path.extension().is_some_and(raw) {
+                    continue;
+                }
+                match parse_entry(&path) {
+                    Ok(entry) => catalog.entries.push(entry),
+                    Err(e) => warn!("Skipping {:?}: {}", path, e),
+                }
+            }
+        }
+
+        catalog.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        debug!("Indexed {} raw files.", catalog.entries.len());
+        return Ok(catalog);
+    }
+
+    /// Returns every catalogued entry.
+    pub fn entries(&self) -> &Vec<Entry> {
+        return &self.entries;
+    }
+
+    /// Returns the entries matching the query.
+    pub fn search(&self, query: &Query) -> Vec<&Entry> {
+        return self.entries.iter().filter(|entry| query.matches(entry)).collect();
+    }
+
+    /// Persists the catalog to a text file, one entry per line.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut file = File::create(path)?;
+        for entry in self.entries.iter() {
+            let fields = [
+                escape(&entry.path.to_string_lossy()),
+                escape(&entry.title),
+                entry.date.map(|date| date.timestamp().to_string()).unwrap_or_default(),
+                escape(&entry.plotname),
+                entry.steps.to_string(),
+                entry.variables.iter().map(|v| escape(v)).collect::<Vec<_>>().join(&LIST_SEPARATOR.to_string()),
+                entry.parameters.iter().map(|p| escape(p)).collect::<Vec<_>>().join(&LIST_SEPARATOR.to_string()),
+            ];
+            writeln!(file, "{}", fields.join(&FIELD_SEPARATOR.to_string()))?;
+        }
+        return Ok(());
+    }
+
+    /// Loads a catalog written by `save`.
+    pub fn load(path: &Path) -> Result<Catalog, Box<dyn Error>> {
+        let mut catalog = Catalog::default();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            let fields: Vec<&str> = line.split(FIELD_SEPARATOR).collect();
+            if fields.len() != 7 {
+                Err("Malformed catalog line.")?;
+            }
+            let list = |field: &str| -> Vec<String> {
+                match field.is_empty() {
+                    true => Vec::new(),
+                    false => field.split(LIST_SEPARATOR).map(unescape).collect(),
+                }
+            };
+            catalog.entries.push(Entry {
+                path: PathBuf::from(unescape(fields[0])),
+                title: unescape(fields[1]),
+                date: fields[2].parse::<i64>().ok().and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)),
+                mode: match fields[3].is_empty() {
+                    true => None,
+                    false => Some(Mode::from_plotname(&unescape(fields[3]))),
+                },
+                plotname: unescape(fields[3]),
+                steps: fields[4].parse()?,
+                variables: list(fields[5]),
+                parameters: list(fields[6]),
+            });
+        }
+        return Ok(catalog);
+    }
+}
+
+impl Query {
+    pub fn new() -> Self {
+        return Query::default();
+    }
+
+    /// Only match simulations of the specified analysis mode.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = Some(mode);
+        return self;
+    }
+
+    /// Only match simulations containing the specified variable.
+    pub fn variable(mut self, name: &str) -> Self {
+        self.variables.push(name.to_string());
+        return self;
+    }
+
+    /// Only match simulations stepping the specified parameter.
+    pub fn parameter(mut self, name: &str) -> Self {
+        self.parameters.push(name.to_lowercase());
+        return self;
+    }
+
+    /// Only match simulations whose title contains the text (case insensitive).
+    pub fn title(mut self, text: &str) -> Self {
+        self.title = Some(text.to_lowercase());
+        return self;
+    }
+
+    /// Only match simulations run at or after the specified date.
+    pub fn since(mut self, date: DateTime<Utc>) -> Self {
+        self.since = Some(date);
+        return self;
+    }
+
+    /// Only match simulations run before the specified date.
+    pub fn until(mut self, date: DateTime<Utc>) -> Self {
+        self.until = Some(date);
+        return self;
+    }
+
+    fn matches(&self, entry: &Entry) -> bool {
+        if self.mode.is_some() && self.mode != entry.mode {
+            return false;
+        }
+        if !self.variables.iter().all(|name| entry.variables.contains(name)) {
+            return false;
+        }
+        if !self.parameters.iter().all(|name| entry.parameters.iter().any(|p| p.to_lowercase() == *name)) {
+            return false;
+        }
+        if let Some(title) = &self.title {
+            if !entry.title.to_lowercase().contains(title) {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if entry.date.is_none_or(|date| date < since) {
+                return false;
+            }
+        }
+        if let Some(until) = self.until {
+            if entry.date.is_none_or(|date| date >= until) {
+                return false;
+            }
+        }
+        return true;
+    }
+}
@@ -0,0 +1,110 @@
+/*
+ * This file contains the InfluxDB line protocol exporter
+ */
+
+use std::error::Error;
+use std::io::Write;
+
+use crate::{Mode, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Escapes a measurement name.
+fn escape_measurement(value: &str) -> String {
+    return value.replace(',', "\\,").replace(' ', "\\ ");
+}
+
+// Escapes a tag key, tag value or field key.
+fn escape_key(value: &str) -> String {
+    return value.replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ");
+}
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Writes the simulation as InfluxDB line protocol, one line per point and step.
+    /// The simulation time is added to the simulation date (or the UNIX epoch if it is unknown,
+    /// or without the "chrono" feature)
+    /// to build nanosecond timestamps,
+    /// every variable becomes a field and the step index, '.step' parameters and hash of the
+    /// source file (`source`) become tags.
+    /// Only transient simulations can be exported, as the x axis must represent time.
+    pub fn write_line_protocol<W: Write>(&self, measurement: &str, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        if *self.get_mode() != Mode::Transient {
+            Err("Only transient simulations can be exported as line protocol.")?;
+        }
+
+        #[cfg(feature = "chrono")]
+        let start = match self.get_date().map_or(Some(0), |date| date.timestamp_nanos_opt()) {
+            Some(start) => start,
+            None => Err("The simulation date is out of the representable timestamp range.")?,
+        };
+        #[cfg(not(feature = "chrono"))]
+        let start: i64 = 0;
+        let measurement = escape_measurement(measurement);
+        let fields: Vec<String> = self
+            .get_variables()
+            .iter()
+            .map(|variable| escape_key(variable.name()))
+            .collect();
+
+        for step in 0..self.get_step_count() as u16 {
+            let columns = match self.get_columns(step) {
+                Some(columns) => columns,
+                None => continue,
+            };
+
+            // Tags
+            let mut tags = format!("{},source={},step={}", measurement, self.provenance().hash_hex(), step);
+            for (name, value) in self.get_step_parameters(step).unwrap_or(&[]).iter() {
+                tags.push_str(&format!(",{}={}", escape_key(name), value));
+            }
+
+            // One line per point, skipping the x column
+            for (point, x) in columns[0].1.iter().enumerate() {
+                let mut line = tags.clone();
+                line.push(' ');
+                let mut first = true;
+                for (field, (_, values)) in fields.iter().zip(columns.iter().skip(1)) {
+                    let value = match values.get(point) {
+                        Some(value) if value.real().is_finite() => value.real(),
+                        _ => continue,
+                    };
+                    if !first {
+                        line.push(',');
+                    }
+                    first = false;
+                    line.push_str(&format!("{}={:e}", field, value));
+                }
+                if first {
+                    continue;
+                }
+                let timestamp = start + (x.real() * 1e9).round() as i64;
+                writeln!(writer, "{} {}", line, timestamp)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Returns the simulation as InfluxDB line protocol, see `write_line_protocol`.
+    pub fn to_line_protocol(&self, measurement: &str) -> Result<String, Box<dyn Error>> {
+        let mut buffer = Vec::new();
+        self.write_line_protocol(measurement, &mut buffer)?;
+        return Ok(String::from_utf8(buffer)?);
+    }
+
+    /// Writes the simulation to an InfluxDB 2.x server through its HTTP API.
+    #[cfg(feature = "influx-http")]
+    pub fn write_influx(&self, url: &str, org: &str, bucket: &str, token: &str, measurement: &str) -> Result<(), Box<dyn Error>> {
+        let body = self.to_line_protocol(measurement)?;
+        ureq::post(&format!("{}/api/v2/write", url.trim_end_matches('/')))
+            .query("org", org)
+            .query("bucket", bucket)
+            .query("precision", "ns")
+            .set("Authorization", &format!("Token {}", token))
+            .set("Content-Type", "text/plain; charset=utf-8")
+            .send_string(&body)?;
+        return Ok(());
+    }
+}
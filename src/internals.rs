@@ -0,0 +1,56 @@
+/*
+ * This file exposes the building blocks of the parser, for benchmarks and alternative front-ends
+ *
+ * `SteppedSimulation::parse_bytes` is the composition of these functions: split the header,
+ * read its fields, decode the points and segment them in steps. They are public so they can be
+ * benchmarked (e.g. with criterion) and reused on their own, but they follow the parser: their
+ * signatures may change in any release, unlike the rest of the API.
+ */
+
+use std::ops::Range;
+
+use crate::ordered::OrderedMap;
+use crate::{Encoding, Mode, StepRule, Value};
+
+pub use ltspice_decode::{read_sample, DataType, Decoded, Layout, CHECK_POINTS};
+
+/* #### Functions #### */
+
+/// Detects the encoding of a binary raw file (UTF8 or UTF16) and returns it with the length of
+/// the header in bytes, up to and including the "Binary:" line. The samples start right after it.
+pub fn split_header(bytes: &[u8]) -> Option<(Encoding, usize)> {
+    let (encoding, _) = crate::detect_encoding(bytes)?;
+    let length = crate::header_length(bytes, &encoding)?;
+    return Some((encoding, length));
+}
+
+/// Splits a decoded header into its "Key: value" fields, in file order. Continuation lines
+/// (e.g. the variable list) are appended to the value of the previous field.
+pub fn header_fields(header: &str) -> OrderedMap<String> {
+    return crate::header_fields(header);
+}
+
+/// Decodes the complete points of the samples of a raw file, split in steps with the rule of the
+/// analysis. Unlike the parser, nothing is validated and the decoding cannot be cancelled.
+pub fn decode_columns(bytes: &[u8], layout: &Layout, mode: &Mode) -> Decoded<Value> {
+    let mut rule = StepRule::new(mode);
+    let is_new_step = |first: &Value, previous: &Value, current: &Value| rule.is_new_step(first, previous, current);
+    return ltspice_decode::decode(bytes, layout, is_new_step, || false).unwrap_or_else(|| unreachable!());
+}
+
+/// Returns the index range of every step of a flat x axis, with the rule of the analysis.
+pub fn segment_steps(x: &[Value], mode: &Mode) -> Vec<Range<usize>> {
+    let mut rule = StepRule::new(mode);
+    let mut steps = Vec::new();
+    let mut start = 0;
+    for index in 1..x.len() {
+        if rule.is_new_step(&x[start], &x[index - 1], &x[index]) {
+            steps.push(start..index);
+            start = index;
+        }
+    }
+    if !x.is_empty() {
+        steps.push(start..x.len());
+    }
+    return steps;
+}
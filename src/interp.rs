@@ -0,0 +1,50 @@
+/*
+ * This file contains the interpolation of traces between the steps of a parameter sweep
+ */
+
+use crate::trace::TraceBuf;
+use crate::transform::interpolate;
+use crate::SteppedSimulation;
+
+/* #### Functions #### */
+
+/// Estimates a variable at an intermediate '.step' parameter value by linear interpolation
+/// between the two steps with the nearest values, e.g. Rload=42 from the 40 and 45 steps.
+/// The samples of the upper step are interpolated on the x axis of the lower one, points
+/// outside of the upper step are dropped. A step matching the value exactly is returned as is.
+/// Returns None if the value is outside the swept range or the variable is unknown.
+pub fn between_steps(simulation: &SteppedSimulation, name: &str, parameter: &str, value: f64) -> Option<TraceBuf> {
+    // (parameter value, step), sorted by value
+    let mut steps: Vec<(f64, u16)> = simulation
+        .traces(name)
+        .iter()
+        .filter_map(|trace| Some((trace.parameter(parameter)?, trace.step())))
+        .filter(|(value, _)| value.is_finite())
+        .collect();
+    steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    if let Some((_, step)) = steps.iter().find(|(step_value, _)| *step_value == value) {
+        return Some(simulation.trace(name, *step)?.to_buf());
+    }
+    let upper = steps.partition_point(|(step_value, _)| *step_value < value);
+    if upper == 0 || upper == steps.len() {
+        return None;
+    }
+    let (low_value, low_step) = steps[upper - 1];
+    let (high_value, high_step) = steps[upper];
+
+    let low = simulation.trace(name, low_step)?;
+    let high = simulation.trace(name, high_step)?;
+    let ratio = (value - low_value) / (high_value - low_value);
+
+    let mut x = Vec::with_capacity(low.len());
+    let mut values = Vec::with_capacity(low.len());
+    for (at, sample) in low.x().iter().zip(low.values().iter()) {
+        if let Some(other) = interpolate(high.x(), high.values(), at.real()) {
+            x.push(*at);
+            values.push(*sample + (other - *sample) * ratio);
+        }
+    }
+
+    return Some(low.with_data(low.name().to_string(), x, values).with_parameter(parameter, value));
+}
@@ -0,0 +1,157 @@
+/*
+ * This file contains a small, dependency-free JSON writer for simulation data
+ */
+
+use std::fmt::Write;
+
+use crate::{Mode, SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+/// Serializes a simulation to a JSON document of the form
+/// `{"mode": .., "date": .., "provenance": {..}, "variables": [..], "markers": [..], "steps": [{"x": [..], "V(out)": [..]}, ..]}`.
+/// The date is written in RFC 3339, or as found in the header without the "chrono" feature.
+/// Complex data (AC/FFT) is written as `[real, imaginary]` pairs, real data as plain numbers.
+pub fn simulation(simulation: &SteppedSimulation) -> String {
+    let complex = *simulation.get_mode() == Mode::AC || *simulation.get_mode() == Mode::FFT;
+
+    let mut output = String::new();
+    output.push('{');
+    write!(output, "\"mode\":{},", string(&simulation.get_mode().to_string())).unwrap();
+    #[cfg(feature = "chrono")]
+    let date = simulation.get_date().map(|date| date.to_rfc3339());
+    #[cfg(not(feature = "chrono"))]
+    let date = simulation.get_date_string().map(|date| date.to_string());
+    match date {
+        Some(date) => write!(output, "\"date\":{},", string(&date)).unwrap(),
+        None => output.push_str("\"date\":null,"),
+    }
+
+    // Provenance
+    let provenance = simulation.provenance();
+    write!(
+        output,
+        "\"provenance\":{{\"path\":{},\"size\":{},\"modified\":{},\"hash\":{},\"parser_version\":{}}},",
+        string(&provenance.path.to_string_lossy()),
+        provenance.size,
+        provenance.modified.map_or("null".to_string(), |modified| modified.to_string()),
+        string(&provenance.hash_hex()),
+        string(&provenance.parser_version)
+    )
+    .unwrap();
+
+    // Variables
+    output.push_str("\"variables\":[");
+    for (index, variable) in simulation.get_variables().iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write!(
+            output,
+            "{{\"name\":{},\"class\":{},\"unit\":{}}}",
+            string(variable.name()),
+            string(&format!("{:?}", variable.class())),
+            string(simulation.get_unit(variable.name()).symbol())
+        )
+        .unwrap();
+    }
+    output.push_str("],");
+
+    // Markers
+    output.push_str("\"markers\":[");
+    for (index, marker) in simulation.get_markers().iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write!(
+            output,
+            "{{\"variable\":{},\"step\":{},\"x\":{},\"value\":{},\"label\":{}}}",
+            string(&marker.variable),
+            marker.step,
+            number(marker.x),
+            number(marker.value),
+            string(&marker.label)
+        )
+        .unwrap();
+    }
+    output.push_str("],");
+
+    // Steps
+    output.push_str("\"steps\":[");
+    for step in 0..simulation.get_step_count() {
+        if step > 0 {
+            output.push(',');
+        }
+        output.push('{');
+        let columns = simulation.get_columns(step as u16).unwrap_or_default();
+        for (index, (name, values)) in columns.iter().enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            write!(output, "{}:{}", string(name), trace(values, complex)).unwrap();
+        }
+        output.push('}');
+    }
+    output.push_str("]}");
+
+    return output;
+}
+
+/// Serializes a list of values to a JSON array.
+pub fn trace(values: &[Value], complex: bool) -> String {
+    let mut output = String::with_capacity(values.len() * 12);
+    output.push('[');
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        if complex {
+            write!(output, "[{},{}]", number(value.real()), number(value.imaginary())).unwrap();
+        } else {
+            output.push_str(&number(value.real()));
+        }
+    }
+    output.push(']');
+    return output;
+}
+
+/// Serializes a list of (x, y) points, such as a downsampled trace, to a JSON array of pairs.
+pub fn points(points: &[(f64, f64)]) -> String {
+    let mut output = String::with_capacity(points.len() * 24);
+    output.push('[');
+    for (index, (x, y)) in points.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write!(output, "[{},{}]", number(*x), number(*y)).unwrap();
+    }
+    output.push(']');
+    return output;
+}
+
+/// Formats a number as JSON, non-finite values become `null`.
+pub fn number(value: f64) -> String {
+    if value.is_finite() {
+        return format!("{:e}", value);
+    }
+    return "null".to_string();
+}
+
+/// Formats a string as a quoted and escaped JSON string.
+pub fn string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    return output;
+}
@@ -3,25 +3,139 @@
  */
 
 use core::panic;
-use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
 use std::{io::Read};
 // Global Imports
+use std::cmp::Ordering;
 use std::error::Error;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
+use std::ops::{Add, Div, Index, Mul, Neg, Sub};
 use std::path::PathBuf;
 use std::vec::Vec;
 
-use chrono::{DateTime, Utc};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDateTime, Utc};
 
-use regex::Regex;
+use tracing::field;
+use tracing::{debug_span, error, instrument, warn};
 
-use tracing::{debug, error, warn};
+use ltspice_decode::{Decoded, Layout, Sample};
+
+use crate::metrics::{ParseMetrics, Stopwatch};
+use crate::options::{Allocator, LoadOptions, Precision};
+use crate::ordered::OrderedMap;
+
+pub use ltspice_decode::DataType;
 
 // Local Imports
+pub mod ac;
+pub mod alias;
+pub mod analysis;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod branches;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+pub mod compare;
+mod compression;
+pub mod convert;
+pub mod derived;
+pub mod downsample;
+pub mod fit;
+pub mod format;
+pub mod grid;
+#[cfg(all(not(target_arch = "wasm32"), feature = "chrono"))]
+pub mod index;
+pub mod influx;
+pub mod internals;
+pub mod interp;
+pub mod json;
+pub mod log;
+mod lru;
+pub mod marker;
+pub mod mc;
+pub mod measure;
+pub mod merge;
+pub mod metrics;
+pub mod noise;
+pub mod options;
+pub mod ordered;
+pub mod power;
+mod preview;
+pub mod provenance;
+pub mod pwm;
+pub mod query;
+pub mod raw;
+pub mod report;
+pub mod rf;
+pub mod shared;
+pub mod signal;
+mod slab;
+pub mod spectral;
+mod spill;
+pub mod stability;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod stream;
+pub mod subset;
+pub mod summary;
+pub mod svg;
+pub mod testing;
+pub mod trace;
+pub mod transform;
+pub mod untrusted;
+pub mod validate;
+pub mod window;
+pub mod writer;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+#[cfg(feature = "evcxr")]
+pub mod display;
+
+#[cfg(feature = "ndarray")]
+pub mod arrays;
+
+#[cfg(feature = "nalgebra")]
+pub mod matrix;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub mod columnar;
+
+#[cfg(feature = "feather")]
+pub mod feather;
+
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+pub mod live;
+
+#[cfg(all(feature = "cli", not(target_arch = "wasm32")))]
+pub mod checks;
+
+#[cfg(feature = "serve")]
+pub mod serve;
+
+#[cfg(feature = "approx")]
+mod tolerance;
+
+#[cfg(feature = "tui")]
+pub mod tui;
 
 /* #### Enums #### */
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Mode {
     Transient,
     FFT,
@@ -29,22 +143,27 @@ pub enum Mode {
     DC,
     Noise,
     OperatingPoint,
+    /// An analysis this library does not know, with its plotname.
+    Other(String),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum FileType {
     Binary,
     ASCII,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum DataType {
-    Float32,
-    Float64,
-    Complex128,
+/// The simulator (and version family) that produced a raw file, see `SteppedSimulation::source`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Source {
+    LtspiceXVII,
+    Ltspice24,
+    Ngspice,
+    Qspice,
+    Unknown,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Encoding {
     UTF8,
     UTF16,
@@ -52,14 +171,18 @@ pub enum Encoding {
     ASCII,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Flags {
     Stepped,
     Real,
+    Complex,
     Double,
+    Forward,
+    Log,
+    FastAccess,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum VariableClass {
     Voltage,
     Current,
@@ -67,15 +190,33 @@ pub enum VariableClass {
     Unknown,
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Unit {
+    Volt,
+    Ampere,
+    Second,
+    Hertz,
+    Watt,
+    Ohm,
+    Siemens,
+    VoltPerRootHertz,
+    AmperePerRootHertz,
+    Decibel,
+    Dimensionless,
+}
+
 /* #### Structs #### */
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SteppedVariable {
     class: VariableClass,
     name: String,
+    index: Option<u32>,
+    derived: bool,
+    data_type: Option<DataType>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Value {
     real: f64,
     imaginary: f64,
@@ -87,7 +228,7 @@ impl PartialEq for Value {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SimulationStats {
     variables: u32,
     points: u32,
@@ -95,20 +236,537 @@ pub struct SimulationStats {
     step_size: u32
 }
 
-#[derive(Debug)]
+/// Splits the x axis of an analysis in steps, point by point, see `StepRule::is_new_step`.
+#[derive(Debug, Clone)]
+pub struct StepRule {
+    mode: Mode,
+    // Direction of the first move of the current step, Equal until it moved
+    direction: Ordering,
+    // Whether the current step moved against its first direction
+    reversed: bool,
+    // Whether the current step went back to its first value after reversing
+    closed: bool,
+}
+
+/// Iterator over the variables of a simulation and their per-step data, in header order.
+pub struct Iter<'a> {
+    simulation: &'a SteppedSimulation,
+    index: usize,
+}
+
+#[derive(Debug, Clone)]
 pub struct SteppedSimulation {
     path: PathBuf,
     encoding: Encoding,
     mode: Mode,
     flags: Vec<Flags>,
-    date: DateTime<Utc>,
+    #[cfg(feature = "chrono")]
+    date: Option<DateTime<Utc>>,
+    date_string: Option<String>,
+    offset: f64,
+    source: Source,
     stats: SimulationStats,
     variables: Vec<SteppedVariable>,
-    data: HashMap<String, Vec<Vec<Value>>>,
+    data: OrderedMap<Vec<Vec<Value>>>,
+    spilled: OrderedMap<spill::SpilledColumn>,
+    compacted: OrderedMap<preview::CompactColumn>,
+    slabs: OrderedMap<slab::SlabColumn>,
+    cache_budget: Option<usize>,
+    column_stats: OrderedMap<Vec<summary::ColumnStats>>,
+    parameters: Vec<Vec<(String, f64)>>,
+    aliases: OrderedMap<String>,
+    renames: OrderedMap<String>,
+    options: LoadOptions,
+    warnings: Vec<String>,
+    metrics: ParseMetrics,
+    markers: Vec<marker::Marker>,
+    provenance: provenance::Provenance,
+}
+
+/* #### Functions #### */
+
+/// Parses the date of a raw file header, as written by LTSpice and ngspice:
+/// "Sat Apr 13 14:06:22 2024". Returns None for any other format.
+#[cfg(feature = "chrono")]
+pub fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.split_whitespace().collect::<Vec<&str>>().join(" ");
+    return NaiveDateTime::parse_from_str(&value, "%a %b %d %H:%M:%S %Y")
+        .ok()
+        .map(|date| date.and_utc());
+}
+
+// Recognizes the bytes following the data of a plot which can safely be ignored:
+// padding (zeros or whitespace, e.g. a trailing newline) or the header of another plot.
+fn trailing_data(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.iter().all(|byte| *byte == 0 || byte.is_ascii_whitespace()) {
+        return Some("padding");
+    }
+    let head = log::decode(&bytes[..bytes.len().min(256)]);
+    let head = head.trim_start_matches(|c: char| c == '\0' || c.is_whitespace());
+    if head.starts_with("Title:") || head.starts_with("Plotname:") {
+        return Some("the header of a following plot");
+    }
+    return None;
+}
+
+// Decodes a raw file as UTF8, then as UTF16, until the header markers are found.
+// Returns the encoding and the (lossy) decoded text.
+pub(crate) fn detect_encoding(buffer: &[u8]) -> Option<(Encoding, String)> {
+    let data = String::from_utf8_lossy(buffer).to_string();
+    if data.contains("Values") || data.contains("Binary") {
+        return Some((Encoding::UTF8, data));
+    }
+
+    let units: Vec<u16> = buffer.chunks_exact(2).map(|a| u16::from_le_bytes([a[0], a[1]])).collect();
+    let data = String::from_utf16_lossy(units.as_slice());
+    if data.contains("Values") || data.contains("Binary") {
+        return Some((Encoding::UTF16, data));
+    }
+    return None;
+}
+
+// Returns the length of the header of a raw file in an unknown encoding, or the whole length
+// without a binary section. The marker of the encoding suggested by the first bytes is searched
+// first, so that the binary section is only scanned when the file is not binary.
+fn header_end(buffer: &[u8]) -> usize {
+    let encodings = match buffer.get(1) {
+        Some(0) => [Encoding::UTF16, Encoding::UTF8],
+        _ => [Encoding::UTF8, Encoding::UTF16],
+    };
+    return encodings.iter().find_map(|encoding| header_length(buffer, encoding)).unwrap_or(buffer.len());
+}
+
+// Returns the length of the header in the buffer, up to and including the "Binary:" line.
+pub(crate) fn header_length(buffer: &[u8], encoding: &Encoding) -> Option<usize> {
+    let (marker, alignment): (Vec<u8>, usize) = match encoding {
+        Encoding::UTF16 => ("Binary:\n".encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect(), 2),
+        Encoding::UTF32 => ("Binary:\n".chars().flat_map(|c| (c as u32).to_le_bytes()).collect(), 4),
+        Encoding::UTF8 | Encoding::ASCII => (b"Binary:\n".to_vec(), 1),
+    };
+    let position = buffer
+        .windows(marker.len())
+        .step_by(alignment)
+        .position(|window| window == marker.as_slice())?;
+    return Some(position * alignment + marker.len());
+}
+
+// Splits a raw file header into its "Key: value" fields.
+// Lines starting with whitespace continue the previous field, e.g. the "Variables" list.
+// The header ends at the "Binary:" line.
+pub(crate) fn header_fields(header: &str) -> OrderedMap<String> {
+    let mut fields: OrderedMap<String> = OrderedMap::new();
+    let mut current: Option<String> = None;
+    for line in header.lines() {
+        let field = match line.starts_with(char::is_whitespace) {
+            true => None,
+            false => line.split_once(':').filter(|(key, _)| {
+                key.ends_with(|c: char| c.is_ascii_alphabetic())
+                    && key.chars().all(|c| c.is_ascii_alphabetic() || c == ' ' || c == '.')
+            }),
+        };
+
+        match (field, &current) {
+            (Some(("Binary", _)), _) => break,
+            (Some((key, value)), _) => {
+                fields.insert(key.to_string(), value.to_string());
+                current = Some(key.to_string());
+            }
+            (None, Some(key)) => {
+                if let Some(value) = fields.get_mut(key) {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    return fields;
 }
 
 /* #### Implementations #### */
 
+impl Mode {
+    // Returns the mode matching a header 'Plotname' value, as written by LTSpice and ngspice,
+    // e.g. "Noise Spectral Density - (V/Hz½ or A/Hz½)". Unknown plotnames are kept as `Other`.
+    pub(crate) fn from_plotname(plotname: &str) -> Mode {
+        let plotname = plotname.trim();
+        let lowercase = plotname.to_lowercase();
+        let starts = |prefixes: &[&str]| prefixes.iter().any(|prefix| lowercase.starts_with(prefix));
+
+        if starts(&["transient"]) {
+            return Mode::Transient;
+        }
+        if starts(&["ac analysis", "ac small signal"]) {
+            return Mode::AC;
+        }
+        if starts(&["dc transfer characteristic", "dc analysis", "dc sweep"]) {
+            return Mode::DC;
+        }
+        if starts(&["noise", "integrated noise"]) {
+            return Mode::Noise;
+        }
+        if starts(&["operating point"]) {
+            return Mode::OperatingPoint;
+        }
+        if starts(&["fft"]) {
+            return Mode::FFT;
+        }
+        return Mode::Other(plotname.to_string());
+    }
+
+    // Returns the mode matching its name, as written by the exporters (see `Display`).
+    pub(crate) fn from_name(name: &str) -> Mode {
+        return match name {
+            "Transient" => Mode::Transient,
+            "FFT" => Mode::FFT,
+            "AC" => Mode::AC,
+            "DC" => Mode::DC,
+            "Noise" => Mode::Noise,
+            "OperatingPoint" => Mode::OperatingPoint,
+            other => Mode::Other(other.to_string()),
+        };
+    }
+
+    // Returns whether a point starts a new step, given the first and previous points of the
+    // current step. Each analysis restarts its sweep differently: time and frequency restart
+    // lower, a DC sweep restarts at its first source value and each operating point is a step.
+    // Sweeps going back to their first value are handled by `StepRule`, which tracks the direction.
+    pub(crate) fn is_new_step(&self, first: &Value, previous: &Value, current: &Value) -> bool {
+        return match self {
+            // LTSpice uses the sign of the time to flag compressed points, the raw samples are
+            // seen here before the time axis is made positive
+            Mode::Transient => current.real.abs() < previous.real.abs(),
+            Mode::AC | Mode::FFT | Mode::Noise => current.real < previous.real,
+            Mode::DC | Mode::Other(_) => current == first,
+            Mode::OperatingPoint => true,
+        };
+    }
+}
+
+impl StepRule {
+    pub fn new(mode: &Mode) -> Self {
+        return StepRule {
+            mode: mode.clone(),
+            direction: Ordering::Equal,
+            reversed: false,
+            closed: false,
+        };
+    }
+
+    /// Returns whether a point starts a new step, given the first and previous points of the
+    /// current step; it must be called for every point after the first in order. A DC sweep going
+    /// back to its first value after reversing (e.g. a 0 → 5 → 0 hysteresis loop) closes its
+    /// step, the next point starts a new one. Reaching it otherwise restarts the sweep.
+    pub fn is_new_step(&mut self, first: &Value, previous: &Value, current: &Value) -> bool {
+        if !matches!(self.mode, Mode::DC | Mode::Other(_)) {
+            return self.mode.is_new_step(first, previous, current);
+        }
+        let new_step = self.closed || (current == first && !self.reversed);
+        if new_step {
+            *self = StepRule::new(&self.mode);
+            return true;
+        }
+
+        let direction = current.real.partial_cmp(&previous.real).unwrap_or(Ordering::Equal);
+        match self.direction {
+            Ordering::Equal => self.direction = direction,
+            _ => self.reversed |= direction == self.direction.reverse(),
+        }
+        self.closed = self.reversed && current == first;
+        return false;
+    }
+}
+
+impl Source {
+    // Guesses the simulator from the header values (keyed as in the header) and its encoding.
+    // LTSpice writes its name and version in 'Command' ("Linear Technology Corporation LTspice XVII",
+    // "LTspice 24.0.12 for Windows") and ngspice its version ("ngspice-42" or "version 42").
+    pub(crate) fn detect(header: &OrderedMap<String>, encoding: &Encoding) -> Source {
+        // QSPICE names itself in 'Command' and its schematics are '.qsch' files
+        if header.get("Title").is_some_and(|title| title.trim().to_lowercase().ends_with(".qsch")) {
+            return Source::Qspice;
+        }
+        if let Some(command) = header.get("Command") {
+            // Only the leading simulator name counts, the rest may hold arbitrary paths
+            let command = command.trim().to_lowercase();
+            let command = command.strip_prefix("linear technology corporation").unwrap_or(&command).trim_start();
+            let mut tokens = command.split_whitespace();
+            let simulator = tokens.next().unwrap_or_default();
+            if simulator.starts_with("qspice") {
+                return Source::Qspice;
+            }
+            if simulator.starts_with("ngspice") || simulator == "version" {
+                return Source::Ngspice;
+            }
+            if simulator == "ltspice" {
+                let major = tokens
+                    .next()
+                    .unwrap_or_default()
+                    .split(|c: char| !c.is_ascii_digit())
+                    .next()
+                    .and_then(|major| major.parse::<u32>().ok());
+                return match major {
+                    Some(major) if major >= 24 => Source::Ltspice24,
+                    _ => Source::LtspiceXVII,
+                };
+            }
+        }
+
+        // Without a command, only LTSpice writes UTF16 headers and the 'Backannotation' key
+        if *encoding == Encoding::UTF16 || header.contains_key("Backannotation") {
+            return Source::LtspiceXVII;
+        }
+        if header.get("Plotname").is_some_and(|plotname| plotname.trim().ends_with("Curves")) {
+            return Source::Ngspice;
+        }
+        return Source::Unknown;
+    }
+}
+
+impl Unit {
+    /// Returns the unit of a variable of the specified class, in the specified analysis mode.
+    /// Noise analyses report spectral densities (V/√Hz, A/√Hz).
+    pub fn of(class: VariableClass, mode: &Mode) -> Unit {
+        return match (class, mode) {
+            (VariableClass::Voltage, Mode::Noise) => Unit::VoltPerRootHertz,
+            (VariableClass::Current, Mode::Noise) => Unit::AmperePerRootHertz,
+            (VariableClass::Voltage, _) => Unit::Volt,
+            (VariableClass::Current, _) => Unit::Ampere,
+            (VariableClass::Frequency, _) => Unit::Hertz,
+            (VariableClass::Unknown, _) => Unit::Dimensionless,
+        };
+    }
+
+    /// Returns the unit of the x axis in the specified analysis mode.
+    /// DC sweeps and operating points have no fixed abscissa unit.
+    pub fn of_x(mode: &Mode) -> Unit {
+        return match mode {
+            Mode::Transient => Unit::Second,
+            Mode::AC | Mode::FFT | Mode::Noise => Unit::Hertz,
+            Mode::DC | Mode::OperatingPoint | Mode::Other(_) => Unit::Dimensionless,
+        };
+    }
+
+    /// Returns the SI symbol of the unit, e.g. "V" or "V/√Hz".
+    pub fn symbol(&self) -> &'static str {
+        return match self {
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Second => "s",
+            Unit::Hertz => "Hz",
+            Unit::Watt => "W",
+            Unit::Ohm => "Ω",
+            Unit::Siemens => "S",
+            Unit::VoltPerRootHertz => "V/√Hz",
+            Unit::AmperePerRootHertz => "A/√Hz",
+            Unit::Decibel => "dB",
+            Unit::Dimensionless => "",
+        };
+    }
+
+    /// Returns a plot/column label for a quantity in this unit, e.g. "V(out) [V]".
+    pub fn label(&self, name: &str) -> String {
+        return match self {
+            Unit::Dimensionless => name.to_string(),
+            unit => format!("{} [{}]", name, unit.symbol()),
+        };
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Mode::Other(plotname) => write!(f, "{}", plotname),
+            mode => write!(f, "{:?}", mode),
+        };
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", self.symbol());
+    }
+}
+
+impl Flags {
+    // Returns the flag matching its debug name, as written by the exporters.
+    pub(crate) fn from_name(name: &str) -> Option<Flags> {
+        return match name {
+            "Stepped" => Some(Flags::Stepped),
+            "Real" => Some(Flags::Real),
+            "Complex" => Some(Flags::Complex),
+            "Double" => Some(Flags::Double),
+            "Forward" => Some(Flags::Forward),
+            "Log" => Some(Flags::Log),
+            "FastAccess" => Some(Flags::FastAccess),
+            _ => None,
+        };
+    }
+}
+
+impl VariableClass {
+    // Returns the class matching its debug name, as written by the exporters.
+    pub(crate) fn from_name(name: &str) -> VariableClass {
+        return match name {
+            "Voltage" => VariableClass::Voltage,
+            "Current" => VariableClass::Current,
+            "Frequency" => VariableClass::Frequency,
+            _ => VariableClass::Unknown,
+        };
+    }
+}
+
+impl SteppedVariable {
+    // Returns the name of the variable, as declared in the header.
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    // Returns the class of the variable.
+    pub fn class(&self) -> &VariableClass {
+        return &self.class;
+    }
+
+    /// Returns the index of the variable in the header of the raw file (the x axis being 0),
+    /// None for derived signals.
+    pub fn index(&self) -> Option<u32> {
+        return self.index;
+    }
+
+    // Returns true if the variable was computed after loading (see `insert_derived`).
+    pub fn is_derived(&self) -> bool {
+        return self.derived;
+    }
+
+    /// Returns how the samples of the variable were stored in the raw file, e.g. `Float32` for
+    /// LTSpice transient data without the 'double' flag. None if the variable was not decoded
+    /// from a raw file (derived signals, caches, other formats).
+    pub fn data_type(&self) -> Option<DataType> {
+        return self.data_type;
+    }
+}
+
+impl Value {
+    pub fn new(real: f64, imaginary: f64) -> Self {
+        return Value { real, imaginary };
+    }
+
+    // Returns the real part of the value.
+    pub fn real(&self) -> f64 {
+        return self.real;
+    }
+
+    // Returns the imaginary part of the value.
+    pub fn imaginary(&self) -> f64 {
+        return self.imaginary;
+    }
+
+    /// Returns the magnitude of the value.
+    pub fn abs(&self) -> f64 {
+        return self.real.hypot(self.imaginary);
+    }
+
+    /// Returns the phase of the value, in radians.
+    pub fn arg(&self) -> f64 {
+        return self.imaginary.atan2(self.real);
+    }
+
+    /// Returns the complex conjugate of the value.
+    pub fn conj(&self) -> Value {
+        return Value::new(self.real, -self.imaginary);
+    }
+}
+
+impl Sample for Value {
+    fn from_parts(real: f64, imaginary: f64) -> Self {
+        return Value::new(real, imaginary);
+    }
+}
+
+impl From<f64> for Value {
+    fn from(real: f64) -> Self {
+        return Value::new(real, 0.0);
+    }
+}
+
+impl Add for Value {
+    type Output = Value;
+
+    fn add(self, other: Value) -> Value {
+        return Value::new(self.real + other.real, self.imaginary + other.imaginary);
+    }
+}
+
+impl Sub for Value {
+    type Output = Value;
+
+    fn sub(self, other: Value) -> Value {
+        return Value::new(self.real - other.real, self.imaginary - other.imaginary);
+    }
+}
+
+impl Mul for Value {
+    type Output = Value;
+
+    fn mul(self, other: Value) -> Value {
+        return Value::new(
+            self.real * other.real - self.imaginary * other.imaginary,
+            self.real * other.imaginary + self.imaginary * other.real,
+        );
+    }
+}
+
+impl Div for Value {
+    type Output = Value;
+
+    fn div(self, other: Value) -> Value {
+        let denominator = other.real * other.real + other.imaginary * other.imaginary;
+        return Value::new(
+            (self.real * other.real + self.imaginary * other.imaginary) / denominator,
+            (self.imaginary * other.real - self.real * other.imaginary) / denominator,
+        );
+    }
+}
+
+impl Neg for Value {
+    type Output = Value;
+
+    fn neg(self) -> Value {
+        return Value::new(-self.real, -self.imaginary);
+    }
+}
+
+impl Mul<f64> for Value {
+    type Output = Value;
+
+    fn mul(self, scalar: f64) -> Value {
+        return Value::new(self.real * scalar, self.imaginary * scalar);
+    }
+}
+
+impl Div<f64> for Value {
+    type Output = Value;
+
+    fn div(self, scalar: f64) -> Value {
+        return Value::new(self.real / scalar, self.imaginary / scalar);
+    }
+}
+
+// Reference variants, so traces can be combined without cloning: &a + &b
+macro_rules! value_reference_operators {
+    ($($trait:ident, $method:ident);*) => {$(
+        impl<'a> $trait<&'a Value> for &'a Value {
+            type Output = Value;
+
+            fn $method(self, other: &'a Value) -> Value {
+                return $trait::$method(*self, *other);
+            }
+        }
+    )*};
+}
+
+value_reference_operators!(Add, add; Sub, sub; Mul, mul; Div, div);
+
 impl SteppedSimulation {
     pub fn new(path: PathBuf) -> Self {
         return SteppedSimulation {
@@ -116,7 +774,11 @@ impl SteppedSimulation {
             encoding: Encoding::UTF8,
             mode: Mode::Transient,
             flags: Vec::new(),
-            date: Utc::now(),
+            #[cfg(feature = "chrono")]
+            date: None,
+            date_string: None,
+            offset: 0.0,
+            source: Source::Unknown,
             stats: SimulationStats {
                 variables: 0,
                 points: 0,
@@ -124,15 +786,111 @@ impl SteppedSimulation {
                 step_size: 0,
             },
             variables: Vec::new(),
-            data: HashMap::new(),
+            data: OrderedMap::new(),
+            spilled: OrderedMap::new(),
+            compacted: OrderedMap::new(),
+            slabs: OrderedMap::new(),
+            cache_budget: None,
+            column_stats: OrderedMap::new(),
+            parameters: Vec::new(),
+            aliases: OrderedMap::new(),
+            renames: OrderedMap::new(),
+            options: LoadOptions::default(),
+            warnings: Vec::new(),
+            metrics: ParseMetrics::default(),
+            markers: Vec::new(),
+            provenance: provenance::Provenance::default(),
         };
     }
 
+    /// Parses a simulation from the raw file contents, without touching the filesystem.
+    /// Compressed contents are decompressed first, see the "gzip" and "zip" features.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        return SteppedSimulation::from_bytes_with_options(bytes, LoadOptions::default());
+    }
+
+    /// Same as `from_bytes`, with the specified load options.
+    pub fn from_bytes_with_options(bytes: &[u8], options: LoadOptions) -> Result<Self, Box<dyn Error>> {
+        let mut simulation = SteppedSimulation::new(PathBuf::new());
+        simulation.set_load_options(options);
+        let stopwatch = Stopwatch::start();
+        let decompressed = compression::decompress(bytes.to_vec())?;
+        let read = stopwatch.elapsed();
+        let log = decompressed.log.map(|log| log::decode(&log));
+        simulation.parse_bytes(&decompressed.raw, log.as_deref().and_then(log::step_count))?;
+        simulation.metrics.read = read;
+        if let Some(log) = log {
+            let stopwatch = Stopwatch::start();
+            simulation.parse_log(&log);
+            simulation.metrics.log = stopwatch.elapsed();
+        }
+        return Ok(simulation);
+    }
+
+    /// Parses a simulation from a reader, e.g. a network stream. See `from_bytes`.
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> Result<Self, Box<dyn Error>> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        return SteppedSimulation::from_bytes(&bytes);
+    }
+
+    /// Sets the options used by the next `reload`.
+    pub fn set_load_options(&mut self, options: LoadOptions) {
+        self.options = options;
+    }
+
+    /// Returns the anomalies recovered from by the last load, in lenient mode.
+    pub fn get_warnings(&self) -> &Vec<String> {
+        return &self.warnings;
+    }
+
+    /// Returns the statistics of every variable and step computed while decoding, keyed by name,
+    /// and kept up to date by `trim_before`, `merge_simulations` and `concat_time`.
+    /// Empty unless enabled with `LoadOptions::column_stats`, derived signals are not included.
+    pub fn column_stats(&self) -> &OrderedMap<Vec<summary::ColumnStats>> {
+        return &self.column_stats;
+    }
+
+    // Reports a header or data anomaly: fails in strict mode, otherwise records a warning.
+    fn anomaly(&mut self, message: String) -> Result<(), Box<dyn Error>> {
+        if self.options.is_strict() {
+            return Err(message.into());
+        }
+        warn!("{}", message);
+        self.warnings.push(message);
+        return Ok(());
+    }
+
+    // Fails if the load was cancelled through the `CancelToken` of the load options.
+    fn check_cancelled(&self) -> Result<(), Box<dyn Error>> {
+        if self.options.is_cancelled() {
+            Err("Cancelled")?;
+        }
+        return Ok(());
+    }
+
+    /// Loads (or reloads) the file, in the LTSpice format. Use `raw::Formats::open` for the
+    /// formats registered by other crates.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn reload(&mut self) -> Result<(), Box<dyn Error>> {
         self.parse()?;
         Ok(())
     }
 
+    /// Loads only the x axis of the file, skipping the samples of the variables, so that the
+    /// span and point density of a huge file can be shown before a full `reload`.
+    /// The variables are listed, but have no data until then.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_x_only(&mut self) -> Result<(), Box<dyn Error>> {
+        let options = self.options.clone();
+        self.options = options.clone().x_only(true);
+        let result = self.parse();
+        self.options = options;
+        return result;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[instrument(level = "debug", skip_all, fields(path = ?self.path))]
     fn parse(&mut self) -> Result<(), Box<dyn Error>> {
 
         /* #### File Checks #### */
@@ -146,78 +904,168 @@ impl SteppedSimulation {
             Err("The specified path is not a file.")?;
         }
 
-        if !self.path.extension().unwrap().eq("raw") {
-            error!("The specified path is not a '.raw' file: {:?}", self.path);
-            Err("The specified path is not a '.raw' file.")?;
+        let raw = self.path.extension().is_some_and(|extension| extension == "raw" || extension == "qraw");
+        let compressed = compression::is_compressed_name(&self.path.to_string_lossy());
+        if !raw && !compressed {
+            error!("The specified path is not a '.raw' or '.qraw' file: {:?}", self.path);
+            Err("The specified path is not a '.raw' or '.qraw' file.")?;
         }
 
         /* #### Read File Binary Contents #### */
 
+        let stopwatch = Stopwatch::start();
         let mut file = File::open(&self.path)?;
 
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
-
-        /* #### Parse Header #### */
-
-        let mut decoded = false;
-        let mut data = String::new();
-
-        // Try UTF8 Encoding
-        if !decoded {
-            let local_buffer = buffer.clone();
-            data = String::from_utf8_lossy(local_buffer.as_slice()).to_string();
-
-            if data.contains("Values") || data.contains("Binary") {
-                decoded = true;
-                self.encoding = Encoding::UTF8;
+        // Under a memory limit, an uncompressed file is mapped instead of read: its pages are
+        // only resident while they are decoded, and the OS reclaims them afterwards.
+        // SAFETY: the mapping is only read during this load, the file must not be truncated meanwhile.
+        let map = match self.options.get_memory_limit() {
+            Some(_) => Some(unsafe { memmap2::Mmap::map(&file)? }).filter(|map| !compression::is_compressed(map)),
+            None => None,
+        };
+        let decompressed = match map {
+            Some(_) => compression::Decompressed { raw: Vec::new(), log: None },
+            None => {
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer)?;
+                compression::decompress(buffer)?
             }
+        };
+        let raw = map.as_deref().unwrap_or(&decompressed.raw);
+        self.check_cancelled()?;
+        let read = stopwatch.elapsed();
+
+        // The log is read first, its step count sizes the steps of a sweep
+        let stopwatch = Stopwatch::start();
+        let log = match &decompressed.log {
+            Some(log) => Some(log::decode(log)),
+            None => self.read_log(),
+        };
+        let log_elapsed = stopwatch.elapsed();
+
+        self.parse_bytes(raw, log.as_deref().and_then(log::step_count))?;
+        self.metrics.read = read;
+        self.provenance.modified = file
+            .metadata()?
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|modified| modified.as_secs());
+
+        let stopwatch = Stopwatch::start();
+        if let Some(log) = log {
+            self.parse_log(&log);
         }
+        self.metrics.log = log_elapsed + stopwatch.elapsed();
 
-        // Try UTF16 Encoding
-        if !decoded {
-            let local_buffer: Vec<u16> = buffer
-                .chunks_exact(2)
-                .into_iter()
-                .map(|a| u16::from_le_bytes([a[0], a[1]]))
-                .collect();
+        Ok(())
+    }
 
-            data = String::from_utf16_lossy(local_buffer.as_slice()).to_string();
+    // Loads the step parameters from the log file next to the raw file, if there is one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn load_log(&mut self) {
+        if let Some(contents) = self.read_log() {
+            self.parse_log(&contents);
+        }
+    }
 
-            if data.contains("Values") || data.contains("Binary") {
-                decoded = true;
-                self.encoding = Encoding::UTF16
-            }
+    // Returns the contents of the log file next to the raw file, if there is one.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_log(&self) -> Option<String> {
+        // LTSpice writes the '.step' values in the log file next to the raw file,
+        // which is not compressed along with it ('tran.raw.gz' -> 'tran.log')
+        let path = match self.path.extension().is_some_and(|extension| extension == "gz") {
+            true => self.path.with_extension(""),
+            false => self.path.clone(),
+        };
+        let log_path = path.with_extension("log");
+        if !log_path.is_file() {
+            return None;
         }
+        return match std::fs::read(&log_path) {
+            Ok(bytes) => Some(log::decode(&bytes)),
+            Err(e) => {
+                warn!("Could not read the log file {:?}: {}", log_path, e);
+                None
+            }
+        };
+    }
 
-        if !decoded {
-            panic!("Could not decode file.");
+    /// Loads the step parameters from the contents of the LTSpice log file of the simulation.
+    /// This is done automatically by `reload()` when a '.log' file exists next to the raw file.
+    pub fn parse_log(&mut self, contents: &str) {
+        self.parameters = log::step_parameters(contents);
+        if !self.parameters.is_empty() && self.parameters.len() != self.get_step_count() {
+            warn!(
+                "The log file declares {} steps, but {} were loaded.",
+                self.parameters.len(),
+                self.get_step_count()
+            );
         }
+    }
+
+    // Parses the contents of a raw file, `step_hint` is the number of steps of a sweep if known
+    // (e.g. from the log file), to size the steps up front.
+    #[instrument(level = "debug", skip_all, fields(bytes = bytes.len() as u64))]
+    fn parse_bytes(&mut self, bytes: &[u8], step_hint: Option<usize>) -> Result<(), Box<dyn Error>> {
+
+        // Start from a clean state, the simulation may be reloaded
+        self.flags.clear();
+        self.variables.clear();
+        self.data.clear();
+        self.spilled.clear();
+        self.compacted.clear();
+        self.slabs.clear();
+        self.column_stats.clear();
+        self.parameters.clear();
+        self.warnings.clear();
+        self.offset = 0.0;
+        self.metrics = ParseMetrics {
+            bytes: bytes.len() as u64,
+            ..ParseMetrics::default()
+        };
+        self.provenance = provenance::Provenance::of_bytes(&self.path, bytes);
+
+        /* #### Parse Header #### */
+
+        let stopwatch = Stopwatch::start();
+        let span = debug_span!(
+            "header",
+            encoding = field::Empty,
+            source = field::Empty,
+            mode = field::Empty,
+            binary_bytes = field::Empty,
+            elapsed_us = field::Empty
+        )
+        .entered();
+
+        // Only the header is decoded as text, the binary section may be a mapped file larger than the memory
+        let (encoding, data) = match detect_encoding(&bytes[..header_end(bytes)]) {
+            Some(detected) => detected,
+            None => Err("Could not decode file.")?,
+        };
+        self.encoding = encoding;
 
         // Split Header & Binary
         let substring = "Binary:\n";
-        let index = data.find(substring).unwrap();
-        let header_length = match self.encoding {
-            Encoding::UTF8 => index + substring.len(),
-            Encoding::UTF16 => (index + substring.len()) * 2,
-            Encoding::UTF32 => (index + substring.len()) * 4,
-            Encoding::ASCII => index + substring.len(),
+        let index = match data.find(substring) {
+            Some(index) => index,
+            None => Err("Only binary raw files are supported.")?,
         };
 
-        buffer.drain(0..header_length);
-        debug!(
-            "Binary Size: {:.2}%",
-            buffer.len() as f32 / data.len() as f32 * 100.0
-        );
+        // The lossy decoding replaces invalid bytes, so the index in `data` does not always
+        // map back to the buffer: the end of the header is searched in the buffer itself
+        let header_length = match header_length(bytes, &self.encoding) {
+            Some(length) => length,
+            None => Err("Only binary raw files are supported.")?,
+        };
+
+        let mut buffer = &bytes[header_length..];
+        span.record("encoding", field::debug(&self.encoding));
+        span.record("binary_bytes", buffer.len() as u64);
 
         let header = data.split_at(index + substring.len()).0;
-        let mut values: HashMap<String, String> = HashMap::new();
-        let re_text =
-            Regex::new(r"(?:^|\n)([a-zA-Z .]*[a-zA-Z]+):((?:.+)|(?:(?:.|\n)+(?:Binary:)))")
-                .unwrap();
-        for cap in re_text.captures_iter(header) {
-            values.insert(cap[1].to_string(), cap[2].to_string());
-        }
+        let values = header_fields(header);
 
         /* #### Parse Binary Data #### */
 
@@ -226,186 +1074,293 @@ impl SteppedSimulation {
             match key.as_str() {
                 "Title" => {}
                 "Date" => {
-                    self.date = match dateparser::parse(&value) {
-                        Ok(date) => date,
-                        Err(_) => Utc::now(),
-                    };
+                    self.date_string = Some(value.trim().to_string());
+                    #[cfg(feature = "chrono")]
+                    {
+                        self.date = parse_date(value);
+                        if self.date.is_none() {
+                            self.anomaly(format!("Could not parse the date '{}'.", value.trim()))?;
+                        }
+                    }
+                }
+                "Plotname" => {
+                    self.mode = Mode::from_plotname(value);
+                    if let Mode::Other(plotname) = &self.mode {
+                        self.anomaly(format!("Unknown plotname '{}'.", plotname))?;
+                    }
+                }
+                "Flags" => {
+                    // Space separated, e.g. "complex forward stepped"
+                    for flag in value.split_whitespace() {
+                        match flag.to_lowercase().as_str() {
+                            "stepped" => self.flags.push(Flags::Stepped),
+                            "real" => self.flags.push(Flags::Real),
+                            "complex" => self.flags.push(Flags::Complex),
+                            "double" => self.flags.push(Flags::Double),
+                            "forward" => self.flags.push(Flags::Forward),
+                            "log" => self.flags.push(Flags::Log),
+                            "fastaccess" => self.flags.push(Flags::FastAccess),
+                            _ => self.anomaly(format!("Unknown flag '{}'.", flag))?,
+                        }
+                    }
                 }
-                "Plotname" => match value.as_str() {
-                    "Transient Analysis" => self.mode = Mode::Transient,
-                    "AC Analysis" => self.mode = Mode::AC,
-                    "DC Analysis" => self.mode = Mode::DC,
-                    "Noise Analysis" => self.mode = Mode::Noise,
-                    "Operating Point" => self.mode = Mode::OperatingPoint,
-                    "FFT" => self.mode = Mode::OperatingPoint,
-                    _ => {}
-                },
-                "Flags" => match value.as_str() {
-                    "stepped" => self.flags.push(Flags::Stepped),
-                    "real" => self.flags.push(Flags::Real),
-                    "double" => self.flags.push(Flags::Double),
-                    _ => {}
-                },
                 "No. Points" => self.stats.points = value.trim().parse::<u32>()?,
                 "No. Variables" => self.stats.variables = value.trim().parse::<u32>()?,
                 "Variables" => {
-                    let re = Regex::new(r"\s*(\d+)\s*([VIx]+\((?:.|:|\d)+\))\s*(\w+)\n").unwrap();
-                    for cap in re.captures_iter(value) {
+                    let _span = debug_span!("variables", lines = value.lines().count() as u64).entered();
+
+                    // One "<index> <name> <type> [<precision>]" line per variable, the first one being the x axis.
+                    // Names are kept verbatim, e.g. "Ix(U1:3)", "Id(M1)" or "V(n001#branch)".
+                    // The optional precision ("float" or "double") overrides the one of the 'Flags' line,
+                    // for files mixing precisions within a point.
+                    for line in value.lines() {
+                        let tokens: Vec<&str> = line.split_whitespace().collect();
+                        let (index, name, class, extra) = match tokens.as_slice() {
+                            [index, name, class, extra @ ..] => (index, name, class, extra),
+                            _ => continue,
+                        };
+                        let data_type = extra.iter().find_map(|token| match *token {
+                            "float" | "float32" => Some(DataType::Float32),
+                            "double" | "float64" => Some(DataType::Float64),
+                            _ => None,
+                        });
+                        let index = match index.parse::<u32>() {
+                            Ok(index) if index > 0 => index,
+                            _ => continue,
+                        };
                         self.variables.push(SteppedVariable {
-                            class: match &cap[3] {
-                                "V" => VariableClass::Voltage,
-                                "I" => VariableClass::Current,
+                            class: match *class {
+                                "V" | "voltage" => VariableClass::Voltage,
+                                "I" | "current" | "device_current" => VariableClass::Current,
+                                "frequency" => VariableClass::Frequency,
                                 _ => VariableClass::Unknown,
                             },
-                            name: cap[2].to_string(),
+                            name: name.to_string(),
+                            index: Some(index),
+                            derived: false,
+                            data_type,
                         });
                     }
                 }
                 "Command" => {}
+                "Provenance" => {}
                 "Backannotation" => {}
-                "Offset" => {}
-                _ => {
-                    warn!("Unknown LTSPICE Simulation Key: {}", key);
-                }
+                "Offset" => match value.trim().parse::<f64>() {
+                    Ok(offset) => self.offset = offset,
+                    Err(_) => self.anomaly(format!("Could not parse the offset '{}'.", value.trim()))?,
+                },
+                _ => self.anomaly(format!("Unknown LTSPICE Simulation Key: {}", key))?,
             }
         }
 
+        if self.variables.len() as u32 + 1 != self.stats.variables {
+            self.anomaly(format!(
+                "The header declares {} variables, but {} were listed.",
+                self.stats.variables,
+                self.variables.len() + 1
+            ))?;
+        }
+
+        self.source = Source::detect(&values, &self.encoding);
+        span.record("source", field::debug(&self.source));
+        span.record("mode", field::debug(&self.mode));
+
         /* #### Binary Parsing #### */
 
         let mut x_type: DataType = DataType::Float64;
         let mut y_type: DataType = DataType::Float32;
 
-        if self.flags.contains(&Flags::Double) {
+        // ngspice and QSPICE always write double precision samples, without the 'double' flag
+        if self.flags.contains(&Flags::Double) || self.source == Source::Ngspice || self.source == Source::Qspice {
             y_type = DataType::Float64;
         }
 
-        if self.mode == Mode::AC || self.mode == Mode::FFT {
+        let complex = self.flags.contains(&Flags::Complex) || self.mode == Mode::AC || self.mode == Mode::FFT;
+        if complex {
             x_type = DataType::Complex128;
             y_type = DataType::Complex128;
         }
 
-        // Compute Data Lengths
-        let y_size = match y_type {
-            DataType::Float32 => 4,
-            DataType::Float64 => 8,
-            DataType::Complex128 => 16,
-        };
+        // Variables declaring their own precision keep it, complex data is always complex128
+        for variable in self.variables.iter_mut() {
+            variable.data_type = match (complex, variable.data_type) {
+                (false, Some(data_type)) => Some(data_type),
+                _ => Some(y_type),
+            };
+        }
 
-        let x_size = match x_type {
-            DataType::Float32 => 4,
-            DataType::Float64 => 8,
-            DataType::Complex128 => 16,
+        // The listed variables are decoded, which only differ from the declared count in lenient mode
+        let layout = Layout {
+            x: x_type,
+            y: self.variables.iter().map(|variable| variable.data_type.unwrap_or(y_type)).collect(),
         };
-
-        let y_length = self.stats.points * (self.stats.variables - 1) * y_size;
-        let x_length = self.stats.points * x_size;
-
-        let expected_length = x_length + y_length;
-
-        if expected_length != buffer.len() as u32 {
-            error!("There is a mismatch between the expected and actual SPICE data length.");
-            error!("It is possible that this library is not yet able to handle this type of file.");
-            error!("Please contact the library author.");
-            Err("Mismatch between expected and actual SPICE data length.")?;
+        let point_size = layout.point_size();
+        let expected_length = self.stats.points as u64 * point_size as u64;
+
+        // Padding or a following plot after the data is skipped, even in strict mode
+        if expected_length < buffer.len() as u64 {
+            if let Some(reason) = trailing_data(&buffer[expected_length as usize..]) {
+                let message = format!(
+                    "Ignored {} bytes after the data: {}.",
+                    buffer.len() as u64 - expected_length,
+                    reason
+                );
+                warn!("{}", message);
+                self.warnings.push(message);
+                buffer = &buffer[..expected_length as usize];
+            }
         }
 
-        // Parse Buffer
-        self.data.insert("x".to_string(), Vec::new());
-        self.stats.step_size = expected_length;
-        let mut iterator = buffer.into_iter();
-        let mut x_buffer: Vec<Value> = Vec::new();
-        while iterator.len() > 0 {
-
-            // X Data
-            let x_data = iterator.by_ref().take(x_size as usize).collect::<Vec<u8>>();
-
-            // Read Real & Imaginary Parts
-            let x_real = match x_type {
-                DataType::Float32 => f32::from_ne_bytes(x_data.clone().try_into().unwrap()) as f64,
-                DataType::Float64 => f64::from_ne_bytes(x_data.clone().try_into().unwrap()),
-                DataType::Complex128 => f64::from_ne_bytes(x_data.clone().try_into().unwrap()),
-            };
-            let x_imaginary = match x_type {
-                DataType::Float32 => 0.0,
-                DataType::Float64 => 0.0,
-                DataType::Complex128 => f64::from_ne_bytes(x_data.clone().try_into().unwrap()),
-            };
-
-            // Create The Value Object
-            let x_value = Value {
-                real: x_real,
-                imaginary: x_imaginary,
-            };
-
-            // If we get the same value twice, we know we have a new step
-            // In this case, we have to rotate the data vector
-            if x_buffer.len() > 0 && x_buffer.first().unwrap().clone() == x_value {
-                self.stats.step_size = x_buffer.len() as u32;
-                self.stats.steps = (self.stats.points / x_buffer.len() as u32) as u16;
-                self.data.get_mut("x").unwrap().push(x_buffer.clone());
-                x_buffer.clear();
+        if expected_length != buffer.len() as u64 {
+            if self.options.is_strict() {
+                error!("There is a mismatch between the expected and actual SPICE data length.");
+                error!("It is possible that this library is not yet able to handle this type of file.");
+                error!("Please contact the library author.");
+                Err("Mismatch between expected and actual SPICE data length.")?;
             }
 
-            x_buffer.push(x_value);
-
-            // After an X datapoint, the following bytes represent the different variables of the simulation.
-            // We read them one by one and store them in the data HashMap.
-            for variable in self.variables.iter() {
-
-                // Create HashMap if it doesn't exist
-                if self.data.get(&variable.name).is_none() {
-                    self.data.insert(variable.name.clone(), Vec::new());
-                }
-
-                // Load the step vector
-                let step_vector = self.data.get_mut(&variable.name).unwrap();
-
-                // Create a new step vector if the current one is full or non-existent
-                if step_vector.len() == 0 || self.stats.step_size == step_vector.last().unwrap().len() as u32 {
-                    step_vector.push(Vec::new());
-                }
-
-                let vector = step_vector.last_mut().unwrap();
+            // Only decode the complete points, e.g. of a file still being written
+            let complete = buffer.len() - buffer.len() % point_size;
+            self.anomaly(format!(
+                "Expected {} bytes of data, found {}: loading {} complete points.",
+                expected_length,
+                buffer.len(),
+                complete / point_size
+            ))?;
+            buffer = &buffer[..complete];
+        }
 
-                // Y Data
-                let y_data = iterator.by_ref().take(y_size as usize).collect::<Vec<u8>>();
+        self.metrics.header = stopwatch.elapsed();
+        span.record("elapsed_us", self.metrics.header.as_micros() as u64);
+        drop(span);
+
+        let stopwatch = Stopwatch::start();
+        let span = debug_span!(
+            "decode",
+            points = field::Empty,
+            steps = field::Empty,
+            variables = self.variables.len() as u64,
+            spilled = field::Empty,
+            elapsed_us = field::Empty
+        )
+        .entered();
 
-                // Read the Real & Imaginary Parts
-                let y_real = match y_type {
-                    DataType::Float32 => {
-                        f32::from_ne_bytes(y_data.clone().try_into().unwrap()) as f64
+        // Parse Buffer
+        // The samples of each point follow the header order, so they are decoded by position
+        // and only keyed by name once the whole buffer is read.
+        // The rule keeps the state of the current step, every pass starts with a fresh one
+        let is_new_step = || {
+            let mut rule = StepRule::new(&self.mode);
+            return move |first: &Value, previous: &Value, current: &Value| rule.is_new_step(first, previous, current);
+        };
+        let cancelled = || self.options.is_cancelled();
+        let statistics = self.options.computes_column_stats();
+        // Under a memory limit, only the x axis is decoded here and the variables one at a time below
+        #[cfg(not(target_arch = "wasm32"))]
+        let memory_limit = self.options.get_memory_limit().filter(|_| !self.options.is_x_only());
+        #[cfg(target_arch = "wasm32")]
+        let memory_limit: Option<usize> = None;
+        // With the slab allocator, the variables are decoded in one slab each and the x axis split in steps
+        let slab = self.options.get_allocator() == Allocator::Slab;
+        if slab && (memory_limit.is_some() || self.options.get_preview() != Precision::Full) {
+            Err("The slab allocator can't be combined with a memory limit or preview precision.")?;
+        }
+        let mut slabs: Option<(Vec<Vec<Value>>, Vec<usize>)> = None;
+        let result = match (self.options.is_x_only() || memory_limit.is_some(), slab) {
+            (true, _) => ltspice_decode::decode_x(buffer, &layout, is_new_step(), cancelled),
+            (false, true) => ltspice_decode::decode_slabs::<Value, _, _>(buffer, &layout, statistics, is_new_step(), cancelled)
+                .map(|decoded| {
+                    let starts = slab::starts(&decoded.steps);
+                    let points = decoded.x.len();
+                    let x = slab::split(&decoded.x, &starts);
+                    slabs = Some((decoded.y, starts));
+                    Decoded { x, y: Vec::new(), points, statistics: decoded.statistics }
+                }),
+            (false, false) => {
+                // A single step holds every point, whose count is bounded by the buffer in case
+                // the header is wrong. The length of the steps of a sweep is not in the header:
+                // the points are shared evenly by the steps of the log (at most one step per
+                // point, the log may be stale), and without a log the step boundaries are found
+                // with a first pass over the x axis.
+                let points = (self.stats.points as usize).min(buffer.len() / point_size);
+                let sizes = match (self.flags.contains(&Flags::Stepped), step_hint) {
+                    (false, _) => Some(vec![points]),
+                    (true, Some(steps)) => {
+                        let steps = steps.clamp(1, points.max(1));
+                        Some(vec![points.div_ceil(steps); steps])
                     }
-                    DataType::Float64 => f64::from_ne_bytes(y_data.clone().try_into().unwrap()),
-                    DataType::Complex128 => f64::from_ne_bytes(y_data.clone().try_into().unwrap()),
-                };
-                let y_imaginary = match y_type {
-                    DataType::Float32 => 0.0,
-                    DataType::Float64 => 0.0,
-                    DataType::Complex128 => f64::from_ne_bytes(y_data.clone().try_into().unwrap()),
-                };
-
-                // Create The Value Object
-                let y_value = Value {
-                    real: y_real,
-                    imaginary: y_imaginary,
+                    (true, None) => ltspice_decode::decode_x::<Value, _, _>(buffer, &layout, is_new_step(), cancelled)
+                        .map(|decoded| decoded.x.iter().map(|x| x.len()).collect()),
                 };
+                match sizes {
+                    Some(sizes) => ltspice_decode::decode_sized(buffer, &layout, &sizes, statistics, is_new_step(), cancelled),
+                    None => None,
+                }
+            }
+        };
+        let Decoded {
+            x: mut x_steps,
+            y: y_steps,
+            points: decoded,
+            statistics: column_stats,
+        } = match result {
+            Some(decoded) => decoded,
+            None => Err("Cancelled")?,
+        };
+        self.stats.step_size = self.stats.points;
+        self.stats.steps = x_steps.len() as u16;
+        if self.stats.steps > 1 {
+            self.stats.step_size = x_steps[0].len() as u32;
+        }
 
-                vector.push(y_value);
+        // LTSpice flags compressed points with a negative time, only the magnitude is a time.
+        // Step detection above sees the raw samples, everything after only sees times.
+        if self.mode == Mode::Transient {
+            for x in x_steps.iter_mut().flatten() {
+                x.real = x.real.abs();
+            }
+        }
+        if self.options.applies_offset() && self.offset != 0.0 {
+            for x in x_steps.iter_mut().flatten() {
+                x.real += self.offset;
+            }
+        }
 
+        self.data.insert("x".to_string(), x_steps);
+        for (variable, steps) in self.variables.iter().zip(y_steps) {
+            self.data.insert(variable.name.clone(), steps);
+        }
+        if let Some((values, starts)) = slabs {
+            let starts = std::sync::Arc::new(starts);
+            for (variable, values) in self.variables.iter().zip(values.into_iter()) {
+                self.slabs.insert(variable.name.clone(), slab::SlabColumn::new(values, starts.clone()));
             }
         }
+        for (variable, steps) in self.variables.iter().zip(column_stats.into_iter()) {
+            self.column_stats.insert(variable.name.clone(), steps.iter().map(summary::ColumnStats::from).collect());
+        }
 
-        // Load The Last X Data
-        // This is necessary because the last step is not detected by the loop above
-        self.data.get_mut("x").unwrap().push(x_buffer.clone());
+        // Store the variables at preview precision if requested
+        self.compact(self.options.get_preview());
 
-        debug!("Loaded {} Variables.", self.data.len());
-        debug!("Detected {} Steps.", self.stats.steps);
-        debug!("Loaded {} Steps.", self.data.get("x").unwrap().len());
-        debug!(
-            "Loaded {} Values Per Step.",
-            self.data.get("V(v_in)").unwrap().last().unwrap().len()
-        );
+        // Decode the variables that fit the memory limit, and spill the others to a scratch file
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(limit) = memory_limit {
+            self.decode_spilled(buffer, &layout, limit)?;
+        }
+
+        self.metrics.decode = stopwatch.elapsed();
+        span.record("points", decoded as u64);
+        span.record("steps", self.stats.steps as u64);
+        span.record("spilled", self.spilled.len() as u64);
+        span.record("elapsed_us", self.metrics.decode.as_micros() as u64);
+        drop(span);
+
+        // Apply the names configured with `set_rename_map`
+        if !self.renames.is_empty() {
+            let renames = self.renames.clone();
+            self.rename_map(renames.iter())?;
+        }
 
         Ok(())
     }
@@ -415,30 +1370,60 @@ impl SteppedSimulation {
     /// Returns a reference to the loaded variable, for the specified step.
     /// Returns None if no variable with the specified name exist.
     /// If no step is specified, the first step is returned.
-    pub fn get(&self, name: &str, step: Option<u16>) -> Option<&Vec<Value>> {
+    /// See `query()` for ranged and downsampled retrievals.
+    pub fn get(&self, name: &str, step: Option<u16>) -> Option<&[Value]> {
 
-        let step = match step {
-            Some(step) => step,
-            None => 0,
-        };
+        let step = step.unwrap_or_default();
 
-        let data = match self.data.get(name) {
-            Some(data) => data,
-            None => return None,
-        };
+        return self.column_step(self.resolve(name), step as usize);
 
-        return match data.get(step as usize) {
-            Some(data) => Some(data),
-            None => None,
-        };
+    }
 
+    /// Same as `get`, but fails instead of returning None when a variable spilled to a scratch
+    /// file (see `LoadOptions::memory_limit`) can't be read back, e.g. its file was truncated.
+    pub fn try_get(&self, name: &str, step: Option<u16>) -> Result<Option<&[Value]>, Box<dyn Error>> {
+        return self.try_column_step(self.resolve(name), step.unwrap_or_default() as usize);
     }
 
     // Returns a reference to the loaded X data.
-    pub fn get_x(&self) -> Option<&Vec<Value>> {
+    pub fn get_x(&self) -> Option<&[Value]> {
         return self.get("x", None);
     }
 
+    /// Returns the data of the variable at the specified header index (0 being the x axis).
+    /// If no step is specified, the first step is returned.
+    pub fn get_by_index(&self, index: u32, step: Option<u16>) -> Option<&[Value]> {
+        if index == 0 {
+            return self.get("x", step);
+        }
+        let variable = self.variables.iter().find(|variable| variable.index == Some(index))?;
+        return self.get(&variable.name, step);
+    }
+
+    // Returns the number of loaded steps.
+    pub fn get_step_count(&self) -> usize {
+        return match self.data.get("x") {
+            Some(data) => data.len(),
+            None => 0,
+        };
+    }
+
+    // Returns the x axis followed by every variable of the specified step, in header order.
+    // Returns None if the step does not exist.
+    pub(crate) fn get_columns(&self, step: u16) -> Option<Vec<(&str, &[Value])>> {
+        let mut columns = vec![("x", self.get("x", Some(step))?)];
+        for variable in self.variables.iter() {
+            columns.push((variable.name(), self.get(&variable.name, Some(step))?));
+        }
+        return Some(columns);
+    }
+
+    // Returns the '.step' parameters of the specified step, as (name, value) pairs.
+    // Returns None if no parameters are known for the step.
+    pub fn get_step_parameters(&self, step: u16) -> Option<&[(String, f64)]> {
+        return self.parameters.get(step as usize).map(|parameters| parameters.as_slice());
+    }
+
     // Returns a reference to the simulation steps.
     pub fn get_stats(&self) -> &SimulationStats {
         return &self.stats;
@@ -449,4 +1434,123 @@ impl SteppedSimulation {
         return &self.variables;
     }
 
+    /// Returns the number of variables, excluding the x axis.
+    pub fn len(&self) -> usize {
+        return self.variables.len();
+    }
+
+    /// Returns true if no variables are loaded.
+    pub fn is_empty(&self) -> bool {
+        return self.variables.is_empty();
+    }
+
+    /// Returns an iterator over (variable name, per-step data), in header order.
+    pub fn iter(&self) -> Iter<'_> {
+        return Iter {
+            simulation: self,
+            index: 0,
+        };
+    }
+
+    // Returns the date at which the simulation was run.
+    #[cfg(feature = "chrono")]
+    pub fn get_date(&self) -> Option<&DateTime<Utc>> {
+        return self.date.as_ref();
+    }
+
+    /// Returns the date as written in the header, even if it could not be parsed.
+    pub fn get_date_string(&self) -> Option<&str> {
+        return self.date_string.as_deref();
+    }
+
+    // Returns the analysis mode of the simulation.
+    pub fn get_mode(&self) -> &Mode {
+        return &self.mode;
+    }
+
+    /// Returns the 'Offset' declared in the header, 0 if there is none.
+    /// It is only included in the x axis when loaded with `LoadOptions::apply_offset`.
+    pub fn offset(&self) -> f64 {
+        return self.offset;
+    }
+
+    /// Returns the simulator that most likely produced the file.
+    pub fn source(&self) -> Source {
+        return self.source;
+    }
+
+    /// Returns the flags declared in the header.
+    pub fn flags(&self) -> &[Flags] {
+        return &self.flags;
+    }
+
+    // Returns the unit of the specified variable ("x" for the abscissa).
+    // Unknown variables are dimensionless.
+    pub fn get_unit(&self, name: &str) -> Unit {
+        if name == "x" {
+            return Unit::of_x(&self.mode);
+        }
+        let name = self.resolve(name);
+        return match self.variables.iter().find(|variable| variable.name == name) {
+            Some(variable) => Unit::of(variable.class, &self.mode),
+            None => Unit::Dimensionless,
+        };
+    }
+
+    /* #### Output Interfaces #### */
+
+    /// Serializes the whole simulation (header information and every step) to JSON.
+    pub fn to_json(&self) -> String {
+        return json::simulation(self);
+    }
+
+}
+
+impl Index<&str> for SteppedSimulation {
+    type Output = [Value];
+
+    /// Returns the first step of the specified variable.
+    /// Panics if the variable does not exist, see `get` for a non-panicking variant.
+    fn index(&self, name: &str) -> &Self::Output {
+        return match self.get(name, None) {
+            Some(data) => data,
+            None => panic!("No variable named '{}' in the simulation.", name),
+        };
+    }
+}
+
+impl Index<(&str, u16)> for SteppedSimulation {
+    type Output = [Value];
+
+    /// Returns the specified step of a variable.
+    /// Panics if the variable or step does not exist, see `get` for a non-panicking variant.
+    fn index(&self, (name, step): (&str, u16)) -> &Self::Output {
+        return match self.get(name, Some(step)) {
+            Some(data) => data,
+            None => panic!("No variable named '{}' with step {} in the simulation.", name, step),
+        };
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a Vec<Vec<Value>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(variable) = self.simulation.variables.get(self.index) {
+            self.index += 1;
+            if let Some(data) = self.simulation.column(&variable.name) {
+                return Some((variable.name.as_str(), data));
+            }
+        }
+        return None;
+    }
+}
+
+impl<'a> IntoIterator for &'a SteppedSimulation {
+    type Item = (&'a str, &'a Vec<Vec<Value>>);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        return self.iter();
+    }
 }
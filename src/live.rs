@@ -0,0 +1,116 @@
+/*
+ * This file contains the async stream of the points of a raw file being written
+ *
+ * The file is read with `RawStream`, polled at a fixed interval once all available points are
+ * consumed, until every point declared in the header was read. Reads are blocking, so they run
+ * on the blocking thread pool of the runtime. Requires the "tokio" feature.
+ */
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::task::JoinHandle;
+use tokio::time::Sleep;
+use tracing::warn;
+
+use crate::stream::{Chunk, RawStream};
+
+/* #### Constants #### */
+
+/// Default interval between two polls of the file, once all available points were read.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/* #### Structs #### */
+
+/// A `Stream` of the chunks of a raw file, yielding new points as the simulator writes them.
+/// The stream ends once every point declared in the header was read, or on a read error.
+///
+/// ```ignore
+/// let mut live = RawStream::open(Path::new("tran.raw"))?.select(&["V(out)"])?.live(POLL_INTERVAL);
+/// while let Some(chunk) = live.next().await {
+///     // ...
+/// }
+/// ```
+pub struct LiveStream {
+    // None while a read is in flight, the stream is then owned by the blocking task
+    stream: Option<RawStream>,
+    interval: Duration,
+    sleep: Option<Pin<Box<Sleep>>>,
+    read: Option<JoinHandle<Read>>,
+    done: bool,
+}
+
+// The outcome of a blocking read, handing the stream back with the chunk or the error message.
+type Read = (RawStream, Result<Option<Chunk>, String>);
+
+/* #### Implementations #### */
+
+impl RawStream {
+    /// Turns the stream into an async `Stream` polling the file at the given interval.
+    pub fn live(self, interval: Duration) -> LiveStream {
+        return LiveStream {
+            stream: Some(self),
+            interval,
+            sleep: None,
+            read: None,
+            done: false,
+        };
+    }
+}
+
+impl Stream for LiveStream {
+    type Item = Chunk;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Chunk>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            if let Some(sleep) = this.sleep.as_mut() {
+                ready!(sleep.as_mut().poll(context));
+                this.sleep = None;
+            }
+            let read = match this.read.as_mut() {
+                Some(read) => read,
+                None => {
+                    let mut stream = match this.stream.take() {
+                        Some(stream) => stream,
+                        None => return Poll::Ready(None),
+                    };
+                    this.read = Some(tokio::task::spawn_blocking(move || {
+                        let chunk = stream.read_chunk().map_err(|e| e.to_string());
+                        return (stream, chunk);
+                    }));
+                    continue;
+                }
+            };
+            let result = ready!(Pin::new(read).poll(context));
+            this.read = None;
+            let (stream, chunk) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("The read of the raw file failed: {}", e);
+                    this.done = true;
+                    continue;
+                }
+            };
+            match chunk {
+                Ok(Some(chunk)) => {
+                    this.stream = Some(stream);
+                    return Poll::Ready(Some(chunk));
+                }
+                Ok(None) if stream.is_complete() => this.done = true,
+                Ok(None) => this.sleep = Some(Box::pin(tokio::time::sleep(this.interval))),
+                Err(e) => {
+                    warn!("Could not read the raw file: {}", e);
+                    this.done = true;
+                }
+            }
+            this.stream = Some(stream);
+        }
+    }
+}
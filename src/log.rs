@@ -0,0 +1,53 @@
+/*
+ * This file contains the parser for the LTSpice '.log' files written next to the '.raw' files
+ */
+
+use crate::format;
+
+/* #### Functions #### */
+
+/// Decodes the contents of a log file, which LTSpice writes either as UTF8 or UTF16.
+pub fn decode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take(256).filter(|byte| **byte == 0).count();
+    if zeros * 4 < bytes.len().min(256) {
+        return String::from_utf8_lossy(bytes).to_string();
+    }
+
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|a| u16::from_le_bytes([a[0], a[1]]))
+        .collect();
+    return String::from_utf16_lossy(wide.as_slice());
+}
+
+/// Extracts the step parameters from the `.step` lines of a log file, one entry per step.
+/// e.g. ".step rload=10k temp=25" -> [("rload", 10000.0), ("temp", 25.0)]
+/// Temperature sweeps (".step temp" and ".temp") are logged the same way, as the "temp" parameter.
+pub fn step_parameters(contents: &str) -> Vec<Vec<(String, f64)>> {
+    let mut steps = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if !line.to_lowercase().starts_with(".step ") {
+            continue;
+        }
+
+        let mut parameters = Vec::new();
+        for token in line[6..].split_whitespace() {
+            let (name, value) = match token.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            };
+            if let Some(value) = format::parse(value) {
+                parameters.push((name.to_string(), value));
+            }
+        }
+        steps.push(parameters);
+    }
+    return steps;
+}
+
+/// Returns the number of steps of the `.step` lines of a log file, None without any.
+pub fn step_count(contents: &str) -> Option<usize> {
+    let steps = contents.lines().filter(|line| line.trim().to_lowercase().starts_with(".step ")).count();
+    return (steps > 0).then_some(steps);
+}
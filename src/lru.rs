@@ -0,0 +1,156 @@
+/*
+ * This file contains the least recently used bookkeeping of the lazy columns
+ *
+ * Spilled, preview and slab columns are paged in (or expanded, or copied) step by step the
+ * first time they are accessed and then stay resident. With a cache budget, the least recently
+ * used decoded columns are dropped until the resident ones fit the budget; they are decoded
+ * again on their next access. The shared accessors (e.g. `get`) return references into the
+ * columns, so nothing can be dropped while they are borrowed: `fetch` makes room for the step it
+ * pages in, and applications only holding shared references call `trim_cache` between frames.
+ */
+
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::debug;
+
+use crate::{SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+// Size of a decoded sample in memory, and in a scratch file (see `spill`)
+pub(crate) const VALUE_SIZE: usize = 16;
+
+// Incremented on every access of a lazy column, to order them by recency
+static CLOCK: AtomicU64 = AtomicU64::new(1);
+
+/* #### Enums #### */
+
+// The storage of a lazy column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    Spilled,
+    Compacted,
+    Slab,
+}
+
+/* #### Structs #### */
+
+/// The last access of a lazy column, clones start with the same recency.
+#[derive(Debug, Default)]
+pub(crate) struct LastAccess(AtomicU64);
+
+/* #### Functions #### */
+
+// Returns the memory used by a decoded column with the specified step lengths.
+pub(crate) fn decoded_size(lengths: &[usize]) -> usize {
+    return lengths.iter().sum::<usize>() * VALUE_SIZE;
+}
+
+/* #### Implementations #### */
+
+impl LastAccess {
+    pub(crate) fn touch(&self) {
+        self.0.store(CLOCK.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub(crate) fn get(&self) -> u64 {
+        return self.0.load(Ordering::Relaxed);
+    }
+}
+
+impl Clone for LastAccess {
+    fn clone(&self) -> Self {
+        return LastAccess(AtomicU64::new(self.get()));
+    }
+}
+
+impl SteppedSimulation {
+    /// Bounds the memory of the decoded lazy columns (spilled, preview or slab variables) to `bytes`,
+    /// evicting the least recently used ones now and on every `trim_cache`. Unbounded by default.
+    pub fn set_cache_budget(&mut self, bytes: usize) {
+        self.cache_budget = Some(bytes);
+        self.trim_cache();
+    }
+
+    /// Returns the budget of the decoded lazy columns, if any.
+    pub fn get_cache_budget(&self) -> Option<usize> {
+        return self.cache_budget;
+    }
+
+    /// Returns the memory currently used by the decoded lazy columns, in bytes.
+    pub fn cache_usage(&self) -> usize {
+        let spilled = self.spilled.values().filter_map(|column| column.resident_size());
+        let compacted = self.compacted.values().filter_map(|column| column.resident_size());
+        let slabs = self.slabs.values().filter_map(|column| column.resident_size());
+        return spilled.chain(compacted).chain(slabs).sum();
+    }
+
+    /// Same as `try_get`, but first evicts the least recently used decoded lazy columns (the
+    /// one of the variable excepted) so the step fits the cache budget once paged in.
+    pub fn fetch(&mut self, name: &str, step: Option<u16>) -> Result<Option<&[Value]>, Box<dyn Error>> {
+        if let Some(budget) = self.cache_budget {
+            let name = self.resolve(name).to_string();
+            let length = match self.data.contains_key(&name) {
+                true => 0,
+                false => self.data.get("x").and_then(|x| x.get(step.unwrap_or_default() as usize)).map_or(0, |x| x.len()),
+            };
+            self.trim_to(budget.saturating_sub(length * VALUE_SIZE), Some(&name));
+        }
+        return self.try_get(name, step);
+    }
+
+    /// Drops the least recently used decoded lazy columns until the others fit the cache
+    /// budget, returns the number of bytes freed. Without a budget, only drops the spilled steps
+    /// paged in both one by one and with their whole column.
+    pub fn trim_cache(&mut self) -> usize {
+        return self.trim_to(self.cache_budget.unwrap_or(usize::MAX), None);
+    }
+
+    // Evicts the least recently used decoded lazy columns, but `keep`, until the others fit `budget`.
+    fn trim_to(&mut self, budget: usize, keep: Option<&str>) -> usize {
+        let before = self.cache_usage();
+        for column in self.spilled.values_mut() {
+            column.consolidate();
+        }
+        let mut usage = self.cache_usage();
+        if usage <= budget {
+            return before - usage;
+        }
+
+        // (last access, storage, name, size) of every resident column, oldest first
+        let mut resident: Vec<(u64, Storage, String, usize)> = self
+            .spilled
+            .iter()
+            .filter_map(|(name, column)| Some((column.last_access(), Storage::Spilled, name.clone(), column.resident_size()?)))
+            .chain(
+                self.compacted
+                    .iter()
+                    .filter_map(|(name, column)| Some((column.last_access(), Storage::Compacted, name.clone(), column.resident_size()?))),
+            )
+            .chain(
+                self.slabs
+                    .iter()
+                    .filter_map(|(name, column)| Some((column.last_access(), Storage::Slab, name.clone(), column.resident_size()?))),
+            )
+            .filter(|(_, _, name, _)| Some(name.as_str()) != keep)
+            .collect();
+        resident.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.cmp(&b.2)));
+
+        let mut freed = before - usage;
+        for (_, storage, name, size) in resident.into_iter() {
+            if usage <= budget {
+                break;
+            }
+            match storage {
+                Storage::Spilled => self.spilled.get_mut(&name).map(|column| column.evict()),
+                Storage::Compacted => self.compacted.get_mut(&name).map(|column| column.evict()),
+                Storage::Slab => self.slabs.get_mut(&name).map(|column| column.evict()),
+            };
+            usage -= size;
+            freed += size;
+        }
+        debug!("Evicted {} bytes of decoded columns.", freed);
+        return freed;
+    }
+}
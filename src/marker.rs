@@ -0,0 +1,114 @@
+/*
+ * This file contains the named markers attached to the traces of a simulation
+ *
+ * Markers annotate a point of a trace (e.g. a peak, a threshold crossing or a user-supplied
+ * event) with a label. They are stored with the simulation, written by the JSON exporter and
+ * drawn by the SVG plots, so analysis results appear on generated figures.
+ */
+
+use crate::trace::Trace;
+use crate::transform::{crossings, interpolate};
+use crate::SteppedSimulation;
+
+/* #### Structs #### */
+
+/// A labelled point of a trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    /// Name of the annotated variable.
+    pub variable: String,
+    pub step: u16,
+    /// Abscissa of the point, e.g. the time in seconds.
+    pub x: f64,
+    /// Value of the trace at the point (real part).
+    pub value: f64,
+    pub label: String,
+}
+
+/* #### Implementations #### */
+
+impl Marker {
+    /// Creates a marker at an abscissa of a trace, with the value interpolated from the trace.
+    /// Returns None if the abscissa is outside the trace.
+    pub fn at(trace: &Trace<'_>, x: f64, label: &str) -> Option<Marker> {
+        let value = interpolate(trace.x(), trace.values(), x)?.real();
+        return Some(Marker {
+            variable: trace.name().to_string(),
+            step: trace.step(),
+            x,
+            value,
+            label: label.to_string(),
+        });
+    }
+
+    /// Creates a marker at the maximum of a trace (real parts), labelled "max".
+    /// Returns None for an empty trace.
+    pub fn peak(trace: &Trace<'_>) -> Option<Marker> {
+        let (x, value) = trace
+            .points()
+            .filter(|(_, value)| value.is_finite())
+            .fold(None, |peak: Option<(f64, f64)>, (x, value)| match peak {
+                Some((_, peak_value)) if peak_value >= value => peak,
+                _ => Some((x, value)),
+            })?;
+        return Some(Marker {
+            variable: trace.name().to_string(),
+            step: trace.step(),
+            x,
+            value,
+            label: "max".to_string(),
+        });
+    }
+
+    /// Creates a marker at every crossing of a threshold, labelled "rise" or "fall".
+    pub fn crossings(trace: &Trace<'_>, threshold: f64) -> Vec<Marker> {
+        return crossings(trace.x(), trace.values(), threshold)
+            .into_iter()
+            .map(|(x, rising)| Marker {
+                variable: trace.name().to_string(),
+                step: trace.step(),
+                x,
+                value: threshold,
+                label: if rising { "rise" } else { "fall" }.to_string(),
+            })
+            .collect();
+    }
+}
+
+impl SteppedSimulation {
+    /// Attaches a marker to the simulation, an alias of the variable is resolved to its name.
+    pub fn add_marker(&mut self, mut marker: Marker) {
+        marker.variable = self.resolve(&marker.variable).to_string();
+        self.markers.push(marker);
+    }
+
+    /// Returns every marker attached to the simulation, in insertion order.
+    pub fn get_markers(&self) -> &[Marker] {
+        return &self.markers;
+    }
+
+    /// Returns the markers of a variable and step.
+    pub fn markers(&self, name: &str, step: u16) -> Vec<&Marker> {
+        let name = self.resolve(name);
+        return self
+            .markers
+            .iter()
+            .filter(|marker| marker.step == step && self.resolve(&marker.variable) == name)
+            .collect();
+    }
+
+    /// Removes every marker of a variable, or of every variable if `name` is None.
+    pub fn clear_markers(&mut self, name: Option<&str>) {
+        match name {
+            Some(name) => {
+                let name = self.resolve(name).to_string();
+                let markers = std::mem::take(&mut self.markers);
+                self.markers = markers
+                    .into_iter()
+                    .filter(|marker| self.resolve(&marker.variable) != name)
+                    .collect();
+            }
+            None => self.markers.clear(),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+/*
+ * This file contains the conversions to nalgebra matrices
+ */
+
+use nalgebra::DMatrix;
+
+use crate::SteppedSimulation;
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Returns the real part of the specified step as a (points × columns) matrix.
+    /// Column 0 is the x axis, followed by the variables in header order.
+    /// Returns None if the step does not exist.
+    pub fn to_dmatrix(&self, step: u16) -> Option<DMatrix<f64>> {
+        let columns = self.get_columns(step)?;
+        let points = columns.iter().map(|(_, values)| values.len()).min().unwrap_or(0);
+
+        return Some(DMatrix::from_fn(points, columns.len(), |point, column| {
+            columns[column].1[point].real()
+        }));
+    }
+
+    /// Returns the real part of the selected variables of a step as a (points × names) matrix,
+    /// convenient for building regressors for least-squares identification.
+    /// Returns None if the step or any of the variables does not exist.
+    pub fn to_dmatrix_of(&self, names: &[&str], step: u16) -> Option<DMatrix<f64>> {
+        let columns = names
+            .iter()
+            .map(|name| self.get(name, Some(step)))
+            .collect::<Option<Vec<_>>>()?;
+        let points = columns.iter().map(|values| values.len()).min().unwrap_or(0);
+
+        return Some(DMatrix::from_fn(points, columns.len(), |point, column| {
+            columns[column][point].real()
+        }));
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * This file contains the Monte Carlo yield estimation over the runs of a sweep
+ *
+ * Each step is treated as one Monte Carlo run, e.g. from '.step param run 1 100 1'.
+ */
+
+use crate::SteppedSimulation;
+
+/* #### Structs #### */
+
+/// The specification limits of a metric, either may be open.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpecLimits {
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+}
+
+/// A histogram with equal-width bins: `counts[i]` samples lie in [edges[i], edges[i + 1]).
+/// The last bin also includes its upper edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+/// The result of `yield_estimate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YieldEstimate {
+    /// Number of runs for which the metric could be evaluated.
+    pub samples: usize,
+    /// Number of runs within the limits.
+    pub passing: usize,
+    pub mean: f64,
+    /// Sample standard deviation.
+    pub std_dev: f64,
+    /// Process capability against the limits, None if no limit is set or the deviation is 0.
+    pub cpk: Option<f64>,
+    pub histogram: Histogram,
+}
+
+/* #### Functions #### */
+
+/// Bins the values in `bins` equal-width bins spanning their range. Non-finite values are ignored.
+pub fn histogram(values: &[f64], bins: usize) -> Histogram {
+    let values: Vec<f64> = values.iter().copied().filter(|value| value.is_finite()).collect();
+    let bins = bins.max(1);
+    let (minimum, maximum) = match values.is_empty() {
+        true => (0.0, 0.0),
+        false => (
+            values.iter().copied().fold(f64::INFINITY, f64::min),
+            values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        ),
+    };
+    let width = (maximum - minimum) / bins as f64;
+
+    let edges = (0..=bins).map(|index| minimum + width * index as f64).collect();
+    let mut counts = vec![0; bins];
+    for value in values.iter() {
+        let index = match width > 0.0 {
+            true => (((value - minimum) / width) as usize).min(bins - 1),
+            false => 0,
+        };
+        counts[index] += 1;
+    }
+    return Histogram { edges, counts };
+}
+
+/// Evaluates the metric on every run and estimates the yield and Cpk against the limits.
+/// Runs for which the metric returns None (or a non-finite value) are skipped, the number of
+/// bins follows Sturges' rule. Returns None if no run could be evaluated.
+pub fn yield_estimate<F>(simulation: &SteppedSimulation, metric: F, limits: SpecLimits) -> Option<YieldEstimate>
+where
+    F: Fn(u16) -> Option<f64>,
+{
+    let values: Vec<f64> = (0..simulation.get_step_count() as u16)
+        .filter_map(&metric)
+        .filter(|value| value.is_finite())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+
+    let samples = values.len();
+    let mean = values.iter().sum::<f64>() / samples as f64;
+    let std_dev = match samples > 1 {
+        true => (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (samples - 1) as f64).sqrt(),
+        false => 0.0,
+    };
+    let passing = values.iter().filter(|value| limits.contains(**value)).count();
+
+    let cpk = match (limits.lower, limits.upper) {
+        _ if std_dev == 0.0 => None,
+        (None, None) => None,
+        (lower, upper) => {
+            let lower = lower.map_or(f64::INFINITY, |lower| (mean - lower) / (3.0 * std_dev));
+            let upper = upper.map_or(f64::INFINITY, |upper| (upper - mean) / (3.0 * std_dev));
+            Some(lower.min(upper))
+        }
+    };
+
+    let bins = (samples as f64).log2().ceil() as usize + 1;
+    return Some(YieldEstimate {
+        samples,
+        passing,
+        mean,
+        std_dev,
+        cpk,
+        histogram: histogram(&values, bins),
+    });
+}
+
+/* #### Implementations #### */
+
+impl SpecLimits {
+    pub fn new(lower: Option<f64>, upper: Option<f64>) -> Self {
+        return SpecLimits { lower, upper };
+    }
+
+    /// Returns whether a value is within the limits, both inclusive.
+    pub fn contains(&self, value: f64) -> bool {
+        return self.lower.is_none_or(|lower| value >= lower) && self.upper.is_none_or(|upper| value <= upper);
+    }
+}
+
+impl YieldEstimate {
+    /// Returns the fraction of the runs within the limits, between 0 and 1.
+    pub fn pass_rate(&self) -> f64 {
+        return self.passing as f64 / self.samples as f64;
+    }
+}
@@ -0,0 +1,217 @@
+/*
+ * This file contains the measurements computed on traces and across the steps of a simulation
+ */
+
+use crate::fit::least_squares;
+use crate::spectral::{power_spectrum, tone_power};
+use crate::trace::Trace;
+use crate::transform::interpolate;
+use crate::SteppedSimulation;
+
+/* #### Constants #### */
+
+/// Reference temperature of the temperature coefficients, in °C.
+pub const REFERENCE_TEMPERATURE: f64 = 25.0;
+
+/* #### Structs #### */
+
+/// A temperature coefficient fit: value(T) = value + linear·ΔT + quadratic·ΔT²,
+/// with ΔT = T - `reference`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempco {
+    pub reference: f64,
+    pub value: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+/* #### Functions #### */
+
+/// Fits the temperature dependence of a measurement across the steps of a temperature sweep.
+/// The metric is evaluated for every step, steps for which it returns None are skipped.
+/// Returns None if there are not enough steps with a known temperature for the fit.
+pub fn tempco<F>(simulation: &SteppedSimulation, metric: F, quadratic: bool) -> Option<Tempco>
+where
+    F: Fn(u16) -> Option<f64>,
+{
+    let points: Vec<(f64, f64)> = (0..simulation.get_step_count() as u16)
+        .filter_map(|step| Some((simulation.get_step_temperature(step)? - REFERENCE_TEMPERATURE, metric(step)?)))
+        .collect();
+
+    let order = match quadratic {
+        true => 2,
+        false => 1,
+    };
+    let columns = (0..=order)
+        .map(|power| points.iter().map(|(t, _)| t.powi(power)).collect())
+        .collect();
+    let solution = least_squares(columns, points.iter().map(|(_, value)| *value).collect())?;
+
+    return Some(Tempco {
+        reference: REFERENCE_TEMPERATURE,
+        value: solution[0],
+        linear: solution[1],
+        quadratic: solution.get(2).copied().unwrap_or(0.0),
+    });
+}
+
+// Integrates the samples over the range (the whole trace if None) with the trapezoidal rule.
+// Segments crossing the range limits are clipped, interpolating linearly.
+pub(crate) fn integrate(x: &[f64], y: &[f64], range: Option<(f64, f64)>) -> f64 {
+    let (start, end) = range.unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+    let mut area = 0.0;
+    for index in 1..x.len().min(y.len()) {
+        let (x0, x1) = (x[index - 1], x[index]);
+        let (a, b) = (x0.max(start), x1.min(end));
+        if b <= a {
+            continue;
+        }
+        let at = |t: f64| y[index - 1] + (y[index] - y[index - 1]) * (t - x0) / (x1 - x0);
+        area += (at(a) + at(b)) * 0.5 * (b - a);
+    }
+    return area;
+}
+
+// Aligns two traces on the union of their x axes, interpolating linearly where both are defined.
+// Returns the abscissa and the real parts of both traces.
+pub(crate) fn align(a: &Trace<'_>, b: &Trace<'_>) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let mut x: Vec<f64> = a.x().iter().chain(b.x().iter()).map(|x| x.real()).collect();
+    x.sort_by(|a, b| a.total_cmp(b));
+    x.dedup();
+
+    let (mut times, mut first, mut second) = (Vec::new(), Vec::new(), Vec::new());
+    for t in x.into_iter() {
+        let (Some(u), Some(v)) = (interpolate(a.x(), a.values(), t), interpolate(b.x(), b.values(), t)) else {
+            continue;
+        };
+        times.push(t);
+        first.push(u.real());
+        second.push(v.real());
+    }
+    return (times, first, second);
+}
+
+/// Returns the energy delivered by a voltage and a current, ∫ v·i dt over the range
+/// (the whole overlap of the traces if None), in joules for V and A traces.
+/// Both traces are aligned on the union of their time points, interpolating linearly,
+/// so traces from different steps or runs can be combined. Only the real parts are used.
+/// Returns None if the traces do not overlap.
+pub fn energy(voltage: &Trace<'_>, current: &Trace<'_>, range: Option<(f64, f64)>) -> Option<f64> {
+    let (times, voltage, current) = align(voltage, current);
+    if times.is_empty() {
+        return None;
+    }
+    let power: Vec<f64> = voltage.iter().zip(current.iter()).map(|(v, i)| v * i).collect();
+    return Some(integrate(&times, &power, range));
+}
+
+/// Returns the charge carried by a current, ∫ i dt over the range (the whole trace if None),
+/// in coulombs for an A trace, e.g. the gate charge of a switching transition.
+/// Only the real parts are used.
+pub fn charge(current: &Trace<'_>, range: Option<(f64, f64)>) -> f64 {
+    let x: Vec<f64> = current.x().iter().map(|x| x.real()).collect();
+    let y: Vec<f64> = current.values().iter().map(|value| value.real()).collect();
+    return integrate(&x, &y, range);
+}
+
+// Time-weighted average of a function of the samples over the whole trace, None if it has no span.
+fn time_average(trace: &Trace<'_>, function: fn(f64) -> f64) -> Option<f64> {
+    let x: Vec<f64> = trace.x().iter().map(|x| x.real()).collect();
+    let y: Vec<f64> = trace.values().iter().map(|value| function(value.real())).collect();
+    let span = x.last()? - x.first()?;
+    if span <= 0.0 {
+        return None;
+    }
+    return Some(integrate(&x, &y, None) / span);
+}
+
+/// Returns the crest factor of a trace, peak / RMS, with the RMS weighted by the time step.
+/// Only the real parts are used. Returns None for an empty or all-zero trace.
+pub fn crest_factor(trace: &Trace<'_>) -> Option<f64> {
+    let rms = time_average(trace, |y| y * y)?.sqrt();
+    let peak = trace.values().iter().fold(0.0f64, |peak, value| peak.max(value.real().abs()));
+    if rms <= 0.0 {
+        return None;
+    }
+    return Some(peak / rms);
+}
+
+/// Returns the form factor of a trace, RMS / rectified mean, both weighted by the time step.
+/// Only the real parts are used. Returns None for an empty or all-zero trace.
+pub fn form_factor(trace: &Trace<'_>) -> Option<f64> {
+    let rms = time_average(trace, |y| y * y)?.sqrt();
+    let mean = time_average(trace, f64::abs)?;
+    if mean <= 0.0 {
+        return None;
+    }
+    return Some(rms / mean);
+}
+
+/// Returns the intermodulation distortion of a two-tone transient trace: the RMS of the
+/// products |m·f1 ± n·f2| (m, n ≥ 1, m + n ≤ `order`) relative to the RMS of the higher tone.
+/// With f1 = 60 Hz and f2 = 7 kHz at 4:1 this is the SMPTE (RP120) measurement, whose
+/// sidebands f2 ± n·f1 are referenced to the HF tone; with two close tones of equal level
+/// (e.g. 19 kHz and 20 kHz) the CCIF one, referenced to one of the tones.
+/// Products falling onto a tone are skipped.
+/// Returns None if the trace is too short or holds no power at the higher tone.
+pub fn imd(trace: &Trace<'_>, f1: f64, f2: f64, order: u32) -> Option<f64> {
+    let (power, resolution) = power_spectrum(trace)?;
+    let nyquist = resolution * power.len() as f64;
+    let reference = tone_power(&power, resolution, f1.max(f2));
+    if reference <= 0.0 {
+        return None;
+    }
+
+    let mut products: Vec<f64> = Vec::new();
+    for m in 1..order {
+        for n in 1..=order - m {
+            for frequency in [m as f64 * f1 + n as f64 * f2, (m as f64 * f1 - n as f64 * f2).abs()] {
+                let tone = [f1, f2].iter().any(|tone| (frequency - tone).abs() < 2.5 * resolution);
+                let duplicate = products.iter().any(|product| (frequency - product).abs() < 0.5 * resolution);
+                if frequency >= 2.5 * resolution && frequency < nyquist && !tone && !duplicate {
+                    products.push(frequency);
+                }
+            }
+        }
+    }
+    let distortion: f64 = products.iter().map(|frequency| tone_power(&power, resolution, *frequency)).sum();
+    return Some((distortion / reference).sqrt());
+}
+
+/* #### Implementations #### */
+
+impl Tempco {
+    /// Returns the linear coefficient relative to the value at the reference, in ppm/°C.
+    pub fn ppm_per_degree(&self) -> f64 {
+        return self.linear / self.value * 1e6;
+    }
+
+    /// Evaluates the fit at a temperature, in °C.
+    pub fn evaluate(&self, temperature: f64) -> f64 {
+        let delta = temperature - self.reference;
+        return self.value + self.linear * delta + self.quadratic * delta * delta;
+    }
+}
+
+impl<'a> Trace<'a> {
+    /// Returns the temperature of the step in °C, if the simulation sweeps it.
+    pub fn temperature(&self) -> Option<f64> {
+        return self.parameter("temp");
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns whether the steps sweep the temperature ('.step temp' or '.temp').
+    pub fn is_temperature_sweep(&self) -> bool {
+        return self.get_step_temperature(0).is_some();
+    }
+
+    /// Returns the temperature of a step in °C, if the simulation sweeps it.
+    pub fn get_step_temperature(&self, step: u16) -> Option<f64> {
+        return self
+            .get_step_parameters(step)?
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("temp"))
+            .map(|(_, value)| *value);
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ * This file contains the combination of several simulations into one dataset
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::{Mode, SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Whether two abscissa values denote the same instant, up to the precision of the raw file.
+fn coincident(a: f64, b: f64) -> bool {
+    return (a - b).abs() <= 1e-9 * a.abs().max(b.abs());
+}
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Loads several raw files and merges them into one stepped simulation, see `merge_simulations`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn merge(files: &[PathBuf]) -> Result<SteppedSimulation, Box<dyn Error>> {
+        let mut simulations = Vec::with_capacity(files.len());
+        for file in files.iter() {
+            let mut simulation = SteppedSimulation::new(file.clone());
+            simulation.reload()?;
+            simulations.push(simulation);
+        }
+        return SteppedSimulation::merge_simulations(simulations);
+    }
+
+    /// Merges simulations run separately (e.g. a manual parameter scan) into one stepped dataset.
+    /// Every step of every simulation becomes a step of the result, in order.
+    /// All simulations must share the same analysis mode and variables.
+    pub fn merge_simulations(simulations: Vec<SteppedSimulation>) -> Result<SteppedSimulation, Box<dyn Error>> {
+        let mut iterator = simulations.into_iter();
+        let mut merged = match iterator.next() {
+            Some(first) => first,
+            None => Err("No simulations to merge.")?,
+        };
+        merged.path = PathBuf::new();
+        merged.materialize()?;
+
+        for mut simulation in iterator {
+            if simulation.mode != merged.mode {
+                Err(format!(
+                    "Cannot merge a {:?} simulation ({:?}) into a {:?} simulation.",
+                    simulation.mode, simulation.path, merged.mode
+                ))?;
+            }
+            let names: Vec<&str> = simulation.variables.iter().map(|variable| variable.name.as_str()).collect();
+            let expected: Vec<&str> = merged.variables.iter().map(|variable| variable.name.as_str()).collect();
+            if names != expected {
+                Err(format!("The variables of {:?} do not match the other simulations.", simulation.path))?;
+            }
+
+            // Simulations without '.step' parameters keep an empty parameter list per step
+            let steps = simulation.get_step_count();
+            let merged_steps = merged.get_step_count();
+            if merged.parameters.len() < merged_steps {
+                merged.parameters.resize(merged_steps, Vec::new());
+            }
+            simulation.materialize()?;
+            let mut parameters = simulation.parameters;
+            parameters.resize(steps, Vec::new());
+            merged.parameters.extend(parameters);
+
+            for (name, data) in simulation.data.into_iter() {
+                merged.data.get_or_default(&name).extend(data);
+            }
+            merged.stats.points += simulation.stats.points;
+        }
+
+        merged.stats.steps = merged.get_step_count() as u16;
+        if merged.stats.steps > 1 && !merged.flags.contains(&crate::Flags::Stepped) {
+            merged.flags.push(crate::Flags::Stepped);
+        }
+        merged.refresh_column_stats();
+        return Ok(merged);
+    }
+
+    /// Stitches sequential transient runs end-to-end, e.g. a simulation restarted from a saved
+    /// operating point. Runs whose time axis restarts are offset to continue where the previous
+    /// run ended, and a boundary point present in both runs is only kept once.
+    /// All runs must be transient, with the same variables and number of steps.
+    pub fn concat_time(simulations: &[SteppedSimulation]) -> Result<SteppedSimulation, Box<dyn Error>> {
+        let mut merged = match simulations.first() {
+            Some(first) => first.clone(),
+            None => Err("No simulations to concatenate.")?,
+        };
+        merged.path = PathBuf::new();
+        merged.materialize()?;
+        if merged.mode != Mode::Transient {
+            Err(format!("Only transient simulations can be concatenated, not {:?}.", merged.mode))?;
+        }
+
+        let steps = merged.get_step_count();
+        for simulation in simulations.iter().skip(1) {
+            if simulation.mode != Mode::Transient {
+                Err(format!("Only transient simulations can be concatenated, not {:?}.", simulation.mode))?;
+            }
+            let names: Vec<&str> = simulation.variables.iter().map(|variable| variable.name.as_str()).collect();
+            let expected: Vec<&str> = merged.variables.iter().map(|variable| variable.name.as_str()).collect();
+            if names != expected {
+                Err(format!("The variables of {:?} do not match the other simulations.", simulation.path))?;
+            }
+            if simulation.get_step_count() != steps {
+                Err(format!("{:?} does not have {} steps.", simulation.path, steps))?;
+            }
+
+            for step in 0..steps {
+                let end = match merged.data.get("x").and_then(|x| x[step].last()) {
+                    Some(end) => end.real(),
+                    None => 0.0,
+                };
+                let x = &simulation.try_column("x")?.ok_or("Missing x axis.")?[step];
+                let start = match x.first() {
+                    Some(start) => start.real(),
+                    None => continue,
+                };
+
+                // Runs restarting at (or before) the previous end continue from it
+                let offset = match start < end || coincident(start, end) {
+                    true => end - start,
+                    false => 0.0,
+                };
+                let skip = match merged.data["x"][step].is_empty() {
+                    true => 0,
+                    false => coincident(start + offset, end) as usize,
+                };
+
+                let names = std::iter::once("x").chain(simulation.variables.iter().map(|variable| variable.name()));
+                for name in names {
+                    let data = simulation.try_column(name)?.ok_or("Mismatching simulation data.")?;
+                    let target = &mut merged.data.get_mut(name).ok_or("Mismatching simulation data.")?[step];
+                    match name {
+                        "x" => target.extend(data[step].iter().skip(skip).map(|value| *value + Value::from(offset))),
+                        _ => target.extend(data[step].iter().skip(skip).copied()),
+                    }
+                }
+            }
+        }
+
+        merged.stats.points = merged.data["x"].iter().map(|x| x.len() as u32).sum();
+        merged.stats.step_size = merged.data["x"].first().map_or(0, |x| x.len() as u32);
+        merged.refresh_column_stats();
+        return Ok(merged);
+    }
+}
@@ -0,0 +1,95 @@
+/*
+ * This file contains the timing of the last load of a simulation
+ *
+ * Allocations are not tracked: measuring them requires a global allocator,
+ * which a library should not impose on its users.
+ */
+
+use std::time::Duration;
+
+use crate::SteppedSimulation;
+
+/* #### Structs #### */
+
+/// Sizes and timings of the last load, see `SteppedSimulation::metrics`.
+/// Timings are zero on wasm32, where no monotonic clock is available.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseMetrics {
+    pub(crate) bytes: u64,
+    pub(crate) read: Duration,
+    pub(crate) header: Duration,
+    pub(crate) decode: Duration,
+    pub(crate) log: Duration,
+}
+
+// Measures the time spent in a phase of the load.
+pub(crate) struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+/* #### Implementations #### */
+
+impl ParseMetrics {
+    /// Returns the size of the raw data, after decompression.
+    pub fn bytes(&self) -> u64 {
+        return self.bytes;
+    }
+
+    /// Returns the time spent reading (and decompressing) the file.
+    pub fn read_time(&self) -> Duration {
+        return self.read;
+    }
+
+    /// Returns the time spent parsing the header.
+    pub fn header_time(&self) -> Duration {
+        return self.header;
+    }
+
+    /// Returns the time spent decoding the samples.
+    pub fn decode_time(&self) -> Duration {
+        return self.decode;
+    }
+
+    /// Returns the time spent loading the step parameters from the log.
+    pub fn log_time(&self) -> Duration {
+        return self.log;
+    }
+
+    /// Returns the wall time of the whole load.
+    pub fn total_time(&self) -> Duration {
+        return self.read + self.header + self.decode + self.log;
+    }
+
+    /// Returns the decoding throughput in bytes per second, None if it was not timed.
+    pub fn throughput(&self) -> Option<f64> {
+        let seconds = (self.header + self.decode).as_secs_f64();
+        if seconds <= 0.0 {
+            return None;
+        }
+        return Some(self.bytes as f64 / seconds);
+    }
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        return Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        };
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed();
+        #[cfg(target_arch = "wasm32")]
+        return Duration::ZERO;
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns the sizes and timings of the last load.
+    pub fn metrics(&self) -> &ParseMetrics {
+        return &self.metrics;
+    }
+}
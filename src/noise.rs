@@ -0,0 +1,69 @@
+/*
+ * This file contains the noise figure computation of '.noise' simulations
+ *
+ * LTSpice writes the output noise density 'onoise' and the input referred noise density
+ * 'inoise' (the output noise divided by the gain from the source). The input referred noise
+ * includes the thermal noise of the source resistance, so the noise factor is the ratio of
+ * both: F = inoise² / (4·k·T·R).
+ */
+
+use crate::trace::{Trace, TraceBuf};
+use crate::transform::interpolate;
+use crate::{Mode, SteppedSimulation, Unit, Value};
+
+/* #### Constants #### */
+
+/// Boltzmann constant, in J/K.
+pub const BOLTZMANN: f64 = 1.380649e-23;
+
+/// Offset between °C and K.
+const ZERO_CELSIUS: f64 = 273.15;
+
+/* #### Functions #### */
+
+// Returns the name of the input referred noise density, e.g. "V(inoise)".
+fn input_noise(simulation: &SteppedSimulation) -> Option<&str> {
+    return simulation
+        .get_variables()
+        .iter()
+        .map(|variable| variable.name())
+        .find(|name| name.to_ascii_lowercase().contains("inoise"));
+}
+
+/// Returns the thermal noise density of a resistance in ohms at a temperature in °C, in V/√Hz.
+pub fn thermal_density(resistance: f64, temperature: f64) -> f64 {
+    return (4.0 * BOLTZMANN * (temperature + ZERO_CELSIUS) * resistance).sqrt();
+}
+
+/// Computes the noise figure vs frequency of every step of a '.noise' simulation, in dB,
+/// for a source resistance in ohms at a temperature in °C (usually 16.85 °C, i.e. 290 K).
+/// Returns an empty vector if the simulation is not a noise analysis or has no 'inoise' trace.
+pub fn figure(simulation: &SteppedSimulation, source_impedance: f64, temperature: f64) -> Vec<TraceBuf> {
+    let name = match input_noise(simulation) {
+        Some(name) if simulation.mode == Mode::Noise => name,
+        _ => return Vec::new(),
+    };
+    let source = thermal_density(source_impedance, temperature);
+
+    return simulation
+        .traces(name)
+        .iter()
+        .map(|trace| {
+            let values = trace
+                .values()
+                .iter()
+                .map(|value| Value::from(10.0 * (value.real() * value.real() / (source * source)).log10()))
+                .collect();
+            trace.with_data("NF".to_string(), trace.x().to_vec(), values).with_unit(Unit::Decibel)
+        })
+        .collect();
+}
+
+/// Returns the spot values of a trace at the specified frequencies, interpolated linearly,
+/// e.g. the noise figure or noise density at 1 kHz. None for frequencies outside the trace.
+pub fn spot(trace: &Trace<'_>, frequencies: &[f64]) -> Vec<Option<f64>> {
+    return frequencies
+        .iter()
+        .map(|frequency| interpolate(trace.x(), trace.values(), *frequency).map(|value| value.real()))
+        .collect();
+}
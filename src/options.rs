@@ -0,0 +1,193 @@
+/*
+ * This file contains the options controlling how raw files are loaded
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/* #### Enums #### */
+
+/// How the decoded samples of the variables are allocated, see `LoadOptions::allocator`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Allocator {
+    /// One vector per step and variable.
+    #[default]
+    Global,
+    /// One vector (a slab) per variable, holding every step back to back.
+    Slab,
+}
+
+/// The precision at which the samples of the variables are stored, see `LoadOptions::preview`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// The decoded f64 samples.
+    #[default]
+    Full,
+    /// f32 samples, about 7 significant digits.
+    Float32,
+    /// i16 samples scaled to the range of every step, about 4.5 significant digits of the range.
+    /// Steps with NaN or infinite samples are stored as f32 instead, which keeps them.
+    Int16,
+}
+
+/* #### Structs #### */
+
+/// A flag shared between threads to abort a load in progress, e.g. from a GUI thread.
+/// Clones refer to the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Options used by `reload` and `from_bytes_with_options`, see `set_load_options`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    strict: bool,
+    apply_offset: bool,
+    memory_limit: Option<usize>,
+    cancel: Option<CancelToken>,
+    x_only: bool,
+    allocator: Allocator,
+    preview: Precision,
+    column_stats: bool,
+}
+
+/* #### Implementations #### */
+
+impl CancelToken {
+    pub fn new() -> Self {
+        return CancelToken::default();
+    }
+
+    /// Requests the load using this token to stop, it then fails with a "Cancelled" error.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the load was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        return self.cancelled.load(Ordering::Relaxed);
+    }
+
+    /// Clears the flag, so the token can be reused for the next load.
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+}
+
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        return Arc::ptr_eq(&self.cancelled, &other.cancelled);
+    }
+}
+
+impl Eq for CancelToken {}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        return LoadOptions::default();
+    }
+
+    /// In strict mode, any header or data anomaly fails the load. Otherwise (the default),
+    /// the loader recovers what it can and records a warning, see `get_warnings`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        return self;
+    }
+
+    /// Returns whether anomalies fail the load.
+    pub fn is_strict(&self) -> bool {
+        return self.strict;
+    }
+
+    /// Adds the header 'Offset' to the x axis, so that the abscissa matches the LTSpice viewer.
+    /// Disabled by default, the offset is then only available through `offset()`.
+    pub fn apply_offset(mut self, apply: bool) -> Self {
+        self.apply_offset = apply;
+        return self;
+    }
+
+    /// Limits the memory used by the decoded samples: the raw file is memory mapped instead of
+    /// read, the variables are decoded one at a time, and those beyond the limit are written to
+    /// a memory mapped scratch file whose steps are paged in when accessed (see
+    /// `SteppedSimulation::set_cache_budget` to bound them). Loading then needs the limit plus
+    /// one decoded variable, whatever the size of the file; compressed files are still
+    /// decompressed in memory. The raw file must not be truncated while it loads, and
+    /// `SteppedSimulation::try_get` reports a spilled variable that can't be read back.
+    /// Not available on wasm32.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        return self;
+    }
+
+    /// Returns the memory limit of the decoded samples, if any.
+    pub fn get_memory_limit(&self) -> Option<usize> {
+        return self.memory_limit;
+    }
+
+    /// Checks the token periodically while loading, and aborts the load once it is cancelled.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        return self;
+    }
+
+    /// Returns whether the token of these options was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        return self.cancel.as_ref().is_some_and(|token| token.is_cancelled());
+    }
+
+    /// Returns whether the header 'Offset' is added to the x axis.
+    pub fn applies_offset(&self) -> bool {
+        return self.apply_offset;
+    }
+
+    /// Only decodes the x axis, skipping the samples of the variables, see `load_x_only`.
+    pub fn x_only(mut self, x_only: bool) -> Self {
+        self.x_only = x_only;
+        return self;
+    }
+
+    /// Returns whether only the x axis is decoded.
+    pub fn is_x_only(&self) -> bool {
+        return self.x_only;
+    }
+
+    /// Decodes every variable into a single slab allocated once for all its steps, instead of one
+    /// vector per step, to cut the allocations of sweeps with thousands of steps. The x axis
+    /// keeps a vector per step. `SteppedSimulation::get` borrows a step from the slab. Loading
+    /// fails if combined with a memory limit or preview precision, which store the variables
+    /// their own way. `Allocator::Global` by default.
+    pub fn allocator(mut self, allocator: Allocator) -> Self {
+        self.allocator = allocator;
+        return self;
+    }
+
+    /// Returns how the decoded samples of the variables are allocated.
+    pub fn get_allocator(&self) -> Allocator {
+        return self.allocator;
+    }
+
+    /// Stores the samples of the variables at a reduced precision, to browse many large files
+    /// with 4 to 8 times less memory. The x axis is kept exact. `Precision::Full` by default.
+    pub fn preview(mut self, precision: Precision) -> Self {
+        self.preview = precision;
+        return self;
+    }
+
+    /// Returns the precision at which the samples of the variables are stored.
+    pub fn get_preview(&self) -> Precision {
+        return self.preview;
+    }
+
+    /// Computes the min, max, mean and RMS of every variable and step while decoding, see
+    /// `SteppedSimulation::column_stats`. Disabled by default.
+    pub fn column_stats(mut self, compute: bool) -> Self {
+        self.column_stats = compute;
+        return self;
+    }
+
+    /// Returns whether the statistics of the variables are computed while decoding.
+    pub fn computes_column_stats(&self) -> bool {
+        return self.column_stats;
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * This file contains the insertion-ordered map used for everything keyed by variable name
+ *
+ * Iterating a `HashMap` yields a different order on every run, which made exports, warnings
+ * and renames nondeterministic. `OrderedMap` keeps the entries in insertion order
+ * (header order for the data), with a hash index for lookups.
+ */
+
+use std::collections::HashMap;
+use std::ops::Index;
+
+/* #### Structs #### */
+
+/// A map from names to values, iterated in insertion order.
+#[derive(Debug, Clone)]
+pub struct OrderedMap<V> {
+    entries: Vec<(String, V)>,
+    index: HashMap<String, usize>,
+}
+
+/* #### Implementations #### */
+
+impl<V> Default for OrderedMap<V> {
+    fn default() -> Self {
+        return OrderedMap {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        };
+    }
+}
+
+impl<V> OrderedMap<V> {
+    pub fn new() -> Self {
+        return OrderedMap::default();
+    }
+
+    /// Inserts a value, returns the previous one. A replaced entry keeps its position.
+    pub fn insert(&mut self, key: String, value: V) -> Option<V> {
+        if let Some(position) = self.index.get(&key) {
+            return Some(std::mem::replace(&mut self.entries[*position].1, value));
+        }
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+        return None;
+    }
+
+    /// Removes an entry, the following entries keep their relative order.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let position = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for (name, _) in self.entries[position..].iter() {
+            if let Some(index) = self.index.get_mut(name) {
+                *index -= 1;
+            }
+        }
+        return Some(value);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        return self.index.get(key).map(|position| &self.entries[*position].1);
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        return match self.index.get(key) {
+            Some(position) => Some(&mut self.entries[*position].1),
+            None => None,
+        };
+    }
+
+    /// Returns the value of a key, inserting the default value at the end if it is missing.
+    pub fn get_or_default(&mut self, key: &str) -> &mut V
+    where
+        V: Default,
+    {
+        if !self.index.contains_key(key) {
+            self.insert(key.to_string(), V::default());
+        }
+        let position = self.index[key];
+        return &mut self.entries[position].1;
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        return self.index.contains_key(key);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// Returns the entries, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &V)> {
+        return self.entries.iter().map(|(key, value)| (key, value));
+    }
+
+    /// Returns the keys, in insertion order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        return self.entries.iter().map(|(key, _)| key);
+    }
+
+    /// Returns the values, in insertion order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        return self.entries.iter().map(|(_, value)| value);
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        return self.entries.iter_mut().map(|(_, value)| value);
+    }
+}
+
+impl<V: PartialEq> PartialEq for OrderedMap<V> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.entries == other.entries;
+    }
+}
+
+impl<V> Index<&str> for OrderedMap<V> {
+    type Output = V;
+
+    fn index(&self, key: &str) -> &V {
+        return match self.get(key) {
+            Some(value) => value,
+            None => panic!("No entry named '{}'.", key),
+        };
+    }
+}
+
+impl<V> FromIterator<(String, V)> for OrderedMap<V> {
+    fn from_iter<I: IntoIterator<Item = (String, V)>>(iterator: I) -> Self {
+        let mut map = OrderedMap::new();
+        for (key, value) in iterator {
+            map.insert(key, value);
+        }
+        return map;
+    }
+}
+
+impl<V> IntoIterator for OrderedMap<V> {
+    type Item = (String, V);
+    type IntoIter = std::vec::IntoIter<(String, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        return self.entries.into_iter();
+    }
+}
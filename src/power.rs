@@ -0,0 +1,284 @@
+/*
+ * This file contains the switching-loss breakdown of power devices
+ *
+ * Every aligned point of the drain-source voltage and drain current is classified as
+ * on (low voltage), off (low current) or switching (neither). Switching intervals
+ * entered from the off state are turn-on events, those entered from the on state are
+ * turn-off events, and the on state is conduction. A cycle runs from the start of a
+ * turn-on event to the start of the next one, incomplete cycles at the ends are ignored.
+ *
+ * The dead time of a half bridge is measured between a gate crossing its threshold
+ * downwards and the other gate crossing it upwards.
+ */
+
+use crate::measure::align;
+use crate::trace::Trace;
+use crate::transform::crossings;
+use crate::SteppedSimulation;
+
+/* #### Enums #### */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    On,
+    Off,
+    Switching,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Loss {
+    TurnOn,
+    TurnOff,
+    Conduction,
+}
+
+/// Which gate turned off first in a dead-time interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Commutation {
+    /// The high-side gate turned off, then the low-side gate turned on.
+    HighToLow,
+    /// The low-side gate turned off, then the high-side gate turned on.
+    LowToHigh,
+}
+
+/* #### Structs #### */
+
+/// The limits of the on and off states of a switch, see `switching_losses`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    /// The switch is on below this drain-source voltage.
+    pub on_voltage: f64,
+    /// The switch is off below this drain current.
+    pub off_current: f64,
+}
+
+/// The energies dissipated during one switching cycle, in joules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CycleLosses {
+    pub start: f64,
+    pub end: f64,
+    pub turn_on: f64,
+    pub turn_off: f64,
+    pub conduction: f64,
+}
+
+/// The result of `switching_losses`: every complete cycle, and the average powers over them in watts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchingLosses {
+    pub cycles: Vec<CycleLosses>,
+    pub turn_on: f64,
+    pub turn_off: f64,
+    pub conduction: f64,
+}
+
+/// A dead-time interval, between a gate turning off and the other turning on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeadTimeEdge {
+    pub commutation: Commutation,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// The dead times and shoot-through intervals of one step, see `dead_time`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadTimes {
+    pub step: u16,
+    pub edges: Vec<DeadTimeEdge>,
+    /// The (start, end) intervals during which both gates are on.
+    pub shoot_through: Vec<(f64, f64)>,
+}
+
+/* #### Functions #### */
+
+/// Measures the dead time of every commutation of a half bridge and flags the intervals where
+/// both gates are on (shoot-through), for every step. A gate is on above the threshold.
+/// Steps without both gates are skipped.
+pub fn dead_time(simulation: &SteppedSimulation, gate_high: &str, gate_low: &str, threshold: f64) -> Vec<DeadTimes> {
+    let mut results = Vec::new();
+    for step in 0..simulation.get_step_count() as u16 {
+        let (Some(high), Some(low)) = (simulation.trace(gate_high, step), simulation.trace(gate_low, step)) else {
+            continue;
+        };
+        let (Some(high_start), Some(low_start)) = (high.values().first(), low.values().first()) else {
+            continue;
+        };
+
+        // (time, gate is high-side, rising), in time order
+        let mut events: Vec<(f64, bool, bool)> = crossings(high.x(), high.values(), threshold)
+            .into_iter()
+            .map(|(time, rising)| (time, true, rising))
+            .chain(crossings(low.x(), low.values(), threshold).into_iter().map(|(time, rising)| (time, false, rising)))
+            .collect();
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut result = DeadTimes {
+            step,
+            edges: Vec::new(),
+            shoot_through: Vec::new(),
+        };
+        let (mut high_on, mut low_on) = (high_start.real() > threshold, low_start.real() > threshold);
+        let mut overlap = match high_on && low_on {
+            true => high.x().first().map(|x| x.real()),
+            false => None,
+        };
+        let mut turned_off: Option<(bool, f64)> = None;
+        for (time, is_high, rising) in events.into_iter() {
+            match is_high {
+                true => high_on = rising,
+                false => low_on = rising,
+            }
+
+            match (rising, turned_off) {
+                (false, _) => turned_off = Some((is_high, time)),
+                (true, Some((off_high, start))) if off_high != is_high => {
+                    result.edges.push(DeadTimeEdge {
+                        commutation: match off_high {
+                            true => Commutation::HighToLow,
+                            false => Commutation::LowToHigh,
+                        },
+                        start,
+                        end: time,
+                    });
+                    turned_off = None;
+                }
+                (true, _) => turned_off = None,
+            }
+
+            match (high_on && low_on, overlap) {
+                (true, None) => overlap = Some(time),
+                (false, Some(start)) => {
+                    result.shoot_through.push((start, time));
+                    overlap = None;
+                }
+                _ => {}
+            }
+        }
+        if let (Some(start), Some(end)) = (overlap, high.x().last()) {
+            result.shoot_through.push((start, end.real()));
+        }
+        results.push(result);
+    }
+    return results;
+}
+
+/// Splits the losses of a switch into turn-on, turn-off and conduction losses, per cycle and
+/// on average. The traces are aligned on the union of their time points, and only the real
+/// parts are used. Returns None if the traces do not contain a complete cycle.
+pub fn switching_losses(vds: &Trace<'_>, id: &Trace<'_>, thresholds: Thresholds) -> Option<SwitchingLosses> {
+    let (times, voltage, current) = align(vds, id);
+    let states: Vec<State> = voltage
+        .iter()
+        .zip(current.iter())
+        .map(|(v, i)| thresholds.classify(*v, *i))
+        .collect();
+
+    let mut cycles: Vec<CycleLosses> = Vec::new();
+    let mut cycle: Option<CycleLosses> = None;
+    let mut stable = State::Off;
+    let mut previous_loss: Option<Loss> = None;
+    for index in 1..times.len() {
+        let (from, to) = (states[index - 1], states[index]);
+        if from != State::Switching {
+            stable = from;
+        }
+        let loss = match (from, to) {
+            (State::On, State::On) => Some(Loss::Conduction),
+            (State::Off, State::Off) => None,
+            _ if stable == State::Off => Some(Loss::TurnOn),
+            _ => Some(Loss::TurnOff),
+        };
+
+        // A new cycle starts with every turn-on event
+        if loss == Some(Loss::TurnOn) && previous_loss != Some(Loss::TurnOn) {
+            if let Some(mut complete) = cycle.take() {
+                complete.end = times[index - 1];
+                cycles.push(complete);
+            }
+            cycle = Some(CycleLosses {
+                start: times[index - 1],
+                end: times[index - 1],
+                turn_on: 0.0,
+                turn_off: 0.0,
+                conduction: 0.0,
+            });
+        }
+        previous_loss = loss;
+
+        let (Some(current_cycle), Some(loss)) = (cycle.as_mut(), loss) else {
+            continue;
+        };
+        let energy = (voltage[index - 1] * current[index - 1] + voltage[index] * current[index])
+            * 0.5
+            * (times[index] - times[index - 1]);
+        match loss {
+            Loss::TurnOn => current_cycle.turn_on += energy,
+            Loss::TurnOff => current_cycle.turn_off += energy,
+            Loss::Conduction => current_cycle.conduction += energy,
+        }
+    }
+
+    let (first, last) = (cycles.first()?, cycles.last()?);
+    let duration = last.end - first.start;
+    if duration <= 0.0 {
+        return None;
+    }
+    let total = |energy: fn(&CycleLosses) -> f64| cycles.iter().map(energy).sum::<f64>() / duration;
+
+    return Some(SwitchingLosses {
+        turn_on: total(|cycle| cycle.turn_on),
+        turn_off: total(|cycle| cycle.turn_off),
+        conduction: total(|cycle| cycle.conduction),
+        cycles,
+    });
+}
+
+/* #### Implementations #### */
+
+impl Thresholds {
+    pub fn new(on_voltage: f64, off_current: f64) -> Self {
+        return Thresholds { on_voltage, off_current };
+    }
+
+    fn classify(&self, voltage: f64, current: f64) -> State {
+        if voltage.abs() <= self.on_voltage {
+            return State::On;
+        }
+        if current.abs() <= self.off_current {
+            return State::Off;
+        }
+        return State::Switching;
+    }
+}
+
+impl DeadTimeEdge {
+    /// Returns the duration of the interval.
+    pub fn dead_time(&self) -> f64 {
+        return self.end - self.start;
+    }
+}
+
+impl DeadTimes {
+    /// Returns the shortest dead time of the step, None if there was no commutation.
+    pub fn minimum(&self) -> Option<f64> {
+        return self.edges.iter().map(|edge| edge.dead_time()).min_by(|a, b| a.total_cmp(b));
+    }
+
+    /// Returns whether both gates were on at the same time.
+    pub fn has_shoot_through(&self) -> bool {
+        return !self.shoot_through.is_empty();
+    }
+}
+
+impl CycleLosses {
+    /// Returns the total energy of the cycle.
+    pub fn total(&self) -> f64 {
+        return self.turn_on + self.turn_off + self.conduction;
+    }
+}
+
+impl SwitchingLosses {
+    /// Returns the total average power.
+    pub fn total(&self) -> f64 {
+        return self.turn_on + self.turn_off + self.conduction;
+    }
+}
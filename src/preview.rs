@@ -0,0 +1,197 @@
+/*
+ * This file contains the compact storage of decoded columns, used for previews
+ *
+ * With `LoadOptions::preview`, every variable (the x axis excepted) is stored as f32 samples, or
+ * as i16 samples with a scale and offset per step, instead of two f64 per sample: 4x (resp. 8x)
+ * less memory for real data. A step is expanded the first time it is accessed (e.g. by `get` or
+ * `trace`), so browsing a few steps of many huge files stays cheap; only the accessors returning
+ * whole columns (e.g. `iter`) expand every step. Expanded steps stay resident until evicted, see
+ * `lru`. `load_full_precision` reloads the exact samples.
+ */
+
+use std::error::Error;
+use std::sync::OnceLock;
+
+use crate::lru::{decoded_size, LastAccess};
+use crate::options::Precision;
+use crate::{SteppedSimulation, Value};
+
+/* #### Structs #### */
+
+// Samples of one component (real or imaginary) of a step.
+#[derive(Debug, Clone)]
+enum Samples {
+    Float32(Vec<f32>),
+    // value = offset + scale * sample
+    Int16 { offset: f64, scale: f64, samples: Vec<i16> },
+}
+
+// One step of a compact column, the imaginary part is only stored for complex data.
+#[derive(Debug, Clone)]
+struct CompactStep {
+    real: Samples,
+    imaginary: Option<Samples>,
+}
+
+/// A column stored at preview precision, expanded step by step on first access.
+#[derive(Debug, Clone)]
+pub(crate) struct CompactColumn {
+    steps: Vec<CompactStep>,
+    // Steps expanded by `get_step`
+    expanded_steps: Vec<OnceLock<Vec<Value>>>,
+    // Whole column expanded by `get`
+    expanded: OnceLock<Vec<Vec<Value>>>,
+    last_access: LastAccess,
+}
+
+/* #### Functions #### */
+
+// Steps with non-finite samples are stored as f32, which keeps NaN and infinities where i16
+// samples would turn them into numbers.
+fn compress(values: impl Iterator<Item = f64> + Clone, precision: Precision) -> Samples {
+    let finite = values.clone().all(|value| value.is_finite());
+    return match precision {
+        Precision::Int16 if finite => {
+            let (min, max) = values
+                .clone()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+            let (offset, scale) = match min <= max {
+                true => ((max + min) / 2.0, ((max - min) / 2.0 / i16::MAX as f64).max(f64::MIN_POSITIVE)),
+                false => (0.0, 1.0),
+            };
+            Samples::Int16 {
+                offset,
+                scale,
+                samples: values.map(|value| ((value - offset) / scale).round() as i16).collect(),
+            }
+        }
+        Precision::Int16 | Precision::Float32 | Precision::Full => Samples::Float32(values.map(|value| value as f32).collect()),
+    };
+}
+
+/* #### Implementations #### */
+
+impl Samples {
+    fn len(&self) -> usize {
+        return match self {
+            Samples::Float32(samples) => samples.len(),
+            Samples::Int16 { samples, .. } => samples.len(),
+        };
+    }
+
+    fn get(&self, index: usize) -> f64 {
+        return match self {
+            Samples::Float32(samples) => samples[index] as f64,
+            Samples::Int16 { offset, scale, samples } => offset + scale * samples[index] as f64,
+        };
+    }
+}
+
+impl CompactStep {
+    fn expand(&self) -> Vec<Value> {
+        return (0..self.real.len())
+            .map(|index| Value {
+                real: self.real.get(index),
+                imaginary: self.imaginary.as_ref().map_or(0.0, |imaginary| imaginary.get(index)),
+            })
+            .collect();
+    }
+}
+
+impl CompactColumn {
+    pub(crate) fn new(steps: &[Vec<Value>], precision: Precision) -> Self {
+        let complex = steps.iter().flatten().any(|value| value.imaginary != 0.0);
+        return CompactColumn {
+            steps: steps
+                .iter()
+                .map(|values| CompactStep {
+                    real: compress(values.iter().map(|value| value.real), precision),
+                    imaginary: complex.then(|| compress(values.iter().map(|value| value.imaginary), precision)),
+                })
+                .collect(),
+            expanded_steps: steps.iter().map(|_| OnceLock::new()).collect(),
+            expanded: OnceLock::new(),
+            last_access: LastAccess::default(),
+        };
+    }
+
+    // Returns the number of points of each step.
+    pub(crate) fn lengths(&self) -> Vec<usize> {
+        return self.steps.iter().map(|step| step.real.len()).collect();
+    }
+
+    // Returns the memory used by the expanded steps, if any, see `lru`.
+    pub(crate) fn resident_size(&self) -> Option<usize> {
+        let lengths = self.lengths();
+        let steps: Vec<usize> = lengths
+            .iter()
+            .zip(self.expanded_steps.iter())
+            .filter_map(|(length, step)| step.get().map(|_| *length))
+            .collect();
+        return match (self.expanded.get(), steps.is_empty()) {
+            (None, true) => None,
+            (None, false) => Some(decoded_size(&steps)),
+            (Some(_), _) => Some(decoded_size(&lengths) + decoded_size(&steps)),
+        };
+    }
+
+    pub(crate) fn last_access(&self) -> u64 {
+        return self.last_access.get();
+    }
+
+    // Drops the expanded steps, they are expanded again on the next access.
+    pub(crate) fn evict(&mut self) {
+        self.expanded.take();
+        for step in self.expanded_steps.iter_mut() {
+            step.take();
+        }
+    }
+
+    // Returns a step, expanding only this step on first access.
+    pub(crate) fn get_step(&self, step: usize) -> Option<&Vec<Value>> {
+        self.last_access.touch();
+        if let Some(steps) = self.expanded.get() {
+            return steps.get(step);
+        }
+        let compact = self.steps.get(step)?;
+        return Some(self.expanded_steps[step].get_or_init(|| compact.expand()));
+    }
+
+    // Returns the whole column, expanding every step on first access.
+    pub(crate) fn get(&self) -> &Vec<Vec<Value>> {
+        self.last_access.touch();
+        return self.expanded.get_or_init(|| self.steps.iter().map(|step| step.expand()).collect());
+    }
+
+    // Returns the expanded column without keeping it.
+    pub(crate) fn expand(&self) -> Vec<Vec<Value>> {
+        return self.steps.iter().map(|step| step.expand()).collect();
+    }
+}
+
+impl SteppedSimulation {
+    // Moves every variable to compact storage, the x axis is kept at full precision.
+    pub(crate) fn compact(&mut self, precision: Precision) {
+        if precision == Precision::Full {
+            return;
+        }
+        let names: Vec<String> = self.data.keys().filter(|name| name.as_str() != "x").cloned().collect();
+        for name in names.into_iter() {
+            if let Some(steps) = self.data.remove(&name) {
+                self.compacted.insert(name, CompactColumn::new(&steps, precision));
+            }
+        }
+    }
+
+    /// Returns whether the variables are stored at preview precision, see `LoadOptions::preview`.
+    pub fn is_preview(&self) -> bool {
+        return !self.compacted.is_empty();
+    }
+
+    /// Reloads the file with the exact samples, keeping the other load options.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_full_precision(&mut self) -> Result<(), Box<dyn Error>> {
+        self.options = self.options.clone().preview(Precision::Full);
+        return self.reload();
+    }
+}
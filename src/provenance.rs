@@ -0,0 +1,94 @@
+/*
+ * This file contains the provenance of a loaded simulation
+ *
+ * The raw contents are hashed while loading, and the hash is carried with the source path,
+ * size, modification time and parser version through the exporters, so that derived datasets
+ * can be traced back to the exact simulation they were produced from.
+ */
+
+use std::path::{Path, PathBuf};
+
+use crate::SteppedSimulation;
+
+/* #### Constants #### */
+
+/// Version of this crate, recorded as the parser version.
+pub const PARSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/* #### Structs #### */
+
+/// Where a simulation was loaded from, see `SteppedSimulation::provenance`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "msgpack", feature = "cbor"), derive(serde::Serialize, serde::Deserialize))]
+pub struct Provenance {
+    /// Path of the raw file, empty for simulations parsed from memory.
+    pub path: PathBuf,
+    /// Size of the raw contents in bytes, after decompression.
+    pub size: u64,
+    /// Modification time of the file in seconds since the UNIX epoch, None for in-memory data.
+    pub modified: Option<u64>,
+    /// FNV-1a hash of the raw contents, after decompression.
+    pub hash: u64,
+    /// Version of the crate which parsed the simulation.
+    pub parser_version: String,
+}
+
+/* #### Functions #### */
+
+/// FNV-1a hash, stable across platforms and compiler versions.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    return fnv1a_continue(0xcbf29ce484222325, bytes);
+}
+
+/// Continues an FNV-1a hash with more bytes, to hash data read in chunks.
+pub(crate) fn fnv1a_continue(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes.iter() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
+
+/* #### Implementations #### */
+
+impl Provenance {
+    /// Returns the provenance of raw contents, without file information.
+    pub(crate) fn of_bytes(path: &Path, bytes: &[u8]) -> Self {
+        return Provenance {
+            path: path.to_path_buf(),
+            size: bytes.len() as u64,
+            modified: None,
+            hash: fnv1a(bytes),
+            parser_version: PARSER_VERSION.to_string(),
+        };
+    }
+
+    /// Returns the hash as 16 hexadecimal digits.
+    pub fn hash_hex(&self) -> String {
+        return format!("{:016x}", self.hash);
+    }
+
+    /// Returns a one line description, e.g. "tran.raw (1234 bytes, fnv1a 0123456789abcdef, ltspice 0.1.0)".
+    pub fn describe(&self) -> String {
+        return format!(
+            "{} ({} bytes, fnv1a {}, ltspice {})",
+            self.path.to_string_lossy(),
+            self.size,
+            self.hash_hex(),
+            self.parser_version
+        );
+    }
+}
+
+impl Default for Provenance {
+    fn default() -> Self {
+        return Provenance::of_bytes(Path::new(""), &[]);
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns where the simulation was loaded from, with the hash of its raw contents.
+    pub fn provenance(&self) -> &Provenance {
+        return &self.provenance;
+    }
+}
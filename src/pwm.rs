@@ -0,0 +1,90 @@
+/*
+ * This file contains the cycle-by-cycle analysis of PWM waveforms
+ *
+ * A cycle runs from a rising crossing of the threshold to the next one, and its duty is the
+ * fraction of the cycle spent above the threshold. Incomplete cycles at the ends are ignored.
+ */
+
+use crate::trace::{Trace, TraceBuf};
+use crate::transform::crossings;
+use crate::{Unit, Value};
+
+/* #### Structs #### */
+
+/// A single PWM cycle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PwmCycle {
+    /// Time of the rising edge starting the cycle.
+    pub start: f64,
+    pub period: f64,
+    /// Fraction of the period spent above the threshold, between 0 and 1.
+    pub duty: f64,
+}
+
+/* #### Functions #### */
+
+/// Splits a switching waveform into cycles, using the midpoint between its minimum and
+/// maximum as threshold. Only the real parts are considered.
+pub fn analyze(trace: &Trace<'_>) -> Vec<PwmCycle> {
+    let values = trace.values().iter().map(|value| value.real());
+    let minimum = values.clone().fold(f64::INFINITY, f64::min);
+    let maximum = values.fold(f64::NEG_INFINITY, f64::max);
+    return analyze_with_threshold(trace, (minimum + maximum) / 2.0);
+}
+
+/// Splits a switching waveform into cycles, see `analyze`.
+pub fn analyze_with_threshold(trace: &Trace<'_>, threshold: f64) -> Vec<PwmCycle> {
+    let edges = crossings(trace.x(), trace.values(), threshold);
+    let rising: Vec<usize> = (0..edges.len()).filter(|index| edges[*index].1).collect();
+
+    let mut cycles = Vec::new();
+    for pair in rising.windows(2) {
+        let (start, end) = (edges[pair[0]].0, edges[pair[1]].0);
+        let period = end - start;
+        if period <= 0.0 {
+            continue;
+        }
+        // The falling edge between the two rising edges, a missing one means 100% duty
+        let falling = edges[pair[0]..pair[1]].iter().find(|(_, rising)| !rising).map_or(end, |(time, _)| *time);
+        cycles.push(PwmCycle {
+            start,
+            period,
+            duty: (falling - start) / period,
+        });
+    }
+    return cycles;
+}
+
+/// Returns the duty of every cycle at its start time, e.g. to reconstruct the control signal.
+pub fn duty(trace: &Trace<'_>) -> TraceBuf {
+    let cycles = analyze(trace);
+    let x = cycles.iter().map(|cycle| Value::from(cycle.start)).collect();
+    let values = cycles.iter().map(|cycle| Value::from(cycle.duty)).collect();
+    return trace
+        .with_data(format!("duty({})", trace.name()), x, values)
+        .with_unit(Unit::Dimensionless);
+}
+
+/// Returns the switching frequency of every cycle at its start time.
+pub fn frequency(trace: &Trace<'_>) -> TraceBuf {
+    let cycles = analyze(trace);
+    let x = cycles.iter().map(|cycle| Value::from(cycle.start)).collect();
+    let values = cycles.iter().map(|cycle| Value::from(cycle.frequency())).collect();
+    return trace
+        .with_data(format!("frequency({})", trace.name()), x, values)
+        .with_unit(Unit::Hertz);
+}
+
+/* #### Implementations #### */
+
+impl PwmCycle {
+    /// Returns the frequency of the cycle.
+    pub fn frequency(&self) -> f64 {
+        return 1.0 / self.period;
+    }
+
+    /// Returns the time spent above the threshold.
+    pub fn on_time(&self) -> f64 {
+        return self.duty * self.period;
+    }
+}
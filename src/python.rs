@@ -0,0 +1,248 @@
+/*
+ * This file contains the Python bindings, built with maturin
+ *
+ * Besides trace access as numpy arrays, the measurements of `measure`, `spectral`, `ac` and
+ * `stability` are exposed as methods taking variable names, e.g. `sim.crest_factor("V(out)")`.
+ */
+
+// The pyo3 0.20 macros expand to impl blocks inside const items, which newer compilers warn about
+#![allow(non_local_definitions)]
+
+use std::path::PathBuf;
+
+use numpy::{Complex64, PyArray1};
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::trace::{Trace, TraceBuf};
+use crate::{ac, downsample, measure, spectral, stability, SteppedSimulation};
+
+// A stability margin: (crossover frequency, margin)
+type Margin = (f64, f64);
+
+/* #### Structs #### */
+
+/// A parsed LTSpice simulation, exposed to Python.
+#[pyclass(name = "SteppedSimulation")]
+pub struct PySimulation {
+    inner: SteppedSimulation,
+}
+
+/* #### Implementations #### */
+
+impl PySimulation {
+    fn trace(&self, name: &str, step: u16) -> PyResult<&Vec<crate::Value>> {
+        return match self.inner.get(name, Some(step)) {
+            Some(trace) => Ok(trace),
+            None => Err(PyKeyError::new_err(format!("No variable '{}' at step {}", name, step))),
+        };
+    }
+
+    fn view(&self, name: &str, step: u16) -> PyResult<Trace<'_>> {
+        return match self.inner.trace(name, step) {
+            Some(trace) => Ok(trace),
+            None => Err(PyKeyError::new_err(format!("No variable '{}' at step {}", name, step))),
+        };
+    }
+}
+
+/* #### Functions #### */
+
+// Returns the range of a measurement, the whole trace unless both bounds are given.
+fn range(start: Option<f64>, end: Option<f64>) -> Option<(f64, f64)> {
+    return match (start, end) {
+        (Some(start), Some(end)) => Some((start, end)),
+        _ => None,
+    };
+}
+
+// Returns a computed trace as numpy arrays (x, complex values).
+fn complex_arrays<'py>(py: Python<'py>, trace: &TraceBuf) -> (&'py PyArray1<f64>, &'py PyArray1<Complex64>) {
+    let x = trace.x().iter().map(|x| x.real()).collect();
+    let values = trace
+        .values()
+        .iter()
+        .map(|value| Complex64::new(value.real(), value.imaginary()))
+        .collect();
+    return (PyArray1::from_vec(py, x), PyArray1::from_vec(py, values));
+}
+
+#[pymethods]
+impl PySimulation {
+    /// Loads and parses the specified '.raw' file.
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        let mut inner = SteppedSimulation::new(path);
+        if let Err(error) = inner.reload() {
+            return Err(PyValueError::new_err(error.to_string()));
+        }
+        return Ok(PySimulation { inner });
+    }
+
+    /// Parses a simulation from the raw file contents.
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        return match SteppedSimulation::from_bytes(bytes) {
+            Ok(inner) => Ok(PySimulation { inner }),
+            Err(error) => Err(PyValueError::new_err(error.to_string())),
+        };
+    }
+
+    /// The names of the loaded variables.
+    #[getter]
+    fn variables(&self) -> Vec<String> {
+        return self
+            .inner
+            .get_variables()
+            .iter()
+            .map(|variable| variable.name().to_string())
+            .collect();
+    }
+
+    /// The number of loaded steps.
+    #[getter]
+    fn steps(&self) -> usize {
+        return self.inner.get_step_count();
+    }
+
+    /// The analysis mode, e.g. "Transient" or "AC".
+    #[getter]
+    fn mode(&self) -> String {
+        return self.inner.get_mode().to_string();
+    }
+
+    /// Returns the real part of a variable as a numpy array.
+    #[pyo3(signature = (name, step = 0))]
+    fn get<'py>(&self, py: Python<'py>, name: &str, step: u16) -> PyResult<&'py PyArray1<f64>> {
+        let values = self.trace(name, step)?.iter().map(|value| value.real()).collect();
+        return Ok(PyArray1::from_vec(py, values));
+    }
+
+    /// Returns a variable as a complex numpy array, for AC and FFT simulations.
+    #[pyo3(signature = (name, step = 0))]
+    fn get_complex<'py>(&self, py: Python<'py>, name: &str, step: u16) -> PyResult<&'py PyArray1<Complex64>> {
+        let values = self
+            .trace(name, step)?
+            .iter()
+            .map(|value| Complex64::new(value.real(), value.imaginary()))
+            .collect();
+        return Ok(PyArray1::from_vec(py, values));
+    }
+
+    /// Returns the x axis (time, frequency, ...) of a step as a numpy array.
+    #[pyo3(signature = (step = 0))]
+    fn get_x<'py>(&self, py: Python<'py>, step: u16) -> PyResult<&'py PyArray1<f64>> {
+        return self.get(py, "x", step);
+    }
+
+    /// Returns a min/max downsampled trace as two numpy arrays (x, y).
+    #[pyo3(signature = (name, step = 0, buckets = 1000, start = None, end = None))]
+    fn downsample<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        step: u16,
+        buckets: usize,
+        start: Option<f64>,
+        end: Option<f64>,
+    ) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<f64>)> {
+        let x = self.trace("x", step)?;
+        let y = self.trace(name, step)?;
+        let (x, y): (Vec<f64>, Vec<f64>) = downsample::min_max(x, y, range(start, end), buckets).into_iter().unzip();
+        return Ok((PyArray1::from_vec(py, x), PyArray1::from_vec(py, y)));
+    }
+
+    /// Returns the crest factor (peak / RMS) of a variable, None for an all-zero trace.
+    #[pyo3(signature = (name, step = 0))]
+    fn crest_factor(&self, name: &str, step: u16) -> PyResult<Option<f64>> {
+        return Ok(measure::crest_factor(&self.view(name, step)?));
+    }
+
+    /// Returns the form factor (RMS / rectified mean) of a variable, None for an all-zero trace.
+    #[pyo3(signature = (name, step = 0))]
+    fn form_factor(&self, name: &str, step: u16) -> PyResult<Option<f64>> {
+        return Ok(measure::form_factor(&self.view(name, step)?));
+    }
+
+    /// Returns the charge carried by a current between start and end (the whole trace by default).
+    #[pyo3(signature = (name, step = 0, start = None, end = None))]
+    fn charge(&self, name: &str, step: u16, start: Option<f64>, end: Option<f64>) -> PyResult<f64> {
+        return Ok(measure::charge(&self.view(name, step)?, range(start, end)));
+    }
+
+    /// Returns the energy ∫ v·i dt between start and end (the overlap of the traces by default).
+    #[pyo3(signature = (voltage, current, step = 0, start = None, end = None))]
+    fn energy(&self, voltage: &str, current: &str, step: u16, start: Option<f64>, end: Option<f64>) -> PyResult<Option<f64>> {
+        return Ok(measure::energy(&self.view(voltage, step)?, &self.view(current, step)?, range(start, end)));
+    }
+
+    /// Returns the intermodulation distortion of a two-tone transient trace, relative to the higher tone.
+    #[pyo3(signature = (name, f1, f2, step = 0, order = 3))]
+    fn imd(&self, name: &str, f1: f64, f2: f64, step: u16, order: u32) -> PyResult<Option<f64>> {
+        return Ok(measure::imd(&self.view(name, step)?, f1, f2, order));
+    }
+
+    /// Returns the THD+N of a transient trace within the (low, high) bandwidth in Hz, as a ratio.
+    #[pyo3(signature = (name, fundamental, low, high, notch_q, step = 0))]
+    fn thd_n(&self, name: &str, fundamental: f64, low: f64, high: f64, notch_q: f64, step: u16) -> PyResult<Option<f64>> {
+        return Ok(spectral::thd_n(&self.view(name, step)?, fundamental, (low, high), notch_q));
+    }
+
+    /// Returns the complex impedance V / I of an AC simulation as numpy arrays (frequency, Z).
+    #[pyo3(signature = (voltage, current, step = 0))]
+    fn impedance<'py>(
+        &self,
+        py: Python<'py>,
+        voltage: &str,
+        current: &str,
+        step: u16,
+    ) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<Complex64>)> {
+        let trace = ac::impedance(&self.inner, voltage, current, step)
+            .ok_or_else(|| PyKeyError::new_err(format!("No variables '{}' and '{}' at step {}", voltage, current, step)))?;
+        return Ok(complex_arrays(py, &trace));
+    }
+
+    /// Returns the complex admittance I / V of an AC simulation as numpy arrays (frequency, Y).
+    #[pyo3(signature = (voltage, current, step = 0))]
+    fn admittance<'py>(
+        &self,
+        py: Python<'py>,
+        voltage: &str,
+        current: &str,
+        step: u16,
+    ) -> PyResult<(&'py PyArray1<f64>, &'py PyArray1<Complex64>)> {
+        let trace = ac::admittance(&self.inner, voltage, current, step)
+            .ok_or_else(|| PyKeyError::new_err(format!("No variables '{}' and '{}' at step {}", voltage, current, step)))?;
+        return Ok(complex_arrays(py, &trace));
+    }
+
+    /// Returns the stability margins of a loop gain variable: ((unity gain frequency, phase
+    /// margin in degrees), (phase crossover frequency, gain margin in dB)), None when not crossed.
+    #[pyo3(signature = (name, step = 0))]
+    fn margins(&self, name: &str, step: u16) -> PyResult<(Option<Margin>, Option<Margin>)> {
+        let margins = stability::margins(&self.view(name, step)?);
+        return Ok((margins.phase_margin, margins.gain_margin));
+    }
+
+    /// Returns the whole simulation as a JSON string.
+    fn to_json(&self) -> String {
+        return self.inner.to_json();
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "<SteppedSimulation mode={:?} variables={} steps={}>",
+            self.inner.get_mode(),
+            self.inner.get_variables().len(),
+            self.inner.get_step_count()
+        );
+    }
+}
+
+/* #### Module #### */
+
+#[pymodule]
+fn ltspice(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PySimulation>()?;
+    Ok(())
+}
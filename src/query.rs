@@ -0,0 +1,109 @@
+/*
+ * This file contains the fluent query interface used for complex retrievals
+ */
+
+use crate::{downsample, SteppedSimulation, Value};
+
+/* #### Structs #### */
+
+/// A composable data retrieval, created with `SteppedSimulation::query()`.
+///
+/// ```ignore
+/// let selection = sim.query().var("V(out)").step(2).between(1e-3, 2e-3).downsample(5000).collect();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Query<'a> {
+    simulation: &'a SteppedSimulation,
+    variable: Option<String>,
+    step: u16,
+    range: Option<(f64, f64)>,
+    points: Option<usize>,
+}
+
+/// The result of a query: the matching abscissa and variable samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    pub x: Vec<Value>,
+    pub y: Vec<Value>,
+}
+
+/* #### Implementations #### */
+
+impl<'a> Query<'a> {
+    pub(crate) fn new(simulation: &'a SteppedSimulation) -> Self {
+        return Query {
+            simulation,
+            variable: None,
+            step: 0,
+            range: None,
+            points: None,
+        };
+    }
+
+    /// Selects the variable to retrieve, the x axis is returned when no variable is selected.
+    pub fn var(mut self, name: &str) -> Self {
+        self.variable = Some(name.to_string());
+        return self;
+    }
+
+    /// Selects the step to retrieve, defaults to the first step.
+    pub fn step(mut self, step: u16) -> Self {
+        self.step = step;
+        return self;
+    }
+
+    /// Only keeps the points whose abscissa lies in [start, end].
+    pub fn between(mut self, start: f64, end: f64) -> Self {
+        self.range = Some((start.min(end), start.max(end)));
+        return self;
+    }
+
+    /// Reduces the result to at most `points` points, preserving peaks (min/max decimation).
+    pub fn downsample(mut self, points: usize) -> Self {
+        self.points = Some(points);
+        return self;
+    }
+
+    /// Runs the query, returns None if the variable or step does not exist.
+    pub fn collect(&self) -> Option<Selection> {
+        let x = self.simulation.get("x", Some(self.step))?;
+        let y = match &self.variable {
+            Some(name) => self.simulation.get(name, Some(self.step))?,
+            None => x,
+        };
+
+        let buckets = match self.points {
+            Some(points) => (points / 2).max(1),
+            None => 0,
+        };
+        let indices = downsample::min_max_indices(x, y, self.range, buckets);
+
+        return Some(Selection {
+            x: indices.iter().map(|index| x[*index]).collect(),
+            y: indices.iter().map(|index| y[*index]).collect(),
+        });
+    }
+}
+
+impl Selection {
+    /// Returns the number of selected points.
+    pub fn len(&self) -> usize {
+        return self.x.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.x.is_empty();
+    }
+
+    /// Returns the selection as (x, y) pairs of real parts.
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        return self.x.iter().zip(self.y.iter()).map(|(x, y)| (x.real(), y.real())).collect();
+    }
+}
+
+impl SteppedSimulation {
+    /// Starts a fluent query on the simulation data.
+    pub fn query(&self) -> Query<'_> {
+        return Query::new(self);
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * This file contains the pluggable raw file formats
+ *
+ * Every format sniffs the start of a file to recognize it and decodes the whole file into a
+ * simulation. `Formats` dispatches a file to the first format recognizing it, so readers for
+ * other simulators can be added without modifying this crate. Only the LTSpice format ships
+ * with it: ngspice and QSPICE ('.qraw') files share it, their quirks are applied from the
+ * detected `Source` rather than by a format of their own.
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+
+use crate::compression;
+use crate::options::LoadOptions;
+#[cfg(feature = "chrono")]
+use crate::parse_date;
+use crate::{log, Flags, Mode, SteppedSimulation, SteppedVariable, Value, VariableClass};
+
+/* #### Constants #### */
+
+/// Number of bytes passed to `RawFormat::sniff`, at most.
+pub const SNIFF_LENGTH: usize = 64 * 1024;
+
+/* #### Traits #### */
+
+/// A raw file format.
+pub trait RawFormat: Send + Sync {
+    /// Returns a short name of the format, e.g. "ltspice".
+    fn name(&self) -> &str;
+
+    /// Returns whether the start of a file (up to `SNIFF_LENGTH` bytes) belongs to this format.
+    fn sniff(&self, head: &[u8]) -> bool;
+
+    /// Decodes a whole file.
+    fn decode(&self, bytes: &[u8], options: &LoadOptions) -> Result<SteppedSimulation, Box<dyn Error>>;
+}
+
+/* #### Structs #### */
+
+/// The LTSpice binary raw format, also written by ngspice and QSPICE.
+#[derive(Debug, Default)]
+pub struct Ltspice;
+
+/// The decoded contents of a raw file, used by formats to build a simulation.
+#[derive(Debug, Clone)]
+pub struct RawData {
+    pub mode: Mode,
+    /// The date as written in the header, e.g. "Sat Apr 13 14:06:22 2024".
+    pub date: Option<String>,
+    /// Name and class of every variable, excluding the x axis.
+    pub variables: Vec<(String, VariableClass)>,
+    /// The x axis of every step.
+    pub x: Vec<Vec<Value>>,
+    /// The samples of every variable (in the order of `variables`) and step.
+    pub data: Vec<Vec<Vec<Value>>>,
+    /// The '.step' parameters of every step, may be empty.
+    pub parameters: Vec<Vec<(String, f64)>>,
+}
+
+/// The formats a file can be decoded with, in order of precedence.
+pub struct Formats {
+    formats: Vec<Box<dyn RawFormat>>,
+}
+
+/* #### Implementations #### */
+
+impl RawFormat for Ltspice {
+    fn name(&self) -> &str {
+        return "ltspice";
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        let header = log::decode(head);
+        return header.starts_with("Title:") || (header.contains("Plotname:") && header.contains("Variables:"));
+    }
+
+    fn decode(&self, bytes: &[u8], options: &LoadOptions) -> Result<SteppedSimulation, Box<dyn Error>> {
+        return SteppedSimulation::from_bytes_with_options(bytes, options.clone());
+    }
+}
+
+impl Default for Formats {
+    /// Returns the formats supported by this crate.
+    fn default() -> Self {
+        return Formats {
+            formats: vec![Box::new(Ltspice)],
+        };
+    }
+}
+
+impl Formats {
+    /// Adds a format, taking precedence over the ones already registered.
+    pub fn register(&mut self, format: Box<dyn RawFormat>) {
+        self.formats.insert(0, format);
+    }
+
+    /// Returns the first format recognizing the start of a file.
+    pub fn detect(&self, bytes: &[u8]) -> Option<&dyn RawFormat> {
+        let head = &bytes[..bytes.len().min(SNIFF_LENGTH)];
+        return self.formats.iter().find(|format| format.sniff(head)).map(|format| format.as_ref());
+    }
+
+    /// Decodes a file with the first format recognizing it, decompressing it first if needed.
+    pub fn decode(&self, bytes: &[u8], options: &LoadOptions) -> Result<SteppedSimulation, Box<dyn Error>> {
+        let decompressed = compression::decompress(bytes.to_vec())?;
+        let mut simulation = match self.detect(&decompressed.raw) {
+            Some(format) => format.decode(&decompressed.raw, options)?,
+            None => Err("Unrecognized raw file format.")?,
+        };
+        if let Some(log) = decompressed.log {
+            simulation.parse_log(&log::decode(&log));
+        }
+        return Ok(simulation);
+    }
+
+    /// Reads and decodes a file with the first format recognizing it, loading the step
+    /// parameters from the '.log' file next to it if there is one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(&self, path: &Path, options: &LoadOptions) -> Result<SteppedSimulation, Box<dyn Error>> {
+        let bytes = std::fs::read(path)?;
+        let mut simulation = self.decode(&bytes, options)?;
+        simulation.path = path.to_path_buf();
+        if simulation.get_step_parameters(0).is_none() {
+            simulation.load_log();
+        }
+        return Ok(simulation);
+    }
+}
+
+impl SteppedSimulation {
+    /// Builds a simulation from decoded data, e.g. in a `RawFormat` implementation.
+    pub fn from_raw_data(raw: RawData) -> Result<SteppedSimulation, Box<dyn Error>> {
+        if raw.data.len() != raw.variables.len() {
+            Err("Every variable must have data.")?;
+        }
+        for ((name, _), steps) in raw.variables.iter().zip(raw.data.iter()) {
+            if steps.len() != raw.x.len() || steps.iter().zip(raw.x.iter()).any(|(y, x)| y.len() != x.len()) {
+                Err(format!("The data of '{}' does not match the x axis.", name))?;
+            }
+        }
+
+        let mut simulation = SteppedSimulation::new(PathBuf::new());
+        simulation.mode = raw.mode;
+        #[cfg(feature = "chrono")]
+        {
+            simulation.date = raw.date.as_deref().and_then(parse_date);
+        }
+        simulation.date_string = raw.date;
+        simulation.stats.variables = raw.variables.len() as u32 + 1;
+        simulation.stats.points = raw.x.iter().map(|x| x.len() as u32).sum();
+        simulation.stats.steps = raw.x.len() as u16;
+        simulation.stats.step_size = raw.x.first().map_or(0, |x| x.len() as u32);
+        if raw.x.len() > 1 {
+            simulation.flags.push(Flags::Stepped);
+        }
+
+        for (index, ((name, class), steps)) in raw.variables.into_iter().zip(raw.data).enumerate() {
+            simulation.variables.push(SteppedVariable {
+                class,
+                name: name.clone(),
+                index: Some(index as u32 + 1),
+                derived: false,
+                data_type: None,
+            });
+            simulation.data.insert(name, steps);
+        }
+        simulation.data.insert("x".to_string(), raw.x);
+        simulation.parameters = raw.parameters;
+
+        return Ok(simulation);
+    }
+}
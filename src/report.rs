@@ -0,0 +1,223 @@
+/*
+ * This file contains the generation of self-contained design review reports
+ *
+ * A report is a list of sections, each a plot or a table of measurements. Plots are rendered
+ * to SVG when added, so the report does not borrow the simulation, and are embedded inline
+ * in both HTML and Markdown (which allows raw HTML).
+ */
+
+use std::error::Error;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::ac::unwrap_phase;
+use crate::convert::db;
+use crate::downsample;
+use crate::format::engineering;
+use crate::marker::Marker;
+use crate::svg::{self, escape, Series};
+use crate::trace::Trace;
+use crate::Unit;
+
+/* #### Constants #### */
+
+const WIDTH: u32 = 720;
+const HEIGHT: u32 = 320;
+const BUCKETS: usize = 600;
+
+/* #### Enums #### */
+
+/// The output format of a report.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+// A section of a report.
+#[derive(Debug, Clone)]
+enum Section {
+    Plot { title: String, svg: String },
+    Table { title: String, rows: Vec<Measurement> },
+}
+
+/* #### Structs #### */
+
+/// A named measurement shown in a report table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    pub value: f64,
+    pub unit: Unit,
+}
+
+/// A design review report, see `Report::render`.
+#[derive(Debug, Clone)]
+pub struct Report {
+    title: String,
+    sections: Vec<Section>,
+}
+
+/* #### Functions #### */
+
+// Returns the label of a trace in a plot legend, with its step if the plot has several.
+fn label(trace: &Trace<'_>, unit: Unit, stepped: bool) -> String {
+    return match stepped {
+        true => unit.label(&format!("{} (step {})", trace.name(), trace.step())),
+        false => unit.label(trace.name()),
+    };
+}
+
+// Returns the y of a plotted series at an abscissa, linearly interpolated between its points.
+fn point_at(points: &[(f64, f64)], x: f64) -> Option<f64> {
+    let index = points.windows(2).position(|pair| pair[0].0 <= x && x <= pair[1].0)?;
+    let ((x0, y0), (x1, y1)) = (points[index], points[index + 1]);
+    return match x1 > x0 {
+        true => Some(y0 + (y1 - y0) * (x - x0) / (x1 - x0)),
+        false => Some(y0),
+    };
+}
+
+/* #### Implementations #### */
+
+impl Measurement {
+    pub fn new(name: &str, value: f64, unit: Unit) -> Self {
+        return Measurement {
+            name: name.to_string(),
+            value,
+            unit,
+        };
+    }
+}
+
+impl Report {
+    /// Creates an empty report.
+    pub fn new(title: &str) -> Self {
+        return Report {
+            title: title.to_string(),
+            sections: Vec::new(),
+        };
+    }
+
+    /// Adds a plot of the real parts of the traces against their x axis, with their markers.
+    pub fn plot(mut self, title: &str, traces: &[Trace<'_>]) -> Self {
+        let series: Vec<Series> = traces
+            .iter()
+            .map(|trace| Series {
+                label: label(trace, trace.unit(), traces.len() > 1),
+                points: downsample::min_max(trace.x(), trace.values(), None, BUCKETS),
+            })
+            .collect();
+        let markers: Vec<Marker> = traces.iter().flat_map(|trace| trace.markers()).cloned().collect();
+        self.sections.push(Section::Plot {
+            title: title.to_string(),
+            svg: svg::plot_with_markers(&series, &markers, WIDTH, HEIGHT),
+        });
+        return self;
+    }
+
+    /// Adds the Bode plots of AC traces: magnitude in dB and unwrapped phase in degrees,
+    /// against log10 of the frequency. Markers are drawn on both curves at their frequency.
+    pub fn bode(mut self, title: &str, traces: &[Trace<'_>]) -> Self {
+        let mut magnitude = Vec::new();
+        let mut phase = Vec::new();
+        let mut magnitude_markers = Vec::new();
+        let mut phase_markers = Vec::new();
+        for trace in traces.iter() {
+            let frequency: Vec<f64> = trace.x().iter().map(|x| x.real().log10()).collect();
+            let phases: Vec<f64> = trace.values().iter().map(|value| value.arg()).collect();
+            let phases = unwrap_phase(&phases, std::f64::consts::PI);
+            magnitude.push(Series {
+                label: label(trace, Unit::Decibel, traces.len() > 1),
+                points: frequency.iter().zip(trace.values()).map(|(f, value)| (*f, db(value.abs()))).collect(),
+            });
+            phase.push(Series {
+                label: format!("{} [°]", label(trace, Unit::Dimensionless, traces.len() > 1)),
+                points: frequency.iter().zip(phases).map(|(f, phase)| (*f, phase.to_degrees())).collect(),
+            });
+            for marker in trace.markers() {
+                let at = |series: &Series| {
+                    let value = point_at(&series.points, marker.x.log10())?;
+                    return Some(Marker {
+                        x: marker.x.log10(),
+                        value,
+                        ..marker.clone()
+                    });
+                };
+                magnitude_markers.extend(magnitude.last().and_then(at));
+                phase_markers.extend(phase.last().and_then(at));
+            }
+        }
+        self.sections.push(Section::Plot {
+            title: format!("{} — magnitude vs log10(f)", title),
+            svg: svg::plot_with_markers(&magnitude, &magnitude_markers, WIDTH, HEIGHT),
+        });
+        self.sections.push(Section::Plot {
+            title: format!("{} — phase vs log10(f)", title),
+            svg: svg::plot_with_markers(&phase, &phase_markers, WIDTH, HEIGHT),
+        });
+        return self;
+    }
+
+    /// Adds a table of measurements, formatted with SI prefixes.
+    pub fn measurements(mut self, title: &str, rows: Vec<Measurement>) -> Self {
+        self.sections.push(Section::Table {
+            title: title.to_string(),
+            rows,
+        });
+        return self;
+    }
+
+    /// Renders the report as a self-contained document.
+    pub fn render(&self, format: ReportFormat) -> String {
+        let mut output = String::new();
+        match format {
+            ReportFormat::Html => {
+                write!(
+                    output,
+                    "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{t}</title></head>\n<body style=\"font-family: sans-serif\">\n<h1>{t}</h1>\n",
+                    t = escape(&self.title)
+                )
+                .unwrap();
+                for section in self.sections.iter() {
+                    match section {
+                        Section::Plot { title, svg } => {
+                            writeln!(output, "<h2>{}</h2>\n{}", escape(title), svg).unwrap();
+                        }
+                        Section::Table { title, rows } => {
+                            writeln!(output, "<h2>{}</h2>\n<table>\n<tr><th>Measurement</th><th>Value</th></tr>", escape(title)).unwrap();
+                            for row in rows.iter() {
+                                writeln!(output, "<tr><td>{}</td><td>{}</td></tr>", escape(&row.name), escape(&engineering(row.value, row.unit))).unwrap();
+                            }
+                            output.push_str("</table>\n");
+                        }
+                    }
+                }
+                output.push_str("</body></html>\n");
+            }
+            ReportFormat::Markdown => {
+                writeln!(output, "# {}", self.title).unwrap();
+                for section in self.sections.iter() {
+                    match section {
+                        Section::Plot { title, svg } => {
+                            writeln!(output, "\n## {}\n\n{}", title, svg).unwrap();
+                        }
+                        Section::Table { title, rows } => {
+                            writeln!(output, "\n## {}\n\n| Measurement | Value |\n|---|---|", title).unwrap();
+                            for row in rows.iter() {
+                                writeln!(output, "| {} | {} |", row.name.replace('|', "\\|"), engineering(row.value, row.unit)).unwrap();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        return output;
+    }
+
+    /// Renders the report and writes it to a file.
+    pub fn write(&self, path: &Path, format: ReportFormat) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.render(format))?;
+        return Ok(());
+    }
+}
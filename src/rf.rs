@@ -0,0 +1,137 @@
+/*
+ * This file contains the S-parameter computation from AC port voltages and currents
+ *
+ * Each port is described by its voltage and the current flowing into the network, and is
+ * converted to power waves: a = (V + Z0·I) / (2·√Z0), b = (V - Z0·I) / (2·√Z0).
+ * Port i driven and every other port terminated in Z0 gives the column Sji = bj / ai.
+ * The results can be written as Touchstone files ('.s1p' / '.s2p') for RF tools.
+ */
+
+use std::fmt::Write;
+
+use crate::ac::current_sign;
+use crate::trace::{Trace, TraceBuf};
+use crate::{Unit, Value};
+
+/* #### Structs #### */
+
+/// The voltage and current traces of one port, from the same simulation step.
+/// Currents follow the `ac::impedance` convention: the current of a voltage source is
+/// inverted, any other current is taken as flowing into the network.
+#[derive(Debug, Clone, Copy)]
+pub struct Port<'a> {
+    voltage: Trace<'a>,
+    current: Trace<'a>,
+}
+
+/// The full S-parameter matrix of a two-port network.
+#[derive(Debug, Clone)]
+pub struct TwoPort {
+    pub z0: f64,
+    pub s11: TraceBuf,
+    pub s21: TraceBuf,
+    pub s12: TraceBuf,
+    pub s22: TraceBuf,
+}
+
+/* #### Functions #### */
+
+// Returns the incident and reflected power waves of a port.
+fn waves(port: &Port<'_>, z0: f64) -> (Vec<Value>, Vec<Value>) {
+    let sign = current_sign(port.current.name());
+    let scale = 2.0 * z0.sqrt();
+    return port
+        .voltage
+        .values()
+        .iter()
+        .zip(port.current.values().iter())
+        .map(|(v, i)| {
+            let i = *i * (sign * z0);
+            ((*v + i) / scale, (*v - i) / scale)
+        })
+        .unzip();
+}
+
+/// Computes the S-parameters of the driven port, the first one of `ports`, with every other
+/// port terminated in `z0`: returns S11, S21, ..., Sn1 in port order.
+/// Returns None if no port is specified or the traces do not share the same frequencies.
+pub fn s_params(ports: &[Port<'_>], z0: f64) -> Option<Vec<TraceBuf>> {
+    let driven = ports.first()?;
+    let frequency = driven.voltage.x();
+    if ports.iter().any(|port| port.voltage.x() != frequency || port.current.x() != frequency) {
+        return None;
+    }
+
+    let (incident, _) = waves(driven, z0);
+    let mut result = Vec::with_capacity(ports.len());
+    for (index, port) in ports.iter().enumerate() {
+        let (_, reflected) = waves(port, z0);
+        let values: Vec<Value> = reflected.iter().zip(incident.iter()).map(|(b, a)| *b / *a).collect();
+        let name = format!("S{}1", index + 1);
+        result.push(
+            driven
+                .voltage
+                .with_data(name, frequency[..values.len()].to_vec(), values)
+                .with_unit(Unit::Dimensionless),
+        );
+    }
+    return Some(result);
+}
+
+/// Computes the full two-port matrix from two runs: `forward` with port 1 driven and port 2
+/// terminated in `z0`, `reverse` with the roles swapped. Both runs list port 1 first.
+pub fn two_port(forward: &[Port<'_>; 2], reverse: &[Port<'_>; 2], z0: f64) -> Option<TwoPort> {
+    let mut forward = s_params(forward, z0)?.into_iter();
+    let mut reverse = s_params(&[reverse[1], reverse[0]], z0)?.into_iter();
+    let (s11, s21) = (forward.next()?, forward.next()?);
+    let (s22, s12) = (reverse.next()?, reverse.next()?);
+
+    return Some(TwoPort {
+        z0,
+        s11,
+        s21,
+        s12: s12.renamed("S12"),
+        s22: s22.renamed("S22"),
+    });
+}
+
+/// Formats S-parameters as a Touchstone file in Hz and real/imaginary pairs: S11 alone for a
+/// '.s1p' file, or S11, S21, S12, S22 (the Touchstone order) for a '.s2p' file.
+/// Returns None for any other number of parameters, or if they do not share the same frequencies.
+pub fn touchstone(parameters: &[&TraceBuf], z0: f64) -> Option<String> {
+    let frequency = parameters.first()?.x();
+    if !(parameters.len() == 1 || parameters.len() == 4) {
+        return None;
+    }
+    if parameters.iter().any(|parameter| parameter.x() != frequency || parameter.values().len() != frequency.len()) {
+        return None;
+    }
+
+    let mut output = String::new();
+    writeln!(output, "! {}", parameters.iter().map(|parameter| parameter.name()).collect::<Vec<_>>().join(" ")).unwrap();
+    writeln!(output, "# Hz S RI R {}", z0).unwrap();
+    for (index, f) in frequency.iter().enumerate() {
+        write!(output, "{:e}", f.real()).unwrap();
+        for parameter in parameters.iter() {
+            let value = parameter.values()[index];
+            write!(output, " {:e} {:e}", value.real(), value.imaginary()).unwrap();
+        }
+        output.push('\n');
+    }
+    return Some(output);
+}
+
+/* #### Implementations #### */
+
+impl<'a> Port<'a> {
+    pub fn new(voltage: Trace<'a>, current: Trace<'a>) -> Self {
+        return Port { voltage, current };
+    }
+}
+
+impl TwoPort {
+    /// Formats the matrix as a '.s2p' Touchstone file, see `touchstone`.
+    pub fn to_touchstone(&self) -> Option<String> {
+        return touchstone(&[&self.s11, &self.s21, &self.s12, &self.s22], self.z0);
+    }
+}
@@ -0,0 +1,478 @@
+/*
+ * This file contains a small HTTP API serving the waveforms of a raw file
+ *
+ * Endpoints (all responses are JSON):
+ *   GET /info                                            mode, steps and variables
+ *   GET /data?var=V(out)&step=0&start=..&end=..&points=..  min/max decimated range
+ *   GET /tail?var=V(out)&step=0&since=..                  points after 'since', reloading
+ *                                                          the file if it changed on disk
+ *   GET /stream?var=V(out)&step=0&since=..                WebSocket pushing the new points as
+ *                                                          text frames whenever the file grows
+ *                                                          (messages from the client are ignored)
+ *
+ * Cross-origin requests are refused by browsers unless an origin is allowed with
+ * `WaveformServer::set_allowed_origin`.
+ *
+ * Binary files are followed with `RawStream`, only the points written since the last check are
+ * read, other files are parsed again whenever they change. The frames of every WebSocket client
+ * are written by a thread of its own, a client which does not read them for `WRITE_TIMEOUT` is
+ * disconnected instead of stalling the server.
+ */
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use base64::Engine;
+use tiny_http::{Header, Method, ReadWrite, Request, Response, Server};
+use tracing::{debug, info, warn};
+
+use crate::stream::RawStream;
+use crate::{downsample, json, SteppedSimulation};
+
+/* #### Constants #### */
+
+const DEFAULT_POINTS: usize = 2000;
+// Bounds of the 'points' parameter, a single bucket needs two points
+const MIN_POINTS: usize = 2;
+const MAX_POINTS: usize = 100_000;
+// How often the file is checked for new points to push to the WebSocket clients
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+// How long a WebSocket client may leave a frame unread before it is disconnected
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+// Appended to the client key to compute the WebSocket handshake answer (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/* #### Structs #### */
+
+/// Serves a single raw file, reloading it when it is modified (e.g. by a running simulation).
+pub struct WaveformServer {
+    path: PathBuf,
+    simulation: SteppedSimulation,
+    // Reads the points appended to a binary file, None for the files parsed again on change
+    follow: Option<RawStream>,
+    modified: Option<SystemTime>,
+    allowed_origin: Option<String>,
+    subscribers: Vec<Subscriber>,
+}
+
+// A WebSocket client of /stream, pushed the points of one trace after 'since'.
+struct Subscriber {
+    // The frames written by the thread of the client, one at most is queued
+    frames: SyncSender<Vec<u8>>,
+    // Since when a frame could not be queued, the client being slower than the file grows
+    blocked: Option<Instant>,
+    name: String,
+    step: u16,
+    since: f64,
+    points: usize,
+}
+
+/* #### Functions #### */
+
+// Decodes a percent-encoded query string component.
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => output.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        output.push(byte);
+                        index += 2;
+                    }
+                    Err(_) => output.push(b'%'),
+                }
+            }
+            byte => output.push(byte),
+        }
+        index += 1;
+    }
+    return String::from_utf8_lossy(&output).to_string();
+}
+
+// Splits a request url into its path and query parameters.
+fn parse_url(url: &str) -> (&str, HashMap<String, String>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let parameters = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (decode(key), decode(value)))
+        .collect();
+    return (path, parameters);
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    return fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+}
+
+fn respond(request: Request, status: u16, body: String, origin: Option<&str>) {
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let mut response = Response::from_string(body).with_status_code(status).with_header(header);
+    if let Some(origin) = origin.and_then(|origin| Header::from_bytes(&b"Access-Control-Allow-Origin"[..], origin).ok()) {
+        response = response.with_header(origin);
+    }
+    if let Err(e) = request.respond(response) {
+        warn!("Could not send the response: {}", e);
+    }
+}
+
+fn error(message: &str) -> String {
+    return format!("{{\"error\":{}}}", json::string(message));
+}
+
+// Returns the value of a request header (case insensitive), if present.
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    return request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str());
+}
+
+// Computes the SHA-1 digest of the data, only used by the WebSocket handshake.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pad with a 1 bit, zeros and the bit length up to a multiple of 64 bytes
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temporary = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temporary;
+        }
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    return digest;
+}
+
+/// Returns the `Sec-WebSocket-Accept` answer to a `Sec-WebSocket-Key` handshake header.
+pub fn websocket_accept(key: &str) -> String {
+    let digest = sha1(format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes());
+    return base64::engine::general_purpose::STANDARD.encode(digest);
+}
+
+/// Encodes a text message as an unmasked WebSocket frame, as sent by servers.
+pub fn websocket_frame(text: &str) -> Vec<u8> {
+    let payload = text.as_bytes();
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x81);
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    return frame;
+}
+
+// Returns the 'points' parameter, clamped so that a request can neither disable the
+// decimation nor ask for an unreasonable amount of data.
+fn points(parameters: &HashMap<String, String>) -> usize {
+    return parameters
+        .get("points")
+        .and_then(|points| points.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_POINTS)
+        .clamp(MIN_POINTS, MAX_POINTS);
+}
+
+// Writes the frames of a WebSocket client until it disconnects or the server drops it.
+fn write_frames(mut stream: Box<dyn ReadWrite + Send>) -> SyncSender<Vec<u8>> {
+    let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(1);
+    thread::spawn(move || {
+        for frame in receiver {
+            if stream.write_all(&frame).and_then(|_| stream.flush()).is_err() {
+                break;
+            }
+        }
+    });
+    return sender;
+}
+
+// Opens a binary file to follow, along with the points it already holds.
+fn follow(path: &Path) -> Result<(SteppedSimulation, RawStream), Box<dyn Error>> {
+    let (mut stream, mut simulation) = RawStream::open_with_header(path)?;
+    simulation.set_load_options(Default::default());
+    while let Some(chunk) = stream.read_chunk()? {
+        simulation.append_chunk(&chunk);
+    }
+    return Ok((simulation, stream));
+}
+
+// Returns the decimated points of a trace after 'since' (excluded), None if it does not exist.
+fn tail(simulation: &SteppedSimulation, name: &str, step: u16, since: f64, points: usize) -> Option<Vec<(f64, f64)>> {
+    let x = simulation.get("x", Some(step))?;
+    let y = simulation.get(name, Some(step))?;
+    let mut data = downsample::min_max(x, y, Some((since, f64::INFINITY)), points / 2);
+    data.retain(|(x, _)| *x > since);
+    return Some(data);
+}
+
+/* #### Implementations #### */
+
+impl WaveformServer {
+    /// Loads the raw file to serve.
+    pub fn new(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let (simulation, stream) = match follow(path) {
+            Ok((simulation, stream)) => (simulation, Some(stream)),
+            // e.g. an ASCII or compressed file, which can't be streamed
+            Err(e) => {
+                debug!("Can't follow {:?}, it is parsed again on change: {}", path, e);
+                let mut simulation = SteppedSimulation::new(path.to_path_buf());
+                simulation.reload()?;
+                (simulation, None)
+            }
+        };
+        return Ok(WaveformServer {
+            path: path.to_path_buf(),
+            simulation,
+            follow: stream,
+            modified: modified(path),
+            allowed_origin: None,
+            subscribers: Vec::new(),
+        });
+    }
+
+    /// Sets the origin sent in `Access-Control-Allow-Origin` (e.g. "http://localhost:3000" or "*"),
+    /// None (the default) lets browsers refuse cross-origin requests.
+    pub fn set_allowed_origin(&mut self, origin: Option<&str>) {
+        self.allowed_origin = origin.map(|origin| origin.to_string());
+    }
+
+    /// Serves requests on the specified address (e.g. "127.0.0.1:8080") until the process exits.
+    pub fn run(&mut self, address: &str) -> Result<(), Box<dyn Error>> {
+        // tiny_http errors are Send + Sync, which `?` cannot convert into Box<dyn Error>
+        let server = Server::http(address).map_err(|e| e.to_string())?;
+        info!("Serving {:?} on http://{}", self.path, address);
+
+        loop {
+            if let Some(request) = server.recv_timeout(POLL_INTERVAL)? {
+                let origin = self.allowed_origin.clone();
+                if *request.method() != Method::Get {
+                    respond(request, 405, error("Only GET requests are supported."), origin.as_deref());
+                    continue;
+                }
+                if request.url().starts_with("/stream") {
+                    self.subscribe(request);
+                    continue;
+                }
+                let (status, body) = self.handle(request.url());
+                respond(request, status, body, origin.as_deref());
+            }
+            if !self.subscribers.is_empty() {
+                self.push();
+            }
+        }
+    }
+
+    // Upgrades a /stream request to a WebSocket and registers it for the pushes.
+    fn subscribe(&mut self, request: Request) {
+        let origin = self.allowed_origin.clone();
+        let (_, parameters) = parse_url(request.url());
+        let key = match (header(&request, "Upgrade"), header(&request, "Sec-WebSocket-Key")) {
+            (Some(upgrade), Some(key)) if upgrade.eq_ignore_ascii_case("websocket") => key.to_string(),
+            _ => return respond(request, 400, error("Expected a WebSocket upgrade."), origin.as_deref()),
+        };
+        let name = match parameters.get("var") {
+            Some(name) => name.clone(),
+            None => return respond(request, 400, error("Missing 'var' parameter."), origin.as_deref()),
+        };
+        let step = parameters.get("step").and_then(|step| step.parse::<u16>().ok()).unwrap_or(0);
+        if self.simulation.get(&name, Some(step)).is_none() {
+            return respond(request, 404, error("Unknown variable or step."), origin.as_deref());
+        }
+
+        let accept = Header::from_bytes(&b"Sec-WebSocket-Accept"[..], websocket_accept(&key)).unwrap();
+        let response = Response::empty(101).with_header(accept);
+        let stream = request.upgrade("websocket", response);
+        debug!("WebSocket client subscribed to {} (step {})", name, step);
+        self.subscribers.push(Subscriber {
+            frames: write_frames(stream),
+            blocked: None,
+            name,
+            step,
+            since: parameters.get("since").and_then(|since| since.parse::<f64>().ok()).unwrap_or(f64::NEG_INFINITY),
+            points: points(&parameters),
+        });
+        self.push();
+    }
+
+    // Queues the new points of their trace to the WebSocket clients, dropping the disconnected
+    // ones and those which left a frame unread for too long.
+    fn push(&mut self) {
+        self.refresh();
+        let simulation = &self.simulation;
+        self.subscribers.retain_mut(|subscriber| {
+            let data = match tail(simulation, &subscriber.name, subscriber.step, subscriber.since, subscriber.points) {
+                Some(data) => data,
+                None => return true,
+            };
+            let last = match data.last() {
+                Some((x, _)) => *x,
+                None => return true,
+            };
+            match subscriber.frames.try_send(websocket_frame(&json::points(&data))) {
+                Ok(()) => {
+                    subscriber.since = last;
+                    subscriber.blocked = None;
+                }
+                // The points are sent along with the next ones once the client caught up
+                Err(TrySendError::Full(_)) => {
+                    let blocked = *subscriber.blocked.get_or_insert_with(Instant::now);
+                    if blocked.elapsed() > WRITE_TIMEOUT {
+                        debug!("WebSocket client of {} is too slow, disconnecting it", subscriber.name);
+                        return false;
+                    }
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    debug!("WebSocket client of {} disconnected", subscriber.name);
+                    return false;
+                }
+            }
+            return true;
+        });
+    }
+
+    // Reads the points appended to the file since the last call, or reloads the file if it
+    // changed and can't be followed. A followed file which shrank was rewritten, e.g. by a new
+    // simulation, and is followed again from its start.
+    fn refresh(&mut self) {
+        if let Some(stream) = self.follow.as_mut() {
+            let length = fs::metadata(&self.path).map(|metadata| metadata.len()).unwrap_or(0);
+            if length >= stream.offset() {
+                loop {
+                    match stream.read_chunk() {
+                        Ok(Some(chunk)) => self.simulation.append_chunk(&chunk),
+                        Ok(None) => return,
+                        Err(e) => return debug!("Could not read {:?}: {}", self.path, e),
+                    }
+                }
+            }
+            match follow(&self.path) {
+                Ok((simulation, stream)) => {
+                    debug!("Reloaded {:?}", self.path);
+                    self.simulation = simulation;
+                    self.follow = Some(stream);
+                }
+                // The header is likely still being written, keep the previous data
+                Err(e) => debug!("Could not reload {:?}: {}", self.path, e),
+            }
+            return;
+        }
+
+        let current = modified(&self.path);
+        if current == self.modified {
+            return;
+        }
+        let mut simulation = SteppedSimulation::new(self.path.clone());
+        match simulation.reload() {
+            Ok(()) => {
+                debug!("Reloaded {:?}", self.path);
+                self.simulation = simulation;
+                self.modified = current;
+            }
+            // The file is likely still being written, keep the previous data
+            Err(e) => debug!("Could not reload {:?}: {}", self.path, e),
+        }
+    }
+
+    fn handle(&mut self, url: &str) -> (u16, String) {
+        let (path, parameters) = parse_url(url);
+        let step = parameters.get("step").and_then(|step| step.parse::<u16>().ok()).unwrap_or(0);
+        let number = |key: &str| parameters.get(key).and_then(|value| value.parse::<f64>().ok());
+
+        match path {
+            "/info" => {
+                self.refresh();
+                let variables: Vec<String> = self
+                    .simulation
+                    .get_variables()
+                    .iter()
+                    .map(|variable| json::string(variable.name()))
+                    .collect();
+                let body = format!(
+                    "{{\"mode\":{},\"steps\":{},\"variables\":[{}]}}",
+                    json::string(&self.simulation.get_mode().to_string()),
+                    self.simulation.get_step_count(),
+                    variables.join(",")
+                );
+                return (200, body);
+            }
+            "/data" | "/tail" => {
+                if path == "/tail" {
+                    self.refresh();
+                }
+                let name = match parameters.get("var") {
+                    Some(name) => name,
+                    None => return (400, error("Missing 'var' parameter.")),
+                };
+                let points = points(&parameters);
+                let data = match path {
+                    "/tail" => tail(&self.simulation, name, step, number("since").unwrap_or(f64::NEG_INFINITY), points),
+                    _ => {
+                        let range = match (number("start"), number("end")) {
+                            (Some(start), Some(end)) => Some((start, end)),
+                            _ => None,
+                        };
+                        match (self.simulation.get("x", Some(step)), self.simulation.get(name, Some(step))) {
+                            (Some(x), Some(y)) => Some(downsample::min_max(x, y, range, points / 2)),
+                            _ => None,
+                        }
+                    }
+                };
+                return match data {
+                    Some(data) => (200, json::points(&data)),
+                    None => (404, error("Unknown variable or step.")),
+                };
+            }
+            _ => return (404, error("Unknown endpoint.")),
+        }
+    }
+}
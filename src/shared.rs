@@ -0,0 +1,82 @@
+/*
+ * This file contains the shared-ownership handle used by multi-threaded pipelines
+ *
+ * SteppedSimulation and all its views are Send + Sync (enforced at compile time
+ * below), so a parsed simulation can be shared across rayon/tokio tasks behind an Arc.
+ */
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::query::{Query, Selection};
+use crate::trace::Trace;
+use crate::{Iter, SteppedSimulation, SteppedVariable, Value};
+
+/* #### Static Assertions #### */
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SteppedSimulation>();
+    assert_send_sync::<SharedSimulation>();
+    assert_send_sync::<SteppedVariable>();
+    assert_send_sync::<Value>();
+    assert_send_sync::<Trace<'static>>();
+    assert_send_sync::<Query<'static>>();
+    assert_send_sync::<Selection>();
+    assert_send_sync::<Iter<'static>>();
+};
+
+/* #### Structs #### */
+
+/// A cheaply clonable, thread-safe handle to an immutable parsed simulation.
+#[derive(Debug, Clone)]
+pub struct SharedSimulation {
+    inner: Arc<SteppedSimulation>,
+}
+
+/* #### Implementations #### */
+
+impl SharedSimulation {
+    pub fn new(simulation: SteppedSimulation) -> Self {
+        return SharedSimulation {
+            inner: Arc::new(simulation),
+        };
+    }
+
+    /// Returns the underlying Arc, e.g. to store it in other shared structures.
+    pub fn as_arc(&self) -> &Arc<SteppedSimulation> {
+        return &self.inner;
+    }
+
+    /// Returns the simulation if this is the last handle to it, otherwise the handle itself.
+    pub fn try_unwrap(self) -> Result<SteppedSimulation, SharedSimulation> {
+        return Arc::try_unwrap(self.inner).map_err(|inner| SharedSimulation { inner });
+    }
+}
+
+impl Deref for SharedSimulation {
+    type Target = SteppedSimulation;
+
+    fn deref(&self) -> &SteppedSimulation {
+        return &self.inner;
+    }
+}
+
+impl From<SteppedSimulation> for SharedSimulation {
+    fn from(simulation: SteppedSimulation) -> Self {
+        return SharedSimulation::new(simulation);
+    }
+}
+
+impl From<Arc<SteppedSimulation>> for SharedSimulation {
+    fn from(inner: Arc<SteppedSimulation>) -> Self {
+        return SharedSimulation { inner };
+    }
+}
+
+impl SteppedSimulation {
+    /// Moves the simulation behind a thread-safe shared handle.
+    pub fn into_shared(self) -> SharedSimulation {
+        return SharedSimulation::new(self);
+    }
+}
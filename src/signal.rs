@@ -0,0 +1,39 @@
+/*
+ * This file contains the construction of differential and common-mode signals
+ *
+ * Both are stored as derived signals, named after the LTSpice convention for differential
+ * probes: V(p,n) for the difference of V(p) and V(n), and Vcm(p,n) for their average.
+ */
+
+use std::error::Error;
+
+use crate::SteppedSimulation;
+
+/* #### Functions #### */
+
+// Returns the name of a signal combining two others, e.g. V(p) and V(n) into V(p,n).
+fn combined_name(prefix: &str, positive: &str, negative: &str) -> String {
+    let node = |name: &'_ str| -> Option<String> {
+        return name.strip_prefix("V(")?.strip_suffix(')').map(|node| node.to_string());
+    };
+    match (node(positive), node(negative)) {
+        (Some(p), Some(n)) => return format!("{}({},{})", prefix, p, n),
+        _ => return format!("{}({},{})", prefix, positive, negative),
+    }
+}
+
+/// Builds the differential signal positive - negative and stores it as a derived signal,
+/// e.g. "V(p,n)" for "V(p)" and "V(n)". Returns the name of the stored signal.
+pub fn differential(simulation: &mut SteppedSimulation, positive: &str, negative: &str) -> Result<String, Box<dyn Error>> {
+    let name = combined_name("V", positive, negative);
+    simulation.derive(&name, &[positive, negative], |v| v[0] - v[1])?;
+    return Ok(name);
+}
+
+/// Builds the common-mode signal (positive + negative) / 2 and stores it as a derived signal,
+/// e.g. "Vcm(p,n)" for "V(p)" and "V(n)". Returns the name of the stored signal.
+pub fn common_mode(simulation: &mut SteppedSimulation, positive: &str, negative: &str) -> Result<String, Box<dyn Error>> {
+    let name = combined_name("Vcm", positive, negative);
+    simulation.derive(&name, &[positive, negative], |v| (v[0] + v[1]) * 0.5)?;
+    return Ok(name);
+}
@@ -0,0 +1,102 @@
+/*
+ * This file contains the slab storage of decoded columns
+ *
+ * With `LoadOptions::allocator(Allocator::Slab)`, every variable is decoded into a single vector
+ * holding all its steps back to back, allocated once from the length of the data: a sweep of
+ * thousands of steps then makes one allocation per variable instead of one per step and
+ * variable, and nothing grows while decoding. The accessors of a step (e.g. `get` or `trace`)
+ * borrow it from the slab, only the accessors returning whole columns (e.g. `iter`) copy the
+ * column split in steps on first access. Copied columns stay resident until evicted, see `lru`.
+ */
+
+use std::sync::{Arc, OnceLock};
+
+use crate::lru::{decoded_size, LastAccess};
+use crate::{SteppedSimulation, Value};
+
+/* #### Structs #### */
+
+/// A column stored in a single slab, steps are borrowed from it.
+#[derive(Debug, Clone)]
+pub(crate) struct SlabColumn {
+    values: Vec<Value>,
+    // Index of the first point of every step, and the total number of points
+    starts: Arc<Vec<usize>>,
+    // Whole column copied by `get`
+    copied: OnceLock<Vec<Vec<Value>>>,
+    last_access: LastAccess,
+}
+
+/* #### Functions #### */
+
+// Returns the index of the first point of every step, followed by the total number of points.
+pub(crate) fn starts(lengths: &[usize]) -> Vec<usize> {
+    let mut starts = Vec::with_capacity(lengths.len() + 1);
+    starts.push(0);
+    for length in lengths.iter() {
+        starts.push(starts[starts.len() - 1] + length);
+    }
+    return starts;
+}
+
+// Splits a slab in one vector per step.
+pub(crate) fn split(values: &[Value], starts: &[usize]) -> Vec<Vec<Value>> {
+    return starts.windows(2).map(|step| values[step[0]..step[1]].to_vec()).collect();
+}
+
+/* #### Implementations #### */
+
+impl SlabColumn {
+    pub(crate) fn new(values: Vec<Value>, starts: Arc<Vec<usize>>) -> Self {
+        return SlabColumn {
+            values,
+            starts,
+            copied: OnceLock::new(),
+            last_access: LastAccess::default(),
+        };
+    }
+
+    // Returns the number of points of each step.
+    pub(crate) fn lengths(&self) -> Vec<usize> {
+        return self.starts.windows(2).map(|step| step[1] - step[0]).collect();
+    }
+
+    // Returns the memory used by the copied column, if any, see `lru`. The slab itself is not
+    // counted, as it can't be evicted.
+    pub(crate) fn resident_size(&self) -> Option<usize> {
+        return self.copied.get().map(|_| decoded_size(&self.lengths()));
+    }
+
+    pub(crate) fn last_access(&self) -> u64 {
+        return self.last_access.get();
+    }
+
+    // Drops the copied column, it is copied again on the next access.
+    pub(crate) fn evict(&mut self) {
+        self.copied.take();
+    }
+
+    // Returns a step, borrowed from the slab.
+    pub(crate) fn get_step(&self, step: usize) -> Option<&[Value]> {
+        let (start, end) = (*self.starts.get(step)?, *self.starts.get(step + 1)?);
+        return Some(&self.values[start..end]);
+    }
+
+    // Returns the whole column, copying every step on first access.
+    pub(crate) fn get(&self) -> &Vec<Vec<Value>> {
+        self.last_access.touch();
+        return self.copied.get_or_init(|| self.expand());
+    }
+
+    // Returns the column split in steps, without keeping it.
+    pub(crate) fn expand(&self) -> Vec<Vec<Value>> {
+        return split(&self.values, &self.starts);
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns whether the variables are stored in slabs, see `LoadOptions::allocator`.
+    pub fn is_slab(&self) -> bool {
+        return !self.slabs.is_empty();
+    }
+}
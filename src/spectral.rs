@@ -0,0 +1,135 @@
+/*
+ * This file contains the spectral metrics of transient traces
+ *
+ * The trace is resampled on a uniform grid of a power-of-two length, Hann windowed and
+ * transformed with a radix-2 FFT. Metrics are ratios of powers, so the scaling of the
+ * window and transform cancels out.
+ */
+
+use std::f64::consts::PI;
+
+use crate::trace::Trace;
+use crate::transform::interpolate;
+
+/* #### Functions #### */
+
+// In-place iterative radix-2 FFT of (real, imaginary) pairs, the length must be a power of two.
+fn fft(data: &mut [(f64, f64)]) {
+    let length = data.len();
+    let mut j = 0;
+    for i in 1..length {
+        let mut bit = length >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= length {
+        let angle = -2.0 * PI / size as f64;
+        for start in (0..length).step_by(size) {
+            for k in 0..size / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (data[start + k], data[start + k + size / 2]);
+                let twiddled = (b.0 * cos - b.1 * sin, b.0 * sin + b.1 * cos);
+                data[start + k] = (a.0 + twiddled.0, a.1 + twiddled.1);
+                data[start + k + size / 2] = (a.0 - twiddled.0, a.1 - twiddled.1);
+            }
+        }
+        size <<= 1;
+    }
+}
+
+// Returns the one-sided power spectrum of the real parts of a trace and its bin width in Hz.
+// None if the trace has fewer than two points or no time span.
+pub(crate) fn power_spectrum(trace: &Trace<'_>) -> Option<(Vec<f64>, f64)> {
+    let (start, end) = (trace.x().first()?.real(), trace.x().last()?.real());
+    if trace.len() < 2 || end <= start {
+        return None;
+    }
+    let length = trace.len().next_power_of_two();
+    let step = (end - start) / length as f64;
+
+    let mut data: Vec<(f64, f64)> = (0..length)
+        .map(|index| {
+            let value = interpolate(trace.x(), trace.values(), start + step * index as f64).map_or(0.0, |value| value.real());
+            let window = 0.5 - 0.5 * (2.0 * PI * index as f64 / length as f64).cos();
+            return (value * window, 0.0);
+        })
+        .collect();
+    fft(&mut data);
+
+    let power = data[..length / 2].iter().map(|(re, im)| re * re + im * im).collect();
+    return Some((power, 1.0 / (step * length as f64)));
+}
+
+// Returns whether a bin is within the main lobe of the Hann window around a tone (two bins on each side).
+fn in_lobe(bin: usize, resolution: f64, frequency: f64) -> bool {
+    return (bin as f64 - (frequency / resolution).round()).abs() <= 2.0;
+}
+
+// Returns the power of a tone, summed over the main lobe of the Hann window.
+pub(crate) fn tone_power(power: &[f64], resolution: f64, frequency: f64) -> f64 {
+    let center = (frequency / resolution).round() as isize;
+    return (center - 2..=center + 2)
+        .filter(|bin| *bin >= 0)
+        .filter_map(|bin| power.get(bin as usize))
+        .sum();
+}
+
+/// Returns the total harmonic distortion of a transient trace: the RMS of the harmonics 2 to
+/// `harmonics` of the fundamental (in Hz) relative to the fundamental, below the Nyquist frequency.
+/// The trace should span an integer number of periods of the fundamental, after settling.
+/// Returns None if the trace is too short or holds no power at the fundamental.
+pub fn thd(trace: &Trace<'_>, fundamental: f64, harmonics: u32) -> Option<f64> {
+    let (power, resolution) = power_spectrum(trace)?;
+    let nyquist = resolution * power.len() as f64;
+    let reference = tone_power(&power, resolution, fundamental);
+    if reference <= 0.0 {
+        return None;
+    }
+    let distortion: f64 = (2..=harmonics)
+        .map(|harmonic| harmonic as f64 * fundamental)
+        .take_while(|frequency| *frequency < nyquist)
+        .map(|frequency| tone_power(&power, resolution, frequency))
+        .sum();
+    return Some((distortion / reference).sqrt());
+}
+
+/// Returns the total harmonic distortion plus noise of a transient trace, as an audio analyzer
+/// measures it: the RMS of the signal within the bandwidth (low, high) in Hz after a notch
+/// filter of quality `notch_q` removes the fundamental, relative to the RMS of the signal within
+/// the bandwidth. The whole window lobe of the fundamental is removed, the notch response only
+/// shapes the other bins. Multiply by 100 for percent, or use `convert::db` for dB.
+/// The trace should span an integer number of periods of the fundamental, after settling.
+/// Returns None if the trace is too short or the bandwidth contains no power.
+pub fn thd_n(trace: &Trace<'_>, fundamental: f64, bandwidth: (f64, f64), notch_q: f64) -> Option<f64> {
+    let (power, resolution) = power_spectrum(trace)?;
+    let (low, high) = (bandwidth.0.min(bandwidth.1), bandwidth.0.max(bandwidth.1));
+
+    let mut total = 0.0;
+    let mut residual = 0.0;
+    for (bin, power) in power.iter().enumerate() {
+        let frequency = bin as f64 * resolution;
+        if frequency < low || frequency > high {
+            continue;
+        }
+        total += power;
+        if in_lobe(bin, resolution, fundamental) {
+            continue;
+        }
+        // Second-order band-stop response centered on the fundamental
+        let detuning = (frequency * frequency - fundamental * fundamental).powi(2);
+        let notch = detuning / (detuning + (frequency * fundamental / notch_q).powi(2));
+        residual += power * notch;
+    }
+    if total <= 0.0 {
+        return None;
+    }
+    return Some((residual / total).sqrt());
+}
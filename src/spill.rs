@@ -0,0 +1,435 @@
+/*
+ * This file contains the spilling of decoded columns to scratch files
+ *
+ * With `LoadOptions::memory_limit`, the raw file is memory mapped and decoded in a single pass:
+ * the first variables stay in memory while the decoded data fits the limit, the samples of the
+ * others are written through a mapping of a scratch file in the temporary directory, sized for
+ * all of them up front. A step of a spilled column is paged in (decoded from the mapping) the
+ * first time it is accessed, so browsing a few steps of a huge sweep only brings those steps in
+ * memory. Paged in steps stay resident, see `lru` to bound them.
+ * Scratch files get an unguessable name and are created exclusively, readable by the owner only,
+ * so a link planted in a shared temporary directory is never followed. The file stays open until
+ * the simulation (and all its clones) are dropped, then it is removed: a temporary directory
+ * cleaner removing its name meanwhile does not lose the data. Reading a scratch file that was
+ * truncated fails, see `SteppedSimulation::try_get`.
+ */
+
+use std::error::Error;
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use memmap2::{Mmap, MmapMut};
+use tracing::{debug, error};
+
+use ltspice_decode::{Layout, Statistics};
+
+use crate::lru::{decoded_size, LastAccess, VALUE_SIZE};
+use crate::options::Precision;
+use crate::preview::CompactColumn;
+use crate::summary::ColumnStats;
+use crate::{SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+// Number of names tried before giving up on creating a scratch file
+const SCRATCH_ATTEMPTS: usize = 16;
+
+static SCRATCH_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/* #### Structs #### */
+
+// A scratch file, mapped read-only once written and deleted when dropped.
+#[derive(Debug)]
+struct Scratch {
+    path: PathBuf,
+    // Taken when dropped, so the file is closed before it is removed
+    file: Option<File>,
+    map: Option<Mmap>,
+}
+
+/// A column stored in a scratch file, paged in step by step on first access.
+#[derive(Debug, Clone)]
+pub(crate) struct SpilledColumn {
+    scratch: Arc<Scratch>,
+    offset: u64,
+    lengths: Vec<usize>,
+    // Steps paged in by `get_step`, moved to `loaded` by `consolidate`
+    steps: Vec<OnceLock<Vec<Value>>>,
+    // Whole column paged in by `get`
+    loaded: OnceLock<Vec<Vec<Value>>>,
+    last_access: LastAccess,
+}
+
+// A scratch file being written through a writable mapping, sized for all its columns.
+struct Spiller {
+    scratch: Scratch,
+    map: MmapMut,
+}
+
+// Where the samples of a variable go during a limited load.
+enum Destination {
+    Memory(Vec<Vec<Value>>),
+    // Offset of the column in the scratch file
+    Scratch(usize),
+}
+
+/* #### Functions #### */
+
+fn size(steps: &[Vec<Value>]) -> usize {
+    return steps.iter().map(|values| values.len() * VALUE_SIZE).sum();
+}
+
+/* #### Implementations #### */
+
+impl Scratch {
+    // Returns the bytes of the scratch file in `range`, checking the file was not truncated
+    // meanwhile, as reading a mapped page past the end of the file is a bus error.
+    fn bytes(&self, range: std::ops::Range<usize>) -> Result<&[u8], Box<dyn Error>> {
+        let truncated = || format!("The scratch file {:?} was truncated.", self.path);
+        let (file, map) = self.file.as_ref().zip(self.map.as_ref()).ok_or("The scratch file is closed.")?;
+        if (file.metadata()?.len() as usize) < range.end {
+            Err(truncated())?;
+        }
+        return Ok(map.get(range).ok_or_else(truncated)?);
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        self.map.take();
+        self.file.take();
+        if let Err(e) = fs::remove_file(&self.path) {
+            debug!("Could not remove the scratch file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+impl Spiller {
+    // Creates a scratch file of `size` bytes and maps it for writing.
+    fn new(size: usize) -> Result<Self, Box<dyn Error>> {
+        for _ in 0..SCRATCH_ATTEMPTS {
+            // The randomly keyed hasher of the standard library makes the name unguessable
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_usize(SCRATCH_COUNTER.fetch_add(1, Ordering::Relaxed));
+            let path = std::env::temp_dir().join(format!("ltspice-{}-{:016x}.scratch", std::process::id(), hasher.finish()));
+
+            // Never follows or truncates an existing file (or link)
+            let mut options = OpenOptions::new();
+            options.read(true).write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let file = match options.open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => Err(e)?,
+            };
+            // Removed by the drop of the scratch if sizing or mapping fails
+            let mut scratch = Scratch { path, file: Some(file), map: None };
+            let file = scratch.file.as_mut().unwrap();
+            file.set_len(size as u64)?;
+            // SAFETY: the file is private to this process (created exclusively, owner only), and
+            // only written through this mapping.
+            let map = unsafe { MmapMut::map_mut(&*file)? };
+            return Ok(Spiller { scratch, map });
+        }
+        Err("Could not create a scratch file.")?
+    }
+
+    // Writes a sample at the byte `offset` of the scratch file.
+    fn write(&mut self, offset: usize, value: &Value) {
+        self.map[offset..offset + 8].copy_from_slice(&value.real.to_le_bytes());
+        self.map[offset + 8..offset + VALUE_SIZE].copy_from_slice(&value.imaginary.to_le_bytes());
+    }
+
+    // Flushes the samples written and maps the scratch file read-only, for its columns.
+    fn finish(mut self) -> Result<Arc<Scratch>, Box<dyn Error>> {
+        self.map.flush()?;
+        debug!("Spilled {} bytes to {:?}.", self.map.len(), self.scratch.path);
+        self.scratch.map = Some(self.map.make_read_only()?);
+        return Ok(Arc::new(self.scratch));
+    }
+}
+
+impl SpilledColumn {
+    fn new(scratch: Arc<Scratch>, offset: usize, lengths: &[usize]) -> Self {
+        return SpilledColumn {
+            scratch,
+            offset: offset as u64,
+            lengths: lengths.to_vec(),
+            steps: lengths.iter().map(|_| OnceLock::new()).collect(),
+            loaded: OnceLock::new(),
+            last_access: LastAccess::default(),
+        };
+    }
+
+    // Returns the number of points of each step.
+    pub(crate) fn lengths(&self) -> &[usize] {
+        return &self.lengths;
+    }
+
+    // Returns the memory used by the steps paged in, if any, see `lru`. Steps paged in by
+    // `get_step` before `get` are counted twice, until `consolidate` drops them.
+    pub(crate) fn resident_size(&self) -> Option<usize> {
+        let steps: Vec<usize> = self
+            .lengths
+            .iter()
+            .zip(self.steps.iter())
+            .filter_map(|(length, step)| step.get().map(|_| *length))
+            .collect();
+        return match (self.loaded.get(), steps.is_empty()) {
+            (None, true) => None,
+            (None, false) => Some(decoded_size(&steps)),
+            (Some(_), _) => Some(decoded_size(&self.lengths) + decoded_size(&steps)),
+        };
+    }
+
+    // Drops the steps paged in by `get_step` once the whole column is paged in, as `get` can
+    // only copy them.
+    pub(crate) fn consolidate(&mut self) {
+        if self.loaded.get().is_some() {
+            for step in self.steps.iter_mut() {
+                step.take();
+            }
+        }
+    }
+
+    pub(crate) fn last_access(&self) -> u64 {
+        return self.last_access.get();
+    }
+
+    // Drops the steps paged in, they are paged in again on the next access.
+    pub(crate) fn evict(&mut self) {
+        self.loaded.take();
+        for step in self.steps.iter_mut() {
+            step.take();
+        }
+    }
+
+    // Returns a step, paging only this step in on first access.
+    fn get_step(&self, step: usize) -> Result<Option<&Vec<Value>>, Box<dyn Error>> {
+        self.last_access.touch();
+        if let Some(steps) = self.loaded.get() {
+            return Ok(steps.get(step));
+        }
+        let Some(cell) = self.steps.get(step) else {
+            return Ok(None);
+        };
+        if let Some(values) = cell.get() {
+            return Ok(Some(values));
+        }
+        let values = self.read(step)?;
+        return Ok(Some(cell.get_or_init(|| values)));
+    }
+
+    // Returns the whole column, paging every step in on first access.
+    fn get(&self) -> Result<&Vec<Vec<Value>>, Box<dyn Error>> {
+        self.last_access.touch();
+        if let Some(steps) = self.loaded.get() {
+            return Ok(steps);
+        }
+        let steps = (0..self.lengths.len())
+            .map(|step| self.read(step))
+            .collect::<Result<Vec<Vec<Value>>, Box<dyn Error>>>()?;
+        return Ok(self.loaded.get_or_init(|| steps));
+    }
+
+    // Returns the whole column, moving the steps already paged in instead of copying them.
+    // The steps moved are paged in again on the next access if it fails.
+    fn take(&mut self) -> Result<Vec<Vec<Value>>, Box<dyn Error>> {
+        if let Some(steps) = self.loaded.take() {
+            return Ok(steps);
+        }
+        return (0..self.lengths.len())
+            .map(|step| match self.steps[step].take() {
+                Some(values) => Ok(values),
+                None => self.read(step),
+            })
+            .collect();
+    }
+
+    // Decodes a step from the mapping of the scratch file.
+    fn read(&self, step: usize) -> Result<Vec<Value>, Box<dyn Error>> {
+        let start = self.offset as usize + decoded_size(&self.lengths[..step]);
+        let bytes = self.scratch.bytes(start..start + decoded_size(&self.lengths[step..=step]))?;
+        return Ok(bytes
+            .chunks_exact(VALUE_SIZE)
+            .map(|a| Value {
+                real: f64::from_le_bytes(a[0..8].try_into().unwrap()),
+                imaginary: f64::from_le_bytes(a[8..16].try_into().unwrap()),
+            })
+            .collect());
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns the data of a variable (or "x") for every step, paging it in if it was spilled.
+    /// Fails if a spilled column can't be read back from its scratch file.
+    pub(crate) fn try_column(&self, name: &str) -> Result<Option<&Vec<Vec<Value>>>, Box<dyn Error>> {
+        if let Some(data) = self.data.get(name) {
+            return Ok(Some(data));
+        }
+        if let Some(column) = self.compacted.get(name) {
+            return Ok(Some(column.get()));
+        }
+        if let Some(column) = self.slabs.get(name) {
+            return Ok(Some(column.get()));
+        }
+        return match self.spilled.get(name) {
+            Some(column) => Ok(Some(column.get()?)),
+            None => Ok(None),
+        };
+    }
+
+    /// Same as `try_column`, a spilled column that can't be read back is logged and missing.
+    pub(crate) fn column(&self, name: &str) -> Option<&Vec<Vec<Value>>> {
+        return self.try_column(name).unwrap_or_else(|e| {
+            error!("Could not read the spilled variable '{}' back: {}", name, e);
+            None
+        });
+    }
+
+    /// Returns the data of a variable (or "x") for a step, only expanding (or paging in) this
+    /// step of a preview (or spilled) column. Fails if a spilled step can't be read back.
+    pub(crate) fn try_column_step(&self, name: &str, step: usize) -> Result<Option<&[Value]>, Box<dyn Error>> {
+        if let Some(column) = self.compacted.get(name) {
+            return Ok(column.get_step(step).map(|values| values.as_slice()));
+        }
+        if let Some(column) = self.slabs.get(name) {
+            return Ok(column.get_step(step));
+        }
+        if let Some(column) = self.spilled.get(name) {
+            return Ok(column.get_step(step)?.map(|values| values.as_slice()));
+        }
+        return Ok(self.data.get(name).and_then(|steps| steps.get(step)).map(|values| values.as_slice()));
+    }
+
+    /// Same as `try_column_step`, a spilled step that can't be read back is logged and missing.
+    pub(crate) fn column_step(&self, name: &str, step: usize) -> Option<&[Value]> {
+        return self.try_column_step(name, step).unwrap_or_else(|e| {
+            error!("Could not read step {} of the spilled variable '{}' back: {}", step, name, e);
+            None
+        });
+    }
+
+    /// Returns whether a variable (or "x") has data, without loading it.
+    pub(crate) fn has_column(&self, name: &str) -> bool {
+        return self.data.contains_key(name)
+            || self.spilled.contains_key(name)
+            || self.compacted.contains_key(name)
+            || self.slabs.contains_key(name);
+    }
+
+    /// Moves every spilled (or compact, or slab) column back to memory, before modifying the data.
+    /// Fails if a spilled column can't be read back, the columns are then left as they are.
+    pub(crate) fn materialize(&mut self) -> Result<(), Box<dyn Error>> {
+        let names: Vec<String> = self.spilled.keys().cloned().collect();
+        let mut spilled = Vec::with_capacity(names.len());
+        for name in names.into_iter() {
+            let steps = self.spilled.get_mut(&name).unwrap().take()?;
+            spilled.push((name, steps));
+        }
+        for (name, column) in std::mem::take(&mut self.compacted).into_iter() {
+            self.data.insert(name, column.expand());
+        }
+        for (name, column) in std::mem::take(&mut self.slabs).into_iter() {
+            self.data.insert(name, column.expand());
+        }
+        for (name, steps) in spilled.into_iter() {
+            self.data.insert(name, steps);
+        }
+        self.spilled.clear();
+        return Ok(());
+    }
+
+    /// Returns the names of the variables currently stored in a scratch file.
+    pub fn spilled_variables(&self) -> Vec<&str> {
+        return self.spilled.keys().map(|name| name.as_str()).collect();
+    }
+
+    // Decodes the variables in a single pass, after the x axis: the first variables are kept in
+    // memory while the decoded data fits the limit, the samples of the others are written to a
+    // scratch file as they are decoded. Variables stored at preview precision are then compacted
+    // one at a time, and the scratch file is dropped.
+    pub(crate) fn decode_spilled(&mut self, buffer: &[u8], layout: &Layout, limit: usize) -> Result<(), Box<dyn Error>> {
+        let x = self.data.get("x").ok_or("Missing x axis data.")?;
+        let lengths: Vec<usize> = x.iter().map(|values| values.len()).collect();
+        let column_size = decoded_size(&lengths);
+        let resident = match column_size {
+            0 => self.variables.len(),
+            _ => (limit.saturating_sub(size(x)) / column_size).min(self.variables.len()),
+        };
+        let precision = self.options.get_preview();
+        let statistics = self.options.computes_column_stats();
+
+        let mut spiller = match resident < self.variables.len() {
+            true => Some(Spiller::new((self.variables.len() - resident) * column_size)?),
+            false => None,
+        };
+        let mut destinations: Vec<Destination> = (0..self.variables.len())
+            .map(|index| match index < resident {
+                true => Destination::Memory(lengths.iter().map(|length| Vec::with_capacity(*length)).collect()),
+                false => Destination::Scratch((index - resident) * column_size),
+            })
+            .collect();
+        let mut step_statistics: Vec<Vec<Statistics>> = match statistics {
+            true => vec![vec![Statistics::default(); lengths.len()]; self.variables.len()],
+            false => Vec::new(),
+        };
+
+        let cancelled = || self.options.is_cancelled();
+        let mut point = 0;
+        ltspice_decode::visit_points::<Value, _, _>(buffer, layout, &lengths, cancelled, |step, samples| {
+            for (index, value) in samples.iter().enumerate() {
+                if let Some(variable) = step_statistics.get_mut(index) {
+                    variable[step].add(value.real);
+                }
+                match &mut destinations[index] {
+                    Destination::Memory(steps) => steps[step].push(*value),
+                    Destination::Scratch(offset) => {
+                        if let Some(spiller) = spiller.as_mut() {
+                            spiller.write(*offset + point * VALUE_SIZE, value);
+                        }
+                    }
+                }
+            }
+            point += 1;
+        })
+        .ok_or("Cancelled")?;
+
+        let scratch = spiller.map(|spiller| spiller.finish()).transpose()?;
+        for (index, destination) in destinations.into_iter().enumerate() {
+            let name = self.variables[index].name.clone();
+            match (destination, scratch.as_ref()) {
+                (Destination::Memory(steps), _) => {
+                    self.data.insert(name, steps);
+                }
+                (Destination::Scratch(offset), Some(scratch)) => {
+                    self.spilled.insert(name, SpilledColumn::new(scratch.clone(), offset, &lengths));
+                }
+                (Destination::Scratch(_), None) => {}
+            }
+        }
+        for (variable, steps) in self.variables.iter().zip(step_statistics) {
+            self.column_stats.insert(variable.name.clone(), steps.iter().map(ColumnStats::from).collect());
+        }
+
+        // Compacts the variables one at a time, from memory or from the scratch file
+        if precision != Precision::Full {
+            for index in 0..self.variables.len() {
+                let name = self.variables[index].name.clone();
+                let steps = match (self.data.remove(&name), self.spilled.remove(&name)) {
+                    (Some(steps), _) => steps,
+                    (None, Some(mut column)) => column.take()?,
+                    (None, None) => continue,
+                };
+                self.compacted.insert(name, CompactColumn::new(&steps, precision));
+            }
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,135 @@
+/*
+ * This file contains the SQLite exporter
+ */
+
+use std::error::Error;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::{json, SteppedSimulation};
+
+/* #### Constants #### */
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        source TEXT NOT NULL,
+        source_hash TEXT NOT NULL,
+        parser_version TEXT NOT NULL,
+        mode TEXT NOT NULL,
+        date TEXT,
+        date_string TEXT,
+        step INTEGER NOT NULL,
+        parameters TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS variables (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        class TEXT NOT NULL,
+        unit TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS samples (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        variable_id INTEGER NOT NULL REFERENCES variables(id),
+        point INTEGER NOT NULL,
+        x REAL NOT NULL,
+        real REAL NOT NULL,
+        imaginary REAL NOT NULL,
+        PRIMARY KEY (run_id, variable_id, point)
+    );
+";
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Exports the simulation to an SQLite database, creating the `runs`, `variables` and
+    /// `samples` tables if needed. Each step becomes one row of `runs`, with the hash of the
+    /// source file and its '.step' parameters stored as a JSON object (queryable with `json_extract`).
+    /// `date` is the RFC 3339 date, NULL if it could not be parsed, and `date_string` the date as
+    /// written in the header.
+    /// Exporting several simulations to the same database appends to it.
+    pub fn to_sqlite(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+
+        let transaction = connection.transaction()?;
+        {
+            // Variables
+            let mut variable_ids = Vec::new();
+            for variable in self.get_variables().iter() {
+                transaction.execute(
+                    "INSERT OR IGNORE INTO variables (name, class, unit) VALUES (?1, ?2, ?3)",
+                    params![
+                        variable.name(),
+                        format!("{:?}", variable.class()),
+                        self.get_unit(variable.name()).symbol()
+                    ],
+                )?;
+                let id: i64 = transaction.query_row(
+                    "SELECT id FROM variables WHERE name = ?1",
+                    params![variable.name()],
+                    |row| row.get(0),
+                )?;
+                variable_ids.push(id);
+            }
+
+            let mut insert_sample = transaction.prepare(
+                "INSERT INTO samples (run_id, variable_id, point, x, real, imaginary) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+
+            for step in 0..self.get_step_count() as u16 {
+
+                // Runs
+                let mut parameters = String::from("{");
+                for (index, (name, value)) in self.get_step_parameters(step).unwrap_or(&[]).iter().enumerate() {
+                    if index > 0 {
+                        parameters.push(',');
+                    }
+                    parameters.push_str(&format!("{}:{}", json::string(name), json::number(*value)));
+                }
+                parameters.push('}');
+
+                transaction.execute(
+                    "INSERT INTO runs (source, source_hash, parser_version, mode, date, date_string, step, parameters) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        self.path.to_string_lossy(),
+                        self.provenance().hash_hex(),
+                        self.provenance().parser_version,
+                        self.get_mode().to_string(),
+                        self.get_date().map(|date| date.to_rfc3339()),
+                        self.get_date_string(),
+                        step,
+                        parameters
+                    ],
+                )?;
+                let run_id = transaction.last_insert_rowid();
+
+                // Samples
+                let x = match self.get("x", Some(step)) {
+                    Some(x) => x,
+                    None => continue,
+                };
+                for (variable, variable_id) in self.get_variables().iter().zip(variable_ids.iter()) {
+                    let values = match self.get(variable.name(), Some(step)) {
+                        Some(values) => values,
+                        None => continue,
+                    };
+                    for (point, (x, value)) in x.iter().zip(values.iter()).enumerate() {
+                        insert_sample.execute(params![
+                            run_id,
+                            variable_id,
+                            point as i64,
+                            x.real(),
+                            value.real(),
+                            value.imaginary()
+                        ])?;
+                    }
+                }
+            }
+        }
+        transaction.commit()?;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * This file contains the loop stability analysis of AC simulations
+ *
+ * The loop gain is measured with Tian's double injection method (as used by the LTSpice
+ * LoopGain2 example): the same AC analysis is run twice, once injecting a voltage in series
+ * with the loop and once injecting a current into the probe node, and the two responses are
+ * combined into the true loop gain, independent of the loading at the probe.
+ */
+
+use crate::ac::unwrap_phase;
+use crate::convert::db;
+use crate::trace::{Trace, TraceBuf};
+use crate::{SteppedSimulation, Unit, Value};
+
+/* #### Structs #### */
+
+/// The signals of a Tian probe and the steps of both injections.
+#[derive(Debug, Clone)]
+pub struct TianProbe<'a> {
+    /// Voltage at the probe node, e.g. "V(x)".
+    pub voltage: &'a str,
+    /// Current through the injection voltage source, e.g. "I(Vi)".
+    pub current: &'a str,
+    /// Step of the voltage injection run.
+    pub voltage_step: u16,
+    /// Step of the current injection run.
+    pub current_step: u16,
+}
+
+/// The stability margins of a loop gain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    /// Unity gain frequency and phase margin in degrees, None if the gain never crosses 0 dB.
+    pub phase_margin: Option<(f64, f64)>,
+    /// Phase crossover frequency and gain margin in dB, None if the phase never reaches -180°.
+    pub gain_margin: Option<(f64, f64)>,
+}
+
+/* #### Functions #### */
+
+/// Computes the loop gain T(f) from the two runs of a Tian probe:
+/// T = -1 / (1 - 1 / (2·(I₁·V₂ - V₁·I₂) + V₁ + I₂)), with 1 the voltage and 2 the current injection.
+/// Returns None if a signal or step does not exist, or the runs have different frequencies.
+pub fn tian(simulation: &SteppedSimulation, probe: &TianProbe<'_>) -> Option<TraceBuf> {
+    let v1 = simulation.trace(probe.voltage, probe.voltage_step)?;
+    let i1 = simulation.trace(probe.current, probe.voltage_step)?;
+    let v2 = simulation.trace(probe.voltage, probe.current_step)?;
+    let i2 = simulation.trace(probe.current, probe.current_step)?;
+    if v1.x() != v2.x() {
+        return None;
+    }
+
+    let one = Value::from(1.0);
+    let values: Vec<Value> = (0..[v1.len(), i1.len(), v2.len(), i2.len()].into_iter().min()?)
+        .map(|index| {
+            let (v1, i1, v2, i2) = (v1.values()[index], i1.values()[index], v2.values()[index], i2.values()[index]);
+            let d = (i1 * v2 - v1 * i2) * 2.0 + v1 + i2;
+            -(one / (one - one / d))
+        })
+        .collect();
+    return Some(
+        v1.with_data("T".to_string(), v1.x()[..values.len()].to_vec(), values)
+            .with_unit(Unit::Dimensionless),
+    );
+}
+
+// Returns the frequency at which the sampled function first crosses the level, interpolated
+// on a logarithmic frequency axis.
+fn crossing(frequency: &[f64], values: &[f64], level: f64) -> Option<(usize, f64)> {
+    for index in 1..frequency.len().min(values.len()) {
+        let (y0, y1) = (values[index - 1] - level, values[index] - level);
+        if y0 == 0.0 {
+            return Some((index - 1, frequency[index - 1]));
+        }
+        if y0.signum() != y1.signum() {
+            let ratio = y0 / (y0 - y1);
+            let (f0, f1) = (frequency[index - 1], frequency[index]);
+            let f = match f0 > 0.0 && f1 > 0.0 {
+                true => 10f64.powf(f0.log10() + ratio * (f1.log10() - f0.log10())),
+                false => f0 + ratio * (f1 - f0),
+            };
+            return Some((index - 1, f));
+        }
+    }
+    return None;
+}
+
+/// Computes the phase and gain margins of a loop gain, e.g. the result of `tian`.
+pub fn margins(loop_gain: &Trace<'_>) -> Margins {
+    let frequency: Vec<f64> = loop_gain.x().iter().map(|x| x.real()).collect();
+    let gain: Vec<f64> = loop_gain.values().iter().map(|value| db(value.abs())).collect();
+    let phases: Vec<f64> = loop_gain.values().iter().map(|value| value.arg()).collect();
+    let phase: Vec<f64> = unwrap_phase(&phases, std::f64::consts::PI)
+        .into_iter()
+        .map(|phase| phase.to_degrees())
+        .collect();
+
+    // Linear interpolation of a sampled quantity between two points, at a given frequency
+    let at = |values: &[f64], index: usize, f: f64| -> f64 {
+        let (f0, f1) = (frequency[index], frequency[index + 1]);
+        match f1 == f0 {
+            true => values[index],
+            false => values[index] + (values[index + 1] - values[index]) * (f - f0) / (f1 - f0),
+        }
+    };
+
+    let phase_margin = crossing(&frequency, &gain, 0.0).map(|(index, f)| (f, 180.0 + at(&phase, index, f)));
+    let gain_margin = crossing(&frequency, &phase, -180.0).map(|(index, f)| (f, -at(&gain, index, f)));
+    return Margins {
+        phase_margin,
+        gain_margin,
+    };
+}
@@ -0,0 +1,252 @@
+/*
+ * This file contains the streaming reader of binary raw files
+ *
+ * Only the header is parsed up front, the points are then read from the file chunk by chunk
+ * and previous chunks are not retained, so running statistics can be computed over files of
+ * any size in constant memory. Compressed and ASCII ('Values:') files cannot be streamed.
+ * A partial point at the end of the file is read again by the next chunk, so a file still
+ * being written by LTSpice can be followed by polling the stream (see `live` for async).
+ */
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use ltspice_decode::{read_sample, DataType, Layout};
+
+use crate::options::LoadOptions;
+use crate::{header_length, Encoding, Mode, StepRule, SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+/// Default number of points per chunk.
+pub const CHUNK_POINTS: usize = 4096;
+
+/* #### Structs #### */
+
+/// A chunk of decoded points, see `RawStream`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chunk {
+    /// The step of every point.
+    pub steps: Vec<u16>,
+    /// The x axis of every point.
+    pub x: Vec<Value>,
+    /// The samples of every selected variable, in the order of `RawStream::names`.
+    pub values: Vec<Vec<Value>>,
+}
+
+/// An iterator over the points of a binary raw file, in chunks of a fixed number of points.
+///
+/// ```ignore
+/// let mut stream = RawStream::open(Path::new("tran.raw"))?.select(&["V(out)"])?;
+/// for chunk in stream {
+///     let chunk = chunk?;
+///     // ...
+/// }
+/// ```
+pub struct RawStream {
+    reader: BufReader<File>,
+    mode: Mode,
+    layout: Layout,
+    names: Vec<String>,
+    selected: Vec<usize>,
+    offsets: Vec<usize>,
+    chunk_points: usize,
+    remaining: Option<u64>,
+    position: u64,
+    rule: StepRule,
+    first: Option<Value>,
+    previous: Option<Value>,
+    step: u16,
+}
+
+/* #### Implementations #### */
+
+impl RawStream {
+    /// Opens a binary raw file and parses its header, with every variable selected.
+    pub fn open(path: &Path) -> Result<RawStream, Box<dyn Error>> {
+        return Ok(RawStream::open_with_header(path)?.0);
+    }
+
+    // Same as `open`, also returning the simulation parsed from the header alone, without points.
+    pub(crate) fn open_with_header(path: &Path) -> Result<(RawStream, SteppedSimulation), Box<dyn Error>> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        // Read until the end of the header, in either encoding
+        let mut header = Vec::new();
+        let mut buffer = [0u8; 4096];
+        let length = loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                Err("The file has no binary data.")?;
+            }
+            header.extend_from_slice(&buffer[..read]);
+            if let Some(length) = header_length(&header, &Encoding::UTF8).or_else(|| header_length(&header, &Encoding::UTF16)) {
+                break length;
+            }
+        };
+
+        // Parse the header alone, then rewind to the start of the binary data
+        let simulation = SteppedSimulation::from_bytes_with_options(&header[..length], LoadOptions::new().x_only(true))?;
+        reader.seek(SeekFrom::Start(length as u64))?;
+
+        let y: Vec<DataType> = simulation
+            .get_variables()
+            .iter()
+            .map(|variable| variable.data_type().unwrap_or(DataType::Float32))
+            .collect();
+        let x = match y.first() {
+            Some(DataType::Complex128) => DataType::Complex128,
+            _ => DataType::Float64,
+        };
+        let layout = Layout { x, y };
+
+        let stream = RawStream {
+            reader,
+            mode: simulation.get_mode().clone(),
+            offsets: layout.offsets(),
+            layout,
+            names: simulation.get_variables().iter().map(|variable| variable.name().to_string()).collect(),
+            selected: (0..simulation.get_variables().len()).collect(),
+            chunk_points: CHUNK_POINTS,
+            remaining: Some(simulation.stats.points as u64).filter(|points| *points > 0),
+            position: length as u64,
+            rule: StepRule::new(simulation.get_mode()),
+            first: None,
+            previous: None,
+            step: 0,
+        };
+        return Ok((stream, simulation));
+    }
+
+    /// Selects the variables decoded in every chunk, in order. Fails for an unknown variable.
+    pub fn select(mut self, names: &[&str]) -> Result<RawStream, Box<dyn Error>> {
+        self.selected = names
+            .iter()
+            .map(|name| {
+                self.names
+                    .iter()
+                    .position(|variable| variable == name)
+                    .ok_or(format!("Unknown variable '{}'.", name))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        return Ok(self);
+    }
+
+    /// Sets the number of points per chunk, `CHUNK_POINTS` by default.
+    pub fn chunk_points(mut self, points: usize) -> RawStream {
+        self.chunk_points = points.max(1);
+        return self;
+    }
+
+    /// Returns the names of the selected variables, in the order of `Chunk::values`.
+    pub fn names(&self) -> Vec<&str> {
+        return self.selected.iter().map(|index| self.names[*index].as_str()).collect();
+    }
+
+    /// Returns the analysis mode of the file.
+    pub fn mode(&self) -> &Mode {
+        return &self.mode;
+    }
+
+    /// Returns whether every point declared in the header was read.
+    pub fn is_complete(&self) -> bool {
+        return self.remaining == Some(0);
+    }
+
+    // Returns the offset in the file of the first point not read yet.
+    #[cfg(feature = "serve")]
+    pub(crate) fn offset(&self) -> u64 {
+        return self.position;
+    }
+
+    // Reads the next chunk, None at the end of the data. A trailing partial point is left for
+    // the next call, once the rest of it has been written.
+    pub(crate) fn read_chunk(&mut self) -> Result<Option<Chunk>, Box<dyn Error>> {
+        // Read the bytes of the whole chunk at once, then decode its complete points by offset
+        let point_size = self.layout.point_size();
+        let points = match self.remaining {
+            Some(remaining) => (remaining as usize).min(self.chunk_points),
+            None => self.chunk_points,
+        };
+        let mut buffer = vec![0u8; points * point_size];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.reader.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => Err(e)?,
+            }
+        }
+        let complete = filled - filled % point_size;
+        self.position += complete as u64;
+        if complete < filled {
+            self.reader.seek(SeekFrom::Start(self.position))?;
+        }
+        self.remaining = self.remaining.map(|remaining| remaining - (complete / point_size) as u64);
+
+        let mut chunk = Chunk {
+            steps: Vec::with_capacity(complete / point_size),
+            x: Vec::with_capacity(complete / point_size),
+            values: vec![Vec::with_capacity(complete / point_size); self.selected.len()],
+        };
+        for point in buffer[..complete].chunks_exact(point_size) {
+            let mut x: Value = read_sample(self.layout.x, point);
+            if let (Some(first), Some(previous)) = (&self.first, &self.previous) {
+                if self.rule.is_new_step(first, previous, &x) {
+                    self.step = self.step.checked_add(1).ok_or("The file has more than 65536 steps.")?;
+                    self.first = None;
+                }
+            }
+            self.first = self.first.or(Some(x));
+            self.previous = Some(x);
+
+            // Compressed points have a negative time, see `SteppedSimulation::reload`
+            if self.mode == Mode::Transient {
+                x.real = x.real.abs();
+            }
+            chunk.steps.push(self.step);
+            chunk.x.push(x);
+            for (values, index) in chunk.values.iter_mut().zip(self.selected.iter()) {
+                values.push(read_sample(self.layout.y[*index], &point[self.offsets[*index]..]));
+            }
+        }
+        if chunk.x.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(chunk));
+    }
+}
+
+impl SteppedSimulation {
+    // Appends the points of a chunk read with every variable selected, e.g. to follow a file
+    // being written without parsing it again.
+    #[cfg(feature = "serve")]
+    pub(crate) fn append_chunk(&mut self, chunk: &Chunk) {
+        let names: Vec<String> = std::iter::once("x".to_string())
+            .chain(self.variables.iter().map(|variable| variable.name.clone()))
+            .collect();
+        for (name, values) in names.iter().zip(std::iter::once(&chunk.x).chain(chunk.values.iter())) {
+            let steps = self.data.get_or_default(name);
+            for (step, value) in chunk.steps.iter().zip(values.iter()) {
+                while steps.len() <= *step as usize {
+                    steps.push(Vec::new());
+                }
+                steps[*step as usize].push(*value);
+            }
+        }
+        self.stats.steps = self.get_step_count() as u16;
+    }
+}
+
+/// Iterates until the end of the data currently in the file: for a file still being written,
+/// `next` can return more chunks after it returned None.
+impl Iterator for RawStream {
+    type Item = Result<Chunk, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.read_chunk().transpose();
+    }
+}
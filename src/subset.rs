@@ -0,0 +1,70 @@
+/*
+ * This file contains the extraction of subsets of a simulation into a new simulation
+ */
+
+use std::error::Error;
+use std::ops::RangeBounds;
+use std::path::PathBuf;
+
+use crate::SteppedSimulation;
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Returns a new simulation containing only the selected variables, steps and x range,
+    /// which can then be exported or written back with `write_raw`.
+    ///
+    /// ```ignore
+    /// let small = sim.extract(&["V(out)", "I(L1)"], 0..4, 1e-3..2e-3)?;
+    /// let all_steps = sim.extract(&["V(out)"], .., ..)?;
+    /// ```
+    pub fn extract<S, R>(&self, names: &[&str], steps: S, range: R) -> Result<SteppedSimulation, Box<dyn Error>>
+    where
+        S: RangeBounds<u16>,
+        R: RangeBounds<f64>,
+    {
+        let mut subset = SteppedSimulation::new(PathBuf::new());
+        subset.encoding = self.encoding.clone();
+        subset.mode = self.mode.clone();
+        subset.flags = self.flags.clone();
+        #[cfg(feature = "chrono")]
+        {
+            subset.date = self.date;
+        }
+        subset.date_string = self.date_string.clone();
+        subset.offset = self.offset;
+        subset.source = self.source;
+        subset.options = self.options.clone();
+
+        // Variables
+        for name in names.iter() {
+            let name = self.resolve(name);
+            match self.variables.iter().find(|variable| variable.name == name) {
+                Some(variable) => subset.variables.push(variable.clone()),
+                None => Err(format!("Unknown variable '{}'.", name))?,
+            }
+        }
+
+        // Steps & Range
+        let mut points = 0;
+        for step in (0..self.get_step_count() as u16).filter(|step| steps.contains(step)) {
+            let x = self.get("x", Some(step)).ok_or("Missing step data.")?;
+            let indices: Vec<usize> = (0..x.len()).filter(|index| range.contains(&x[*index].real())).collect();
+            points += indices.len();
+
+            for name in std::iter::once("x").chain(subset.variables.iter().map(|variable| variable.name.as_str())) {
+                let values = self.get(name, Some(step)).ok_or("Missing step data.")?;
+                let selected = indices.iter().filter_map(|index| values.get(*index).copied()).collect();
+                subset.data.get_or_default(name).push(selected);
+            }
+            if let Some(parameters) = self.parameters.get(step as usize) {
+                subset.parameters.push(parameters.clone());
+            }
+        }
+
+        subset.stats.variables = subset.variables.len() as u32 + 1;
+        subset.stats.points = points as u32;
+        subset.stats.steps = subset.get_step_count() as u16;
+        return Ok(subset);
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * This file contains the post-load summary of a simulation
+ */
+
+use std::fmt;
+
+use ltspice_decode::Statistics;
+
+use crate::ordered::OrderedMap;
+use crate::{Mode, SteppedSimulation, VariableClass};
+
+/* #### Structs #### */
+
+/// The result of `SteppedSimulation::summary`, with the shape of every loaded variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub mode: Mode,
+    /// Number of loaded steps.
+    pub steps: usize,
+    /// Number of points of each step of the x axis.
+    pub points_per_step: Vec<usize>,
+    /// The variables, in header order followed by the derived signals.
+    pub variables: Vec<VariableSummary>,
+}
+
+/// Statistics of the real parts of a variable in a step, computed while decoding and recomputed
+/// when the data changes (e.g. `trim_before`), see `SteppedSimulation::column_stats`. The mean and RMS are over the samples, not weighted by the
+/// x axis; every value is NaN for a step without samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub rms: f64,
+    /// Number of samples, NaN samples excluded.
+    pub count: usize,
+}
+
+/// The shape of a single variable, see `Summary`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableSummary {
+    pub name: String,
+    pub class: VariableClass,
+    /// Number of steps with data, 0 if the variable has no data.
+    pub steps: usize,
+    /// Number of points over every step.
+    pub points: usize,
+    pub derived: bool,
+    /// Whether the data is currently stored in a scratch file, see `LoadOptions::memory_limit`.
+    pub spilled: bool,
+    /// Whether the data is stored at preview precision, see `LoadOptions::preview`.
+    pub preview: bool,
+}
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    // Returns the number of points of each step of a column, without loading spilled data.
+    fn step_lengths(&self, name: &str) -> Vec<usize> {
+        if let Some(steps) = self.data.get(name) {
+            return steps.iter().map(|values| values.len()).collect();
+        }
+        if let Some(column) = self.compacted.get(name) {
+            return column.lengths();
+        }
+        if let Some(column) = self.slabs.get(name) {
+            return column.lengths();
+        }
+        return self.spilled.get(name).map_or(Vec::new(), |column| column.lengths().to_vec());
+    }
+
+    // Recomputes the column statistics from the data in memory, after it changed. Nothing is
+    // computed if they were not requested, see `LoadOptions::column_stats`.
+    pub(crate) fn refresh_column_stats(&mut self) {
+        if self.column_stats.is_empty() {
+            return;
+        }
+        let mut column_stats = OrderedMap::new();
+        for variable in self.variables.iter().filter(|variable| !variable.derived) {
+            let Some(steps) = self.data.get(&variable.name) else {
+                continue;
+            };
+            let steps = steps.iter().map(|values| {
+                let mut statistics = Statistics::default();
+                for value in values.iter() {
+                    statistics.add(value.real);
+                }
+                return ColumnStats::from(&statistics);
+            });
+            column_stats.insert(variable.name.clone(), steps.collect());
+        }
+        self.column_stats = column_stats;
+    }
+
+    /// Summarizes the loaded data, never panics on missing or partial variables.
+    pub fn summary(&self) -> Summary {
+        let variables = self
+            .variables
+            .iter()
+            .map(|variable| {
+                let lengths = self.step_lengths(&variable.name);
+                return VariableSummary {
+                    name: variable.name.clone(),
+                    class: variable.class,
+                    steps: lengths.len(),
+                    points: lengths.iter().sum(),
+                    derived: variable.derived,
+                    spilled: self.spilled.contains_key(&variable.name),
+                    preview: self.compacted.contains_key(&variable.name),
+                };
+            })
+            .collect();
+
+        return Summary {
+            mode: self.mode.clone(),
+            steps: self.get_step_count(),
+            points_per_step: self.step_lengths("x"),
+            variables,
+        };
+    }
+}
+
+impl From<&Statistics> for ColumnStats {
+    fn from(statistics: &Statistics) -> Self {
+        if statistics.count == 0 {
+            return ColumnStats {
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                rms: f64::NAN,
+                count: 0,
+            };
+        }
+        return ColumnStats {
+            min: statistics.min,
+            max: statistics.max,
+            mean: statistics.mean().unwrap_or(f64::NAN),
+            rms: statistics.mean_square().map_or(f64::NAN, f64::sqrt),
+            count: statistics.count,
+        };
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let points: usize = self.points_per_step.iter().sum();
+        writeln!(f, "{} analysis, {} steps, {} points", self.mode, self.steps, points)?;
+        for variable in self.variables.iter() {
+            write!(f, "  {} ({:?}): {} steps, {} points", variable.name, variable.class, variable.steps, variable.points)?;
+            if variable.derived {
+                write!(f, ", derived")?;
+            }
+            if variable.spilled {
+                write!(f, ", spilled")?;
+            }
+            if variable.preview {
+                write!(f, ", preview")?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,127 @@
+/*
+ * This file contains a minimal SVG line plot renderer
+ */
+
+use std::fmt::Write;
+
+use crate::marker::Marker;
+
+/* #### Constants #### */
+
+const COLORS: [&str; 6] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b"];
+const MARGIN: f64 = 50.0;
+
+/* #### Structs #### */
+
+/// A named series of (x, y) points to be drawn on a plot.
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub label: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+/* #### Functions #### */
+
+/// Renders the series as a self-contained SVG line plot, with axes labels and a legend.
+pub fn plot(series: &[Series], width: u32, height: u32) -> String {
+    return plot_with_markers(series, &[], width, height);
+}
+
+/// Renders the series as `plot` does, with a dot and the label of every marker.
+pub fn plot_with_markers(series: &[Series], markers: &[Marker], width: u32, height: u32) -> String {
+    let (width, height) = (width as f64, height as f64);
+
+    // Compute The Bounds
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for (x, y) in series.iter().flat_map(|s| s.points.iter()) {
+        if !x.is_finite() || !y.is_finite() {
+            continue;
+        }
+        x_min = x_min.min(*x);
+        x_max = x_max.max(*x);
+        y_min = y_min.min(*y);
+        y_max = y_max.max(*y);
+    }
+    if x_min > x_max {
+        (x_min, x_max, y_min, y_max) = (0.0, 1.0, 0.0, 1.0);
+    }
+    if x_min == x_max {
+        (x_min, x_max) = (x_min - 1.0, x_max + 1.0);
+    }
+    if y_min == y_max {
+        (y_min, y_max) = (y_min - 1.0, y_max + 1.0);
+    }
+
+    let scale_x = |x: f64| MARGIN + (x - x_min) / (x_max - x_min) * (width - 2.0 * MARGIN);
+    let scale_y = |y: f64| height - MARGIN - (y - y_min) / (y_max - y_min) * (height - 2.0 * MARGIN);
+
+    let mut output = String::new();
+    write!(
+        output,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"11\">",
+        w = width,
+        h = height
+    )
+    .unwrap();
+
+    // Axes
+    write!(
+        output,
+        "<rect x=\"{m}\" y=\"{m}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#888\"/>",
+        width - 2.0 * MARGIN,
+        height - 2.0 * MARGIN,
+        m = MARGIN
+    )
+    .unwrap();
+    write!(output, "<text x=\"{}\" y=\"{}\">{:.3e}</text>", MARGIN, height - MARGIN + 15.0, x_min).unwrap();
+    write!(
+        output,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.3e}</text>",
+        width - MARGIN,
+        height - MARGIN + 15.0,
+        x_max
+    )
+    .unwrap();
+    write!(output, "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.3e}</text>", MARGIN - 3.0, height - MARGIN, y_min).unwrap();
+    write!(output, "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.3e}</text>", MARGIN - 3.0, MARGIN + 10.0, y_max).unwrap();
+
+    // Series & Legend
+    for (index, s) in series.iter().enumerate() {
+        let color = COLORS[index % COLORS.len()];
+        output.push_str("<polyline fill=\"none\" stroke-width=\"1.2\" stroke=\"");
+        output.push_str(color);
+        output.push_str("\" points=\"");
+        for (x, y) in s.points.iter().filter(|(x, y)| x.is_finite() && y.is_finite()) {
+            write!(output, "{:.2},{:.2} ", scale_x(*x), scale_y(*y)).unwrap();
+        }
+        output.push_str("\"/>");
+        write!(
+            output,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+            MARGIN + 5.0,
+            MARGIN - 8.0 - 13.0 * (series.len() - 1 - index) as f64,
+            color,
+            escape(&s.label)
+        )
+        .unwrap();
+    }
+
+    // Markers
+    for marker in markers.iter().filter(|marker| marker.x.is_finite() && marker.value.is_finite()) {
+        let (x, y) = (scale_x(marker.x), scale_y(marker.value));
+        write!(output, "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\" fill=\"#000\"/>", x, y).unwrap();
+        write!(output, "<text x=\"{:.2}\" y=\"{:.2}\">{}</text>", x + 5.0, y - 5.0, escape(&marker.label)).unwrap();
+    }
+
+    output.push_str("</svg>");
+    return output;
+}
+
+/// Escapes a string for use inside XML/HTML text.
+pub fn escape(value: &str) -> String {
+    return value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
@@ -0,0 +1,375 @@
+/*
+ * This file contains the snapshot testing helpers, for crates writing waveform unit tests
+ *
+ * A snapshot is a text file with one "x real imaginary" line per point. It is written when
+ * it does not exist yet (or when LTSPICE_UPDATE_SNAPSHOTS is set), and compared otherwise:
+ * the trace is interpolated at the abscissas of the snapshot, so a different time step is
+ * not a failure as long as the waveform stays within the tolerance.
+ *
+ *   assert_trace_snapshot!(simulation.trace("V(out)", 0).unwrap(), "buck_output");
+ *
+ * `generate_raw` fabricates deterministic raw files of any size, for benchmarks and fuzzing corpora.
+ */
+
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::trace::Trace;
+use crate::transform::interpolate;
+use crate::writer::{plotname, x_name};
+use crate::{DataType, Encoding, FileType, Mode, Value};
+
+/* #### Structs #### */
+
+/// The tolerance of a snapshot comparison: a sample matches if it is within the absolute
+/// tolerance or the relative tolerance (of the snapshot value).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+/// The description of a synthetic raw file, see `generate_raw`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawSpec {
+    mode: Mode,
+    variables: usize,
+    points: usize,
+    steps: u16,
+    precision: DataType,
+    file_type: FileType,
+    encoding: Encoding,
+    seed: u64,
+}
+
+/* #### Constants #### */
+
+/// Environment variable which makes every snapshot assertion overwrite its snapshot.
+pub const UPDATE_VARIABLE: &str = "LTSPICE_UPDATE_SNAPSHOTS";
+
+/* #### Functions #### */
+
+fn write_snapshot(trace: &Trace<'_>, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut text = format!("# {} [{}] vs [{}]\n", trace.name(), trace.unit(), trace.x_unit());
+    for (x, value) in trace.x().iter().zip(trace.values().iter()) {
+        writeln!(text, "{:e} {:e} {:e}", x.real(), value.real(), value.imaginary())?;
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text)?;
+    return Ok(());
+}
+
+fn read_snapshot(path: &Path) -> Result<Vec<(f64, Value)>, Box<dyn Error>> {
+    let mut points = Vec::new();
+    for line in fs::read_to_string(path)?.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = line
+            .split_whitespace()
+            .map(|field| field.parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()?;
+        match fields[..] {
+            [x, real, imaginary] => points.push((x, Value { real, imaginary })),
+            _ => Err(format!("Invalid snapshot line '{}'.", line))?,
+        }
+    }
+    return Ok(points);
+}
+
+/// Compares a trace with its snapshot, creating the snapshot if it does not exist yet.
+/// Returns a description of the first mismatch (and the number of mismatches) as an error.
+pub fn compare_snapshot(trace: &Trace<'_>, path: &Path, tolerance: Tolerance) -> Result<(), Box<dyn Error>> {
+    if !path.is_file() || std::env::var_os(UPDATE_VARIABLE).is_some() {
+        return write_snapshot(trace, path);
+    }
+
+    let expected = read_snapshot(path)?;
+    let mut mismatches = Vec::new();
+    for (x, expected) in expected.iter() {
+        match interpolate(trace.x(), trace.values(), *x) {
+            Some(actual) if tolerance.matches(actual, *expected) => {}
+            Some(actual) => mismatches.push(format!(
+                "at x = {:e}: expected {:e}{:+e}i, got {:e}{:+e}i",
+                x,
+                expected.real(),
+                expected.imaginary(),
+                actual.real(),
+                actual.imaginary()
+            )),
+            None => mismatches.push(format!("at x = {:e}: outside of the trace", x)),
+        }
+    }
+    if let Some(first) = mismatches.first() {
+        Err(format!(
+            "{} does not match its snapshot {:?} ({} of {} points differ), first {}. Set {} to update it.",
+            trace.name(),
+            path,
+            mismatches.len(),
+            expected.len(),
+            first,
+            UPDATE_VARIABLE
+        ))?;
+    }
+    return Ok(());
+}
+
+/// Panics if a trace does not match its snapshot, see `compare_snapshot`.
+#[track_caller]
+pub fn assert_snapshot(trace: &Trace<'_>, path: &Path, tolerance: Tolerance) {
+    if let Err(e) = compare_snapshot(trace, path, tolerance) {
+        panic!("{}", e);
+    }
+}
+
+/// Asserts that a trace matches the snapshot '<name>.snap' in the 'tests/snapshots' directory
+/// of the calling crate, with the default or the specified `Tolerance`.
+#[macro_export]
+macro_rules! assert_trace_snapshot {
+    ($trace:expr, $name:expr) => {
+        $crate::assert_trace_snapshot!($trace, $name, $crate::testing::Tolerance::default())
+    };
+    ($trace:expr, $name:expr, $tolerance:expr) => {
+        $crate::testing::assert_snapshot(
+            &$trace,
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("snapshots")
+                .join(format!("{}.snap", $name)),
+            $tolerance,
+        )
+    };
+}
+
+// Deterministic pseudo-random generator (xorshift64*), returning values in [-1, 1).
+fn noise(state: &mut u64) -> f64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    let value = state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+    return value as f64 / (1u64 << 52) as f64 - 1.0;
+}
+
+// Returns the abscissa of a point.
+fn abscissa(mode: &Mode, point: usize, points: usize) -> f64 {
+    let position = point as f64 / (points.max(2) - 1) as f64;
+    return match mode {
+        // 1 Hz to 1 MHz, logarithmically spaced
+        Mode::AC | Mode::FFT | Mode::Noise => 10f64.powf(6.0 * position),
+        Mode::Transient => position * 1e-3,
+        Mode::DC | Mode::OperatingPoint | Mode::Other(_) => position * 10.0 - 5.0,
+    };
+}
+
+// Returns the sample of a variable at a point, scaled by the variable and step: a first-order
+// response (AC), a flicker density (noise), a damped sine (transient) or a soft limiter (DC sweep).
+// Real samples get a little deterministic noise.
+fn sample(mode: &Mode, x: f64, variable: usize, step: u16, state: &mut u64) -> Value {
+    let scale = (variable + 1) as f64 * (1.0 + 0.1 * step as f64);
+    return match mode {
+        Mode::AC | Mode::FFT => {
+            let pole = 1e3 * (variable + 1) as f64;
+            let denominator = 1.0 + (x / pole).powi(2);
+            Value {
+                real: scale / denominator,
+                imaginary: -scale * (x / pole) / denominator,
+            }
+        }
+        Mode::Noise => Value::from(scale * 1e-9 / x.sqrt() * (1.0 + 1e-3 * noise(state))),
+        Mode::Transient => Value::from(scale * (x * 1e4 / (variable + 1) as f64).sin() * (-x * 1e3).exp() + 1e-3 * noise(state)),
+        Mode::DC | Mode::OperatingPoint | Mode::Other(_) => Value::from(scale * (x / (variable + 1) as f64).tanh() + 1e-3 * noise(state)),
+    };
+}
+
+fn encode(text: &str, encoding: &Encoding) -> Vec<u8> {
+    return match encoding {
+        Encoding::UTF16 => text.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect(),
+        Encoding::UTF32 => text.chars().flat_map(|c| (c as u32).to_le_bytes()).collect(),
+        Encoding::UTF8 | Encoding::ASCII => text.as_bytes().to_vec(),
+    };
+}
+
+/// Fabricates a raw file from a specification. The same specification always produces the
+/// same bytes, so generated files can be used as reproducible benchmarks and fuzzing seeds.
+pub fn generate_raw(spec: &RawSpec) -> Vec<u8> {
+    let complex = spec.mode == Mode::AC || spec.mode == Mode::FFT;
+    let double = spec.precision == DataType::Float64;
+    let mut state = spec.seed.max(1);
+
+    // Header
+    let mut flags = vec![if complex { "complex" } else { "real" }, "forward"];
+    if complex {
+        flags.push("log");
+    }
+    if spec.steps > 1 {
+        flags.push("stepped");
+    }
+    if double && !complex {
+        flags.push("double");
+    }
+    let mut header = String::new();
+    header.push_str("Title: * generated by ltspice::testing\n");
+    header.push_str("Date: Thu Jan  1 00:00:00 1970\n");
+    header.push_str(&format!("Plotname: {}\n", plotname(&spec.mode)));
+    header.push_str(&format!("Flags: {}\n", flags.join(" ")));
+    header.push_str(&format!("No. Variables: {}\n", spec.variables + 1));
+    header.push_str(&format!("No. Points: {}\n", spec.points * spec.steps as usize));
+    header.push_str("Offset:   0.0000000000000000e+000\n");
+    header.push_str(&format!("Command: Synthetic {:?} (seed {})\n", spec.mode, spec.seed));
+    header.push_str("Variables:\n");
+    header.push_str(&format!("\t0\t{}\t{}\n", x_name(&spec.mode), x_name(&spec.mode)));
+    for variable in 0..spec.variables {
+        header.push_str(&format!("\t{}\tV(n{:03})\tvoltage\n", variable + 1, variable + 1));
+    }
+
+    // Samples
+    match spec.file_type {
+        FileType::Binary => {
+            header.push_str("Binary:\n");
+            let mut buffer = encode(&header, &spec.encoding);
+            for step in 0..spec.steps {
+                for point in 0..spec.points {
+                    let x = abscissa(&spec.mode, point, spec.points);
+                    match complex {
+                        true => buffer.extend_from_slice(&[x.to_le_bytes(), 0f64.to_le_bytes()].concat()),
+                        false => buffer.extend_from_slice(&x.to_le_bytes()),
+                    }
+                    for variable in 0..spec.variables {
+                        let value = sample(&spec.mode, x, variable, step, &mut state);
+                        match (complex, double) {
+                            (true, _) => buffer.extend_from_slice(&[value.real.to_le_bytes(), value.imaginary.to_le_bytes()].concat()),
+                            (false, true) => buffer.extend_from_slice(&value.real.to_le_bytes()),
+                            (false, false) => buffer.extend_from_slice(&(value.real as f32).to_le_bytes()),
+                        }
+                    }
+                }
+            }
+            return buffer;
+        }
+        FileType::ASCII => {
+            header.push_str("Values:\n");
+            for step in 0..spec.steps {
+                for point in 0..spec.points {
+                    let x = abscissa(&spec.mode, point, spec.points);
+                    let index = step as usize * spec.points + point;
+                    match complex {
+                        true => header.push_str(&format!("{}\t{:e},{:e}\n", index, x, 0.0)),
+                        false => header.push_str(&format!("{}\t{:e}\n", index, x)),
+                    }
+                    for variable in 0..spec.variables {
+                        let value = sample(&spec.mode, x, variable, step, &mut state);
+                        match complex {
+                            true => header.push_str(&format!("\t{:e},{:e}\n", value.real, value.imaginary)),
+                            false => header.push_str(&format!("\t{:e}\n", value.real)),
+                        }
+                    }
+                }
+            }
+            return encode(&header, &spec.encoding);
+        }
+    }
+}
+
+/* #### Implementations #### */
+
+impl RawSpec {
+    /// Creates the specification of a UTF16 binary transient file, as LTSpice writes it, with
+    /// two float32 variables and 1000 points.
+    pub fn new() -> Self {
+        return RawSpec::default();
+    }
+
+    /// Sets the analysis, which also selects the abscissa and complex samples for AC and FFT.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        return self;
+    }
+
+    /// Sets the number of variables, excluding the x axis.
+    pub fn variables(mut self, variables: usize) -> Self {
+        self.variables = variables;
+        return self;
+    }
+
+    /// Sets the number of points of every step.
+    pub fn points(mut self, points: usize) -> Self {
+        self.points = points;
+        return self;
+    }
+
+    /// Sets the number of steps, at least one.
+    pub fn steps(mut self, steps: u16) -> Self {
+        self.steps = steps.max(1);
+        return self;
+    }
+
+    /// Sets the precision of the real samples: float32 (the default) or float64 ("double" flag).
+    pub fn precision(mut self, precision: DataType) -> Self {
+        self.precision = precision;
+        return self;
+    }
+
+    /// Sets whether the samples are written in binary (the default) or ASCII ('Values:'), as
+    /// other simulators do. Note that only binary files can be loaded by this crate.
+    pub fn file_type(mut self, file_type: FileType) -> Self {
+        self.file_type = file_type;
+        return self;
+    }
+
+    /// Sets the text encoding of the header (and of the samples of ASCII files).
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        return self;
+    }
+
+    /// Sets the seed of the noise added to real samples.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        return self;
+    }
+}
+
+impl Default for RawSpec {
+    fn default() -> Self {
+        return RawSpec {
+            mode: Mode::Transient,
+            variables: 2,
+            points: 1000,
+            steps: 1,
+            precision: DataType::Float32,
+            file_type: FileType::Binary,
+            encoding: Encoding::UTF16,
+            seed: 1,
+        };
+    }
+}
+
+impl Tolerance {
+    pub fn new(absolute: f64, relative: f64) -> Self {
+        return Tolerance { absolute, relative };
+    }
+
+    /// Returns whether a sample matches the expected one. Complex samples are compared through
+    /// the magnitude of their difference.
+    pub fn matches(&self, actual: Value, expected: Value) -> bool {
+        if actual == expected {
+            return true;
+        }
+        let difference = (actual - expected).abs();
+        return difference <= self.absolute || difference <= expected.abs() * self.relative;
+    }
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        return Tolerance {
+            absolute: 1e-12,
+            relative: 1e-6,
+        };
+    }
+}
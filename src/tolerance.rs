@@ -0,0 +1,71 @@
+/*
+ * This file contains the approx crate integration, for tolerant comparisons in tests
+ *
+ * Complex values are compared through the magnitude of their difference, so the
+ * tolerance applies to the distance in the complex plane rather than per component.
+ * Slices of values are supported through approx's blanket slice implementations.
+ */
+
+use approx::{AbsDiffEq, RelativeEq};
+
+use crate::trace::Trace;
+use crate::Value;
+
+/* #### Implementations #### */
+
+impl AbsDiffEq for Value {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> f64 {
+        return f64::EPSILON;
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        if self == other {
+            return true;
+        }
+        return (*self - *other).abs() <= epsilon;
+    }
+}
+
+impl RelativeEq for Value {
+    fn default_max_relative() -> f64 {
+        return f64::EPSILON;
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+        if self == other {
+            return true;
+        }
+        let difference = (*self - *other).abs();
+        if difference <= epsilon {
+            return true;
+        }
+        return difference <= self.abs().max(other.abs()) * max_relative;
+    }
+}
+
+impl<'a> AbsDiffEq for Trace<'a> {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> f64 {
+        return f64::EPSILON;
+    }
+
+    /// Compares the samples and the x axis, the metadata is ignored.
+    fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+        return self.x().abs_diff_eq(other.x(), epsilon) && self.values().abs_diff_eq(other.values(), epsilon);
+    }
+}
+
+impl<'a> RelativeEq for Trace<'a> {
+    fn default_max_relative() -> f64 {
+        return f64::EPSILON;
+    }
+
+    /// Compares the samples and the x axis, the metadata is ignored.
+    fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+        return self.x().relative_eq(other.x(), epsilon, max_relative)
+            && self.values().relative_eq(other.values(), epsilon, max_relative);
+    }
+}
@@ -0,0 +1,229 @@
+/*
+ * This file contains the Trace view type, bundling a variable's samples with its metadata
+ */
+
+use crate::marker::Marker;
+use crate::{SteppedSimulation, Unit, Value, VariableClass};
+
+/* #### Structs #### */
+
+/// A borrowed view of one variable for one step, with the matching x axis and metadata.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trace<'a> {
+    name: &'a str,
+    x: &'a [Value],
+    values: &'a [Value],
+    class: VariableClass,
+    unit: Unit,
+    x_unit: Unit,
+    step: u16,
+    parameters: &'a [(String, f64)],
+    // Every marker of the simulation, see `markers`
+    markers: &'a [Marker],
+}
+
+/// An owned trace, as returned by the transformations of a `Trace`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceBuf {
+    name: String,
+    x: Vec<Value>,
+    values: Vec<Value>,
+    class: VariableClass,
+    unit: Unit,
+    x_unit: Unit,
+    step: u16,
+    parameters: Vec<(String, f64)>,
+}
+
+/* #### Implementations #### */
+
+impl<'a> Trace<'a> {
+    /// Returns the name of the variable.
+    pub fn name(&self) -> &'a str {
+        return self.name;
+    }
+
+    /// Returns the abscissa (time, frequency, ...) of the samples. Times are never negative: the
+    /// sign LTSpice uses to flag compressed points is dropped when loading.
+    pub fn x(&self) -> &'a [Value] {
+        return self.x;
+    }
+
+    /// Returns the samples of the variable.
+    pub fn values(&self) -> &'a [Value] {
+        return self.values;
+    }
+
+    /// Returns the class of the variable.
+    pub fn class(&self) -> VariableClass {
+        return self.class;
+    }
+
+    /// Returns the unit of the samples.
+    pub fn unit(&self) -> Unit {
+        return self.unit;
+    }
+
+    /// Returns the unit of the abscissa.
+    pub fn x_unit(&self) -> Unit {
+        return self.x_unit;
+    }
+
+    /// Returns the index of the step the samples belong to.
+    pub fn step(&self) -> u16 {
+        return self.step;
+    }
+
+    /// Returns the '.step' parameters of the step, empty if unknown.
+    pub fn parameters(&self) -> &'a [(String, f64)] {
+        return self.parameters;
+    }
+
+    /// Returns the markers attached to the variable and step, see `SteppedSimulation::add_marker`.
+    /// Traces returned by transformations have none.
+    pub fn markers(&self) -> impl Iterator<Item = &'a Marker> + '_ {
+        return self
+            .markers
+            .iter()
+            .filter(|marker| marker.step == self.step && marker.variable == self.name);
+    }
+
+    /// Returns the value of a '.step' parameter (case insensitive), if known.
+    pub fn parameter(&self, name: &str) -> Option<f64> {
+        return self
+            .parameters
+            .iter()
+            .find(|(parameter, _)| parameter.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value);
+    }
+
+    /// Returns the number of samples.
+    pub fn len(&self) -> usize {
+        return self.values.len().min(self.x.len());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// Returns an iterator over the (x, y) real parts of the samples.
+    pub fn points(&self) -> impl Iterator<Item = (f64, f64)> + 'a {
+        return self.x.iter().zip(self.values.iter()).map(|(x, y)| (x.real(), y.real()));
+    }
+
+    /// Copies the view into an owned trace.
+    pub fn to_buf(&self) -> TraceBuf {
+        return self.with_data(self.name.to_string(), self.x.to_vec(), self.values.to_vec());
+    }
+
+    /// Returns an owned trace with the metadata of this one and the specified samples.
+    pub(crate) fn with_data(&self, name: String, x: Vec<Value>, values: Vec<Value>) -> TraceBuf {
+        return TraceBuf {
+            name,
+            x,
+            values,
+            class: self.class,
+            unit: self.unit,
+            x_unit: self.x_unit,
+            step: self.step,
+            parameters: self.parameters.to_vec(),
+        };
+    }
+}
+
+impl TraceBuf {
+    /// Returns a borrowed view of the trace, giving access to its accessors and transformations.
+    pub fn as_trace(&self) -> Trace<'_> {
+        return Trace {
+            name: &self.name,
+            x: &self.x,
+            values: &self.values,
+            class: self.class,
+            unit: self.unit,
+            x_unit: self.x_unit,
+            step: self.step,
+            parameters: &self.parameters,
+            markers: &[],
+        };
+    }
+
+    /// Returns the name of the trace.
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns the abscissa of the samples.
+    pub fn x(&self) -> &[Value] {
+        return &self.x;
+    }
+
+    /// Returns the samples of the trace.
+    pub fn values(&self) -> &[Value] {
+        return &self.values;
+    }
+
+    // Replaces the unit of the samples, for transformations changing it.
+    pub(crate) fn with_unit(mut self, unit: Unit) -> TraceBuf {
+        self.unit = unit;
+        return self;
+    }
+
+    // Replaces the name of the trace.
+    pub(crate) fn renamed(mut self, name: &str) -> TraceBuf {
+        self.name = name.to_string();
+        return self;
+    }
+
+    // Sets the value of a '.step' parameter, for traces estimated at another parameter value.
+    pub(crate) fn with_parameter(mut self, name: &str, value: f64) -> TraceBuf {
+        match self.parameters.iter_mut().find(|(parameter, _)| parameter.eq_ignore_ascii_case(name)) {
+            Some(parameter) => parameter.1 = value,
+            None => self.parameters.push((name.to_string(), value)),
+        }
+        return self;
+    }
+
+    /// Splits the trace into its abscissa and samples, e.g. to store it with `insert_derived`.
+    pub fn into_data(self) -> (Vec<Value>, Vec<Value>) {
+        return (self.x, self.values);
+    }
+}
+
+impl<'a> From<Trace<'a>> for TraceBuf {
+    fn from(trace: Trace<'a>) -> Self {
+        return trace.to_buf();
+    }
+}
+
+impl SteppedSimulation {
+    /// Returns a view of the specified variable and step, None if either does not exist.
+    /// The x axis itself can be retrieved with the name "x".
+    pub fn trace(&self, name: &str, step: u16) -> Option<Trace<'_>> {
+        let x = self.get("x", Some(step))?;
+        let values = self.get(name, Some(step))?;
+        let name = self.resolve(name);
+        let (name, class) = match self.get_variables().iter().find(|variable| variable.name() == name) {
+            Some(variable) => (variable.name(), *variable.class()),
+            None => ("x", VariableClass::Unknown),
+        };
+
+        return Some(Trace {
+            name,
+            x,
+            values,
+            class,
+            unit: self.get_unit(name),
+            x_unit: self.get_unit("x"),
+            step,
+            parameters: self.get_step_parameters(step).unwrap_or(&[]),
+            markers: &self.markers,
+        });
+    }
+
+    /// Returns a view of the specified variable for every step.
+    pub fn traces(&self, name: &str) -> Vec<Trace<'_>> {
+        return (0..self.get_step_count() as u16)
+            .filter_map(|step| self.trace(name, step))
+            .collect();
+    }
+}
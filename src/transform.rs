@@ -0,0 +1,262 @@
+/*
+ * This file contains the trace transformations used to align and pre-process runs before comparing them
+ */
+
+use std::error::Error;
+
+use crate::trace::{Trace, TraceBuf};
+use crate::{SteppedSimulation, Value};
+
+/* #### Enums #### */
+
+/// How `Trace::repair` handles NaN and infinite samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repair {
+    /// Linearly interpolate between the surrounding finite samples, holding the nearest one at the ends.
+    Interpolate,
+    /// Remove the points.
+    Drop,
+}
+
+/// Which sample `Trace::dedup_x` keeps for points sharing the same abscissa, as LTSpice writes
+/// around breakpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dedup {
+    First,
+    Last,
+    Average,
+}
+
+/* #### Functions #### */
+
+fn is_finite(value: &Value) -> bool {
+    return value.real().is_finite() && value.imaginary().is_finite();
+}
+
+/// Returns the abscissa at which the samples first rise through the threshold, linearly
+/// interpolated between the surrounding points. Only the real parts are considered.
+pub fn rising_crossing(x: &[Value], y: &[Value], threshold: f64) -> Option<f64> {
+    for index in 1..x.len().min(y.len()) {
+        let (y0, y1) = (y[index - 1].real(), y[index].real());
+        if y0 < threshold && y1 >= threshold {
+            let (x0, x1) = (x[index - 1].real(), x[index].real());
+            return Some(x0 + (threshold - y0) / (y1 - y0) * (x1 - x0));
+        }
+    }
+    return None;
+}
+
+/// Returns every crossing of the threshold as (abscissa, rising), linearly interpolated
+/// between the surrounding points. Only the real parts are considered.
+pub fn crossings(x: &[Value], y: &[Value], threshold: f64) -> Vec<(f64, bool)> {
+    let mut crossings = Vec::new();
+    for index in 1..x.len().min(y.len()) {
+        let (y0, y1) = (y[index - 1].real(), y[index].real());
+        if (y0 > threshold) != (y1 > threshold) {
+            let (x0, x1) = (x[index - 1].real(), x[index].real());
+            crossings.push((x0 + (threshold - y0) / (y1 - y0) * (x1 - x0), y1 > threshold));
+        }
+    }
+    return crossings;
+}
+
+/// Linearly interpolates the samples at the abscissa `at`, None if it lies outside the trace.
+/// The abscissa must be increasing.
+pub fn interpolate(x: &[Value], y: &[Value], at: f64) -> Option<Value> {
+    let length = x.len().min(y.len());
+    let index = x[..length].partition_point(|x| x.real() < at);
+    if index == length {
+        return None;
+    }
+    if x[index].real() == at {
+        return Some(y[index]);
+    }
+    if index == 0 {
+        return None;
+    }
+    let (x0, x1) = (x[index - 1].real(), x[index].real());
+    let ratio = (at - x0) / (x1 - x0);
+    return Some(y[index - 1] + (y[index] - y[index - 1]) * ratio);
+}
+
+// Average of the samples over the abscissa (trapezoidal rule), so that non-uniform time steps
+// are weighted correctly. Falls back to the arithmetic mean if the trace has no span.
+fn mean(x: &[Value], y: &[Value]) -> Value {
+    let length = x.len().min(y.len());
+    if length == 0 {
+        return Value::default();
+    }
+    let span = x[length - 1].real() - x[0].real();
+    if length == 1 || span == 0.0 {
+        return y[..length].iter().fold(Value::default(), |sum, value| sum + *value) / length as f64;
+    }
+    let mut area = Value::default();
+    for index in 1..length {
+        area = area + (y[index - 1] + y[index]) * (0.5 * (x[index].real() - x[index - 1].real()));
+    }
+    return area / span;
+}
+
+// Collapses the runs of points sharing the same abscissa into one point.
+fn dedup(x: &[Value], y: &[Value], strategy: Dedup) -> (Vec<Value>, Vec<Value>) {
+    let length = x.len().min(y.len());
+    let (mut xs, mut ys) = (Vec::with_capacity(length), Vec::with_capacity(length));
+    let mut start = 0;
+    while start < length {
+        let mut end = start + 1;
+        while end < length && x[end].real() == x[start].real() {
+            end += 1;
+        }
+        let value = match strategy {
+            Dedup::First => y[start],
+            Dedup::Last => y[end - 1],
+            Dedup::Average => y[start..end].iter().fold(Value::default(), |sum, value| sum + *value) / (end - start) as f64,
+        };
+        xs.push(x[start]);
+        ys.push(value);
+        start = end;
+    }
+    return (xs, ys);
+}
+
+/* #### Implementations #### */
+
+impl<'a> Trace<'a> {
+    /// Returns a copy of the trace with a single point per abscissa, e.g. to remove the duplicate
+    /// time points LTSpice writes around breakpoints.
+    pub fn dedup_x(&self, strategy: Dedup) -> TraceBuf {
+        let (x, values) = dedup(self.x(), self.values(), strategy);
+        return self.with_data(self.name().to_string(), x, values);
+    }
+
+    /// Returns the trace linearly interpolated at the abscissas `x`, skipping the ones outside
+    /// the trace. Duplicate abscissas are collapsed first if a strategy is given.
+    pub fn resample(&self, x: &[f64], dedup_x: Option<Dedup>) -> TraceBuf {
+        let (source_x, source_values) = match dedup_x {
+            Some(strategy) => dedup(self.x(), self.values(), strategy),
+            None => (self.x().to_vec(), self.values().to_vec()),
+        };
+        let (x, values) = x
+            .iter()
+            .filter_map(|at| Some((Value::from(*at), interpolate(&source_x, &source_values, *at)?)))
+            .unzip();
+        return self.with_data(self.name().to_string(), x, values);
+    }
+
+    /// Returns a copy of the trace with `dt` added to every abscissa.
+    pub fn shift_x(&self, dt: f64) -> TraceBuf {
+        let x = self.x().iter().map(|x| *x + Value::from(dt)).collect();
+        return self.with_data(self.name().to_string(), x, self.values().to_vec());
+    }
+
+    /// Returns a copy of the trace shifted so that the first rising crossing of the threshold
+    /// happens at x = 0, or None if the trace never crosses it.
+    pub fn normalize_to_trigger(&self, threshold: f64) -> Option<TraceBuf> {
+        let trigger = rising_crossing(self.x(), self.values(), threshold)?;
+        return Some(self.shift_x(-trigger));
+    }
+
+    /// Returns a copy of the trace with its average value removed.
+    pub fn remove_dc(&self) -> TraceBuf {
+        let offset = mean(self.x(), self.values());
+        let values = self.values().iter().map(|value| *value - offset).collect();
+        return self.with_data(self.name().to_string(), self.x().to_vec(), values);
+    }
+
+    /// Returns a copy of the trace scaled so that its largest magnitude is 1.
+    /// An all-zero trace is returned unchanged.
+    pub fn normalize_peak(&self) -> TraceBuf {
+        let peak = self.values().iter().fold(0.0f64, |peak, value| peak.max(value.abs()));
+        let values = match peak > 0.0 {
+            true => self.values().iter().map(|value| *value / peak).collect(),
+            false => self.values().to_vec(),
+        };
+        return self.with_data(self.name().to_string(), self.x().to_vec(), values);
+    }
+
+    /// Returns the difference between this trace and another one, on the abscissa of this trace.
+    /// The other trace is linearly interpolated, so runs with different time steps can be compared.
+    /// Returns None if the other trace does not cover the abscissa of this one.
+    pub fn subtract(&self, other: &Trace<'_>) -> Option<TraceBuf> {
+        let values = self
+            .x()
+            .iter()
+            .zip(self.values().iter())
+            .map(|(x, value)| Some(*value - interpolate(other.x(), other.values(), x.real())?))
+            .collect::<Option<Vec<Value>>>()?;
+        let name = format!("{}-{}", self.name(), other.name());
+        return Some(self.with_data(name, self.x()[..values.len()].to_vec(), values));
+    }
+
+    /// Returns whether any sample is NaN or infinite, e.g. after a diverged simulation.
+    pub fn has_non_finite(&self) -> bool {
+        return !self.values().iter().all(is_finite);
+    }
+
+    /// Returns the indices of the NaN or infinite samples.
+    pub fn non_finite_indices(&self) -> Vec<usize> {
+        return (0..self.values().len()).filter(|index| !is_finite(&self.values()[*index])).collect();
+    }
+
+    /// Returns a copy of the trace with its NaN and infinite samples repaired.
+    /// A trace without any finite sample is returned unchanged.
+    pub fn repair(&self, strategy: Repair) -> TraceBuf {
+        let (x, values) = (&self.x()[..self.len()], &self.values()[..self.len()]);
+        let finite: Vec<usize> = (0..values.len()).filter(|index| is_finite(&values[*index])).collect();
+        if finite.is_empty() {
+            return self.to_buf();
+        }
+
+        let (x, values) = match strategy {
+            Repair::Drop => (
+                finite.iter().map(|index| x[*index]).collect(),
+                finite.iter().map(|index| values[*index]).collect(),
+            ),
+            Repair::Interpolate => {
+                let mut repaired = values.to_vec();
+                for index in 0..repaired.len() {
+                    if is_finite(&repaired[index]) {
+                        continue;
+                    }
+                    let next = finite.partition_point(|finite| *finite < index);
+                    let before = next.checked_sub(1).map(|previous| finite[previous]);
+                    repaired[index] = match (before, finite.get(next).copied()) {
+                        (Some(before), Some(after)) => {
+                            let ratio = (x[index].real() - x[before].real()) / (x[after].real() - x[before].real());
+                            values[before] + (values[after] - values[before]) * ratio
+                        }
+                        (Some(before), None) => values[before],
+                        (None, Some(after)) => values[after],
+                        (None, None) => repaired[index],
+                    };
+                }
+                (x.to_vec(), repaired)
+            }
+        };
+        return self.with_data(self.name().to_string(), x, values);
+    }
+}
+
+impl SteppedSimulation {
+    /// Removes every point before `t` from every step and variable, e.g. a start-up transient.
+    /// Fails if a spilled variable can't be read back, see `LoadOptions::memory_limit`.
+    pub fn trim_before(&mut self, t: f64) -> Result<(), Box<dyn Error>> {
+        self.materialize()?;
+        let Some(x) = self.data.get("x") else {
+            return Ok(());
+        };
+        let starts: Vec<usize> = x
+            .iter()
+            .map(|values| values.iter().position(|value| value.real() >= t).unwrap_or(values.len()))
+            .collect();
+
+        for steps in self.data.values_mut() {
+            for (values, start) in steps.iter_mut().zip(starts.iter()) {
+                values.drain(..(*start).min(values.len()));
+            }
+        }
+        self.stats.points = self.data["x"].iter().map(|values| values.len() as u32).sum();
+        self.refresh_column_stats();
+        return Ok(());
+    }
+}
@@ -0,0 +1,289 @@
+/*
+ * This file contains a minimal terminal waveform viewer
+ */
+
+use std::error::Error;
+use std::io::{stdout, Stdout};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+
+use ratatui::backend::{CrosstermBackend, TestBackend};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Span;
+use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph};
+use ratatui::{Frame, Terminal};
+
+use crate::{downsample, format, SteppedSimulation};
+
+/* #### Constants #### */
+
+const COLORS: [Color; 6] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+];
+
+const HELP: &str = "q: quit | up/down: variable | left/right: pan | +/-: zoom | [/]: cursor | 0-9: toggle step | a: all steps | r: reset";
+
+/* #### Structs #### */
+
+struct Viewer<'a> {
+    simulation: &'a SteppedSimulation,
+    variable: usize,
+    steps: Vec<bool>,
+    span: (f64, f64),
+    window: (f64, f64),
+    cursor: f64,
+}
+
+/* #### Implementations #### */
+
+impl<'a> Viewer<'a> {
+    fn new(simulation: &'a SteppedSimulation) -> Self {
+        let span = simulation.get_x().and_then(downsample::span).unwrap_or((0.0, 1.0));
+
+        return Viewer {
+            simulation,
+            variable: 0,
+            steps: vec![true; simulation.get_step_count()],
+            span,
+            window: span,
+            cursor: (span.0 + span.1) / 2.0,
+        };
+    }
+
+    fn width(&self) -> f64 {
+        return self.window.1 - self.window.0;
+    }
+
+    fn zoom(&mut self, factor: f64) {
+        let center = self.cursor.clamp(self.window.0, self.window.1);
+        let width = (self.width() * factor).min(self.span.1 - self.span.0);
+        let start = (center - (center - self.window.0) * factor).max(self.span.0);
+        self.window = (start, (start + width).min(self.span.1));
+    }
+
+    fn pan(&mut self, fraction: f64) {
+        let width = self.width();
+        let start = (self.window.0 + width * fraction).clamp(self.span.0, self.span.1 - width);
+        self.window = (start, start + width);
+    }
+
+    fn move_cursor(&mut self, fraction: f64) {
+        self.cursor = (self.cursor + self.width() * fraction).clamp(self.window.0, self.window.1);
+    }
+
+    fn toggle_step(&mut self, step: usize) {
+        if let Some(enabled) = self.steps.get_mut(step) {
+            *enabled = !*enabled;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.window = self.span;
+        self.cursor = (self.span.0 + self.span.1) / 2.0;
+        self.steps.iter_mut().for_each(|enabled| *enabled = true);
+    }
+
+    /// Handles a key press, returns false when the viewer should exit.
+    fn handle(&mut self, key: KeyCode) -> bool {
+        let variables = self.simulation.get_variables().len();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up if variables > 0 => self.variable = (self.variable + variables - 1) % variables,
+            KeyCode::Down if variables > 0 => self.variable = (self.variable + 1) % variables,
+            KeyCode::Left => self.pan(-0.1),
+            KeyCode::Right => self.pan(0.1),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom(0.5),
+            KeyCode::Char('-') => self.zoom(2.0),
+            KeyCode::Char('[') => self.move_cursor(-0.02),
+            KeyCode::Char(']') => self.move_cursor(0.02),
+            KeyCode::Char('a') => {
+                let enable = self.steps.iter().any(|enabled| !enabled);
+                self.steps.iter_mut().for_each(|enabled| *enabled = enable);
+            }
+            KeyCode::Char('r') => self.reset(),
+            KeyCode::Char(c) if c.is_ascii_digit() => self.toggle_step(c.to_digit(10).unwrap() as usize),
+            _ => {}
+        }
+        return true;
+    }
+
+    /// Returns the value of the selected variable under the cursor, for the given step.
+    fn cursor_value(&self, name: &str, step: u16) -> Option<f64> {
+        let x = self.simulation.get("x", Some(step))?;
+        let y = self.simulation.get(name, Some(step))?;
+        let index = x.iter().position(|x| x.real() >= self.cursor)?;
+        return y.get(index).map(|y| y.real());
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(3), Constraint::Length(1)])
+            .split(frame.size());
+
+        let name = match self.simulation.get_variables().get(self.variable) {
+            Some(variable) => variable.name().to_string(),
+            None => String::new(),
+        };
+
+        // Downsample each enabled step to roughly two points per terminal cell
+        let buckets = areas[0].width as usize * 2;
+        let mut traces: Vec<(String, Vec<(f64, f64)>)> = Vec::new();
+        for (step, enabled) in self.steps.iter().enumerate() {
+            if !enabled {
+                continue;
+            }
+            let x = self.simulation.get("x", Some(step as u16));
+            let y = self.simulation.get(&name, Some(step as u16));
+            if let (Some(x), Some(y)) = (x, y) {
+                traces.push((
+                    format!("step {}", step),
+                    downsample::min_max(x, y, Some(self.window), buckets),
+                ));
+            }
+        }
+
+        let (mut low, mut high) = (f64::MAX, f64::MIN);
+        for (_, points) in traces.iter() {
+            for (_, y) in points.iter() {
+                low = low.min(*y);
+                high = high.max(*y);
+            }
+        }
+        if low > high {
+            (low, high) = (0.0, 1.0);
+        }
+        if low == high {
+            (low, high) = (low - 1.0, high + 1.0);
+        }
+        let cursor_line = vec![(self.cursor, low), (self.cursor, high)];
+
+        let mut datasets: Vec<Dataset> = traces
+            .iter()
+            .enumerate()
+            .map(|(index, (label, points))| {
+                Dataset::default()
+                    .name(label.clone())
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(COLORS[index % COLORS.len()]))
+                    .data(points)
+            })
+            .collect();
+        datasets.push(
+            Dataset::default()
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::White))
+                .data(&cursor_line),
+        );
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::default()
+                    .title(self.simulation.get_unit(&name).label(&name))
+                    .borders(Borders::ALL),
+            )
+            .x_axis(
+                Axis::default()
+                    .title(self.simulation.get_unit("x").label("x"))
+                    .bounds([self.window.0, self.window.1])
+                    .labels(vec![
+                        Span::raw(format::engineering(self.window.0, self.simulation.get_unit("x"))),
+                        Span::raw(format::engineering(self.window.1, self.simulation.get_unit("x"))),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([low, high])
+                    .labels(vec![
+                        Span::raw(format::engineering(low, self.simulation.get_unit(&name))),
+                        Span::raw(format::engineering(high, self.simulation.get_unit(&name))),
+                    ]),
+            );
+        frame.render_widget(chart, areas[0]);
+
+        // Cursor Readout
+        let unit = self.simulation.get_unit(&name);
+        let mut readout = format!("x = {}", format::engineering_digits(self.cursor, self.simulation.get_unit("x"), 6));
+        for (step, enabled) in self.steps.iter().enumerate() {
+            if !enabled {
+                continue;
+            }
+            if let Some(value) = self.cursor_value(&name, step as u16) {
+                readout.push_str(&format!(" | step {}: {}", step, format::engineering_digits(value, unit, 6)));
+            }
+        }
+        let readout = Paragraph::new(readout).block(Block::default().title("Cursor").borders(Borders::ALL));
+        frame.render_widget(readout, areas[1]);
+        frame.render_widget(Paragraph::new(HELP), areas[2]);
+    }
+}
+
+/* #### Functions #### */
+
+/// Opens an interactive viewer for the specified simulation, blocking until the user quits.
+pub fn run(simulation: &SteppedSimulation) -> Result<(), Box<dyn Error>> {
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = event_loop(&mut terminal, Viewer::new(simulation));
+
+    // Always restore the terminal, even if the viewer failed
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    return result;
+}
+
+/// Renders the viewer off-screen after replaying the key presses, returning the screen as text
+/// (one line per row). Useful for snapshots of the viewer without a terminal.
+pub fn render(simulation: &SteppedSimulation, keys: &[KeyCode], width: u16, height: u16) -> Result<String, Box<dyn Error>> {
+    let mut viewer = Viewer::new(simulation);
+    for key in keys.iter() {
+        if !viewer.handle(*key) {
+            break;
+        }
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| viewer.draw(frame))?;
+
+    let buffer = terminal.backend().buffer();
+    let mut screen = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            screen.push_str(buffer.get(x, y).symbol());
+        }
+        screen.push('\n');
+    }
+    return Ok(screen);
+}
+
+fn event_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut viewer: Viewer) -> Result<(), Box<dyn Error>> {
+    loop {
+        terminal.draw(|frame| viewer.draw(frame))?;
+
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !viewer.handle(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * This file contains the entry point for raw files from untrusted sources
+ *
+ * The input is checked against size limits before it is parsed, so that a user-uploaded
+ * file cannot make a service allocate unbounded memory. Compressed input is refused, as
+ * its decompressed size cannot be known in advance, and no file is read next to it.
+ * A small header can still declare a huge sweep of tiny points, so the x axis is decoded first
+ * and the step count and decoded size are checked before any variable is decoded.
+ */
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::options::LoadOptions;
+use crate::lru::VALUE_SIZE;
+use crate::{compression, header_fields, header_length, Encoding, SteppedSimulation};
+
+/* #### Constants #### */
+
+const DEFAULT_MAX_BYTES: usize = 256 * 1024 * 1024;
+const DEFAULT_MAX_HEADER_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_VARIABLES: usize = 100_000;
+const DEFAULT_MAX_STEPS: usize = 10_000;
+const DEFAULT_MAX_DECODED_BYTES: usize = 1024 * 1024 * 1024;
+
+/* #### Structs #### */
+
+/// The limits enforced by `SteppedSimulation::parse_untrusted_with_limits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limits {
+    max_bytes: usize,
+    max_header_bytes: usize,
+    max_variables: usize,
+    max_steps: usize,
+    max_decoded_bytes: usize,
+}
+
+/* #### Implementations #### */
+
+impl Default for Limits {
+    fn default() -> Self {
+        return Limits {
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_header_bytes: DEFAULT_MAX_HEADER_BYTES,
+            max_variables: DEFAULT_MAX_VARIABLES,
+            max_steps: DEFAULT_MAX_STEPS,
+            max_decoded_bytes: DEFAULT_MAX_DECODED_BYTES,
+        };
+    }
+}
+
+impl Limits {
+    /// Returns the default limits: 256 MiB of input, 1 MiB of header, 100 000 variables,
+    /// 10 000 steps and 1 GiB of decoded samples.
+    pub fn new() -> Self {
+        return Limits::default();
+    }
+
+    /// Sets the maximum size of the whole input, in bytes.
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        return self;
+    }
+
+    /// Sets the maximum size of the text header, in bytes.
+    pub fn max_header_bytes(mut self, bytes: usize) -> Self {
+        self.max_header_bytes = bytes;
+        return self;
+    }
+
+    /// Sets the maximum number of variables, excluding the x axis.
+    pub fn max_variables(mut self, variables: usize) -> Self {
+        self.max_variables = variables;
+        return self;
+    }
+
+    /// Sets the maximum number of steps.
+    pub fn max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = steps;
+        return self;
+    }
+
+    /// Sets the maximum memory of the decoded samples of the x axis and every variable, in bytes.
+    pub fn max_decoded_bytes(mut self, bytes: usize) -> Self {
+        self.max_decoded_bytes = bytes;
+        return self;
+    }
+
+    // Checks the steps and decoded size of a simulation whose x axis only is decoded.
+    fn check_decoded(&self, simulation: &SteppedSimulation) -> Result<(), Box<dyn Error>> {
+        let steps = simulation.column("x").map_or(0, |x| x.len());
+        if steps > self.max_steps {
+            Err(format!("The input has more than {} steps.", self.max_steps))?;
+        }
+        let points: usize = simulation.column("x").map_or(0, |x| x.iter().map(|values| values.len()).sum());
+        let decoded = (points as u128) * (simulation.variables.len() as u128 + 1) * VALUE_SIZE as u128;
+        if decoded > self.max_decoded_bytes as u128 {
+            Err(format!("The input decodes to more than {} bytes.", self.max_decoded_bytes))?;
+        }
+        return Ok(());
+    }
+
+    // Checks the input before it is parsed.
+    fn check(&self, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+        if bytes.len() > self.max_bytes {
+            Err(format!("The input is larger than {} bytes.", self.max_bytes))?;
+        }
+        if compression::is_compressed(bytes) {
+            Err("Compressed input is not accepted from untrusted sources.")?;
+        }
+
+        // The header must end within the limit, in either supported encoding
+        let head = &bytes[..bytes.len().min(self.max_header_bytes)];
+        let header = match header_length(head, &Encoding::UTF8) {
+            Some(length) => String::from_utf8_lossy(&head[..length]).to_string(),
+            None => match header_length(head, &Encoding::UTF16) {
+                Some(length) => {
+                    let units: Vec<u16> = head[..length]
+                        .chunks_exact(2)
+                        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                        .collect();
+                    String::from_utf16_lossy(&units)
+                }
+                None => Err(format!("No binary header found in the first {} bytes.", self.max_header_bytes))?,
+            },
+        };
+
+        // One line per variable starting with its index, in the "Variables" field
+        let variables = header_fields(&header).get("Variables").map_or(0, |list| {
+            list.lines()
+                .filter(|line| line.split_whitespace().next().is_some_and(|index| index.parse::<u32>().is_ok()))
+                .count()
+        });
+        if variables > self.max_variables + 1 {
+            Err(format!("The header lists more than {} variables.", self.max_variables))?;
+        }
+        return Ok(());
+    }
+}
+
+impl SteppedSimulation {
+    /// Parses a raw file received from an untrusted source with the default `Limits`.
+    /// Malformed input fails with an error and never panics.
+    pub fn parse_untrusted(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        return SteppedSimulation::parse_untrusted_with_limits(bytes, &Limits::default(), LoadOptions::default());
+    }
+
+    /// Same as `parse_untrusted`, with the specified limits and load options.
+    pub fn parse_untrusted_with_limits(
+        bytes: &[u8],
+        limits: &Limits,
+        options: LoadOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        limits.check(bytes)?;
+
+        // The x axis is bounded by the input size, the variables are only decoded once it is checked
+        let mut simulation = SteppedSimulation::new(PathBuf::new());
+        simulation.set_load_options(options.clone().x_only(true));
+        simulation.parse_bytes(bytes, None)?;
+        limits.check_decoded(&simulation)?;
+        if !options.is_x_only() {
+            simulation.set_load_options(options);
+            simulation.parse_bytes(bytes, None)?;
+        }
+        return Ok(simulation);
+    }
+}
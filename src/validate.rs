@@ -0,0 +1,116 @@
+/*
+ * This file contains the data quality checks of a loaded simulation
+ */
+
+use crate::branches::branch_ranges;
+use crate::{Mode, SteppedSimulation, Value};
+
+/* #### Structs #### */
+
+/// The result of `SteppedSimulation::validate`, listing every anomaly found in the data.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Steps whose x axis is not monotonic.
+    pub non_monotonic_steps: Vec<u16>,
+    /// Steps of a DC sweep reversing its x axis on purpose (hysteresis loops), see
+    /// `Trace::branches`. These are not anomalies.
+    pub hysteresis_steps: Vec<u16>,
+    /// (variable, step) pairs whose number of points differs from the x axis.
+    pub length_mismatches: Vec<(String, u16)>,
+    /// (variable, count) of the variables containing NaN or infinite samples.
+    pub non_finite: Vec<(String, usize)>,
+    /// Number of points declared in the header.
+    pub declared_points: u32,
+    /// Number of points actually loaded, over every step.
+    pub loaded_points: usize,
+    /// Variables declared in the header without data.
+    pub missing_variables: Vec<String>,
+    /// Whether the x axis contains points flagged by LTSpice's waveform compression
+    /// (negative time values in a transient analysis).
+    pub suspected_compression: bool,
+}
+
+/* #### Functions #### */
+
+// Whether the reversals of a DC sweep trace a hysteresis loop rather than glitches:
+// every branch has several points and the branches retrace the same range.
+fn is_hysteresis(x: &[Value]) -> bool {
+    let branches = branch_ranges(x);
+    let span = |range: &std::ops::Range<usize>| -> f64 {
+        return (x[range.end - 1].real() - x[range.start].real()).abs();
+    };
+    let longest = branches.iter().map(|(range, _)| span(range)).fold(0.0, f64::max);
+    return branches
+        .iter()
+        .all(|(range, _)| range.len() >= 3 && span(range) >= 0.5 * longest);
+}
+
+/* #### Implementations #### */
+
+impl ValidationReport {
+    /// Returns true if no anomaly was found. Compression alone is not an anomaly.
+    pub fn is_valid(&self) -> bool {
+        return self.non_monotonic_steps.is_empty()
+            && self.length_mismatches.is_empty()
+            && self.non_finite.is_empty()
+            && self.missing_variables.is_empty()
+            && self.declared_points as usize == self.loaded_points;
+    }
+}
+
+impl SteppedSimulation {
+    /// Checks the loaded data for anomalies, so that pipelines can reject bad files before analysis.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport {
+            declared_points: self.stats.points,
+            ..ValidationReport::default()
+        };
+        let steps: &[Vec<Value>] = self.column("x").map_or(&[], |x| x.as_slice());
+        report.loaded_points = steps.iter().map(|x| x.len()).sum();
+
+        for (step, x) in steps.iter().enumerate() {
+            let values: Vec<f64> = x.iter().map(|x| x.real()).collect();
+            let increasing = values.windows(2).all(|pair| pair[0] <= pair[1]);
+            let decreasing = values.windows(2).all(|pair| pair[0] >= pair[1]);
+
+            // Only DC sweeps can run downwards, or up and back down to trace a hysteresis loop
+            let monotonic = match self.mode {
+                Mode::DC | Mode::Other(_) => increasing || decreasing,
+                _ => increasing,
+            };
+            if !monotonic && self.mode == Mode::DC && is_hysteresis(x) {
+                report.hysteresis_steps.push(step as u16);
+            } else if !monotonic {
+                report.non_monotonic_steps.push(step as u16);
+            }
+            if self.mode == Mode::Transient && x.iter().any(|x| x.real() < 0.0) {
+                report.suspected_compression = true;
+            }
+        }
+
+        for variable in self.variables.iter() {
+            let data = match self.column(&variable.name) {
+                Some(data) => data,
+                None => {
+                    report.missing_variables.push(variable.name.clone());
+                    continue;
+                }
+            };
+            for (step, x) in steps.iter().enumerate() {
+                if data.get(step).map(|values| values.len()) != Some(x.len()) {
+                    report.length_mismatches.push((variable.name.clone(), step as u16));
+                }
+            }
+            let count = data
+                .iter()
+                .flatten()
+                .filter(|value| !value.real().is_finite() || !value.imaginary().is_finite())
+                .count();
+            if count > 0 {
+                report.non_finite.push((variable.name.clone(), count));
+            }
+        }
+
+        return report;
+    }
+}
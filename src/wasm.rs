@@ -0,0 +1,59 @@
+/*
+ * This file contains the WebAssembly bindings, used by browser-based viewers
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::{downsample, json, SteppedSimulation};
+
+/* #### Structs #### */
+
+/// A parsed simulation, exposed to JavaScript.
+#[wasm_bindgen]
+pub struct Simulation {
+    inner: SteppedSimulation,
+}
+
+/* #### Implementations #### */
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Parses the contents of a '.raw' file, e.g. read through a `FileReader`.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Simulation, JsError> {
+        return match SteppedSimulation::from_bytes(bytes) {
+            Ok(inner) => Ok(Simulation { inner }),
+            Err(error) => Err(JsError::new(&error.to_string())),
+        };
+    }
+
+    /// Returns the names of the loaded variables.
+    pub fn variables(&self) -> Vec<String> {
+        return self
+            .inner
+            .get_variables()
+            .iter()
+            .map(|variable| variable.name().to_string())
+            .collect();
+    }
+
+    /// Returns the number of loaded steps.
+    pub fn steps(&self) -> usize {
+        return self.inner.get_step_count();
+    }
+
+    /// Returns the whole simulation as a JSON string.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        return self.inner.to_json();
+    }
+
+    /// Returns a min/max downsampled trace as a JSON array of `[x, y]` pairs.
+    /// The range is ignored when `start >= end`.
+    pub fn downsample(&self, name: &str, step: u16, start: f64, end: f64, buckets: usize) -> Option<String> {
+        let x = self.inner.get("x", Some(step))?;
+        let y = self.inner.get(name, Some(step))?;
+        let range = if start < end { Some((start, end)) } else { None };
+        return Some(json::points(&downsample::min_max(x, y, range, buckets)));
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * This file contains the statistics of a trace over a moving time window
+ *
+ * The window trails each point: the value at x covers the samples in [x - window, x].
+ * Mean and RMS are weighted by the time step (trapezoidal rule), so that the variable
+ * time steps of a transient analysis do not bias them.
+ */
+
+use std::collections::VecDeque;
+
+use crate::trace::{Trace, TraceBuf};
+use crate::Value;
+
+/* #### Enums #### */
+
+/// The statistic computed by `Trace::windowed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statistic {
+    Mean,
+    Rms,
+    Min,
+    Max,
+}
+
+/* #### Functions #### */
+
+// Cumulative trapezoidal integral of the samples.
+fn cumulative(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let mut integral = Vec::with_capacity(y.len());
+    let mut area = 0.0;
+    for index in 0..y.len() {
+        if index > 0 {
+            area += (y[index - 1] + y[index]) * 0.5 * (x[index] - x[index - 1]);
+        }
+        integral.push(area);
+    }
+    return integral;
+}
+
+// Average over the samples start..=end, from the cumulative integral.
+fn average(x: &[f64], y: &[f64], integral: &[f64], start: usize, end: usize) -> f64 {
+    let span = x[end] - x[start];
+    if span <= 0.0 {
+        return y[end];
+    }
+    return (integral[end] - integral[start]) / span;
+}
+
+// Rolling minimum (or maximum, with `greater`) with a monotonic queue of indices.
+fn extreme(x: &[f64], y: &[f64], window: f64, greater: bool) -> Vec<f64> {
+    let better = |a: f64, b: f64| if greater { a >= b } else { a <= b };
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    let mut result = Vec::with_capacity(y.len());
+    for index in 0..y.len() {
+        while queue.back().is_some_and(|back| better(y[index], y[*back])) {
+            queue.pop_back();
+        }
+        queue.push_back(index);
+        while queue.front().is_some_and(|front| x[*front] < x[index] - window) {
+            queue.pop_front();
+        }
+        result.push(y[*queue.front().unwrap()]);
+    }
+    return result;
+}
+
+/* #### Implementations #### */
+
+impl<'a> Trace<'a> {
+    /// Returns the statistic of the samples over a window trailing each point, in units of the
+    /// x axis, e.g. the RMS current over the last millisecond. Only the real parts are considered.
+    /// Until a full window is available, the statistic covers the samples since the start.
+    pub fn windowed(&self, statistic: Statistic, window: f64) -> TraceBuf {
+        let length = self.len();
+        let x: Vec<f64> = self.x()[..length].iter().map(|x| x.real()).collect();
+        let y: Vec<f64> = self.values()[..length].iter().map(|value| value.real()).collect();
+
+        let values: Vec<f64> = match statistic {
+            Statistic::Min => extreme(&x, &y, window, false),
+            Statistic::Max => extreme(&x, &y, window, true),
+            Statistic::Mean | Statistic::Rms => {
+                let samples: Vec<f64> = match statistic {
+                    Statistic::Rms => y.iter().map(|y| y * y).collect(),
+                    _ => y.clone(),
+                };
+                let integral = cumulative(&x, &samples);
+
+                let mut start = 0;
+                let mut values = Vec::with_capacity(length);
+                for end in 0..length {
+                    while x[start] < x[end] - window {
+                        start += 1;
+                    }
+                    let value = average(&x, &samples, &integral, start, end);
+                    values.push(match statistic {
+                        Statistic::Rms => value.max(0.0).sqrt(),
+                        _ => value,
+                    });
+                }
+                values
+            }
+        };
+
+        let function = match statistic {
+            Statistic::Mean => "mean",
+            Statistic::Rms => "rms",
+            Statistic::Min => "min",
+            Statistic::Max => "max",
+        };
+        let name = format!("{}({})", function, self.name());
+        let values = values.into_iter().map(Value::from).collect();
+        return self.with_data(name, self.x()[..length].to_vec(), values);
+    }
+}
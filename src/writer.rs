@@ -0,0 +1,136 @@
+/*
+ * This file contains the LTSpice binary '.raw' writer
+ *
+ * Files are written in the default LTSpice layout: a UTF8 header followed by the
+ * binary block, with a float64 abscissa and float32 values for real analyses (float64 if the
+ * source file stored them in double precision), or complex128 for everything in AC and FFT analyses.
+ */
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::{DataType, Mode, SteppedSimulation, VariableClass};
+
+/* #### Functions #### */
+
+pub(crate) fn plotname(mode: &Mode) -> &str {
+    return match mode {
+        Mode::Transient => "Transient Analysis",
+        Mode::AC => "AC Analysis",
+        Mode::DC => "DC transfer characteristic",
+        Mode::Noise => "Noise Spectral Density",
+        Mode::OperatingPoint => "Operating Point",
+        Mode::FFT => "FFT",
+        Mode::Other(plotname) => plotname,
+    };
+}
+
+pub(crate) fn x_name(mode: &Mode) -> &'static str {
+    return match mode {
+        Mode::Transient => "time",
+        Mode::AC | Mode::FFT | Mode::Noise => "frequency",
+        Mode::DC | Mode::OperatingPoint | Mode::Other(_) => "sweep",
+    };
+}
+
+fn type_name(class: &VariableClass) -> &'static str {
+    return match class {
+        VariableClass::Voltage => "voltage",
+        VariableClass::Current => "device_current",
+        VariableClass::Frequency => "frequency",
+        VariableClass::Unknown => "voltage",
+    };
+}
+
+/* #### Implementations #### */
+
+impl SteppedSimulation {
+    /// Returns the simulation encoded as an LTSpice binary '.raw' file.
+    pub fn to_raw_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let complex = self.mode == Mode::AC || self.mode == Mode::FFT;
+        let steps = self.get_step_count();
+
+        // Collect and validate the columns of every step
+        let mut columns = Vec::with_capacity(steps);
+        let mut points = 0;
+        for step in 0..steps as u16 {
+            let step_columns = self.get_columns(step).ok_or("Missing step data.")?;
+            let length = step_columns[0].1.len();
+            if step_columns.iter().any(|(_, values)| values.len() != length) {
+                Err(format!("Step {} has columns of different lengths.", step))?;
+            }
+            points += length;
+            columns.push(step_columns);
+        }
+
+        // Header
+        let mut flags = vec![if complex { "complex" } else { "real" }, "forward"];
+        if steps > 1 {
+            flags.push("stepped");
+        }
+        // Double precision samples are kept as such, anything else is written as LTSpice does
+        let double = !complex && self.variables.iter().any(|variable| variable.data_type == Some(DataType::Float64));
+        if double {
+            flags.push("double");
+        }
+        let mut header = String::new();
+        writeln!(header, "Title: * {}", self.path.to_string_lossy())?;
+        if let Some(date) = &self.date_string {
+            writeln!(header, "Date: {}", date)?;
+        }
+        writeln!(header, "Plotname: {}", plotname(&self.mode))?;
+        writeln!(header, "Flags: {}", flags.join(" "))?;
+        writeln!(header, "No. Variables: {}", self.variables.len() + 1)?;
+        writeln!(header, "No. Points: {}", points)?;
+        // An offset already applied to the x axis must not be applied again when reading
+        let offset = match self.options.applies_offset() {
+            true => 0.0,
+            false => self.offset,
+        };
+        writeln!(header, "Offset:   {:.16e}", offset)?;
+        writeln!(header, "Command: Written by the ltspice crate")?;
+        // Kept out of 'Command', whose leading token names the simulator
+        if self.provenance.size > 0 {
+            writeln!(header, "Provenance: {}", self.provenance.describe())?;
+        }
+        writeln!(header, "Variables:")?;
+        writeln!(header, "\t0\t{}\t{}", x_name(&self.mode), x_name(&self.mode))?;
+        for (index, variable) in self.variables.iter().enumerate() {
+            writeln!(header, "\t{}\t{}\t{}", index + 1, variable.name, type_name(&variable.class))?;
+        }
+        writeln!(header, "Binary:")?;
+
+        // Binary Data
+        let point_size = match (complex, double) {
+            (true, _) => 16 * (self.variables.len() + 1),
+            (false, true) => 8 * (self.variables.len() + 1),
+            (false, false) => 8 + 4 * self.variables.len(),
+        };
+        let mut buffer = header.into_bytes();
+        buffer.reserve(points * point_size);
+        for step_columns in columns.iter() {
+            for point in 0..step_columns[0].1.len() {
+                for (index, (_, values)) in step_columns.iter().enumerate() {
+                    let value = values[point];
+                    if complex {
+                        buffer.extend_from_slice(&value.real.to_le_bytes());
+                        buffer.extend_from_slice(&value.imaginary.to_le_bytes());
+                    } else if index == 0 || double {
+                        buffer.extend_from_slice(&value.real.to_le_bytes());
+                    } else {
+                        buffer.extend_from_slice(&(value.real as f32).to_le_bytes());
+                    }
+                }
+            }
+        }
+
+        return Ok(buffer);
+    }
+
+    /// Writes the simulation to an LTSpice binary '.raw' file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write_raw(&self, path: &std::path::Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.to_raw_bytes()?)?;
+        return Ok(());
+    }
+}
@@ -0,0 +1,152 @@
+/*
+ * This file contains the Excel (.xlsx) exporter
+ */
+
+use std::error::Error;
+use std::path::Path;
+
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use crate::SteppedSimulation;
+
+/* #### Constants #### */
+
+// Excel row limit, including the header row
+const MAX_ROWS: usize = 1_048_576;
+// Excel column limit
+const MAX_COLUMNS: usize = 16_384;
+// Excel sheet name limit, in characters
+const MAX_NAME: usize = 31;
+
+/* #### Enums #### */
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum XlsxLayout {
+    /// One sheet per step, with the x axis followed by every variable.
+    PerStep,
+    /// One sheet per variable, with an (x, value) column pair per step.
+    PerVariable,
+}
+
+/* #### Structs #### */
+
+#[derive(Debug, Clone)]
+pub struct XlsxOptions {
+    pub layout: XlsxLayout,
+    /// Keep one point every `decimation` points, 1 keeps every point.
+    pub decimation: usize,
+}
+
+/* #### Functions #### */
+
+// Excel sheet names are limited to 31 characters, cannot contain []:*?/\ and must be unique
+// (case insensitive), so colliding names get a numeric suffix.
+fn sheet_name(name: &str, used: &mut Vec<String>) -> String {
+    let base: String = name
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .collect();
+    let mut candidate: String = base.chars().take(MAX_NAME).collect();
+    let mut suffix = 2;
+    while used.iter().any(|name| name.to_lowercase() == candidate.to_lowercase()) {
+        let tail = format!(" ({})", suffix);
+        candidate = base.chars().take(MAX_NAME - tail.len()).collect::<String>() + &tail;
+        suffix += 1;
+    }
+    used.push(candidate.clone());
+    return candidate;
+}
+
+// Returns an error if a column of `length` points, decimated and with its header row, does not
+// fit in a sheet.
+fn check_rows(length: usize, decimation: usize) -> Result<(), Box<dyn Error>> {
+    if length.div_ceil(decimation) + 1 > MAX_ROWS {
+        Err("Too many points for an Excel sheet, increase the decimation.")?;
+    }
+    return Ok(());
+}
+
+fn write_header(sheet: &mut Worksheet, column: u16, name: &str, format: &Format) -> Result<(), Box<dyn Error>> {
+    sheet.write_string_with_format(0, column, name, format)?;
+    sheet.set_column_width(column, 14)?;
+    return Ok(());
+}
+
+/* #### Implementations #### */
+
+impl Default for XlsxOptions {
+    fn default() -> Self {
+        return XlsxOptions {
+            layout: XlsxLayout::PerStep,
+            decimation: 1,
+        };
+    }
+}
+
+impl SteppedSimulation {
+    /// Writes the simulation to an Excel workbook, using the real part of the values.
+    pub fn to_xlsx(&self, path: &Path, options: &XlsxOptions) -> Result<(), Box<dyn Error>> {
+        let decimation = options.decimation.max(1);
+        let header = Format::new().set_bold();
+        let mut workbook = Workbook::new();
+
+        match options.layout {
+            XlsxLayout::PerStep => {
+                for step in 0..self.get_step_count() as u16 {
+                    let columns = match self.get_columns(step) {
+                        Some(columns) => columns,
+                        None => continue,
+                    };
+                    let sheet = workbook.add_worksheet();
+                    sheet.set_name(format!("Step {}", step))?;
+                    sheet.set_freeze_panes(1, 0)?;
+                    if columns.len() > MAX_COLUMNS {
+                        Err("Too many variables for an Excel sheet.")?;
+                    }
+                    for (column, (name, values)) in columns.iter().enumerate() {
+                        check_rows(values.len(), decimation)?;
+                        write_header(sheet, column as u16, &self.get_unit(name).label(name), &header)?;
+                        for (row, value) in values.iter().step_by(decimation).enumerate() {
+                            sheet.write_number(row as u32 + 1, column as u16, value.real())?;
+                        }
+                    }
+                }
+            }
+            XlsxLayout::PerVariable => {
+                if self.get_step_count() > MAX_COLUMNS / 2 {
+                    Err(format!("Too many steps for an Excel sheet, at most {} fit.", MAX_COLUMNS / 2))?;
+                }
+                let mut names = Vec::new();
+                for variable in self.get_variables().iter() {
+                    let sheet = workbook.add_worksheet();
+                    sheet.set_name(sheet_name(variable.name(), &mut names))?;
+                    sheet.set_freeze_panes(1, 0)?;
+                    for step in 0..self.get_step_count() as u16 {
+                        let x = self.get("x", Some(step));
+                        let y = self.get(variable.name(), Some(step));
+                        let (x, y) = match (x, y) {
+                            (Some(x), Some(y)) => (x, y),
+                            _ => continue,
+                        };
+                        check_rows(x.len().min(y.len()), decimation)?;
+                        let column = match step.checked_mul(2).filter(|column| *column < u16::MAX) {
+                            Some(column) => column,
+                            None => Err("Too many steps for an Excel sheet.")?,
+                        };
+                        let x_label = self.get_unit("x").label(&format!("x (step {})", step));
+                        let y_label = self.get_unit(variable.name()).label(&format!("{} (step {})", variable.name(), step));
+                        write_header(sheet, column, &x_label, &header)?;
+                        write_header(sheet, column + 1, &y_label, &header)?;
+                        for (row, (x, y)) in x.iter().zip(y.iter()).step_by(decimation).enumerate() {
+                            sheet.write_number(row as u32 + 1, column, x.real())?;
+                            sheet.write_number(row as u32 + 1, column + 1, y.real())?;
+                        }
+                    }
+                }
+            }
+        }
+
+        workbook.save(path)?;
+        return Ok(());
+    }
+}
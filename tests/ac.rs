@@ -0,0 +1,50 @@
+/*
+ * This file contains the tests of the AC phase unwrapping
+ */
+
+use std::f64::consts::PI;
+
+use ltspice::ac::unwrap_phase;
+
+/* #### Functions #### */
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (actual, expected) in actual.iter().zip(expected.iter()) {
+        assert!((actual - expected).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+    }
+}
+
+/* #### Tests #### */
+
+#[test]
+fn wrapped_ramps_are_recovered() {
+    // A phase falling to -6π as written by atan2, wrapped into (-π, π]
+    let ramp: Vec<f64> = (0..=120).map(|index| -6.0 * PI * index as f64 / 120.0).collect();
+    let wrapped: Vec<f64> = ramp.iter().map(|phase| phase.sin().atan2(phase.cos())).collect();
+    assert!(wrapped.iter().all(|phase| phase.abs() <= PI));
+    assert_close(&unwrap_phase(&wrapped, PI), &ramp);
+
+    let rising: Vec<f64> = ramp.iter().map(|phase| -phase).collect();
+    assert_close(&unwrap_phase(&rising.iter().map(|phase| phase.sin().atan2(phase.cos())).collect::<Vec<f64>>(), PI), &rising);
+}
+
+#[test]
+fn jumps_across_pi_are_wraps() {
+    // Just below +π to just above -π is a step of 0.2 rad, not -2π + 0.2
+    assert_close(&unwrap_phase(&[PI - 0.1, -PI + 0.1], PI), &[PI - 0.1, PI + 0.1]);
+    assert_close(&unwrap_phase(&[-PI + 0.1, PI - 0.1], PI), &[-PI + 0.1, -PI - 0.1]);
+    assert_close(&unwrap_phase(&[PI, -PI, PI], PI), &[PI, PI, PI]);
+
+    // A jump of exactly π is ambiguous and kept
+    assert_close(&unwrap_phase(&[0.0, PI], PI), &[0.0, PI]);
+    assert!(unwrap_phase(&[], PI).is_empty());
+}
+
+#[test]
+fn tolerances_below_pi_are_raised_to_pi() {
+    assert_close(&unwrap_phase(&[0.0, 3.0], 1.0), &[0.0, 3.0]);
+    assert_close(&unwrap_phase(&[0.0, 4.0], 1.0), &[0.0, 4.0 - 2.0 * PI]);
+    // A larger tolerance keeps real phase changes steeper than π
+    assert_close(&unwrap_phase(&[0.0, 4.0], 1.5 * PI), &[0.0, 4.0]);
+}
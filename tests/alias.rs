@@ -0,0 +1,46 @@
+/*
+ * This file contains the tests of the variable aliases and renames
+ */
+
+use std::collections::HashMap;
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::ac::{admittance, impedance};
+use ltspice::{Mode, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn rename_map_accepts_any_pairs() {
+    let bytes = generate_raw(&RawSpec::new().points(10).variables(3));
+    let original = SteppedSimulation::from_bytes(&bytes).unwrap();
+
+    let mut simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let renames: HashMap<&str, String> = HashMap::from([("V(n001)", "in".to_string()), ("V(missing)", "unused".to_string())]);
+    simulation.rename_map(&renames).unwrap();
+    // Pairs are applied in order, so a chained rename follows the first one
+    simulation.rename_map([("V(n002)", "tmp"), ("tmp", "out")]).unwrap();
+
+    assert_eq!(simulation.get("in", Some(0)), original.get("V(n001)", Some(0)));
+    assert_eq!(simulation.get("out", Some(0)), original.get("V(n002)", Some(0)));
+    assert!(simulation.get("V(n001)", Some(0)).is_none());
+    assert!(simulation.get("tmp", Some(0)).is_none());
+    assert!(simulation.get("unused", Some(0)).is_none());
+}
+
+#[test]
+fn impedance_inverts_aliased_source_currents() {
+    let bytes = generate_raw(&RawSpec::new().mode(Mode::AC).points(20).variables(2));
+    let mut simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    simulation.rename("V(n002)", "I(V1)").unwrap();
+    simulation.alias("I(V1)", "iin").unwrap();
+
+    let direct = impedance(&simulation, "V(n001)", "I(V1)", 0).unwrap();
+    let aliased = impedance(&simulation, "V(n001)", "iin", 0).unwrap();
+    assert_eq!(aliased.values(), direct.values());
+    let v = simulation.get("V(n001)", None).unwrap();
+    let i = simulation.get("I(V1)", None).unwrap();
+    assert_eq!(direct.values()[3], v[3] / (i[3] * -1.0));
+    let admittances = (admittance(&simulation, "V(n001)", "iin", 0), admittance(&simulation, "V(n001)", "I(V1)", 0));
+    assert_eq!(admittances.0.unwrap().values(), admittances.1.unwrap().values());
+}
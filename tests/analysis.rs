@@ -0,0 +1,142 @@
+/*
+ * This file contains the tests of the analyses across runs and steps
+ */
+
+use ltspice::analysis::{cmrr, corners, psrr, sensitivities, sensitivity, Metric};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Unit, Value};
+
+/* #### Functions #### */
+
+// Loads an AC simulation from 1 Hz to 1 MHz with "V(out)" computed from the frequency.
+fn ac(points: usize, response: impl Fn(f64) -> Value) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().mode(Mode::AC).points(points))).unwrap();
+    let values = simulation.get("x", None).unwrap().iter().map(|f| response(f.real())).collect();
+    simulation.insert_derived("V(out)", vec![values]).unwrap();
+    return simulation;
+}
+
+// Loads a sweep of r over 1, 2, 3 nested in c over 10, 20, with a constant "V(out)" of
+// 3 + 2·r + 0.5·c in every step.
+fn sweep() -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(6).points(10))).unwrap();
+    simulation.parse_log(
+        ".step r=1 c=10\n.step r=2 c=10\n.step r=3 c=10\n.step r=1 c=20\n.step r=2 c=20\n.step r=3 c=20\n",
+    );
+    let steps = (0..6)
+        .map(|step| {
+            let (r, c) = ((step % 3 + 1) as f64, (step / 3 + 1) as f64 * 10.0);
+            return vec![Value::from(3.0 + 2.0 * r + 0.5 * c); 10];
+        })
+        .collect();
+    simulation.insert_derived("V(out)", steps).unwrap();
+    return simulation;
+}
+
+// The last value of "V(out)" in a step.
+fn output(simulation: &SteppedSimulation, step: u16) -> Option<f64> {
+    return simulation.get("V(out)", Some(step))?.last().map(|value| value.real());
+}
+
+/* #### Tests #### */
+
+#[test]
+fn rejection_ratios_compare_two_runs_in_db() {
+    // A flat differential gain of 1000 and a common-mode gain rising as f / 1 kHz, on another
+    // frequency grid: 120 dB - 20 dB/decade, linear in f so the interpolation is exact
+    let dm = ac(61, |_| Value::new(1000.0, 0.0));
+    let cm = ac(25, |f| Value::new(0.0, f / 1e3));
+    let (dm, cm) = (dm.trace("V(out)", 0).unwrap(), cm.trace("V(out)", 0).unwrap());
+
+    for (ratio, name) in [(cmrr(&dm, &cm).unwrap(), "CMRR"), (psrr(&dm, &cm).unwrap(), "PSRR")] {
+        assert_eq!(ratio.name(), name);
+        assert_eq!(ratio.as_trace().unit(), Unit::Decibel);
+        assert_eq!(ratio.x(), dm.x());
+        for (f, value) in ratio.x().iter().zip(ratio.values().iter()) {
+            let expected = 120.0 - 20.0 * f.real().log10();
+            assert!((value.real() - expected).abs() < 1e-9, "{} {}", f.real(), value.real());
+        }
+    }
+}
+
+#[test]
+fn rejection_ratios_cover_the_shared_frequencies_only() {
+    let dm = ac(61, |_| Value::from(1.0));
+    let mut cm = ac(61, |_| Value::from(0.1));
+    cm.trim_before(1e3).unwrap();
+    let ratio = cmrr(&dm.trace("V(out)", 0).unwrap(), &cm.trace("V(out)", 0).unwrap()).unwrap();
+    assert_eq!(ratio.x().first(), cm.get("x", None).unwrap().first());
+    assert!(ratio.values().iter().all(|value| (value.real() - 20.0).abs() < 1e-9));
+
+    cm.trim_before(2e6).unwrap();
+    assert!(cmrr(&dm.trace("V(out)", 0).unwrap(), &cm.trace("V(out)", 0).unwrap()).is_none());
+}
+
+#[test]
+fn sensitivities_are_the_partial_derivatives() {
+    let simulation = sweep();
+    let found = sensitivities(&simulation, |step| output(&simulation, step));
+    assert_eq!(found.len(), 2);
+
+    // The mean output is 3 + 2·2 + 0.5·15 = 14.5
+    let r = sensitivity(&simulation, |step| output(&simulation, step), "R").unwrap();
+    assert_eq!(r.parameter, "r");
+    assert!((r.slope - 2.0).abs() < 1e-9, "{:?}", r);
+    assert!((r.normalized - 2.0 * 2.0 / 14.5).abs() < 1e-9, "{:?}", r);
+    let c = sensitivity(&simulation, |step| output(&simulation, step), "c").unwrap();
+    assert!((c.slope - 0.5).abs() < 1e-9, "{:?}", c);
+    assert!((c.normalized - 0.5 * 15.0 / 14.5).abs() < 1e-9, "{:?}", c);
+
+    // Without the steps at c = 20, c has a single value and is not reported
+    let first = sensitivities(&simulation, |step| output(&simulation, step).filter(|_| step < 3));
+    assert_eq!(first.len(), 1);
+    assert!((first[0].slope - 2.0).abs() < 1e-9);
+    assert!(sensitivities(&simulation, |step| output(&simulation, step).filter(|_| step == 0)).is_empty());
+    assert!(sensitivity(&simulation, |step| output(&simulation, step), "l").is_none());
+}
+
+#[test]
+fn corners_and_pareto_front_of_a_sweep() {
+    // Gain is the output (10 to 19), power is r·c (10 to 60)
+    let simulation = sweep();
+    let power = |step: u16| simulation.get_step_parameters(step).map(|parameters| parameters[0].1 * parameters[1].1);
+    let summary = corners(
+        &simulation,
+        vec![
+            Metric::maximize("gain", |step| output(&simulation, step)),
+            Metric::minimize("power", power),
+            Metric::minimize("unknown", |_| None),
+        ],
+    );
+
+    assert_eq!(summary.metrics.len(), 2);
+    let gain = &summary.metrics[0];
+    assert_eq!(gain.metric, "gain");
+    assert_eq!((gain.best.step, gain.best.value), (5, 19.0));
+    assert_eq!(gain.best.parameters, vec![("r".to_string(), 3.0), ("c".to_string(), 20.0)]);
+    assert_eq!((gain.worst.step, gain.worst.value), (0, 10.0));
+    assert_eq!((gain.typical.step, gain.typical.value), (2, 14.0));
+    let power_corners = &summary.metrics[1];
+    assert_eq!((power_corners.best.step, power_corners.worst.step, power_corners.typical.step), (0, 5, 2));
+
+    // The metric without any value has no corners, and leaves no step with every metric
+    assert!(summary.pareto.is_empty());
+
+    // Steps 1 and 2 draw as much or more power than step 3 for less gain
+    let summary = corners(
+        &simulation,
+        vec![Metric::maximize("gain", |step| output(&simulation, step)), Metric::minimize("power", power)],
+    );
+    assert_eq!(summary.pareto, vec![0, 3, 4, 5]);
+
+    // Steps without every metric are left out of the front
+    let summary = corners(
+        &simulation,
+        vec![
+            Metric::maximize("gain", |step| output(&simulation, step).filter(|_| step != 5)),
+            Metric::minimize("power", power),
+        ],
+    );
+    assert_eq!(summary.pareto, vec![0, 3, 4]);
+    assert_eq!(summary.metrics[0].best.step, 4);
+}
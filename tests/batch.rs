@@ -0,0 +1,80 @@
+/*
+ * This file contains the tests of the parallel conversion of raw files
+ */
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use ltspice::batch::{glob, Batch, BatchFormat};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Functions #### */
+
+fn directory(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ltspice-batch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    return path;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn converts_every_matching_file_and_reports_failures() {
+    let root = directory("convert");
+    let out = root.join("out");
+    fs::create_dir_all(root.join("runs/a/deep")).unwrap();
+    fs::create_dir_all(root.join("runs/b")).unwrap();
+    let bytes = generate_raw(&RawSpec::new().points(20).variables(2));
+    fs::write(root.join("runs/a/tran.raw"), &bytes).unwrap();
+    fs::write(root.join("runs/a/deep/tran.raw"), &bytes).unwrap();
+    fs::write(root.join("runs/b/tran.raw"), &bytes).unwrap();
+    fs::write(root.join("runs/b/broken.raw"), b"not a raw file").unwrap();
+    fs::write(root.join("runs/b/notes.txt"), b"ignored").unwrap();
+
+    let pattern = format!("{}/runs/**/*.raw", root.display());
+    assert_eq!(glob(&pattern).len(), 4);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let report = Batch::new()
+        .threads(3)
+        .on_progress(move |progress| {
+            assert_eq!(progress.total, 4);
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+        .convert(&pattern, BatchFormat::Raw, &out)
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::Relaxed), 4);
+    assert_eq!(report.converted.len(), 3);
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].0.ends_with("runs/b/broken.raw"));
+
+    // Outputs mirror the inputs under the output directory, and read back identically
+    let expected = SteppedSimulation::from_bytes(&bytes).unwrap();
+    for relative in ["a/tran.raw", "a/deep/tran.raw", "b/tran.raw"] {
+        let path = out.join(relative);
+        assert!(report.converted.iter().any(|(_, output)| *output == path));
+        let converted = SteppedSimulation::from_bytes(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(converted.get("V(n002)", None), expected.get("V(n002)", None));
+    }
+    assert!(Batch::new().convert(&format!("{}/runs/**/*.none", root.display()), BatchFormat::Json, &out).is_err());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_directories_are_not_walked() {
+    let root = directory("symlink");
+    fs::create_dir_all(root.join("runs/a")).unwrap();
+    fs::write(root.join("runs/a/tran.raw"), generate_raw(&RawSpec::new().points(10))).unwrap();
+    // A link back to a parent would loop forever, a link elsewhere would escape the pattern
+    std::os::unix::fs::symlink(root.join("runs"), root.join("runs/a/loop")).unwrap();
+
+    let files = glob(&format!("{}/runs/**/*.raw", root.display()));
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(files, vec![root.join("runs/a/tran.raw")]);
+}
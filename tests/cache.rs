@@ -0,0 +1,92 @@
+/*
+ * This file contains the tests of the on-disk cache of parsed simulations
+ */
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use ltspice::cache::Cache;
+use ltspice::marker::Marker;
+use ltspice::options::LoadOptions;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{DataType, Mode, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn snapshots_restore_what_a_parse_does() {
+    let path: PathBuf = std::env::temp_dir().join(format!("ltspice-cache-{}.raw", std::process::id()));
+    let spec = RawSpec::new().mode(Mode::Transient).steps(2).points(50).precision(DataType::Float64);
+    fs::write(&path, generate_raw(&spec)).unwrap();
+
+    let mut parsed = Cache::load_or_parse(&path).unwrap();
+    parsed.derive("P(n001)", &["V(n001)", "V(n002)"], |v| v[0] * v[1]).unwrap();
+    let trace = parsed.trace("V(n001)", 1).unwrap();
+    let marker = Marker::at(&trace, trace.x()[10].real(), "checkpoint").unwrap();
+    parsed.add_marker(marker);
+    Cache::store(&parsed).unwrap();
+
+    let restored = Cache::load(&path).unwrap().expect("an up to date snapshot");
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(Cache::snapshot_path(&path)).unwrap();
+
+    assert_eq!(restored.flags(), parsed.flags());
+    assert_eq!(restored.source(), parsed.source());
+    assert_eq!(restored.get_variables().len(), parsed.get_variables().len());
+    for (restored, parsed) in restored.get_variables().iter().zip(parsed.get_variables().iter()) {
+        assert_eq!(restored.name(), parsed.name());
+        assert_eq!(restored.is_derived(), parsed.is_derived());
+        assert_eq!(restored.data_type(), parsed.data_type());
+    }
+    assert_eq!(restored.markers("V(n001)", 1), parsed.markers("V(n001)", 1));
+    assert_eq!(restored.get("P(n001)", Some(1)), parsed.get("P(n001)", Some(1)));
+}
+
+#[test]
+fn snapshots_restore_aliases_statistics_and_warnings() {
+    let path: PathBuf = std::env::temp_dir().join(format!("ltspice-cache-extras-{}.raw", std::process::id()));
+    let mut bytes = generate_raw(&RawSpec::new().steps(2).points(20));
+    bytes.extend_from_slice(b"\n\n");
+    fs::write(&path, bytes).unwrap();
+
+    let mut parsed = SteppedSimulation::new(path.clone());
+    parsed.set_load_options(LoadOptions::new().column_stats(true));
+    parsed.reload().unwrap();
+    parsed.alias("V(n001)", "out").unwrap();
+    parsed.set_rename_map([("V(n002)".to_string(), "in".to_string())].into_iter().collect());
+    Cache::store(&parsed).unwrap();
+
+    let restored = Cache::load(&path).unwrap().expect("an up to date snapshot");
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(Cache::snapshot_path(&path)).unwrap();
+
+    assert_eq!(restored.get("out", Some(1)), parsed.get("V(n001)", Some(1)));
+    assert_eq!(restored.aliases(), parsed.aliases());
+    assert_eq!(restored.column_stats(), parsed.column_stats());
+    assert!(!parsed.column_stats().is_empty());
+    assert_eq!(restored.get_warnings(), parsed.get_warnings());
+    assert_eq!(parsed.get_warnings().len(), 1);
+}
+
+#[test]
+fn rewrites_past_the_header_invalidate_snapshots() {
+    let path: PathBuf = std::env::temp_dir().join(format!("ltspice-cache-rewrite-{}.raw", std::process::id()));
+    let bytes = generate_raw(&RawSpec::new().points(10_000).variables(2));
+    assert!(bytes.len() > 64 * 1024);
+    fs::write(&path, &bytes).unwrap();
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+    Cache::load_or_parse(&path).unwrap();
+    assert!(Cache::load(&path).unwrap().is_some());
+
+    // Same size and modification time, one sample changed near the end
+    let mut rewritten = bytes.clone();
+    let last = rewritten.len() - 3;
+    rewritten[last] ^= 0xff;
+    fs::write(&path, &rewritten).unwrap();
+    File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+    let stale = Cache::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(Cache::snapshot_path(&path)).unwrap();
+    assert!(stale.is_none());
+}
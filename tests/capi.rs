@@ -0,0 +1,46 @@
+/*
+ * This file contains the tests of the C ABI
+ */
+
+#![cfg(feature = "capi")]
+
+use std::ffi::CString;
+use std::ptr;
+
+use ltspice::capi::*;
+use ltspice::testing::{generate_raw, RawSpec};
+
+/* #### Tests #### */
+
+#[test]
+fn traces_are_copied_into_caller_buffers() {
+    let bytes = generate_raw(&RawSpec::new().variables(2).points(20).steps(2));
+    unsafe {
+        let simulation = ltspice_open_bytes(bytes.as_ptr(), bytes.len());
+        assert!(!simulation.is_null());
+        assert_eq!(ltspice_get_variable_count(simulation), 2);
+        assert_eq!(ltspice_get_step_count(simulation), 2);
+
+        let mut name = [0 as std::ffi::c_char; 16];
+        let length = ltspice_get_variable_name(simulation, 0, name.as_mut_ptr(), name.len());
+        assert_eq!(length, "V(n001)".len() as isize);
+
+        let trace = CString::new("V(n001)").unwrap();
+        assert_eq!(ltspice_get_trace_length(simulation, trace.as_ptr(), 1), 20);
+        let mut real = [0.0; 8];
+        assert_eq!(ltspice_get_trace_data(simulation, trace.as_ptr(), 1, real.as_mut_ptr(), ptr::null_mut(), 8), 8);
+        ltspice_free(simulation);
+    }
+}
+
+#[test]
+fn invalid_arguments_are_reported() {
+    let garbage = b"not a raw file";
+    unsafe {
+        assert!(ltspice_open(ptr::null()).is_null());
+        assert!(ltspice_open_bytes(garbage.as_ptr(), garbage.len()).is_null());
+        assert_eq!(ltspice_get_variable_count(ptr::null()), 0);
+        assert_eq!(ltspice_get_trace_length(ptr::null(), ptr::null(), 0), -1);
+        ltspice_free(ptr::null_mut());
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * This file contains the tests of the verification suites of the CLI
+ */
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+
+use ltspice::checks::{render, Check, CheckResult, Config, FileReport, Measure, OutputFormat};
+use ltspice::mc::SpecLimits;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Loads two steps of a transient with "V(n<1>)" = 1000 * t + step, a ramp from the step to
+// one volt more over the 1 ms of the generated transient.
+fn ramps() -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(1001).steps(2))).unwrap();
+    let steps = (0..2)
+        .map(|step| {
+            let x = simulation.get("x", Some(step)).unwrap();
+            x.iter().map(|t| Value::from(1000.0 * t.real() + step as f64)).collect()
+        })
+        .collect();
+    simulation.insert_derived("V(n<1>)", steps).unwrap();
+    return simulation;
+}
+
+fn check(measure: Measure, min: Option<f64>, max: Option<f64>) -> Check {
+    return Check {
+        name: "ramp".to_string(),
+        variable: "V(n<1>)".to_string(),
+        measure,
+        step: None,
+        from: None,
+        to: None,
+        min,
+        max,
+    };
+}
+
+fn result(check: &str, value: Option<f64>, passed: bool) -> CheckResult {
+    return CheckResult {
+        check: check.to_string(),
+        step: 0,
+        value,
+        limits: SpecLimits::new(None, Some(0.05)),
+        passed,
+    };
+}
+
+/* #### Tests #### */
+
+#[test]
+fn suites_are_parsed_from_toml() {
+    let config = Config::parse(
+        r#"
+        variables = ["V(out)", "I(L1)"]
+
+        [[check]]
+        name = "ripple"
+        variable = "V(out)"
+        measure = "peak_to_peak"
+        step = 1
+        from = 1e-3
+        max = 0.05
+
+        [[export]]
+        format = "lp"
+        dir = "exports"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.variables, vec!["V(out)", "I(L1)"]);
+    assert_eq!(config.checks.len(), 1);
+    let ripple = &config.checks[0];
+    assert_eq!((ripple.measure, ripple.step, ripple.from, ripple.to), (Measure::PeakToPeak, Some(1), Some(1e-3), None));
+    assert_eq!(ripple.limits(), SpecLimits::new(None, Some(0.05)));
+    assert_eq!(config.exports[0].dir, PathBuf::from("exports"));
+
+    assert!(Config::parse("[[export]]\nformat = \"xlsx\"\ndir = \"out\"").unwrap_err().to_string().contains("xlsx"));
+    assert!(Config::parse("[[check]]\nname = \"a\"\nvariable = \"V(out)\"\nmeasure = \"median\"").is_err());
+    assert!(Config::parse("").unwrap().checks.is_empty());
+}
+
+#[test]
+fn checks_evaluate_their_measure_on_every_step_within_the_limits() {
+    let simulation = ramps();
+
+    // Minimum 0 then 1, only the second step is at least 0.5
+    let results = check(Measure::Min, Some(0.5), None).run(&simulation);
+    assert_eq!(results.len(), 2);
+    assert_eq!((results[0].value, results[0].passed), (Some(0.0), false));
+    assert_eq!((results[1].value, results[1].passed), (Some(1.0), true));
+    assert_eq!(results[1].name(), "ramp [step 1]");
+
+    // Limits are inclusive, the ramp spans exactly one volt
+    let results = check(Measure::PeakToPeak, Some(1.0), Some(1.0)).run(&simulation);
+    assert!(results.iter().all(|result| result.passed && (result.value.unwrap() - 1.0).abs() < 1e-9));
+
+    // Within [0.25 ms, 0.75 ms] of the first step, the mean is 0.5
+    let mut mean = check(Measure::Mean, Some(0.499), Some(0.501));
+    (mean.step, mean.from, mean.to) = (Some(0), Some(0.25e-3), Some(0.75e-3));
+    let results = mean.run(&simulation);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].passed, "{}", results[0].message());
+
+    // A missing variable or step can't be measured, which fails the check
+    let mut missing = check(Measure::Max, None, None);
+    missing.step = Some(5);
+    assert_eq!(missing.run(&simulation)[0].value, None);
+    assert!(!missing.run(&simulation)[0].passed);
+    missing.variable = "V(none)".to_string();
+    missing.step = None;
+    assert!(missing.run(&simulation).iter().all(|result| !result.passed));
+
+    let config = Config {
+        variables: vec!["V(n<1>)".to_string(), "V(none)".to_string()],
+        checks: vec![check(Measure::Final, None, Some(1.5))],
+        exports: Vec::new(),
+    };
+    assert_eq!(config.missing_variables(&simulation), vec!["V(none)"]);
+    let results = config.run(&simulation);
+    assert_eq!(results.iter().map(|result| result.passed).collect::<Vec<bool>>(), vec![true, false]);
+}
+
+#[test]
+fn junit_escapes_names_and_counts_failures() {
+    let report = FileReport {
+        path: PathBuf::from("runs/a&b.raw"),
+        errors: vec!["missing variable I(\"L1\")".to_string()],
+        results: vec![result("V(n<1>) ripple", Some(0.01), true), result("V(n<1>) peak", Some(0.1), false)],
+    };
+    assert_eq!(report.failures(), 1);
+    assert!(!report.passed());
+
+    let xml = render(&[report], OutputFormat::Junit);
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains("<testsuites name=\"ltspice\" tests=\"3\" failures=\"1\" errors=\"1\">"), "{}", xml);
+    assert!(xml.contains("<testsuite name=\"runs/a&amp;b.raw\" tests=\"3\" failures=\"1\" errors=\"1\">"), "{}", xml);
+    assert!(xml.contains("<testcase name=\"V(n&lt;1&gt;) ripple [step 0]\" classname=\"runs/a&amp;b.raw\"/>"), "{}", xml);
+    assert!(xml.contains("<failure message=\"1.000000e-1 is outside [-, 5e-2]\"/>"), "{}", xml);
+    assert!(xml.contains("<error message=\"missing variable I(&quot;L1&quot;)\"/>"), "{}", xml);
+    assert!(!xml.contains("n<1>"));
+    assert_eq!(xml.matches("<testcase").count(), xml.matches("</testcase>").count() + 1);
+}
+
+#[test]
+fn github_annotates_the_failures_only() {
+    let report = FileReport {
+        path: PathBuf::from("C:/runs,a.raw"),
+        errors: vec!["could not be loaded: 100%\nbroken".to_string()],
+        results: vec![result("ok", Some(0.01), true), result("V(a,b): ripple", None, false)],
+    };
+    let annotations = render(std::slice::from_ref(&report), OutputFormat::Github);
+    assert_eq!(
+        annotations,
+        "::error file=C%3A/runs%2Ca.raw::could not be loaded: 100%25%0Abroken\n\
+         ::error file=C%3A/runs%2Ca.raw,title=V(a%2Cb)%3A ripple [step 0]::could not be measured, expected within [-, 5e-2]\n"
+    );
+
+    let text = render(&[report], OutputFormat::Text);
+    assert!(text.contains("PASS C:/runs,a.raw ok [step 0] = 1.000000e-2\n"), "{}", text);
+    assert!(text.contains("FAIL C:/runs,a.raw V(a,b): ripple [step 0] = n/a\n"), "{}", text);
+    assert_eq!(OutputFormat::from_name("XML"), Some(OutputFormat::Junit));
+    assert_eq!(OutputFormat::from_name("html"), None);
+}
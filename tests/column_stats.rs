@@ -0,0 +1,48 @@
+/*
+ * This file contains the tests of the column statistics after the data changes
+ */
+
+use ltspice::options::LoadOptions;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Functions #### */
+
+fn load(spec: &RawSpec) -> SteppedSimulation {
+    let options = LoadOptions::new().column_stats(true);
+    return SteppedSimulation::from_bytes_with_options(&generate_raw(spec), options).unwrap();
+}
+
+// Checks that the statistics of every variable and step match its current data.
+fn assert_up_to_date(simulation: &SteppedSimulation) {
+    assert!(!simulation.column_stats().is_empty());
+    for variable in simulation.get_variables().iter() {
+        let statistics = simulation.column_stats().get(variable.name()).unwrap();
+        assert_eq!(statistics.len(), simulation.get_step_count(), "{}", variable.name());
+        for (step, statistics) in statistics.iter().enumerate() {
+            let values = simulation.get(variable.name(), Some(step as u16)).unwrap();
+            assert_eq!(statistics.count, values.len(), "{} step {}", variable.name(), step);
+            let max = values.iter().map(|value| value.real()).fold(f64::NEG_INFINITY, f64::max);
+            assert_eq!(statistics.max, max, "{} step {}", variable.name(), step);
+        }
+    }
+}
+
+/* #### Tests #### */
+
+#[test]
+fn trim_before_recomputes_the_statistics() {
+    let mut simulation = load(&RawSpec::new().steps(2).points(200));
+    simulation.trim_before(0.5e-3).unwrap();
+    assert_up_to_date(&simulation);
+}
+
+#[test]
+fn merges_recompute_the_statistics() {
+    let spec = RawSpec::new().points(100);
+    let merged = SteppedSimulation::merge_simulations(vec![load(&spec), load(&spec.clone().seed(2))]).unwrap();
+    assert_up_to_date(&merged);
+
+    let concatenated = SteppedSimulation::concat_time(&[load(&spec), load(&spec.clone().seed(2))]).unwrap();
+    assert_up_to_date(&concatenated);
+}
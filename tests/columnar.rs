@@ -0,0 +1,34 @@
+/*
+ * This file contains the tests of the MessagePack and CBOR exports
+ */
+#![cfg(all(feature = "msgpack", feature = "cbor"))]
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, Mode, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn header_metadata_survives_a_round_trip() {
+    let spec = RawSpec::new().mode(Mode::AC).steps(2).points(10).encoding(Encoding::UTF8);
+    let mut bytes = generate_raw(&spec);
+    // Same length, so the data section is left untouched
+    let offset = b"Offset:   0.0000000000000000e+000";
+    let position = bytes.windows(offset.len()).position(|window| window == offset).unwrap();
+    bytes[position..position + offset.len()].copy_from_slice(b"Offset:   2.5000000000000000e-003");
+    let simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    assert_eq!(simulation.offset(), 2.5e-3);
+    assert!(!simulation.flags().is_empty());
+    assert!(simulation.get_date_string().is_some());
+
+    for restored in [
+        SteppedSimulation::from_msgpack(&simulation.to_msgpack().unwrap()).unwrap(),
+        SteppedSimulation::from_cbor(&simulation.to_cbor().unwrap()).unwrap(),
+    ] {
+        assert_eq!(restored.flags(), simulation.flags());
+        assert_eq!(restored.get_date_string(), simulation.get_date_string());
+        assert_eq!(restored.offset(), simulation.offset());
+        assert_eq!(restored.get_step_count(), 2);
+        assert_eq!(restored.get("V(n001)", Some(1)), simulation.get("V(n001)", Some(1)));
+    }
+}
@@ -0,0 +1,59 @@
+/*
+ * This file contains the tests of transient files with compressed points
+ */
+
+use ltspice::measure::charge;
+use ltspice::stream::RawStream;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Flags every other point of a UTF8 file as compressed, as LTSpice does by negating its time.
+fn compress(bytes: &[u8], points: usize) -> Vec<u8> {
+    let start = bytes.windows(8).position(|window| window == b"Binary:\n").unwrap() + 8;
+    let point_size = (bytes.len() - start) / points;
+    let mut compressed = bytes.to_vec();
+    for point in (1..points).step_by(2) {
+        let offset = start + point * point_size;
+        let time = f64::from_le_bytes(compressed[offset..offset + 8].try_into().unwrap());
+        compressed[offset..offset + 8].copy_from_slice(&(-time).to_le_bytes());
+    }
+    return compressed;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn compressed_times_are_positive() {
+    let spec = RawSpec::new().encoding(Encoding::UTF8).points(100).steps(2);
+    let bytes = generate_raw(&spec);
+    let plain = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let mut compressed = SteppedSimulation::from_bytes(&compress(&bytes, 200)).unwrap();
+
+    assert_eq!(compressed.get_step_count(), 2);
+    for step in 0..2 {
+        assert_eq!(compressed.get("x", Some(step)), plain.get("x", Some(step)));
+        let (plain, compressed) = (plain.trace("V(n001)", step).unwrap(), compressed.trace("V(n001)", step).unwrap());
+        assert_eq!(charge(&compressed, None), charge(&plain, None));
+    }
+    assert!(compressed.validate().is_valid());
+
+    let middle = plain.get("x", Some(0)).unwrap()[50].real();
+    compressed.trim_before(middle).unwrap();
+    assert_eq!(compressed.get("x", Some(0)).unwrap().len(), 50);
+}
+
+#[test]
+fn streamed_compressed_times_are_positive() {
+    let path = std::env::temp_dir().join(format!("ltspice-compressed-{}.raw", std::process::id()));
+    std::fs::write(&path, compress(&generate_raw(&RawSpec::new().encoding(Encoding::UTF8).points(100)), 100)).unwrap();
+    let stream = RawStream::open(&path).unwrap().select(&["V(n001)"]).unwrap().chunk_points(16);
+    let mut times = Vec::new();
+    for chunk in stream {
+        times.extend(chunk.unwrap().x.iter().map(|x| x.real()));
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(times.len(), 100);
+    assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+}
@@ -0,0 +1,68 @@
+/*
+ * This file contains the tests of the logarithmic unit conversions
+ */
+
+use ltspice::convert::{db, dbm, dbv, from_db, from_dbm, from_dbv};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Unit};
+
+/* #### Functions #### */
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() <= 1e-9 * expected.abs().max(1.0), "{} != {}", actual, expected);
+}
+
+/* #### Tests #### */
+
+#[test]
+fn decibels_round_trip() {
+    for ratio in [1e-6, 0.5, 1.0, 2.0, 10.0, 1e6] {
+        assert_close(from_db(db(ratio)), ratio);
+        assert_close(from_dbv(dbv(ratio)), ratio);
+        assert_close(from_dbm(dbm(ratio, 50.0), 50.0), ratio);
+    }
+    for decibels in [-120.0, -3.0, 0.0, 6.0, 40.0] {
+        assert_close(db(from_db(decibels)), decibels);
+        assert_close(dbm(from_dbm(decibels, 600.0), 600.0), decibels);
+    }
+}
+
+#[test]
+fn decibels_match_reference_points() {
+    assert_close(db(10.0), 20.0);
+    assert_close(db(0.1), -20.0);
+    assert_close(dbv(1.0), 0.0);
+    assert_close(from_dbv(-6.0), 0.501_187_233_627_272_3);
+    // 1 mW in 50 Ω is 223.6 mV RMS, and 0 dBu is 0 dBm in 600 Ω
+    assert_close(dbm(0.05f64.sqrt(), 50.0), 0.0);
+    assert_close(dbm(10.0, 50.0), 33.010_299_956_639_81);
+    assert_close(from_dbm(0.0, 600.0), 0.6f64.sqrt());
+}
+
+#[test]
+fn decibels_of_zero_and_negative_inputs() {
+    assert_eq!(db(0.0), f64::NEG_INFINITY);
+    assert_eq!(dbv(0.0), f64::NEG_INFINITY);
+    assert_eq!(dbm(0.0, 50.0), f64::NEG_INFINITY);
+    assert_eq!(from_db(f64::NEG_INFINITY), 0.0);
+    assert_eq!(from_dbm(f64::NEG_INFINITY, 50.0), 0.0);
+
+    // Only the magnitude of an amplitude counts
+    assert_eq!(db(-10.0), db(10.0));
+    assert_eq!(dbv(-0.5), dbv(0.5));
+    assert_eq!(dbm(-1.0, 50.0), dbm(1.0, 50.0));
+}
+
+#[test]
+fn traces_convert_to_decibels() {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().mode(Mode::AC).points(50))).unwrap();
+    let trace = simulation.trace("V(n001)", 0).unwrap();
+    let decibels = trace.to_db();
+    assert_eq!(decibels.as_trace().unit(), Unit::Decibel);
+    assert_eq!(decibels.name(), trace.name());
+    assert_eq!(decibels.x(), trace.x());
+    for (decibels, value) in decibels.values().iter().zip(trace.values().iter()) {
+        assert_close(decibels.real(), db(value.abs()));
+        assert_eq!(decibels.imaginary(), 0.0);
+    }
+}
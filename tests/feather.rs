@@ -0,0 +1,72 @@
+/*
+ * This file contains the tests of the Arrow IPC (Feather v2) export
+ */
+#![cfg(feature = "feather")]
+
+use std::fs;
+use std::io::Cursor;
+
+use arrow::array::{Array, Float64Array, UInt16Array};
+use arrow::ipc::reader::FileReader;
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, Mode, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Writes a simulation to a Feather file and reads back its record batches.
+fn round_trip(simulation: &SteppedSimulation, name: &str) -> FileReader<Cursor<Vec<u8>>> {
+    let path = std::env::temp_dir().join(format!("ltspice-feather-{}-{}.feather", name, std::process::id()));
+    simulation.to_feather(&path).unwrap();
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    return FileReader::try_new(Cursor::new(bytes), None).unwrap();
+}
+
+fn column<'a>(batch: &'a arrow::record_batch::RecordBatch, name: &str) -> &'a Float64Array {
+    return batch.column_by_name(name).unwrap().as_any().downcast_ref::<Float64Array>().unwrap();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn steps_survive_a_round_trip() {
+    let spec = RawSpec::new().steps(3).points(40).variables(2).encoding(Encoding::UTF8);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    let reader = round_trip(&simulation, "transient");
+
+    let schema = reader.schema();
+    let names: Vec<&str> = schema.fields().iter().map(|field| field.name().as_str()).collect();
+    assert_eq!(names, vec!["step", "x", "V(n001)", "V(n002)"]);
+    assert_eq!(schema.metadata()["mode"], simulation.get_mode().to_string());
+    assert_eq!(schema.metadata()["source_hash"], simulation.provenance().hash_hex());
+    assert_eq!(schema.field_with_name("x").unwrap().metadata()["unit"], "s");
+
+    // One record batch per step, every sample read back exactly
+    let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+    assert_eq!(batches.len(), 3);
+    for (step, batch) in batches.iter().enumerate() {
+        assert_eq!(batch.num_rows(), 40);
+        let steps = batch.column(0).as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert!(steps.values().iter().all(|value| *value as usize == step));
+        for name in ["x", "V(n001)", "V(n002)"] {
+            let expected: Vec<f64> = simulation.get(name, Some(step as u16)).unwrap().iter().map(|v| v.real()).collect();
+            assert_eq!(column(batch, name).values().to_vec(), expected);
+        }
+    }
+}
+
+#[test]
+fn complex_samples_get_an_imaginary_column() {
+    let spec = RawSpec::new().mode(Mode::AC).points(20).variables(1).encoding(Encoding::UTF8);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    let batch = round_trip(&simulation, "ac").next().unwrap().unwrap();
+    assert_eq!(batch.num_columns(), 4);
+
+    let values = simulation.get("V(n001)", None).unwrap();
+    let real: Vec<f64> = values.iter().map(|v| v.real()).collect();
+    let imaginary: Vec<f64> = values.iter().map(|v| v.imaginary()).collect();
+    assert_eq!(column(&batch, "V(n001)").values().to_vec(), real);
+    assert_eq!(column(&batch, "V(n001).imag").values().to_vec(), imaginary);
+    assert!(imaginary.iter().any(|value| *value != 0.0));
+}
@@ -0,0 +1,73 @@
+/*
+ * This file contains the tests of the rational approximation of frequency responses
+ */
+
+use std::f64::consts::PI;
+
+use ltspice::fit::vector_fit;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Loads a 1 Hz to 1 MHz AC sweep with an analytic response H(s) as "H".
+fn response(h: impl Fn(Value) -> Value) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().mode(Mode::AC).points(400))).unwrap();
+    let values = simulation
+        .get("x", Some(0))
+        .unwrap()
+        .iter()
+        .map(|f| h(Value::new(0.0, 2.0 * PI * f.real())))
+        .collect();
+    simulation.insert_derived("H", vec![values]).unwrap();
+    return simulation;
+}
+
+fn assert_close(actual: Value, expected: Value) {
+    assert!((actual - expected).abs() <= 1e-4 * expected.abs(), "{:?} != {:?}", actual, expected);
+}
+
+/* #### Tests #### */
+
+#[test]
+fn rc_pole_is_recovered() {
+    // First-order low-pass, f = 1 / (2π·RC) = 1 kHz
+    let pole = 2.0 * PI * 1e3;
+    let simulation = response(|s| Value::from(1.0) / (Value::from(1.0) + s / pole));
+    let trace = simulation.trace("H", 0).unwrap();
+    let fit = vector_fit(&trace, 1).unwrap();
+    assert_eq!(fit.poles().len(), 1);
+    assert_close(fit.poles()[0], Value::from(-pole));
+    assert!(fit.rms_error(&trace) < 1e-6);
+}
+
+#[test]
+fn rlc_poles_are_recovered() {
+    // Second-order low-pass, f0 = 10 kHz and Q = 2
+    let (w0, q) = (2.0 * PI * 1e4, 2.0);
+    let simulation = response(|s| Value::from(w0 * w0) / (s * s + s * (w0 / q) + Value::from(w0 * w0)));
+    let fit = vector_fit(&simulation.trace("H", 0).unwrap(), 2).unwrap();
+
+    let mut poles = fit.dominant_poles();
+    poles.sort_by(|a, b| a.imaginary().total_cmp(&b.imaginary()));
+    let damping = w0 / (2.0 * q);
+    let natural = w0 * (1.0 - 1.0 / (4.0 * q * q)).sqrt();
+    assert_close(poles[0], Value::new(-damping, -natural));
+    assert_close(poles[1], Value::new(-damping, natural));
+    assert!(fit.zeros().iter().all(|zero| zero.abs() > 1e3 * w0));
+}
+
+#[test]
+fn double_poles_are_finite() {
+    // Two identical RC sections (buffered), a double pole at 1 kHz
+    let pole = 2.0 * PI * 1e3;
+    let simulation = response(|s| {
+        let section = Value::from(1.0) / (Value::from(1.0) + s / pole);
+        return section * section;
+    });
+    let fit = vector_fit(&simulation.trace("H", 0).unwrap(), 2).unwrap();
+    for root in fit.poles() {
+        assert!(root.real().is_finite() && root.imaginary().is_finite());
+        assert!((*root - Value::from(-pole)).abs() < 1e-2 * pole, "{:?}", root);
+    }
+}
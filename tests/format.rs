@@ -0,0 +1,63 @@
+/*
+ * This file contains the tests of the engineering-notation helpers
+ */
+
+use ltspice::format::{engineering, engineering_digits, parse};
+use ltspice::Unit;
+
+/* #### Functions #### */
+
+fn close(actual: Option<f64>, expected: f64) -> bool {
+    return actual.is_some_and(|actual| (actual - expected).abs() <= expected.abs() * 1e-12);
+}
+
+/* #### Tests #### */
+
+#[test]
+fn zero_and_negative_values() {
+    assert_eq!(engineering(0.0, Unit::Volt), "0.00 V");
+    assert_eq!(engineering(0.0, Unit::Dimensionless), "0.00");
+    assert_eq!(engineering(-3.3e-3, Unit::Volt), "-3.30 mV");
+    assert_eq!(engineering(-4700.0, Unit::Ohm), "-4.70 kΩ");
+    assert_eq!(engineering(f64::NAN, Unit::Volt), "NaN V");
+}
+
+#[test]
+fn prefix_boundaries() {
+    assert_eq!(engineering(1e-15, Unit::Second), "1.00 fs");
+    assert_eq!(engineering(1e-16, Unit::Second), "0.100 fs");
+    assert_eq!(engineering(1e-12, Unit::Second), "1.00 ps");
+    assert_eq!(engineering(1.0, Unit::Ampere), "1.00 A");
+    assert_eq!(engineering(1e9, Unit::Hertz), "1.00 GHz");
+    assert_eq!(engineering(1e12, Unit::Hertz), "1.00 THz");
+    assert_eq!(engineering(4.7e13, Unit::Hertz), "47.0 THz");
+}
+
+#[test]
+fn rounding_carries_into_the_next_prefix() {
+    assert_eq!(engineering(999.96e-3, Unit::Volt), "1.00 V");
+    assert_eq!(engineering(-999.96e-3, Unit::Volt), "-1.00 V");
+    assert_eq!(engineering(999.96e9, Unit::Hertz), "1.00 THz");
+    assert_eq!(engineering(999.4e-3, Unit::Volt), "999 mV");
+    assert_eq!(engineering(99.96, Unit::Volt), "100 V");
+    assert_eq!(engineering_digits(9.9996e3, Unit::Ohm, 4), "10.00 kΩ");
+}
+
+#[test]
+fn meg_is_mega_and_m_is_milli() {
+    assert!(close(parse("1meg"), 1e6));
+    assert!(close(parse("1MEG"), 1e6));
+    assert!(close(parse("1m"), 1e-3));
+    assert!(close(parse("1M"), 1e-3));
+    assert!(close(parse("2.2megohm"), 2.2e6));
+    assert!(close(parse("10kohm"), 1e4));
+    assert!(close(parse("4.7u"), 4.7e-6));
+    assert!(close(parse("4.7µ"), 4.7e-6));
+    assert!(close(parse("1f"), 1e-15));
+    assert!(close(parse("1t"), 1e12));
+    assert!(close(parse("-1.5e-3"), -1.5e-3));
+    assert!(close(parse(" 25 "), 25.0));
+    assert_eq!(parse("0"), Some(0.0));
+    assert_eq!(parse("meg"), None);
+    assert_eq!(parse(""), None);
+}
@@ -0,0 +1,65 @@
+/*
+ * This file contains the tests of the raw file format registry
+ */
+
+use ltspice::options::LoadOptions;
+use ltspice::raw::{Formats, RawFormat};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{DataType, Encoding, Source, SteppedSimulation};
+
+/* #### Structs #### */
+
+// A format recognizing files starting with "CSV", to check the precedence of registered formats.
+struct Csv;
+
+/* #### Functions #### */
+
+// Replaces the first occurrence of `from` in the header of a UTF8 file.
+fn patch(bytes: &[u8], from: &str, to: &str) -> Vec<u8> {
+    let start = bytes.windows(from.len()).position(|window| window == from.as_bytes()).unwrap();
+    return [&bytes[..start], to.as_bytes(), &bytes[start + from.len()..]].concat();
+}
+
+/* #### Implementations #### */
+
+impl RawFormat for Csv {
+    fn name(&self) -> &str {
+        return "csv";
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        return head.starts_with(b"CSV");
+    }
+
+    fn decode(&self, _bytes: &[u8], _options: &LoadOptions) -> Result<SteppedSimulation, Box<dyn std::error::Error>> {
+        Err("Not a raw file.")?
+    }
+}
+
+/* #### Tests #### */
+
+#[test]
+fn qspice_files_use_the_ltspice_format() {
+    // QSPICE writes doubles without the 'double' flag, and names itself in 'Command'
+    let spec = RawSpec::new().encoding(Encoding::UTF8).precision(DataType::Float64).points(50).variables(2);
+    let bytes = generate_raw(&spec);
+    let qspice = patch(&patch(&bytes, " double", ""), "Command: Synthetic", "Command: QSPICE64 Synthetic");
+
+    let formats = Formats::default();
+    assert_eq!(formats.detect(&qspice).unwrap().name(), "ltspice");
+    let simulation = formats.decode(&qspice, &LoadOptions::new()).unwrap();
+    let expected = SteppedSimulation::from_bytes(&bytes).unwrap();
+    assert_eq!(simulation.source(), Source::Qspice);
+    assert_eq!(simulation.get_variables()[1].data_type(), Some(DataType::Float64));
+    assert_eq!(simulation.get("V(n002)", None), expected.get("V(n002)", None));
+}
+
+#[test]
+fn registered_formats_take_precedence() {
+    let mut formats = Formats::default();
+    assert!(formats.detect(b"CSV,time").is_none());
+    formats.register(Box::new(Csv));
+    assert_eq!(formats.detect(b"CSV,time").unwrap().name(), "csv");
+    assert!(formats.decode(b"CSV,time", &LoadOptions::new()).is_err());
+    assert!(formats.decode(b"neither", &LoadOptions::new()).unwrap_err().to_string().contains("Unrecognized"));
+}
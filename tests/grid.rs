@@ -0,0 +1,63 @@
+/*
+ * This file contains the tests of the 2D view of two-parameter sweeps
+ */
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Constants #### */
+
+// Steps rload over 3 values (unsorted) and temp over 2
+const LOG: &str = ".step rload=100 temp=25\n.step rload=10 temp=25\n.step rload=1k temp=25\n\
+                   .step rload=100 temp=85\n.step rload=10 temp=85\n.step rload=1k temp=85\n";
+
+/* #### Functions #### */
+
+// Loads a 6 step simulation with the step parameters of a log file.
+fn simulation(log: &str) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(6).points(10))).unwrap();
+    simulation.parse_log(log);
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn steps_are_arranged_by_sorted_parameter_values() {
+    let simulation = simulation(LOG);
+    let grid = simulation.grid("RLOAD", "temp").unwrap();
+
+    assert_eq!(grid.names(), ("rload", "temp"));
+    assert_eq!(grid.first(), &[10.0, 100.0, 1000.0]);
+    assert_eq!(grid.second(), &[25.0, 85.0]);
+    assert_eq!(grid.step(0, 0), Some(1));
+    assert_eq!(grid.step(2, 1), Some(5));
+    assert_eq!(grid.step(3, 0), None);
+    assert_eq!(grid.step_at(100.0, 85.0), Some(3));
+    assert_eq!(grid.step_at(100.0, 50.0), None);
+    assert_eq!(grid.along_first(1), vec![(10.0, 4), (100.0, 3), (1000.0, 5)]);
+    assert_eq!(grid.along_second(2), vec![(25.0, 2), (85.0, 5)]);
+    assert_eq!(grid.trace("V(n001)", 1, 0).unwrap().step(), 0);
+}
+
+#[test]
+fn missing_or_repeated_cells() {
+    // rload=1k is not simulated at 85°C
+    let partial = simulation(
+        ".step rload=100 temp=25\n.step rload=10 temp=25\n.step rload=1k temp=25\n\
+         .step rload=100 temp=85\n.step rload=10 temp=85\n.step rload=100 temp=125\n",
+    );
+    let grid = partial.grid("rload", "temp").unwrap();
+    assert_eq!(grid.second(), &[25.0, 85.0, 125.0]);
+    assert_eq!(grid.step(2, 1), None);
+    assert_eq!(grid.along_second(2), vec![(25.0, 2)]);
+
+    // A third stepped parameter repeats the (rload, temp) pairs
+    let repeated = simulation(
+        ".step rload=10 temp=25 vin=1\n.step rload=10 temp=25 vin=2\n.step rload=10 temp=85 vin=1\n\
+         .step rload=10 temp=85 vin=2\n.step rload=20 temp=25 vin=1\n.step rload=20 temp=25 vin=2\n",
+    );
+    assert!(repeated.grid("rload", "temp").is_none());
+    assert!(repeated.grid("rload", "vin").is_none());
+    assert!(simulation(LOG).grid("rload", "vin").is_none());
+}
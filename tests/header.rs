@@ -0,0 +1,128 @@
+/*
+ * This file contains the tests of the parsing of the header
+ */
+
+use ltspice::options::LoadOptions;
+use ltspice::testing::{generate_raw, RawSpec};
+#[cfg(feature = "chrono")]
+use ltspice::parse_date;
+use ltspice::{Encoding, Mode, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Generates a UTF8 transient of 20 points of V(n001).
+fn transient() -> Vec<u8> {
+    return generate_raw(&RawSpec::new().points(20).variables(1).encoding(Encoding::UTF8));
+}
+
+// Replaces the first occurrence of `from` in the header of a UTF8 file.
+fn patch(bytes: &[u8], from: &str, to: &str) -> Vec<u8> {
+    let start = bytes.windows(from.len()).position(|window| window == from.as_bytes()).unwrap();
+    return [&bytes[..start], to.as_bytes(), &bytes[start + from.len()..]].concat();
+}
+
+fn strict() -> LoadOptions {
+    return LoadOptions::new().strict(true);
+}
+
+/* #### Tests #### */
+
+#[test]
+fn anomalies_are_warnings_unless_strict() {
+    let bytes = transient();
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, strict()).unwrap();
+    assert!(simulation.get_warnings().is_empty());
+
+    // (file, warning, error in strict mode)
+    for (anomaly, warning, error) in [
+        (patch(&bytes, "Command:", "Extra: 1\nCommand:"), "Unknown LTSPICE Simulation Key: Extra", "Extra"),
+        (patch(&bytes, "Flags: real forward", "Flags: real forward sparse"), "Unknown flag 'sparse'.", "sparse"),
+        (patch(&bytes, "Offset:   0.0000000000000000e+000", "Offset: none"), "Could not parse the offset 'none'.", "offset"),
+        (bytes[..bytes.len() - 6].to_vec(), "loading 19 complete points", "Mismatch"),
+    ] {
+        let lenient = SteppedSimulation::from_bytes(&anomaly).unwrap();
+        assert!(lenient.get_warnings().iter().any(|message| message.contains(warning)), "{:?}", lenient.get_warnings());
+        let expected = SteppedSimulation::from_bytes(&bytes).unwrap();
+        assert_eq!(lenient.get("V(n001)", None).unwrap()[..19], expected.get("V(n001)", None).unwrap()[..19]);
+
+        let message = SteppedSimulation::from_bytes_with_options(&anomaly, strict()).unwrap_err().to_string();
+        assert!(message.contains(error), "{}", message);
+    }
+}
+
+#[test]
+fn trailing_padding_is_ignored_even_in_strict_mode() {
+    let mut bytes = transient();
+    bytes.extend_from_slice(b"\n\0\0");
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, strict()).unwrap();
+    assert_eq!(simulation.get("V(n001)", None).unwrap().len(), 20);
+    assert!(simulation.get_warnings()[0].starts_with("Ignored 3 bytes after the data"), "{:?}", simulation.get_warnings());
+
+    // Anything else after the data is a length mismatch
+    bytes.extend_from_slice(b"garbage");
+    assert!(SteppedSimulation::from_bytes_with_options(&bytes, strict()).is_err());
+}
+
+#[test]
+fn plotnames_map_to_modes() {
+    let bytes = transient();
+    for (plotname, mode) in [
+        ("Transient Analysis", Mode::Transient),
+        ("DC transfer characteristic", Mode::DC),
+        ("dc sweep", Mode::DC),
+        ("Noise Spectral Density - (V/Hz½ or A/Hz½)", Mode::Noise),
+        ("Integrated Noise", Mode::Noise),
+        ("Operating Point", Mode::OperatingPoint),
+    ] {
+        let simulation = SteppedSimulation::from_bytes_with_options(&patch(&bytes, "Transient Analysis", plotname), strict()).unwrap();
+        assert_eq!(simulation.get_mode(), &mode, "{}", plotname);
+    }
+
+    // Unknown plotnames are kept, with a warning, and their sweeps restart at the first value
+    let other = patch(&bytes, "Transient Analysis", "Pole-Zero Analysis");
+    let simulation = SteppedSimulation::from_bytes(&other).unwrap();
+    assert_eq!(simulation.get_mode(), &Mode::Other("Pole-Zero Analysis".to_string()));
+    assert_eq!(simulation.get_warnings(), &vec!["Unknown plotname 'Pole-Zero Analysis'.".to_string()]);
+    assert_eq!(simulation.get_step_count(), 1);
+    assert_eq!(simulation.get("V(n001)", None).unwrap().len(), 20);
+    assert!(SteppedSimulation::from_bytes_with_options(&other, strict()).is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn dates_are_parsed_in_the_ltspice_format() {
+    let rfc3339 = |value: &str| parse_date(value).map(|date| date.to_rfc3339());
+    assert_eq!(rfc3339("Sat Apr 13 14:06:22 2024").as_deref(), Some("2024-04-13T14:06:22+00:00"));
+    // Days are padded with a space, as in "Thu Jan  1"
+    assert_eq!(rfc3339(" Thu Jan  1 00:00:00 1970 ").as_deref(), Some("1970-01-01T00:00:00+00:00"));
+    assert_eq!(rfc3339("2024-04-13 14:06:22"), None);
+    assert_eq!(rfc3339("Sat Apr 13 14:06:22"), None);
+    assert_eq!(rfc3339(""), None);
+
+    // An unknown format leaves the date unset, its text is kept
+    let bytes = patch(&transient(), "Thu Jan  1 00:00:00 1970", "13/04/2024 14:06");
+    let simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    assert_eq!(simulation.get_date(), None);
+    assert_eq!(simulation.get_date_string(), Some("13/04/2024 14:06"));
+    assert!(simulation.get_warnings()[0].contains("Could not parse the date"), "{:?}", simulation.get_warnings());
+    let simulation = SteppedSimulation::from_bytes(&transient()).unwrap();
+    assert_eq!(simulation.get_date().map(|date| date.timestamp()), Some(0));
+}
+
+#[test]
+fn offset_is_only_applied_on_request() {
+    let bytes = transient();
+    let shifted = patch(&bytes, "Offset:   0.0000000000000000e+000", "Offset:   2.5000000000000000e-003");
+    let plain = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let stored = SteppedSimulation::from_bytes(&shifted).unwrap();
+    assert_eq!(stored.offset(), 2.5e-3);
+    assert_eq!(stored.get_x(), plain.get_x());
+
+    let applied = SteppedSimulation::from_bytes_with_options(&shifted, LoadOptions::new().apply_offset(true)).unwrap();
+    assert_eq!(applied.offset(), 2.5e-3);
+    for (applied, plain) in applied.get_x().unwrap().iter().zip(plain.get_x().unwrap().iter()) {
+        assert_eq!(applied.real(), plain.real() + 2.5e-3);
+    }
+    assert_eq!(applied.get("V(n001)", None), plain.get("V(n001)", None));
+    assert_eq!(SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().apply_offset(true)).unwrap().get_x(), plain.get_x());
+}
@@ -0,0 +1,71 @@
+/*
+ * This file contains the tests of the simulation corpus indexer
+ */
+#![cfg(feature = "chrono")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use ltspice::index::{read_header, Catalog, Query};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::Mode;
+
+/* #### Functions #### */
+
+fn directory(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ltspice-index-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    return path;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn long_headers_end_at_the_data_section() {
+    let root = directory("header");
+    let path = root.join("wide.raw");
+    // Thousands of UTF16 variable lines span several read chunks
+    fs::write(&path, generate_raw(&RawSpec::new().variables(3000).points(2))).unwrap();
+    let header = read_header(&path).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(header.len() > 64 * 1024);
+    assert!(header.contains("V(n3000)"));
+    assert!(!header.contains("Binary:"));
+    assert!(header.trim_end().ends_with("voltage"), "{}", &header[header.len() - 64..]);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_directories_are_not_followed() {
+    let root = directory("symlink");
+    fs::create_dir(root.join("nested")).unwrap();
+    fs::write(root.join("nested").join("tran.raw"), generate_raw(&RawSpec::new().mode(Mode::Transient).points(4))).unwrap();
+    // A link back to the root would make the scan loop forever
+    std::os::unix::fs::symlink(&root, root.join("nested").join("loop")).unwrap();
+
+    let catalog = Catalog::scan(&root).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(catalog.entries().len(), 1);
+    assert_eq!(catalog.search(&Query::new().mode(Mode::Transient)).len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn saved_catalogs_keep_separators_in_names() {
+    let root = directory("escape");
+    let name = "tab\there\\line\nbreak.raw";
+    fs::write(root.join(name), generate_raw(&RawSpec::new().points(4).variables(2))).unwrap();
+
+    let catalog = Catalog::scan(&root).unwrap();
+    let saved = root.join("catalog.tsv");
+    catalog.save(&saved).unwrap();
+    let loaded = Catalog::load(&saved).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(loaded.entries().len(), 1);
+    assert_eq!(loaded.entries()[0].path, root.join(name));
+    assert_eq!(loaded.entries()[0].variables, catalog.entries()[0].variables);
+    assert_eq!(loaded.entries()[0].plotname, catalog.entries()[0].plotname);
+}
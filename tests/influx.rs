@@ -0,0 +1,56 @@
+/*
+ * This file contains the tests of the InfluxDB line protocol export
+ */
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, Mode, SteppedSimulation, Value};
+
+/* #### Tests #### */
+
+#[test]
+fn points_survive_a_round_trip() {
+    let spec = RawSpec::new().steps(2).points(30).variables(1).encoding(Encoding::UTF8);
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    // A non-finite sample is left out of its line
+    let mut steps: Vec<Vec<Value>> = (0..2).map(|step| simulation.get("V(n001)", Some(step)).unwrap().to_vec()).collect();
+    steps[0][3] = Value::from(f64::NAN);
+    simulation.insert_derived("V(d)", steps).unwrap();
+
+    let text = simulation.to_line_protocol("my run,1").unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2 * 30);
+    let hash = simulation.provenance().hash_hex();
+
+    // "<measurement>,source=<hash>,step=<step> <field>=<value>,... <timestamp>"
+    for (index, line) in lines.iter().enumerate() {
+        let (step, point) = ((index / 30) as u16, index % 30);
+        let parts: Vec<&str> = line.split(' ').collect();
+        let (tags, fields, timestamp) = (format!("{} {}", parts[0], parts[1]), parts[2], parts[3]);
+        assert_eq!(tags, format!("my\\ run\\,1,source={},step={}", hash, step));
+
+        let x = simulation.get("x", Some(step)).unwrap()[point].real();
+        assert_eq!(timestamp.parse::<i64>().unwrap(), (x * 1e9).round() as i64);
+        let fields: Vec<(&str, f64)> = fields
+            .split(',')
+            .map(|field| field.split_once('=').unwrap())
+            .map(|(name, value)| (name, value.parse::<f64>().unwrap()))
+            .collect();
+        let mut expected = Vec::new();
+        for name in ["V(n001)", "V(d)"] {
+            let value = simulation.get(name, Some(step)).unwrap()[point].real();
+            if value.is_finite() {
+                expected.push((name, value));
+            }
+        }
+        assert_eq!(fields, expected);
+    }
+    assert_eq!(lines[3].matches('=').count(), 3);
+    assert_eq!(lines[4].matches('=').count(), 4);
+}
+
+#[test]
+fn only_transients_are_exported() {
+    let spec = RawSpec::new().mode(Mode::AC).points(10).encoding(Encoding::UTF8);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    assert!(simulation.to_line_protocol("ac").is_err());
+}
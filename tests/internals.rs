@@ -0,0 +1,43 @@
+/*
+ * This file contains the tests of the parser building blocks
+ */
+
+use ltspice::internals::{decode_columns, header_fields, segment_steps, split_header, DataType, Layout};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, Mode, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn building_blocks_compose_into_the_parser() {
+    let spec = RawSpec::new().steps(3).points(50).variables(2).precision(DataType::Float64);
+    let bytes = generate_raw(&spec.clone().encoding(Encoding::UTF8));
+    let simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+
+    let (encoding, length) = split_header(&bytes).unwrap();
+    assert_eq!(encoding, Encoding::UTF8);
+    assert!(bytes[..length].ends_with(b"Binary:\n"));
+    let fields = header_fields(std::str::from_utf8(&bytes[..length]).unwrap());
+    assert_eq!(fields.get("No. Variables").unwrap().trim(), "3");
+    assert!(fields.get("Variables").unwrap().contains("V(n002)"));
+
+    let layout = Layout::uniform(DataType::Float64, DataType::Float64, 2);
+    let decoded = decode_columns(&bytes[length..], &layout, &Mode::Transient);
+    assert_eq!(decoded.points, 150);
+    assert_eq!(decoded.x.len(), 3);
+    for step in 0..3 {
+        assert_eq!(decoded.x[step], simulation.get("x", Some(step as u16)).unwrap());
+        assert_eq!(decoded.y[1][step], simulation.get("V(n002)", Some(step as u16)).unwrap());
+    }
+
+    let x: Vec<_> = decoded.x.concat();
+    assert_eq!(segment_steps(&x, &Mode::Transient), vec![0..50, 50..100, 100..150]);
+    assert!(segment_steps(&[], &Mode::Transient).is_empty());
+
+    // UTF16 headers are detected too, and end at the same marker
+    let utf16 = generate_raw(&spec);
+    let (encoding, length) = split_header(&utf16).unwrap();
+    assert_eq!(encoding, Encoding::UTF16);
+    assert_eq!(utf16.len() - length, bytes.len() - split_header(&bytes).unwrap().1);
+    assert!(split_header(b"not a raw file").is_none());
+}
@@ -0,0 +1,56 @@
+/*
+ * This file contains the tests of the interpolation between the steps of a sweep
+ */
+
+use ltspice::interp::between_steps;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Loads a 3 step sweep of rload (40, 45, 60) with V(out) = rload · t, which is linear in rload.
+fn simulation() -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(3).points(100))).unwrap();
+    simulation.parse_log(".step rload=45\n.step rload=40\n.step rload=60\n");
+    let steps = [45.0, 40.0, 60.0]
+        .iter()
+        .enumerate()
+        .map(|(step, rload)| {
+            let x = simulation.get("x", Some(step as u16)).unwrap();
+            return x.iter().map(|t| Value::from(rload * t.real())).collect();
+        })
+        .collect();
+    simulation.insert_derived("V(out)", steps).unwrap();
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn intermediate_values_are_interpolated_linearly() {
+    let simulation = simulation();
+    let estimate = between_steps(&simulation, "V(out)", "Rload", 42.0).unwrap();
+
+    assert_eq!(estimate.as_trace().parameter("rload"), Some(42.0));
+    assert_eq!(estimate.x(), &simulation.get("x", Some(1)).unwrap()[..estimate.x().len()]);
+    assert!(estimate.x().len() > 90);
+    for (t, value) in estimate.x().iter().zip(estimate.values().iter()) {
+        assert!((value.real() - 42.0 * t.real()).abs() < 1e-9, "{} {:?}", t.real(), value);
+    }
+
+    let upper = between_steps(&simulation, "V(out)", "rload", 50.0).unwrap();
+    let last = upper.values().len() - 1;
+    assert!((upper.values()[last].real() - 50.0 * upper.x()[last].real()).abs() < 1e-9);
+}
+
+#[test]
+fn exact_and_out_of_range_values() {
+    let simulation = simulation();
+    let exact = between_steps(&simulation, "V(out)", "rload", 60.0).unwrap();
+    assert_eq!(exact.values(), simulation.get("V(out)", Some(2)).unwrap());
+
+    assert!(between_steps(&simulation, "V(out)", "rload", 39.0).is_none());
+    assert!(between_steps(&simulation, "V(out)", "rload", 61.0).is_none());
+    assert!(between_steps(&simulation, "V(out)", "temp", 42.0).is_none());
+    assert!(between_steps(&simulation, "V(missing)", "rload", 42.0).is_none());
+}
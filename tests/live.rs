@@ -0,0 +1,37 @@
+/*
+ * This file contains the tests of the async stream of a raw file being written
+ */
+#![cfg(feature = "tokio")]
+
+use std::fs;
+use std::future::poll_fn;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures_core::Stream;
+use ltspice::stream::RawStream;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn live_stream_reads_every_point_off_the_executor() {
+    let path: PathBuf = std::env::temp_dir().join(format!("ltspice-live-{}.raw", std::process::id()));
+    let bytes = generate_raw(&RawSpec::new().mode(Mode::Transient).points(5000));
+    fs::write(&path, &bytes).unwrap();
+    let expected = SteppedSimulation::from_bytes(&bytes).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    let mut live = RawStream::open(&path).unwrap().select(&["V(n001)"]).unwrap().live(Duration::from_millis(10));
+    let mut values = Vec::new();
+    runtime.block_on(async {
+        while let Some(chunk) = poll_fn(|context| Pin::new(&mut live).poll_next(context)).await {
+            values.extend_from_slice(&chunk.values[0]);
+        }
+    });
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(&values, expected.get("V(n001)", Some(0)).unwrap());
+}
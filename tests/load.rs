@@ -0,0 +1,101 @@
+/*
+ * This file contains the tests of the load options and reports
+ */
+
+use std::fs;
+
+use ltspice::options::{CancelToken, LoadOptions, Precision};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, VariableClass};
+
+/* #### Tests #### */
+
+#[test]
+fn cancelled_loads_fail_without_panicking() {
+    let bytes = generate_raw(&RawSpec::new().steps(4).points(500).variables(3));
+    let token = CancelToken::new();
+    token.cancel();
+    let options = LoadOptions::new().cancel_token(token.clone());
+    let error = SteppedSimulation::from_bytes_with_options(&bytes, options.clone()).unwrap_err();
+    assert!(error.to_string().contains("Cancelled"));
+
+    // From a file, read or memory mapped
+    let path = std::env::temp_dir().join(format!("ltspice-load-cancel-{}.raw", std::process::id()));
+    fs::write(&path, &bytes).unwrap();
+    for options in [options.clone(), options.memory_limit(1 << 20)] {
+        let mut simulation = SteppedSimulation::new(path.clone());
+        simulation.set_load_options(options);
+        assert!(simulation.reload().unwrap_err().to_string().contains("Cancelled"));
+    }
+
+    // The same token loads again once reset
+    token.reset();
+    let mut simulation = SteppedSimulation::new(path.clone());
+    simulation.set_load_options(LoadOptions::new().cancel_token(token));
+    let result = simulation.reload();
+    fs::remove_file(&path).unwrap();
+    result.unwrap();
+    assert_eq!(simulation.get_step_count(), 4);
+}
+
+#[test]
+fn metrics_report_the_size_and_phases_of_the_last_load() {
+    let simulation = SteppedSimulation::new("unloaded.raw".into());
+    assert_eq!(simulation.metrics().bytes(), 0);
+    assert_eq!(simulation.metrics().throughput(), None);
+
+    let bytes = generate_raw(&RawSpec::new().steps(2).points(2000).variables(4));
+    let simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let metrics = simulation.metrics();
+    assert_eq!(metrics.bytes(), bytes.len() as u64);
+    let phases = metrics.read_time() + metrics.header_time() + metrics.decode_time() + metrics.log_time();
+    assert_eq!(metrics.total_time(), phases);
+    assert!(metrics.decode_time() > std::time::Duration::ZERO);
+    let seconds = (metrics.header_time() + metrics.decode_time()).as_secs_f64();
+    assert_eq!(metrics.throughput(), Some(bytes.len() as f64 / seconds));
+
+    // A reload replaces the metrics of the previous load
+    let path = std::env::temp_dir().join(format!("ltspice-load-metrics-{}.raw", std::process::id()));
+    fs::write(&path, &bytes).unwrap();
+    let mut simulation = SteppedSimulation::new(path.clone());
+    simulation.reload().unwrap();
+    let smaller = generate_raw(&RawSpec::new().points(10).variables(1));
+    fs::write(&path, &smaller).unwrap();
+    let result = simulation.reload();
+    fs::remove_file(&path).unwrap();
+    result.unwrap();
+    assert_eq!(simulation.metrics().bytes(), smaller.len() as u64);
+}
+
+#[test]
+fn summary_lists_the_shape_of_every_variable() {
+    let bytes = generate_raw(&RawSpec::new().steps(3).points(100).variables(2));
+    let mut simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let steps = (0..3).map(|step| simulation.get("V(n001)", Some(step)).unwrap().to_vec()).collect();
+    simulation.insert_derived("V(d)", steps).unwrap();
+    let summary = simulation.summary();
+    assert_eq!(summary.mode, Mode::Transient);
+    assert_eq!(summary.steps, 3);
+    assert_eq!(summary.points_per_step, vec![100, 100, 100]);
+    let names: Vec<&str> = summary.variables.iter().map(|variable| variable.name.as_str()).collect();
+    assert_eq!(names, vec!["V(n001)", "V(n002)", "V(d)"]);
+    assert_eq!(summary.variables[2].class, VariableClass::Voltage);
+    assert!(summary.variables[2].derived && !summary.variables[0].derived);
+    assert!(summary.variables.iter().all(|variable| variable.steps == 3 && variable.points == 300));
+    assert_eq!(
+        summary.to_string(),
+        "Transient analysis, 3 steps, 300 points\n  V(n001) (Voltage): 3 steps, 300 points\n  \
+         V(n002) (Voltage): 3 steps, 300 points\n  V(d) (Voltage): 3 steps, 300 points, derived\n"
+    );
+
+    // Variables without data are listed with no steps, and previews are flagged
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().x_only(true)).unwrap();
+    let summary = simulation.summary();
+    assert_eq!(summary.points_per_step, vec![100, 100, 100]);
+    assert!(summary.variables.iter().all(|variable| variable.steps == 0 && variable.points == 0));
+    let options = LoadOptions::new().preview(Precision::Float32);
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+    let summary = simulation.summary();
+    assert!(summary.variables.iter().all(|variable| variable.preview && variable.points == 300));
+    assert!(summary.to_string().contains("V(n002) (Voltage): 3 steps, 300 points, preview"));
+}
@@ -0,0 +1,69 @@
+/*
+ * This file contains the tests of the cache budget of the lazy columns
+ */
+
+use ltspice::options::{LoadOptions, Precision};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Constants #### */
+
+// A step of 500 samples of 16 bytes
+const STEP: usize = 500 * 16;
+
+/* #### Functions #### */
+
+fn preview() -> SteppedSimulation {
+    let bytes = generate_raw(&RawSpec::new().steps(2).points(500).variables(3));
+    return SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().preview(Precision::Float32)).unwrap();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn trim_cache_evicts_the_least_recently_used_columns() {
+    let mut simulation = preview();
+    for name in ["V(n001)", "V(n002)", "V(n003)"] {
+        assert!(simulation.get(name, Some(0)).is_some());
+    }
+    assert_eq!(simulation.cache_usage(), 3 * STEP);
+
+    // Without a budget nothing is evicted
+    assert_eq!(simulation.trim_cache(), 0);
+    assert_eq!(simulation.cache_usage(), 3 * STEP);
+
+    // V(n001) is accessed again, V(n002) is now the oldest
+    assert!(simulation.get("V(n001)", Some(1)).is_some());
+    simulation.set_cache_budget(2 * STEP);
+    assert_eq!(simulation.cache_usage(), 2 * STEP);
+    assert_eq!(simulation.trim_cache(), 0);
+    assert_eq!(simulation.get_cache_budget(), Some(2 * STEP));
+
+    // Another step goes over the budget until the next trim, which frees the oldest column
+    assert!(simulation.get("V(n002)", Some(1)).is_some());
+    assert_eq!(simulation.cache_usage(), 3 * STEP);
+    assert_eq!(simulation.trim_cache(), 2 * STEP);
+    assert_eq!(simulation.cache_usage(), STEP);
+
+    // Evicted steps are decoded again on access
+    assert_eq!(simulation.get("V(n001)", Some(0)).unwrap().len(), 500);
+}
+
+#[test]
+fn fetch_keeps_the_budget_on_access() {
+    let mut simulation = preview();
+    simulation.set_cache_budget(2 * STEP);
+    for step in 0..2 {
+        for name in ["V(n001)", "V(n002)", "V(n003)"] {
+            assert_eq!(simulation.fetch(name, Some(step)).unwrap().unwrap().len(), 500);
+            assert!(simulation.cache_usage() <= 2 * STEP);
+        }
+    }
+
+    // The steps of the column fetched are kept, the other columns are evicted
+    assert!(simulation.fetch("V(n003)", Some(0)).unwrap().is_some());
+    assert_eq!(simulation.cache_usage(), 2 * STEP);
+    assert!(simulation.fetch("V(n004)", Some(0)).unwrap().is_none());
+    let x = simulation.get("x", Some(1)).map(|x| x.to_vec());
+    assert_eq!(simulation.fetch("x", Some(1)).unwrap(), x.as_deref());
+}
@@ -0,0 +1,52 @@
+/*
+ * This file contains the tests of the markers drawn on generated figures
+ */
+
+use ltspice::compare::overlay;
+use ltspice::marker::Marker;
+use ltspice::report::{Report, ReportFormat};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Loads a generated simulation with a marker on V(n001), at the middle of its x axis.
+fn marked(spec: &RawSpec) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(spec)).unwrap();
+    let trace = simulation.trace("V(n001)", 0).unwrap();
+    let x = trace.x()[trace.len() / 2].real();
+    let marker = Marker::at(&trace, x, "checkpoint").unwrap();
+    simulation.add_marker(marker);
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn plots_draw_the_markers_of_their_traces() {
+    let simulation = marked(&RawSpec::new().points(200));
+    let traces = [simulation.trace("V(n001)", 0).unwrap(), simulation.trace("V(n002)", 0).unwrap()];
+    assert_eq!(traces[0].markers().count(), 1);
+    assert_eq!(traces[1].markers().count(), 0);
+
+    let html = Report::new("report").plot("outputs", &traces).render(ReportFormat::Html);
+    assert_eq!(html.matches(">checkpoint</text>").count(), 1);
+}
+
+#[test]
+fn bode_plots_draw_the_markers_on_both_curves() {
+    let simulation = marked(&RawSpec::new().mode(Mode::AC).points(200));
+    let html = Report::new("report")
+        .bode("response", &[simulation.trace("V(n001)", 0).unwrap()])
+        .render(ReportFormat::Html);
+    assert_eq!(html.matches(">checkpoint</text>").count(), 2);
+}
+
+#[test]
+fn overlays_draw_the_markers_of_every_simulation() {
+    let spec = RawSpec::new().points(200);
+    let simulations = [marked(&spec), marked(&spec.clone().seed(2))];
+    let overlay = overlay(&simulations, "V(n001)").unwrap();
+    assert_eq!(overlay.markers.len(), 2);
+    assert_eq!(overlay.svg(640, 480).matches("<circle").count(), 2);
+}
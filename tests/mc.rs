@@ -0,0 +1,54 @@
+/*
+ * This file contains the tests of the Monte Carlo yield estimation
+ */
+
+use ltspice::mc::{histogram, yield_estimate, SpecLimits};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Tests #### */
+
+#[test]
+fn histogram_bins_span_the_range() {
+    let histogram = histogram(&[0.0, 1.0, 2.0, 3.0, 4.0, f64::NAN], 4);
+    assert_eq!(histogram.edges, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    // The maximum falls in the last bin, NaN is ignored
+    assert_eq!(histogram.counts, vec![1, 1, 1, 2]);
+
+    let constant = ltspice::mc::histogram(&[5.0, 5.0], 3);
+    assert_eq!(constant.counts, vec![2, 0, 0]);
+    assert_eq!(ltspice::mc::histogram(&[], 0).counts, vec![0]);
+}
+
+#[test]
+fn yield_and_cpk_of_known_runs() {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(10).points(4))).unwrap();
+    // Runs 0..9 give 1..10, run 9 cannot be evaluated
+    let metric = |step: u16| match step {
+        9 => None,
+        step => Some(step as f64 + 1.0),
+    };
+
+    let estimate = yield_estimate(&simulation, metric, SpecLimits::new(Some(2.0), Some(8.0))).unwrap();
+    assert_eq!(estimate.samples, 9);
+    assert_eq!(estimate.passing, 7);
+    assert!((estimate.pass_rate() - 7.0 / 9.0).abs() < 1e-12);
+    assert_eq!(estimate.mean, 5.0);
+    // Sample deviation of 1..9: √7.5
+    assert!((estimate.std_dev - 7.5f64.sqrt()).abs() < 1e-12);
+    let cpk = 3.0 / (3.0 * 7.5f64.sqrt());
+    assert!((estimate.cpk.unwrap() - cpk).abs() < 1e-12);
+    // Sturges' rule: ⌈log2(9)⌉ + 1 bins
+    assert_eq!(estimate.histogram.counts.len(), 5);
+    assert_eq!(estimate.histogram.counts.iter().sum::<usize>(), 9);
+
+    let open = yield_estimate(&simulation, metric, SpecLimits::default()).unwrap();
+    assert_eq!(open.passing, 9);
+    assert_eq!(open.cpk, None);
+    let upper = yield_estimate(&simulation, metric, SpecLimits::new(None, Some(11.0))).unwrap();
+    assert!((upper.cpk.unwrap() - 6.0 / (3.0 * 7.5f64.sqrt())).abs() < 1e-12);
+
+    assert!(yield_estimate(&simulation, |_| None, SpecLimits::default()).is_none());
+    assert!(SpecLimits::new(Some(1.0), None).contains(1.0));
+    assert!(!SpecLimits::new(None, Some(1.0)).contains(1.5));
+}
@@ -0,0 +1,117 @@
+/*
+ * This file contains the tests of the measurements of transient traces
+ */
+
+use std::f64::consts::PI;
+
+use ltspice::measure::{energy, imd, tempco, REFERENCE_TEMPERATURE};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Loads a transient trace "V(out)" over the 1 ms of the generated transient.
+fn signal(function: impl Fn(f64) -> f64) -> SteppedSimulation {
+    return sampled(16384, function);
+}
+
+// Same as `signal`, with the specified number of points.
+fn sampled(points: usize, function: impl Fn(f64) -> f64) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(points))).unwrap();
+    let values = simulation
+        .get("x", Some(0))
+        .unwrap()
+        .iter()
+        .map(|t| Value::from(function(t.real())))
+        .collect();
+    simulation.insert_derived("V(out)", vec![values]).unwrap();
+    return simulation;
+}
+
+fn tone(frequency: f64, amplitude: f64, t: f64) -> f64 {
+    return amplitude * (2.0 * PI * frequency * t).sin();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn smpte_sidebands_are_relative_to_the_hf_tone() {
+    // 10 kHz and 200 kHz at 4:1, with sidebands at f2 ± f1 of 1% of the HF tone each
+    let simulation = signal(|t| {
+        tone(10e3, 1.0, t) + tone(200e3, 0.25, t) + tone(190e3, 0.0025, t) + tone(210e3, 0.0025, t)
+    });
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    let imd = imd(&trace, 10e3, 200e3, 3).unwrap();
+    assert!((imd - 0.01 * 2f64.sqrt()).abs() < 1e-4, "{}", imd);
+}
+
+#[test]
+fn ccif_products_are_relative_to_one_tone() {
+    // 190 kHz and 200 kHz of equal level, with a 10 kHz difference product of 1% of a tone
+    let simulation = signal(|t| tone(190e3, 0.5, t) + tone(200e3, 0.5, t) + tone(10e3, 0.005, t));
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    let imd = imd(&trace, 190e3, 200e3, 2).unwrap();
+    assert!((imd - 0.01).abs() < 1e-4, "{}", imd);
+}
+
+#[test]
+fn linear_tones_have_no_imd() {
+    let simulation = signal(|t| tone(10e3, 1.0, t) + tone(200e3, 0.25, t));
+    let products = imd(&simulation.trace("V(out)", 0).unwrap(), 10e3, 200e3, 3).unwrap();
+    assert!(products < 1e-4, "{}", products);
+
+    let silence = signal(|_| 0.0);
+    assert_eq!(imd(&silence.trace("V(out)", 0).unwrap(), 10e3, 200e3, 3), None);
+}
+
+#[test]
+fn energy_is_integrated_on_the_union_of_the_time_points() {
+    // 2 V and a current ramping as 1000·t on other time points: 2000·t W, 1000·t² J
+    let voltage = sampled(101, |_| 2.0);
+    let current = sampled(37, |t| 1000.0 * t);
+    let (v, i) = (voltage.trace("V(out)", 0).unwrap(), current.trace("V(out)", 0).unwrap());
+    let joules = |t: f64| 1000.0 * t * t;
+
+    assert!((energy(&v, &i, None).unwrap() - joules(1e-3)).abs() < 1e-12);
+    let within = energy(&v, &i, Some((0.3e-3, 0.7e-3))).unwrap();
+    assert!((within - (joules(0.7e-3) - joules(0.3e-3))).abs() < 1e-12, "{}", within);
+    assert_eq!(energy(&v, &i, Some((2e-3, 3e-3))), Some(0.0));
+
+    // Only the overlap of the traces counts
+    let mut late = sampled(37, |t| 1000.0 * t);
+    late.trim_before(0.5e-3).unwrap();
+    let overlap = energy(&v, &late.trace("V(out)", 0).unwrap(), None).unwrap();
+    let start = late.get("x", None).unwrap()[0].real();
+    assert!((overlap - (joules(1e-3) - joules(start))).abs() < 1e-12, "{}", overlap);
+    late.trim_before(1.0).unwrap();
+    assert_eq!(energy(&v, &late.trace("V(out)", 0).unwrap(), None), None);
+}
+
+#[test]
+fn tempco_fits_a_temperature_sweep() {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(4).points(10))).unwrap();
+    simulation.parse_log(".step temp=-40\n.step temp=25\n.step temp=85\n.step temp=125\n");
+    let temperature = |step: u16| simulation.get_step_temperature(step).unwrap() - REFERENCE_TEMPERATURE;
+
+    // 1.2 V drifting by 3 mV/°C and 10 µV/°C² around 25 °C
+    let quadratic = |step: u16| {
+        let dt = temperature(step);
+        return Some(1.2 + 3e-3 * dt + 1e-5 * dt * dt);
+    };
+    let fit = tempco(&simulation, quadratic, true).unwrap();
+    assert_eq!(fit.reference, 25.0);
+    assert!((fit.value - 1.2).abs() < 1e-9, "{:?}", fit);
+    assert!((fit.linear - 3e-3).abs() < 1e-9, "{:?}", fit);
+    assert!((fit.quadratic - 1e-5).abs() < 1e-12, "{:?}", fit);
+
+    // A linear fit has no quadratic term, steps without a value are skipped
+    let linear = |step: u16| Some(1.2 - 2e-3 * temperature(step)).filter(|_| step != 2);
+    let fit = tempco(&simulation, linear, false).unwrap();
+    assert!((fit.value - 1.2).abs() < 1e-9 && (fit.linear + 2e-3).abs() < 1e-9, "{:?}", fit);
+    assert_eq!(fit.quadratic, 0.0);
+
+    // Not enough temperatures for the fit, or none known at all
+    assert!(tempco(&simulation, |step| quadratic(step).filter(|_| step < 2), true).is_none());
+    simulation.parse_log(".step r=1\n.step r=2\n.step r=3\n.step r=4\n");
+    assert!(tempco(&simulation, |_| Some(1.0), false).is_none());
+}
@@ -0,0 +1,44 @@
+/*
+ * This file contains the tests of the conversions to nalgebra matrices
+ */
+#![cfg(feature = "nalgebra")]
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, SteppedSimulation, Value};
+
+/* #### Tests #### */
+
+#[test]
+fn columns_are_the_x_axis_then_the_variables() {
+    let spec = RawSpec::new().steps(2).points(12).variables(2).encoding(Encoding::UTF8);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+
+    let matrix = simulation.to_dmatrix(1).unwrap();
+    assert_eq!(matrix.shape(), (12, 3));
+    for (column, name) in ["x", "V(n001)", "V(n002)"].iter().enumerate() {
+        let expected: Vec<f64> = simulation.get(name, Some(1)).unwrap().iter().map(|v| v.real()).collect();
+        assert_eq!(matrix.column(column).iter().copied().collect::<Vec<f64>>(), expected);
+    }
+    assert!(simulation.to_dmatrix(2).is_none());
+
+    let selected = simulation.to_dmatrix_of(&["V(n002)", "x"], 1).unwrap();
+    assert_eq!(selected.column(0), matrix.column(2));
+    assert_eq!(selected.column(1), matrix.column(0));
+    assert!(simulation.to_dmatrix_of(&["V(none)"], 0).is_none());
+}
+
+#[test]
+fn least_squares_recovers_a_linear_model() {
+    // y = 3 * x + 2, identified from the regressors [x, 1]
+    let spec = RawSpec::new().points(50).variables(1).encoding(Encoding::UTF8);
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    let x = simulation.get("x", None).unwrap().to_vec();
+    simulation.insert_derived("V(y)", vec![x.iter().map(|x| Value::from(3.0 * x.real() + 2.0)).collect()]).unwrap();
+    simulation.insert_derived("one", vec![vec![Value::from(1.0); x.len()]]).unwrap();
+
+    let regressors = simulation.to_dmatrix_of(&["x", "one"], 0).unwrap();
+    let y = simulation.to_dmatrix_of(&["V(y)"], 0).unwrap();
+    let solution = regressors.svd(true, true).solve(&y, 1e-12).unwrap();
+    assert!((solution[0] - 3.0).abs() < 1e-9, "{}", solution);
+    assert!((solution[1] - 2.0).abs() < 1e-9, "{}", solution);
+}
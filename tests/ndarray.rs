@@ -0,0 +1,53 @@
+/*
+ * This file contains the tests of the conversions to ndarray arrays
+ */
+#![cfg(feature = "ndarray")]
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn columns_are_the_x_axis_then_the_variables() {
+    let spec = RawSpec::new().steps(2).points(15).variables(2).encoding(Encoding::UTF8);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    assert_eq!(simulation.column_names(), vec!["x", "V(n001)", "V(n002)"]);
+
+    let array = simulation.to_ndarray(1).unwrap();
+    assert_eq!(array.dim(), (15, 3));
+    for (column, name) in simulation.column_names().iter().enumerate() {
+        let expected: Vec<f64> = simulation.get(name, Some(1)).unwrap().iter().map(|v| v.real()).collect();
+        assert_eq!(array.column(column).to_vec(), expected);
+    }
+    assert!(simulation.to_ndarray(2).is_none());
+
+    let stacked = simulation.to_ndarray3().unwrap();
+    assert_eq!(stacked.dim(), (2, 15, 3));
+    assert_eq!(stacked.index_axis(ndarray::Axis(0), 1), array);
+    assert_eq!(stacked.index_axis(ndarray::Axis(0), 0), simulation.to_ndarray(0).unwrap());
+}
+
+#[test]
+fn steps_of_different_lengths_are_not_stacked() {
+    // A stepped DC sweep of V1 restarting at 0 after three then two points, V(out) = 2 * V1
+    let sweep = [0.0f64, 1.0, 2.0, 0.0, 1.0];
+    let mut bytes = format!(
+        "Title: * dc.asc\nDate: Thu Jan  1 00:00:00 1970\nPlotname: DC transfer characteristic\n\
+         Flags: real forward double stepped\nNo. Variables: 2\nNo. Points: {}\nOffset:   0.0000000000000000e+000\n\
+         Command: Test\nVariables:\n\t0\tv1\tvoltage\n\t1\tV(out)\tvoltage\nBinary:\n",
+        sweep.len()
+    )
+    .into_bytes();
+    for x in sweep.iter() {
+        bytes.extend_from_slice(&x.to_le_bytes());
+        bytes.extend_from_slice(&(2.0 * x).to_le_bytes());
+    }
+    let simulation = SteppedSimulation::from_bytes(&bytes).unwrap();
+    assert_eq!(simulation.get_step_count(), 2);
+
+    assert!(simulation.to_ndarray3().is_none());
+    let array = simulation.to_ndarray(1).unwrap();
+    assert_eq!(array.dim(), (2, 2));
+    assert_eq!(array.row(1).to_vec(), vec![1.0, 2.0]);
+}
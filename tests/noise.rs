@@ -0,0 +1,64 @@
+/*
+ * This file contains the tests of the noise figure computation
+ */
+
+use ltspice::noise::{figure, spot, thermal_density};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Unit, Value};
+
+/* #### Constants #### */
+
+// 290 K, the reference temperature of noise figures
+const REFERENCE: f64 = 16.85;
+
+/* #### Functions #### */
+
+// Loads a two-step noise analysis whose input referred noise is `factor` times the thermal
+// noise of a 50 Ω source, per step.
+fn simulation(mode: Mode, factors: [f64; 2]) -> SteppedSimulation {
+    let spec = RawSpec::new().mode(mode).steps(2).points(100);
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    let density = thermal_density(50.0, REFERENCE);
+    let steps = factors
+        .iter()
+        .enumerate()
+        .map(|(step, factor)| vec![Value::from(density * factor); simulation.get("x", Some(step as u16)).unwrap().len()])
+        .collect();
+    simulation.insert_derived("V(inoise)", steps).unwrap();
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn thermal_noise_of_a_resistor() {
+    // 4·k·T·R at 290 K: 0.895 nV/√Hz for 50 Ω, 4.0 nV/√Hz for 1 kΩ
+    assert!((thermal_density(50.0, REFERENCE) - 0.8948e-9).abs() < 1e-13);
+    assert!((thermal_density(1e3, REFERENCE) - 4.0017e-9).abs() < 1e-12);
+    assert_eq!(thermal_density(0.0, 25.0), 0.0);
+}
+
+#[test]
+fn noise_figure_of_every_step() {
+    // A noiseless amplifier has a 0 dB noise figure, doubling the noise power gives 3 dB
+    let simulation = simulation(Mode::Noise, [1.0, 2f64.sqrt()]);
+    let figures = figure(&simulation, 50.0, REFERENCE);
+    assert_eq!(figures.len(), 2);
+    assert_eq!(figures[0].name(), "NF");
+    assert_eq!(figures[0].as_trace().unit(), Unit::Decibel);
+    assert!(figures[0].values().iter().all(|nf| nf.real().abs() < 1e-9));
+    assert!(figures[1].values().iter().all(|nf| (nf.real() - 3.0103).abs() < 1e-4));
+
+    // Spot values, None outside of the swept frequencies
+    let frequency = figures[1].x()[10].real();
+    let spots = spot(&figures[1].as_trace(), &[frequency, 1e-3]);
+    assert!((spots[0].unwrap() - 3.0103).abs() < 1e-4);
+    assert_eq!(spots[1], None);
+}
+
+#[test]
+fn other_analyses_have_no_noise_figure() {
+    assert!(figure(&simulation(Mode::AC, [1.0, 1.0]), 50.0, REFERENCE).is_empty());
+    let plain = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().mode(Mode::Noise).points(10))).unwrap();
+    assert!(figure(&plain, 50.0, REFERENCE).is_empty());
+}
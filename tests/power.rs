@@ -0,0 +1,131 @@
+/*
+ * This file contains the tests of the switching-loss breakdown of power devices
+ */
+
+use ltspice::power::{dead_time, switching_losses, Commutation, Thresholds};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+// Fixture switch: 100 V, 10 A, 10 mΩ, switching at 10 kHz with 1 µs linear edges
+const VOLTAGE: f64 = 100.0;
+const CURRENT: f64 = 10.0;
+const RESISTANCE: f64 = 0.01;
+const PERIOD: f64 = 100e-6;
+const EDGE: f64 = 1e-6;
+
+/* #### Functions #### */
+
+// A derived signal: its name and value as a function of time
+type Signal<'a> = (&'a str, fn(f64) -> f64);
+
+// Loads the 1 ms of the generated transient, sampled every 10 ns, with derived signals.
+fn simulation(signals: &[Signal]) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(100_001))).unwrap();
+    for (name, signal) in signals.iter() {
+        let values = simulation.get("x", Some(0)).unwrap().iter().map(|t| Value::from(signal(t.real()))).collect();
+        simulation.insert_derived(name, vec![values]).unwrap();
+    }
+    return simulation;
+}
+
+// Position of a hard-switched edge of the switch (0 off, 1 on): turning on 10 µs into every
+// period, on for 40 µs, with linear edges.
+fn conduction(t: f64) -> f64 {
+    let t = t % PERIOD - 10e-6;
+    return (t / EDGE).min((40e-6 + EDGE - t) / EDGE).clamp(0.0, 1.0);
+}
+
+// A gate of 10 V with 100 ns edges, on during the (start, end) intervals.
+fn gate(t: f64, intervals: &[(f64, f64)]) -> f64 {
+    let edge = 100e-9;
+    return intervals
+        .iter()
+        .map(|(start, end)| 10.0 * ((t - start) / edge).min(1.0 - (t - end) / edge).clamp(0.0, 1.0))
+        .fold(0.0, f64::max);
+}
+
+// Half bridge gates: high-side on from 10 µs to 50 µs, low-side from 52 µs to 109 µs of every
+// period, except the fifth period where the low side turns on at 49 µs, overlapping the high side.
+fn high_gate(t: f64) -> f64 {
+    let intervals: Vec<(f64, f64)> = (0..10).map(|k| (k as f64 * PERIOD + 10e-6, k as f64 * PERIOD + 50e-6)).collect();
+    return gate(t, &intervals);
+}
+
+fn low_gate(t: f64) -> f64 {
+    let intervals: Vec<(f64, f64)> = (0..10)
+        .map(|k| {
+            let start = if k == 4 { 49e-6 } else { 52e-6 };
+            return (k as f64 * PERIOD + start, k as f64 * PERIOD + 109e-6);
+        })
+        .collect();
+    return gate(t, &intervals);
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!((actual - expected).abs() <= tolerance * expected.abs(), "{} != {}", actual, expected);
+}
+
+/* #### Tests #### */
+
+#[test]
+fn hard_switched_losses_per_cycle() {
+    let simulation = simulation(&[
+        ("V(d)", |t| {
+            let on = conduction(t);
+            return VOLTAGE * (1.0 - on) + RESISTANCE * CURRENT * on;
+        }),
+        ("Id(M1)", |t| CURRENT * conduction(t)),
+    ]);
+    let losses = switching_losses(
+        &simulation.trace("V(d)", 0).unwrap(),
+        &simulation.trace("Id(M1)", 0).unwrap(),
+        Thresholds::new(1.0, 0.1),
+    )
+    .unwrap();
+
+    // Linear edges dissipate ∫ v·i dt = (V·I/6 + R·I²/3)·t, conduction R·I² over 39 µs
+    let edge = (VOLTAGE * CURRENT / 6.0 + RESISTANCE * CURRENT * CURRENT / 3.0) * EDGE;
+    let conduction = RESISTANCE * CURRENT * CURRENT * 39e-6;
+    assert_eq!(losses.cycles.len(), 9);
+    for cycle in losses.cycles.iter() {
+        assert_close(cycle.end - cycle.start, PERIOD, 2e-4);
+        assert_close(cycle.turn_on, edge, 2e-3);
+        assert_close(cycle.turn_off, edge, 2e-3);
+        assert_close(cycle.conduction, conduction, 2e-2);
+    }
+    assert_close(losses.turn_on, edge / PERIOD, 2e-3);
+    assert_close(losses.total(), (2.0 * edge + conduction) / PERIOD, 1e-2);
+}
+
+#[test]
+fn half_bridge_dead_times_and_shoot_through() {
+    let simulation = simulation(&[("V(gh)", high_gate), ("V(gl)", low_gate)]);
+    let results = dead_time(&simulation, "V(gh)", "V(gl)", 5.0);
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+
+    let high_to_low: Vec<f64> = result
+        .edges
+        .iter()
+        .filter(|edge| edge.commutation == Commutation::HighToLow)
+        .map(|edge| edge.dead_time())
+        .collect();
+    let low_to_high: Vec<f64> = result
+        .edges
+        .iter()
+        .filter(|edge| edge.commutation == Commutation::LowToHigh)
+        .map(|edge| edge.dead_time())
+        .collect();
+    assert_eq!(high_to_low.len(), 9);
+    assert_eq!(low_to_high.len(), 9);
+    assert!(high_to_low.iter().all(|dead_time| (dead_time - 2e-6).abs() < 1e-12));
+    assert!(low_to_high.iter().all(|dead_time| (dead_time - 1e-6).abs() < 1e-12));
+    assert!((result.minimum().unwrap() - 1e-6).abs() < 1e-12);
+
+    // Both gates cross the threshold 50 ns into their edges
+    assert_eq!(result.shoot_through.len(), 1);
+    let (start, end) = result.shoot_through[0];
+    assert!((start - 449.05e-6).abs() < 1e-12 && (end - 450.05e-6).abs() < 1e-12, "{:?}", (start, end));
+}
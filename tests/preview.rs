@@ -0,0 +1,76 @@
+/*
+ * This file contains the tests of the preview precision
+ */
+
+use ltspice::options::{LoadOptions, Precision};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn reading_a_step_only_expands_this_step() {
+    let bytes = generate_raw(&RawSpec::new().steps(10).points(1000).variables(2));
+    let options = LoadOptions::new().preview(Precision::Float32);
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+    assert_eq!(simulation.cache_usage(), 0);
+
+    // 1000 samples of 16 bytes
+    assert_eq!(simulation.trace("V(n001)", 3).unwrap().values().len(), 1000);
+    assert_eq!(simulation.cache_usage(), 16_000);
+    simulation.get("V(n001)", Some(3)).unwrap();
+    simulation.get("V(n002)", Some(9)).unwrap();
+    assert_eq!(simulation.cache_usage(), 32_000);
+}
+
+#[test]
+fn expanded_steps_are_evicted() {
+    let bytes = generate_raw(&RawSpec::new().steps(4).points(1000).variables(2));
+    let full = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let options = LoadOptions::new().preview(Precision::Float32);
+    let mut simulation = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+    for step in 0..4 {
+        simulation.get("V(n001)", Some(step)).unwrap();
+    }
+    simulation.set_cache_budget(0);
+    assert_eq!(simulation.cache_usage(), 0);
+
+    // Expanded again on the next access
+    let expected = full.get("V(n001)", Some(2)).unwrap();
+    let values = simulation.get("V(n001)", Some(2)).unwrap();
+    for (value, expected) in values.iter().zip(expected.iter()) {
+        assert!((value.real() - expected.real()).abs() <= expected.real().abs() * 1e-6);
+    }
+}
+
+#[test]
+fn non_finite_samples_survive_int16() {
+    let points = 10;
+    let mut bytes = generate_raw(&RawSpec::new().encoding(Encoding::UTF8).steps(2).points(points).variables(1));
+    let start = bytes.windows(8).position(|window| window == b"Binary:\n").unwrap() + 8;
+    let point_size = (bytes.len() - start) / (2 * points);
+
+    // The x axis is a double, followed by the float sample of V(n001)
+    for (point, value) in [(3, f32::NAN), (5, f32::INFINITY), (6, f32::NEG_INFINITY)] {
+        let offset = start + point * point_size + 8;
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    let full = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let options = LoadOptions::new().preview(Precision::Int16);
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+
+    let values = simulation.get("V(n001)", Some(0)).unwrap();
+    assert!(values[3].real().is_nan());
+    assert_eq!(values[5].real(), f64::INFINITY);
+    assert_eq!(values[6].real(), f64::NEG_INFINITY);
+    for point in [0, 1, 2, 4, 7, 8, 9] {
+        assert_eq!(values[point].real(), full.get("V(n001)", Some(0)).unwrap()[point].real());
+    }
+
+    // The finite step stays quantized
+    let expected = full.get("V(n001)", Some(1)).unwrap();
+    let range = expected.iter().map(|value| value.real().abs()).fold(0.0, f64::max);
+    for (value, expected) in simulation.get("V(n001)", Some(1)).unwrap().iter().zip(expected.iter()) {
+        assert!((value.real() - expected.real()).abs() <= range / 30_000.0);
+    }
+}
@@ -0,0 +1,69 @@
+/*
+ * This file contains the tests of the cycle-by-cycle PWM analysis
+ */
+
+use ltspice::pwm::{analyze, analyze_with_threshold, duty, frequency};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{SteppedSimulation, Unit, Value};
+
+/* #### Constants #### */
+
+// Fixture PWM: 20 kHz, 0 to 12 V, with the duty ramping from 20% to 70% over 1 ms
+const PERIOD: f64 = 50e-6;
+
+/* #### Functions #### */
+
+fn target(t: f64) -> f64 {
+    return 0.2 + 0.5 * (t - t % PERIOD) / 1e-3;
+}
+
+// Loads the 1 ms of the generated transient, sampled every 10 ns, with the PWM signal.
+fn simulation() -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(100_001))).unwrap();
+    let values = simulation
+        .get("x", Some(0))
+        .unwrap()
+        .iter()
+        .map(|t| Value::from(if t.real() % PERIOD < target(t.real()) * PERIOD { 12.0 } else { 0.0 }))
+        .collect();
+    simulation.insert_derived("V(sw)", vec![values]).unwrap();
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn cycles_follow_the_duty_ramp() {
+    let simulation = simulation();
+    let trace = simulation.trace("V(sw)", 0).unwrap();
+    let cycles = analyze(&trace);
+
+    // The first edge is at t = 0, where the crossing needs a previous point, and the last
+    // cycle is incomplete
+    assert!((18..=19).contains(&cycles.len()), "{}", cycles.len());
+    for cycle in cycles.iter() {
+        assert!((cycle.period - PERIOD).abs() < 2e-8, "{:?}", cycle);
+        assert!((cycle.frequency() - 2e4).abs() < 10.0);
+        assert!((cycle.duty - target(cycle.start + PERIOD / 2.0)).abs() < 1e-3, "{:?}", cycle);
+        assert!((cycle.on_time() - cycle.duty * cycle.period).abs() < 1e-15);
+    }
+    assert_eq!(analyze_with_threshold(&trace, 6.0), cycles);
+    assert!(analyze_with_threshold(&trace, 20.0).is_empty());
+}
+
+#[test]
+fn duty_and_frequency_traces() {
+    let simulation = simulation();
+    let trace = simulation.trace("V(sw)", 0).unwrap();
+    let cycles = analyze(&trace);
+
+    let duty = duty(&trace);
+    assert_eq!(duty.name(), "duty(V(sw))");
+    assert_eq!(duty.as_trace().unit(), Unit::Dimensionless);
+    assert_eq!(duty.values().len(), cycles.len());
+    assert_eq!(duty.x()[0].real(), cycles[0].start);
+
+    let frequency = frequency(&trace);
+    assert_eq!(frequency.as_trace().unit(), Unit::Hertz);
+    assert!(frequency.values().iter().all(|f| (f.real() - 2e4).abs() < 10.0));
+}
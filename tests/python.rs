@@ -0,0 +1,82 @@
+/*
+ * This file contains the tests of the Python bindings, run in an embedded interpreter
+ *
+ * Only the methods returning Python scalars and lists are covered, the numpy arrays need the
+ * numpy package of the interpreter.
+ */
+
+#![cfg(feature = "python")]
+
+use ltspice::measure;
+use ltspice::python::PySimulation;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+/* #### Functions #### */
+
+fn raw() -> Vec<u8> {
+    return generate_raw(&RawSpec::new().steps(2));
+}
+
+// Runs the code with the class bound to "SteppedSimulation" and the raw file bound to "raw",
+// returning the value of "result".
+fn evaluate<T: for<'py> FromPyObject<'py>>(code: &str) -> PyResult<T> {
+    pyo3::prepare_freethreaded_python();
+    return Python::with_gil(|py| {
+        let locals = PyDict::new(py);
+        locals.set_item("SteppedSimulation", py.get_type::<PySimulation>())?;
+        locals.set_item("raw", PyBytes::new(py, &raw()))?;
+        py.run(code, None, Some(locals))?;
+        return locals.get_item("result")?.unwrap().extract();
+    });
+}
+
+/* #### Tests #### */
+
+#[test]
+fn simulation_properties() {
+    let simulation = SteppedSimulation::from_bytes(&raw()).unwrap();
+    let variables: Vec<String> = evaluate("result = SteppedSimulation.from_bytes(raw).variables").unwrap();
+    let expected: Vec<String> = simulation.get_variables().iter().map(|v| v.name().to_string()).collect();
+    assert_eq!(variables, expected);
+
+    let (steps, mode): (usize, String) =
+        evaluate("s = SteppedSimulation.from_bytes(raw)\nresult = (s.steps, s.mode)").unwrap();
+    assert_eq!(steps, 2);
+    assert_eq!(mode, simulation.get_mode().to_string());
+
+    let repr: String = evaluate("result = repr(SteppedSimulation.from_bytes(raw))").unwrap();
+    assert_eq!(repr, format!("<SteppedSimulation mode={:?} variables=2 steps=2>", simulation.get_mode()));
+    let json: String = evaluate("result = SteppedSimulation.from_bytes(raw).to_json()").unwrap();
+    assert_eq!(json, simulation.to_json());
+}
+
+#[test]
+fn measurements_match_the_rust_api() {
+    let simulation = SteppedSimulation::from_bytes(&raw()).unwrap();
+    let trace = simulation.trace("V(n001)", 1).unwrap();
+
+    let crest: Option<f64> = evaluate("result = SteppedSimulation.from_bytes(raw).crest_factor('V(n001)', step=1)").unwrap();
+    assert_eq!(crest, measure::crest_factor(&trace));
+    let form: Option<f64> = evaluate("result = SteppedSimulation.from_bytes(raw).form_factor('V(n001)', 1)").unwrap();
+    assert_eq!(form, measure::form_factor(&trace));
+
+    let charge: f64 = evaluate("result = SteppedSimulation.from_bytes(raw).charge('V(n001)', 1, start=0.0, end=5e-4)").unwrap();
+    assert_eq!(charge, measure::charge(&trace, Some((0.0, 5e-4))));
+}
+
+#[test]
+fn errors_raise_python_exceptions() {
+    pyo3::prepare_freethreaded_python();
+    let error = evaluate::<f64>("result = SteppedSimulation.from_bytes(raw).charge('V(missing)')").unwrap_err();
+    Python::with_gil(|py| assert!(error.is_instance_of::<PyKeyError>(py), "{}", error));
+
+    let error = evaluate::<f64>("result = SteppedSimulation.from_bytes(b'not a raw file')").unwrap_err();
+    Python::with_gil(|py| assert!(error.is_instance_of::<PyValueError>(py), "{}", error));
+
+    let error = evaluate::<f64>("result = SteppedSimulation('/nonexistent/file.raw')").unwrap_err();
+    Python::with_gil(|py| assert!(error.is_instance_of::<PyValueError>(py), "{}", error));
+}
@@ -0,0 +1,45 @@
+/*
+ * This file contains the tests of the fluent query interface
+ */
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Tests #### */
+
+#[test]
+fn queries_match_the_plain_accessors() {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(3).points(1000))).unwrap();
+
+    let selection = simulation.query().var("V(n001)").step(2).collect().unwrap();
+    assert_eq!(&selection.x, simulation.get("x", Some(2)).unwrap());
+    assert_eq!(&selection.y, simulation.get("V(n001)", Some(2)).unwrap());
+    assert_eq!(selection.points()[10], (selection.x[10].real(), selection.y[10].real()));
+
+    // Without a variable, the x axis is selected
+    let axis = simulation.query().collect().unwrap();
+    assert_eq!(axis.x, axis.y);
+    assert_eq!(axis.len(), 1000);
+
+    assert!(simulation.query().var("V(missing)").collect().is_none());
+    assert!(simulation.query().step(3).collect().is_none());
+}
+
+#[test]
+fn ranges_and_downsampling_compose() {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(1000))).unwrap();
+
+    // Reversed bounds are accepted
+    let range = simulation.query().var("V(n001)").between(0.6e-3, 0.2e-3).collect().unwrap();
+    assert!(!range.is_empty());
+    assert!(range.x.iter().all(|x| x.real() >= 0.2e-3 && x.real() <= 0.6e-3));
+
+    let reduced = simulation.query().var("V(n001)").between(0.2e-3, 0.6e-3).downsample(50).collect().unwrap();
+    assert!(reduced.len() <= 50);
+    assert!(reduced.x.windows(2).all(|pair| pair[0].real() < pair[1].real()));
+    // Min/max decimation keeps the extremes of the range
+    let maximum = |selection: &Vec<ltspice::Value>| selection.iter().map(|y| y.real()).fold(f64::NEG_INFINITY, f64::max);
+    assert_eq!(maximum(&reduced.y), maximum(&range.y));
+
+    assert!(simulation.query().between(2.0, 3.0).collect().unwrap().is_empty());
+}
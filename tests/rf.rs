@@ -0,0 +1,87 @@
+/*
+ * This file contains the tests of the S-parameter computation and the Touchstone export
+ */
+
+use ltspice::rf::{s_params, touchstone, two_port, Port};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+const Z0: f64 = 50.0;
+// Resistance of the fixture networks
+const R: f64 = 100.0;
+
+/* #### Functions #### */
+
+// Loads a 1 Hz to 1 MHz AC sweep with constant derived port signals.
+fn simulation(signals: &[(&str, f64)]) -> SteppedSimulation {
+    let spec = RawSpec::new().mode(Mode::AC).points(50);
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    for (name, value) in signals.iter() {
+        simulation.insert_derived(name, vec![vec![Value::from(*value); 50]]).unwrap();
+    }
+    return simulation;
+}
+
+// Returns the two ports of a two-port fixture.
+fn ports(simulation: &SteppedSimulation) -> [Port<'_>; 2] {
+    return [
+        Port::new(simulation.trace("V(p1)", 0).unwrap(), simulation.trace("I(P1)", 0).unwrap()),
+        Port::new(simulation.trace("V(p2)", 0).unwrap(), simulation.trace("I(P2)", 0).unwrap()),
+    ];
+}
+
+fn close(actual: Value, expected: f64) -> bool {
+    return (actual.real() - expected).abs() < 1e-12 && actual.imaginary().abs() < 1e-12;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn resistor_reflection_and_s1p() {
+    // A 1 V source with a Z0 output impedance driving R to ground
+    let current = 1.0 / (R + Z0);
+    let simulation = simulation(&[("V(p1)", R * current), ("I(R1)", current)]);
+    let port = Port::new(simulation.trace("V(p1)", 0).unwrap(), simulation.trace("I(R1)", 0).unwrap());
+
+    let parameters = s_params(&[port], Z0).unwrap();
+    assert_eq!(parameters.len(), 1);
+    assert_eq!(parameters[0].name(), "S11");
+    assert!(parameters[0].values().iter().all(|s11| close(*s11, (R - Z0) / (R + Z0))));
+
+    let file = touchstone(&[&parameters[0]], Z0).unwrap();
+    let lines: Vec<&str> = file.lines().collect();
+    assert_eq!(lines[0], "! S11");
+    assert_eq!(lines[1], "# Hz S RI R 50");
+    assert_eq!(lines.len(), 52);
+    let first: Vec<f64> = lines[2].split_whitespace().map(|value| value.parse().unwrap()).collect();
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[0], 1.0);
+    assert!((first[1] - 1.0 / 3.0).abs() < 1e-12);
+}
+
+#[test]
+fn series_resistor_two_port_and_s2p() {
+    // Port 1 driven by 1 V behind Z0, port 2 terminated in Z0: the current leaves port 2
+    let current = 1.0 / (R + 2.0 * Z0);
+    let forward = simulation(&[("V(p1)", 1.0 - Z0 * current), ("V(p2)", Z0 * current), ("I(P1)", current), ("I(P2)", -current)]);
+    // The network is symmetric, the reverse run swaps the ports
+    let reverse = simulation(&[("V(p1)", Z0 * current), ("V(p2)", 1.0 - Z0 * current), ("I(P1)", -current), ("I(P2)", current)]);
+    let matrix = two_port(&ports(&forward), &ports(&reverse), Z0).unwrap();
+    let reflection = R / (R + 2.0 * Z0);
+    let transmission = 2.0 * Z0 / (R + 2.0 * Z0);
+    assert!(matrix.s11.values().iter().all(|s| close(*s, reflection)));
+    assert!(matrix.s22.values().iter().all(|s| close(*s, reflection)));
+    assert!(matrix.s21.values().iter().all(|s| close(*s, transmission)));
+    assert!(matrix.s12.values().iter().all(|s| close(*s, transmission)));
+
+    let file = matrix.to_touchstone().unwrap();
+    assert!(file.starts_with("! S11 S21 S12 S22\n# Hz S RI R 50\n"));
+    let last: Vec<f64> = file.lines().last().unwrap().split_whitespace().map(|value| value.parse().unwrap()).collect();
+    assert_eq!(last.len(), 9);
+    assert!((last[0] - 1e6).abs() < 1e-3);
+    assert!((last[3] - transmission).abs() < 1e-12);
+
+    assert!(touchstone(&[&matrix.s11, &matrix.s21], Z0).is_none());
+}
@@ -0,0 +1,60 @@
+/*
+ * This file contains the tests of the scratch files of spilled variables
+ *
+ * Kept apart from the other spill tests: scratch files are found by the process id, so no
+ * other test of this process may spill concurrently.
+ */
+
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+
+use ltspice::options::LoadOptions;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Functions #### */
+
+// Returns the scratch files of this process.
+fn scratch_files() -> Vec<PathBuf> {
+    let prefix = format!("ltspice-{}-", std::process::id());
+    return fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            return name.starts_with(&prefix) && name.ends_with(".scratch");
+        })
+        .collect();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn scratch_files_are_private_and_checked() {
+    let bytes = generate_raw(&RawSpec::new().points(1000).variables(4));
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().memory_limit(0)).unwrap();
+    let files = scratch_files();
+    assert_eq!(files.len(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&files[0]).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    // A truncated scratch file is an error, not a panic
+    OpenOptions::new().write(true).open(&files[0]).unwrap().set_len(0).unwrap();
+    assert!(simulation.try_get("V(n001)", Some(0)).is_err());
+    assert!(simulation.get("V(n001)", Some(0)).is_none());
+    drop(simulation);
+    assert!(scratch_files().is_empty());
+
+    // The scratch file stays open, removing its name does not lose the data
+    #[cfg(unix)]
+    {
+        let simulation = SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().memory_limit(0)).unwrap();
+        let files = scratch_files();
+        assert_eq!(files.len(), 1);
+        fs::remove_file(&files[0]).unwrap();
+        assert_eq!(simulation.try_get("V(n001)", Some(0)).unwrap().unwrap().len(), 1000);
+    }
+}
@@ -0,0 +1,177 @@
+/*
+ * This file contains the tests of the waveform server
+ */
+#![cfg(feature = "serve")]
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use ltspice::serve::{websocket_accept, websocket_frame, WaveformServer};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::Encoding;
+
+/* #### Functions #### */
+
+// Serves a generated file on a free local port, returning the address.
+fn start(points: usize, origin: Option<&'static str>) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("ltspice-serve-{}-{}.raw", std::process::id(), points));
+    fs::write(&path, generate_raw(&RawSpec::new().points(points))).unwrap();
+    return serve(&path, origin);
+}
+
+// Serves a file on a free local port, returning the address.
+fn serve(path: &Path, origin: Option<&'static str>) -> String {
+    let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+
+    let mut server = WaveformServer::new(path).unwrap();
+    server.set_allowed_origin(origin);
+    let served = address.clone();
+    thread::spawn(move || server.run(&served).unwrap());
+    thread::sleep(Duration::from_millis(100));
+    return address;
+}
+
+// Sends a raw request and returns what the server answered before closing or going quiet.
+fn exchange(address: &str, request: &str) -> Vec<u8> {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while let Ok(count) = stream.read(&mut buffer) {
+        if count == 0 {
+            break;
+        }
+        response.extend_from_slice(&buffer[..count]);
+    }
+    return response;
+}
+
+// Sends a WebSocket upgrade for the url, returning the connection once the answer is read.
+fn subscribe(address: &str, url: &str) -> TcpStream {
+    let mut stream = TcpStream::connect(address).unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        url, address
+    );
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+    assert!(head.starts_with(b"HTTP/1.1 101"));
+    return stream;
+}
+
+// Reads a text frame of less than 64 KiB.
+fn frame(stream: &mut TcpStream) -> String {
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head).unwrap();
+    let length = match head[1] {
+        126 => {
+            let mut length = [0u8; 2];
+            stream.read_exact(&mut length).unwrap();
+            u16::from_be_bytes(length) as usize
+        }
+        length => length as usize,
+    };
+    let mut text = vec![0u8; length];
+    stream.read_exact(&mut text).unwrap();
+    return String::from_utf8(text).unwrap();
+}
+
+fn get(address: &str, url: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", url, address);
+    return String::from_utf8(exchange(address, &request)).unwrap();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn handshake_matches_the_rfc_example() {
+    assert_eq!(websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    assert_eq!(websocket_frame("hi"), vec![0x81, 2, b'h', b'i']);
+    assert_eq!(websocket_frame(&"a".repeat(300))[..4], [0x81, 126, 1, 44]);
+    assert_eq!(websocket_frame(&"a".repeat(70_000))[..10], [0x81, 127, 0, 0, 0, 0, 0, 1, 0x11, 0x70]);
+}
+
+#[test]
+fn data_points_are_clamped_and_cors_is_opt_in() {
+    let address = start(5000, None);
+    for url in ["/data?var=V(n001)&points=0", "/data?var=V(n001)&points=1"] {
+        let response = get(&address, url);
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(!response.contains("Access-Control-Allow-Origin"));
+        // One bucket keeps the minimum and maximum of the trace
+        let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+        assert_eq!(body.matches("],[").count(), 1, "{}", body);
+    }
+
+    let address = start(100, Some("http://localhost:3000"));
+    let response = get(&address, "/info");
+    assert!(response.contains("Access-Control-Allow-Origin: http://localhost:3000"), "{}", response);
+}
+
+#[test]
+fn stream_pushes_the_points_after_since() {
+    let address = start(10, None);
+    let request = format!(
+        "GET /stream?var=V(n001)&since=-1 HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        address
+    );
+    let response = exchange(&address, &request);
+    let split = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap() + 4;
+    let head = String::from_utf8_lossy(&response[..split]);
+    assert!(head.starts_with("HTTP/1.1 101"), "{}", head);
+    assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="), "{}", head);
+
+    // A single text frame with every point, nothing is pushed while the file is unchanged
+    let frame = &response[split..];
+    assert_eq!(frame[..2], [0x81, 126]);
+    let text = String::from_utf8_lossy(&frame[4..]);
+    assert_eq!(u16::from_be_bytes([frame[2], frame[3]]) as usize, text.len());
+    assert_eq!(text.matches("],[").count(), 9, "{}", text);
+}
+
+#[test]
+fn stream_follows_the_points_appended_to_the_file() {
+    let bytes = generate_raw(&RawSpec::new().points(20).variables(1).encoding(Encoding::UTF8));
+    let header = bytes.windows(7).position(|window| window == b"Binary:").unwrap() + 8;
+    let point_size = (bytes.len() - header) / 20;
+    let path = std::env::temp_dir().join(format!("ltspice-serve-follow-{}.raw", std::process::id()));
+    fs::write(&path, &bytes[..header + 12 * point_size + 3]).unwrap();
+    let address = serve(&path, None);
+
+    // The partial point is only read once it is complete
+    let mut stream = subscribe(&address, "/stream?var=V(n001)&since=-1");
+    assert_eq!(frame(&mut stream).matches("],[").count(), 11);
+    fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(&bytes[header + 12 * point_size + 3..]).unwrap();
+    assert_eq!(frame(&mut stream).matches("],[").count(), 7);
+    let response = get(&address, "/tail?var=V(n001)&since=-1");
+    assert_eq!(response.matches("],[").count(), 19, "{}", response);
+
+    // A rewritten file is followed from its start again
+    fs::write(&path, &bytes[..header + 5 * point_size]).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    let response = get(&address, "/tail?var=V(n001)&since=-1");
+    fs::remove_file(&path).unwrap();
+    assert_eq!(response.matches("],[").count(), 4, "{}", response);
+}
+
+#[test]
+fn a_client_which_does_not_read_does_not_stall_the_server() {
+    let address = start(300_000, None);
+    // Megabytes of points, more than the socket buffers hold
+    let _stalled = subscribe(&address, "/stream?var=V(n001)&points=100000");
+    let response = get(&address, "/info");
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+}
@@ -0,0 +1,47 @@
+/*
+ * This file contains the tests of the shared simulation handle
+ */
+
+use std::sync::Arc;
+use std::thread;
+
+use ltspice::shared::SharedSimulation;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Tests #### */
+
+#[test]
+fn shared_simulations_are_read_from_several_threads() {
+    let bytes = generate_raw(&RawSpec::new().steps(4).points(100).variables(2));
+    let expected = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let shared = SteppedSimulation::from_bytes(&bytes).unwrap().into_shared();
+
+    let handles: Vec<_> = (0..4u16)
+        .map(|step| {
+            let shared = shared.clone();
+            return thread::spawn(move || shared.get("V(n002)", Some(step)).unwrap().to_vec());
+        })
+        .collect();
+    for (step, handle) in handles.into_iter().enumerate() {
+        assert_eq!(handle.join().unwrap(), expected.get("V(n002)", Some(step as u16)).unwrap());
+    }
+    assert_eq!(Arc::strong_count(shared.as_arc()), 1);
+}
+
+#[test]
+fn the_last_handle_unwraps_the_simulation() {
+    let bytes = generate_raw(&RawSpec::new().points(10).variables(1));
+    let shared = SharedSimulation::from(SteppedSimulation::from_bytes(&bytes).unwrap());
+    let other = shared.clone();
+    let shared = shared.try_unwrap().unwrap_err();
+    drop(other);
+    let simulation = shared.try_unwrap().unwrap();
+    assert_eq!(simulation.get_step_count(), 1);
+
+    // Handles built from an Arc share it
+    let arc = Arc::new(simulation);
+    let shared = SharedSimulation::from(arc.clone());
+    assert!(Arc::ptr_eq(shared.as_arc(), &arc));
+    assert!(shared.try_unwrap().is_err());
+}
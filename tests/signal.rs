@@ -0,0 +1,45 @@
+/*
+ * This file contains the tests of the differential and common-mode signals
+ */
+
+use ltspice::signal::{common_mode, differential};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Value};
+
+/* #### Tests #### */
+
+#[test]
+fn differential_and_common_mode_of_every_step() {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(2).points(30).variables(2))).unwrap();
+    assert_eq!(differential(&mut simulation, "V(n001)", "V(n002)").unwrap(), "V(n001,n002)");
+    assert_eq!(common_mode(&mut simulation, "V(n001)", "V(n002)").unwrap(), "Vcm(n001,n002)");
+
+    for step in 0..2 {
+        let p = simulation.get("V(n001)", Some(step)).unwrap();
+        let n = simulation.get("V(n002)", Some(step)).unwrap();
+        let dm = simulation.get("V(n001,n002)", Some(step)).unwrap();
+        let cm = simulation.get("Vcm(n001,n002)", Some(step)).unwrap();
+        for index in 0..p.len() {
+            assert_eq!(dm[index].real(), p[index].real() - n[index].real());
+            assert_eq!(cm[index].real(), (p[index].real() + n[index].real()) * 0.5);
+            // p = cm + dm / 2 and n = cm - dm / 2
+            assert!((cm[index].real() + dm[index].real() / 2.0 - p[index].real()).abs() < 1e-12);
+        }
+    }
+    assert!(differential(&mut simulation, "V(n001)", "V(none)").is_err());
+}
+
+#[test]
+fn complex_signals_and_other_names() {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().mode(Mode::AC).points(20).variables(2))).unwrap();
+    let name = differential(&mut simulation, "V(n002)", "V(n001)").unwrap();
+    let (p, n) = (simulation.get("V(n002)", None).unwrap(), simulation.get("V(n001)", None).unwrap());
+    let expected: Vec<Value> = p.iter().zip(n.iter()).map(|(p, n)| *p - *n).collect();
+    assert_eq!(simulation.get(&name, None).unwrap(), &expected[..]);
+    assert!(expected.iter().any(|value| value.imaginary() != 0.0));
+
+    // Names which are not node voltages are kept whole
+    let x = simulation.get("x", None).unwrap().to_vec();
+    simulation.insert_derived("I(R1)", vec![x.iter().map(|_| Value::from(1.0)).collect()]).unwrap();
+    assert_eq!(common_mode(&mut simulation, "I(R1)", "V(n001)").unwrap(), "Vcm(I(R1),V(n001))");
+}
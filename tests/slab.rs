@@ -0,0 +1,64 @@
+/*
+ * This file contains the tests of the slab allocator
+ */
+
+use ltspice::options::{Allocator, LoadOptions, Precision};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Tests #### */
+
+#[test]
+fn slab_steps_are_contiguous_and_match_the_global_allocator() {
+    let bytes = generate_raw(&RawSpec::new().steps(50).points(40).variables(3));
+    let full = SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().column_stats(true)).unwrap();
+    let options = LoadOptions::new().allocator(Allocator::Slab).column_stats(true);
+    let slab = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+    assert!(slab.is_slab());
+    assert!(!full.is_slab());
+    assert_eq!(slab.get_step_count(), 50);
+    assert_eq!(slab.column_stats(), full.column_stats());
+
+    // Every step of a variable is borrowed from the same allocation, without copies
+    for variable in full.get_variables().iter() {
+        for step in 0..50 {
+            let values = slab.get(variable.name(), Some(step)).unwrap();
+            assert_eq!(values, full.get(variable.name(), Some(step)).unwrap());
+            if step > 0 {
+                let previous = slab.get(variable.name(), Some(step - 1)).unwrap();
+                assert_eq!(previous.as_ptr_range().end, values.as_ptr());
+            }
+        }
+    }
+    assert_eq!(slab.cache_usage(), 0);
+    assert_eq!(slab.get("x", Some(7)), full.get("x", Some(7)));
+
+    // Whole columns of 50 steps of 40 samples of 16 bytes are copied by `iter`
+    assert!(slab.iter().eq(full.iter()));
+    assert_eq!(slab.cache_usage(), 3 * 50 * 40 * 16);
+}
+
+#[test]
+fn slab_columns_are_renamed_and_modified() {
+    let bytes = generate_raw(&RawSpec::new().steps(3).points(100).variables(2));
+    let mut full = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let mut slab = SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().allocator(Allocator::Slab)).unwrap();
+    slab.rename("V(n001)", "out").unwrap();
+    assert_eq!(slab.get("out", Some(2)), full.get("V(n001)", Some(2)));
+
+    let middle = full.get_x().unwrap()[50].real();
+    full.trim_before(middle).unwrap();
+    slab.trim_before(middle).unwrap();
+    assert!(!slab.is_slab());
+    assert_eq!(slab.get("out", Some(1)), full.get("V(n001)", Some(1)));
+    assert_eq!(slab.get("V(n002)", Some(2)), full.get("V(n002)", Some(2)));
+}
+
+#[test]
+fn slab_is_refused_with_other_storages() {
+    let bytes = generate_raw(&RawSpec::new().steps(2).points(10).variables(1));
+    let slab = LoadOptions::new().allocator(Allocator::Slab);
+    assert!(SteppedSimulation::from_bytes_with_options(&bytes, slab.clone().memory_limit(1 << 20)).is_err());
+    assert!(SteppedSimulation::from_bytes_with_options(&bytes, slab.clone().preview(Precision::Float32)).is_err());
+    assert!(SteppedSimulation::from_bytes_with_options(&bytes, slab.x_only(true)).unwrap().get_x().is_some());
+}
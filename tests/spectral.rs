@@ -0,0 +1,75 @@
+/*
+ * This file contains the tests of the spectral metrics of transient traces
+ */
+
+use std::f64::consts::PI;
+
+use ltspice::spectral::{thd, thd_n};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+// Ten periods of the fundamental over the 1 ms of the generated transient
+const FUNDAMENTAL: f64 = 1e4;
+
+/* #### Functions #### */
+
+// Loads a transient trace "V(out)" of 1 V at the fundamental with a 1% third harmonic,
+// plus deterministic white noise of the specified RMS.
+fn distorted(noise: f64) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(16384))).unwrap();
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let values = simulation
+        .get("x", Some(0))
+        .unwrap()
+        .iter()
+        .map(|t| {
+            let phase = 2.0 * PI * FUNDAMENTAL * t.real();
+            // Uniform in [-√3, √3), so of unit RMS
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let uniform = (state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0;
+            return Value::from(phase.sin() + 0.01 * (3.0 * phase).sin() + noise * 3f64.sqrt() * uniform);
+        })
+        .collect();
+    simulation.insert_derived("V(out)", vec![values]).unwrap();
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn thd_of_a_known_harmonic() {
+    let simulation = distorted(0.0);
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    let thd = thd(&trace, FUNDAMENTAL, 10).unwrap();
+    assert!((thd - 0.01).abs() < 1e-4, "{}", thd);
+
+    // Without noise, only the harmonic passes the notch: 1% of a total of √(1 + 0.01²)
+    let thd_n = thd_n(&trace, FUNDAMENTAL, (20.0, 80e3), 10.0).unwrap();
+    assert!((thd_n - 0.01).abs() < 1e-4, "{}", thd_n);
+}
+
+#[test]
+fn thd_n_includes_the_noise() {
+    let simulation = distorted(0.1);
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    assert!((thd(&trace, FUNDAMENTAL, 3).unwrap() - 0.01).abs() < 2e-3);
+
+    // The band holds at most the fraction 80 kHz / 8.2 MHz (half the sampling rate) of the noise
+    // power, less as resampling the trace on the FFT grid averages neighbouring samples
+    let noise: f64 = 0.1f64.powi(2) * 80e3 / (16384.0 / 1e-3 / 2.0);
+    let residual = 0.01f64.powi(2) / 2.0 + noise;
+    let thd_n = thd_n(&trace, FUNDAMENTAL, (20.0, 80e3), 10.0).unwrap();
+    assert!(thd_n > 0.012, "{}", thd_n);
+    assert!(thd_n < (residual / (0.5 + residual)).sqrt(), "{}", thd_n);
+}
+
+#[test]
+fn short_or_silent_traces_have_no_thd() {
+    let simulation = distorted(0.0);
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    assert_eq!(thd(&trace, 2e9, 5), None);
+}
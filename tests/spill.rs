@@ -0,0 +1,78 @@
+/*
+ * This file contains the tests of the memory limit of a load
+ */
+
+use ltspice::options::{LoadOptions, Precision};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Tests #### */
+
+#[test]
+fn spilled_variables_read_back_identically() {
+    let bytes = generate_raw(&RawSpec::new().steps(3).points(1000).variables(6));
+    let full = SteppedSimulation::from_bytes(&bytes).unwrap();
+
+    // The x axis and two variables of 3000 samples of 16 bytes fit
+    let options = LoadOptions::new().memory_limit(3 * 48_000).column_stats(true);
+    let limited = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+    assert_eq!(limited.spilled_variables(), vec!["V(n003)", "V(n004)", "V(n005)", "V(n006)"]);
+    assert_eq!(limited.column_stats().len(), 6);
+    assert_eq!(limited.get_step_count(), 3);
+    for variable in full.get_variables().iter() {
+        for step in 0..3 {
+            assert_eq!(limited.get(variable.name(), Some(step)), full.get(variable.name(), Some(step)));
+        }
+    }
+}
+
+#[test]
+fn preview_variables_are_not_spilled() {
+    let bytes = generate_raw(&RawSpec::new().points(1000).variables(4));
+    let options = LoadOptions::new().memory_limit(0).preview(Precision::Int16);
+    let simulation = SteppedSimulation::from_bytes_with_options(&bytes, options).unwrap();
+    assert!(simulation.spilled_variables().is_empty());
+    assert!(simulation.is_preview());
+    assert_eq!(simulation.get("V(n004)", Some(0)).unwrap().len(), 1000);
+}
+
+#[test]
+fn mapped_file_pages_spilled_steps_in_on_access() {
+    let bytes = generate_raw(&RawSpec::new().steps(4).points(500).variables(3));
+    let full = SteppedSimulation::from_bytes(&bytes).unwrap();
+    let path = std::env::temp_dir().join(format!("ltspice-spill-mapped-{}.raw", std::process::id()));
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut limited = SteppedSimulation::new(path.clone());
+    limited.set_load_options(LoadOptions::new().memory_limit(0));
+    let result = limited.reload();
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+    assert_eq!(limited.spilled_variables().len(), 3);
+    assert_eq!(limited.cache_usage(), 0);
+
+    // Only the accessed step of 500 samples of 16 bytes is paged in
+    assert_eq!(limited.try_get("V(n002)", Some(2)).unwrap(), full.get("V(n002)", Some(2)));
+    assert_eq!(limited.cache_usage(), 500 * 16);
+    for variable in full.get_variables().iter() {
+        for step in 0..4 {
+            assert_eq!(limited.get(variable.name(), Some(step)), full.get(variable.name(), Some(step)));
+        }
+    }
+    assert_eq!(limited.cache_usage(), 3 * 4 * 500 * 16);
+}
+
+#[test]
+fn whole_column_reads_drop_the_steps_paged_in() {
+    let bytes = generate_raw(&RawSpec::new().steps(2).points(500).variables(2));
+    let mut limited = SteppedSimulation::from_bytes_with_options(&bytes, LoadOptions::new().memory_limit(0)).unwrap();
+    assert_eq!(limited.spilled_variables().len(), 2);
+
+    // A step paged in alone and again with its whole column is counted twice, until trimmed
+    assert!(limited.get("V(n001)", Some(1)).is_some());
+    assert_eq!(limited.iter().count(), 2);
+    assert_eq!(limited.cache_usage(), 2 * 2 * 500 * 16 + 500 * 16);
+    assert_eq!(limited.trim_cache(), 500 * 16);
+    assert_eq!(limited.cache_usage(), 2 * 2 * 500 * 16);
+    assert_eq!(limited.get("V(n001)", Some(1)).unwrap().len(), 500);
+}
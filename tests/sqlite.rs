@@ -0,0 +1,67 @@
+/*
+ * This file contains the tests of the SQLite export
+ */
+#![cfg(feature = "sqlite")]
+
+use std::fs;
+
+use rusqlite::{params, Connection};
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, Mode, SteppedSimulation};
+
+/* #### Tests #### */
+
+#[test]
+fn samples_survive_a_round_trip() {
+    let spec = RawSpec::new().mode(Mode::AC).steps(2).points(25).variables(2).encoding(Encoding::UTF8);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    let path = std::env::temp_dir().join(format!("ltspice-sqlite-{}.sqlite", std::process::id()));
+    let _ = fs::remove_file(&path);
+    simulation.to_sqlite(&path).unwrap();
+
+    let connection = Connection::open(&path).unwrap();
+    let count = |sql: &str| -> i64 { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+    assert_eq!(count("SELECT COUNT(*) FROM runs"), 2);
+    assert_eq!(count("SELECT COUNT(*) FROM variables"), 2);
+    assert_eq!(count("SELECT COUNT(*) FROM samples"), 2 * 2 * 25);
+
+    let (mode, date, hash): (String, Option<String>, String) = connection
+        .query_row("SELECT mode, date, source_hash FROM runs WHERE step = 1", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .unwrap();
+    assert_eq!(mode, simulation.get_mode().to_string());
+    assert_eq!(date.as_deref(), Some("1970-01-01T00:00:00+00:00"));
+    assert_eq!(hash, simulation.provenance().hash_hex());
+
+    // Every complex sample reads back exactly, in point order
+    for step in 0..2u16 {
+        let mut statement = connection
+            .prepare(
+                "SELECT s.x, s.real, s.imaginary FROM samples s JOIN runs r ON r.id = s.run_id \
+                 JOIN variables v ON v.id = s.variable_id WHERE r.step = ?1 AND v.name = ?2 ORDER BY s.point",
+            )
+            .unwrap();
+        let rows: Vec<(f64, f64, f64)> = statement
+            .query_map(params![step, "V(n002)"], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        let x = simulation.get("x", Some(step)).unwrap();
+        let values = simulation.get("V(n002)", Some(step)).unwrap();
+        assert_eq!(rows.len(), 25);
+        for ((row, x), value) in rows.iter().zip(x.iter()).zip(values.iter()) {
+            assert_eq!(*row, (x.real(), value.real(), value.imaginary()));
+        }
+    }
+    drop(connection);
+
+    // A second export appends runs and reuses the variables
+    simulation.to_sqlite(&path).unwrap();
+    let connection = Connection::open(&path).unwrap();
+    let runs: i64 = connection.query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0)).unwrap();
+    let variables: i64 = connection.query_row("SELECT COUNT(*) FROM variables", [], |row| row.get(0)).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!((runs, variables), (4, 2));
+}
@@ -0,0 +1,91 @@
+/*
+ * This file contains the tests of the loop stability analysis
+ */
+
+use ltspice::stability::{margins, tian, TianProbe};
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Mode, SteppedSimulation, Value};
+
+/* #### Constants #### */
+
+// Gain of the fixture loop, with three coinciding poles at 10 kHz
+const GAIN: f64 = 4.0;
+const POLE: f64 = 1e4;
+
+/* #### Functions #### */
+
+// A derived signal: its name and value as a function of frequency and step
+type Signal<'a> = (&'a str, fn(f64, u16) -> Value);
+
+// Loop gain T(f) = K / (1 + jf/p)³: its phase reaches -180° at √3·p, where |T| = K/8.
+fn loop_gain(frequency: f64) -> Value {
+    let section = Value::from(1.0) / Value::new(1.0, frequency / POLE);
+    return section * section * section * GAIN;
+}
+
+// Loads a two-step 1 Hz to 1 MHz AC sweep, with the signals of a derived loop.
+fn simulation(signals: &[Signal]) -> SteppedSimulation {
+    let spec = RawSpec::new().mode(Mode::AC).points(2000).steps(2);
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    for (name, signal) in signals.iter() {
+        let steps = (0..2)
+            .map(|step| {
+                let x = simulation.get("x", Some(step)).unwrap();
+                return x.iter().map(|f| signal(f.real(), step)).collect();
+            })
+            .collect();
+        simulation.insert_derived(name, steps).unwrap();
+    }
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn margins_of_a_known_loop() {
+    let simulation = simulation(&[("T", |f, _| loop_gain(f))]);
+    let result = margins(&simulation.trace("T", 0).unwrap());
+
+    // |T| = 1 where (1 + (f/p)²)^(3/2) = K, the phase there is -3·atan(f/p)
+    let crossover = POLE * (GAIN.powf(2.0 / 3.0) - 1.0).sqrt();
+    let phase_margin = 180.0 - 3.0 * (crossover / POLE).atan().to_degrees();
+    let (f, margin) = result.phase_margin.unwrap();
+    assert!((f / crossover - 1.0).abs() < 1e-3, "{} != {}", f, crossover);
+    assert!((margin - phase_margin).abs() < 0.05, "{} != {}", margin, phase_margin);
+
+    let (f, margin) = result.gain_margin.unwrap();
+    assert!((f / (3f64.sqrt() * POLE) - 1.0).abs() < 1e-3);
+    assert!((margin - 20.0 * (8.0 / GAIN).log10()).abs() < 0.05);
+}
+
+#[test]
+fn margins_are_none_without_crossings() {
+    // A single pole never reaches -180°, and a gain below 1 never crosses 0 dB
+    let simulation = simulation(&[("T", |f, _| Value::from(0.5) / Value::new(1.0, f / POLE))]);
+    let result = margins(&simulation.trace("T", 0).unwrap());
+    assert_eq!(result.phase_margin, None);
+    assert_eq!(result.gain_margin, None);
+}
+
+#[test]
+fn tian_recovers_the_loop_gain() {
+    // With an ideal probe, the voltage injection sees T / (1 + T) and no current flows
+    let simulation = simulation(&[
+        ("V(x)", |f, step| match step {
+            0 => loop_gain(f) / (Value::from(1.0) + loop_gain(f)),
+            _ => Value::default(),
+        }),
+        ("I(Vi)", |_, _| Value::default()),
+    ]);
+    let probe = TianProbe {
+        voltage: "V(x)",
+        current: "I(Vi)",
+        voltage_step: 0,
+        current_step: 1,
+    };
+    let t = tian(&simulation, &probe).unwrap();
+    for (f, value) in t.x().iter().zip(t.values().iter()) {
+        let expected = loop_gain(f.real());
+        assert!((*value - expected).abs() <= 1e-9 * expected.abs().max(1.0));
+    }
+}
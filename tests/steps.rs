@@ -0,0 +1,208 @@
+/*
+ * This file contains the tests of the segmentation of sweeps in steps
+ */
+
+use ltspice::branches::Direction;
+use ltspice::stream::RawStream;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{DataType, Encoding, Mode, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Builds a UTF8 binary DC sweep of V1 with a single variable, V(out) = 2 * V1.
+fn dc_sweep(sweep: &[f64], stepped: bool) -> Vec<u8> {
+    let flags = match stepped {
+        true => "real forward double stepped",
+        false => "real forward double",
+    };
+    let mut bytes = format!(
+        "Title: * dc.asc\nDate: Thu Jan  1 00:00:00 1970\nPlotname: DC transfer characteristic\nFlags: {}\n\
+         No. Variables: 2\nNo. Points: {}\nOffset:   0.0000000000000000e+000\nCommand: Test\n\
+         Variables:\n\t0\tv1\tvoltage\n\t1\tV(out)\tvoltage\nBinary:\n",
+        flags,
+        sweep.len()
+    )
+    .into_bytes();
+    for x in sweep.iter() {
+        bytes.extend_from_slice(&x.to_le_bytes());
+        bytes.extend_from_slice(&(2.0 * x).to_le_bytes());
+    }
+    return bytes;
+}
+
+// Returns the number of points of every step.
+fn step_lengths(simulation: &SteppedSimulation) -> Vec<usize> {
+    return (0..simulation.get_step_count() as u16)
+        .map(|step| simulation.get("x", Some(step)).map_or(0, |x| x.len()))
+        .collect();
+}
+
+// Loads a generated sweep and checks it has `steps` steps of `points` points, each starting at the first x.
+fn assert_segmented(spec: RawSpec, mode: Mode, steps: usize, points: usize) -> SteppedSimulation {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    assert_eq!(simulation.get_mode(), &mode);
+    assert_eq!(step_lengths(&simulation), vec![points; steps]);
+    let first = simulation.get("x", Some(0)).unwrap()[0];
+    for step in 0..steps as u16 {
+        assert_eq!(simulation.get("x", Some(step)).unwrap()[0], first);
+    }
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn stepped_ac_restarts_at_the_lowest_frequency() {
+    for encoding in [Encoding::UTF8, Encoding::UTF16] {
+        let spec = RawSpec::new().mode(Mode::AC).steps(4).points(60).variables(2).encoding(encoding);
+        let simulation = assert_segmented(spec, Mode::AC, 4, 60);
+        // Each step scales the response, the steps must not be mixed up
+        let first = simulation.get("V(n001)", Some(0)).unwrap()[0].real();
+        let last = simulation.get("V(n001)", Some(3)).unwrap()[0].real();
+        assert!((last / first - 1.3).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn stepped_noise_restarts_at_the_lowest_frequency() {
+    for precision in [DataType::Float32, DataType::Float64] {
+        let spec = RawSpec::new().mode(Mode::Noise).steps(3).points(80).precision(precision);
+        assert_segmented(spec, Mode::Noise, 3, 80);
+    }
+}
+
+#[test]
+fn stepped_dc_restarts_at_the_first_source_value() {
+    for precision in [DataType::Float32, DataType::Float64] {
+        let spec = RawSpec::new().mode(Mode::DC).steps(5).points(21).precision(precision);
+        assert_segmented(spec, Mode::DC, 5, 21);
+    }
+}
+
+#[test]
+fn single_step_sweeps_are_not_split() {
+    for mode in [Mode::AC, Mode::Noise, Mode::DC, Mode::Transient] {
+        assert_segmented(RawSpec::new().mode(mode.clone()).points(50), mode, 1, 50);
+    }
+}
+
+#[test]
+fn dc_sweep_restarts_at_its_first_value() {
+    let sweep: Vec<f64> = (0..3).flat_map(|_| (0..=5).map(|x| x as f64)).collect();
+    let simulation = SteppedSimulation::from_bytes(&dc_sweep(&sweep, true)).unwrap();
+    assert_eq!(step_lengths(&simulation), vec![6, 6, 6]);
+}
+
+#[test]
+fn dc_hysteresis_loop_is_a_single_step() {
+    // 0 → 5 → 0 goes back to its first value without restarting
+    let sweep: Vec<f64> = (0..=5).chain((0..5).rev()).map(|x| x as f64).collect();
+    let simulation = SteppedSimulation::from_bytes(&dc_sweep(&sweep, false)).unwrap();
+    assert_eq!(step_lengths(&simulation), vec![11]);
+}
+
+#[test]
+fn dc_stepped_hysteresis_loops() {
+    let sweep: Vec<f64> = (0..2).flat_map(|_| (0..=5).chain((0..5).rev())).map(|x| x as f64).collect();
+    let simulation = SteppedSimulation::from_bytes(&dc_sweep(&sweep, true)).unwrap();
+    assert_eq!(step_lengths(&simulation), vec![11, 11]);
+    let out = simulation.get("V(out)", Some(1)).unwrap();
+    assert_eq!(out[5].real(), 10.0);
+    assert_eq!(out[10].real(), 0.0);
+}
+
+#[test]
+fn dc_downward_hysteresis_loop() {
+    let sweep = [5.0, 2.5, 0.0, -2.5, 0.0, 2.5, 5.0, 5.0, 2.5, 0.0];
+    let simulation = SteppedSimulation::from_bytes(&dc_sweep(&sweep, true)).unwrap();
+    assert_eq!(step_lengths(&simulation), vec![7, 3]);
+}
+
+#[test]
+fn dc_hysteresis_loop_splits_into_branches() {
+    // The repeated 3 continues the forward branch, the turning points belong to both branches.
+    // The sweep never goes back to its first value, so it stays a single step
+    let sweep = [0.5, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 0.0, -1.0, 0.0, 1.0];
+    let simulation = SteppedSimulation::from_bytes(&dc_sweep(&sweep, false)).unwrap();
+    let branches = simulation.trace("V(out)", 0).unwrap().branches();
+    let directions: Vec<Direction> = branches.iter().map(|branch| branch.direction).collect();
+    assert_eq!(directions, vec![Direction::Forward, Direction::Backward, Direction::Forward]);
+    let x: Vec<Vec<f64>> = branches.iter().map(|branch| branch.trace.x().iter().map(|x| x.real()).collect()).collect();
+    assert_eq!(x, vec![vec![0.5, 1.0, 2.0, 3.0, 3.0], vec![3.0, 2.0, 1.0, 0.0, -1.0], vec![-1.0, 0.0, 1.0]]);
+    assert_eq!(branches[1].trace.name(), "V(out)");
+    assert_eq!(branches[1].trace.values()[4].real(), -2.0);
+
+    // A monotonic trace is a single forward branch
+    let monotonic = SteppedSimulation::from_bytes(&dc_sweep(&[0.0, 1.0, 2.0], false)).unwrap();
+    let branches = monotonic.trace("V(out)", 0).unwrap().branches();
+    assert_eq!(branches.len(), 1);
+    assert_eq!(branches[0].direction, Direction::Forward);
+    assert_eq!(branches[0].trace.x().len(), 3);
+}
+
+#[test]
+fn stepped_load_sizes_every_step_up_front() {
+    let spec = RawSpec::new().steps(5).points(300).variables(3);
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+    for (name, steps) in simulation.iter() {
+        assert_eq!(steps.len(), 5);
+        for (step, values) in steps.iter().enumerate() {
+            assert_eq!(values.len(), 300);
+            assert_eq!(values.capacity(), 300, "{} was grown in step {}", name, step);
+        }
+    }
+}
+
+#[test]
+fn stepped_load_is_sized_from_the_log_step_count() {
+    let path = std::env::temp_dir().join(format!("ltspice-steps-hint-{}.raw", std::process::id()));
+    std::fs::write(&path, generate_raw(&RawSpec::new().steps(4).points(250).variables(2))).unwrap();
+    std::fs::write(path.with_extension("log"), ".step r=1\n.step r=2\n.step r=3\n.step r=4\n").unwrap();
+
+    let mut simulation = SteppedSimulation::new(path.clone());
+    let result = simulation.reload();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(path.with_extension("log")).unwrap();
+    result.unwrap();
+
+    assert_eq!(simulation.get_step_parameters(3).unwrap(), &vec![("r".to_string(), 4.0)]);
+    let (_, steps) = simulation.iter().find(|(name, _)| *name == "V(n002)").unwrap();
+    for values in steps.iter() {
+        assert_eq!(values.capacity(), 250);
+    }
+}
+
+#[test]
+fn stale_log_step_counts_do_not_over_reserve() {
+    for (index, steps) in [2, 6, 5000].into_iter().enumerate() {
+        let path = std::env::temp_dir().join(format!("ltspice-steps-stale-{}-{}.raw", std::process::id(), index));
+        std::fs::write(&path, generate_raw(&RawSpec::new().steps(4).points(300).variables(1))).unwrap();
+        let log: String = (1..=steps).map(|step| format!(".step r={}\n", step)).collect();
+        std::fs::write(path.with_extension("log"), log).unwrap();
+
+        let mut simulation = SteppedSimulation::new(path.clone());
+        let result = simulation.reload();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(path.with_extension("log")).unwrap();
+        result.unwrap();
+
+        // The first step is shrunk to fit, the others are sized from it
+        let (_, values) = simulation.iter().next().unwrap();
+        assert_eq!(values.len(), 4);
+        for values in values.iter() {
+            assert_eq!(values.capacity(), 300, "with a log of {} steps", steps);
+        }
+    }
+}
+
+#[test]
+fn streaming_more_than_65536_steps_fails() {
+    // Every point of a constant DC sweep starts a new step
+    let path = std::env::temp_dir().join(format!("ltspice-steps-{}.raw", std::process::id()));
+    std::fs::write(&path, dc_sweep(&vec![0.0; 65537], true)).unwrap();
+    let chunks: Vec<_> = RawStream::open(&path).unwrap().chunk_points(65536).collect();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(*chunks[0].as_ref().unwrap().steps.last().unwrap(), u16::MAX);
+    assert!(chunks[1].as_ref().unwrap_err().to_string().contains("65536 steps"));
+}
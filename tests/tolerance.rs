@@ -0,0 +1,41 @@
+/*
+ * This file contains the tests of the approx integration
+ */
+#![cfg(feature = "approx")]
+
+use approx::{abs_diff_eq, assert_relative_eq, assert_relative_ne, relative_eq};
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{DataType, SteppedSimulation, Value};
+
+/* #### Tests #### */
+
+#[test]
+fn values_compare_by_their_distance_in_the_complex_plane() {
+    let value = Value::new(3.0, 4.0);
+    assert!(abs_diff_eq!(value, Value::new(3.0, 4.0 + 1e-12), epsilon = 1e-9));
+    // Each component differs by 0.8, their distance is 0.8 * √2
+    assert!(!abs_diff_eq!(value, Value::new(3.8, 4.8), epsilon = 1.0));
+    assert!(abs_diff_eq!(value, Value::new(3.6, 4.6), epsilon = 1.0));
+
+    // Relative to the larger magnitude, 5
+    assert!(relative_eq!(value, Value::new(3.0, 4.0005), max_relative = 1e-4));
+    assert!(!relative_eq!(value, Value::new(3.0, 4.001), max_relative = 1e-4));
+    assert_relative_eq!(Value::new(f64::INFINITY, 0.0), Value::new(f64::INFINITY, 0.0));
+    assert_relative_ne!(Value::new(f64::NAN, 0.0), Value::new(f64::NAN, 0.0));
+}
+
+#[test]
+fn traces_compare_their_x_axis_and_samples() {
+    let spec = RawSpec::new().points(200).variables(2);
+    let double = SteppedSimulation::from_bytes(&generate_raw(&spec.clone().precision(DataType::Float64))).unwrap();
+    let single = SteppedSimulation::from_bytes(&generate_raw(&spec.precision(DataType::Float32))).unwrap();
+    let (double, single) = (double.trace("V(n001)", 0).unwrap(), single.trace("V(n001)", 0).unwrap());
+
+    // Samples rounded to single precision
+    assert_relative_ne!(double, single);
+    assert_relative_eq!(double, single, max_relative = 1e-6);
+    assert_relative_eq!(double.values(), single.values(), max_relative = 1e-6);
+    let other = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(200).variables(2))).unwrap();
+    assert_relative_ne!(double, other.trace("V(n002)", 0).unwrap(), max_relative = 1e-6);
+}
@@ -0,0 +1,158 @@
+/*
+ * This file contains the tests of the trace transformations
+ */
+
+use ltspice::transform::{Dedup, Repair};
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Builds a UTF8 binary transient of a single variable V(out) from its points.
+fn transient(x: &[f64], y: &[f64]) -> SteppedSimulation {
+    let mut bytes = format!(
+        "Title: * tran.asc\nDate: Thu Jan  1 00:00:00 1970\nPlotname: Transient Analysis\nFlags: real forward double\n\
+         No. Variables: 2\nNo. Points: {}\nOffset:   0.0000000000000000e+000\nCommand: Test\n\
+         Variables:\n\t0\ttime\ttime\n\t1\tV(out)\tvoltage\nBinary:\n",
+        x.len()
+    )
+    .into_bytes();
+    for (x, y) in x.iter().zip(y.iter()) {
+        bytes.extend_from_slice(&x.to_le_bytes());
+        bytes.extend_from_slice(&y.to_le_bytes());
+    }
+    return SteppedSimulation::from_bytes(&bytes).unwrap();
+}
+
+fn reals(values: &[Value]) -> Vec<f64> {
+    return values.iter().map(|value| value.real()).collect();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn traces_are_shifted_to_their_trigger() {
+    // V(out) = 2 * t rises through 0.5 at t = 0.25
+    let x: Vec<f64> = (0..=10).map(|index| index as f64 / 10.0).collect();
+    let simulation = transient(&x, &x.iter().map(|t| 2.0 * t).collect::<Vec<f64>>());
+    let trace = simulation.trace("V(out)", 0).unwrap();
+
+    let shifted = trace.shift_x(1.5);
+    assert_eq!(reals(shifted.x()), x.iter().map(|t| t + 1.5).collect::<Vec<f64>>());
+    assert_eq!(shifted.values(), trace.values());
+
+    let aligned = trace.normalize_to_trigger(0.5).unwrap();
+    for (aligned, original) in aligned.x().iter().zip(x.iter()) {
+        assert!((aligned.real() - (original - 0.25)).abs() < 1e-12);
+    }
+    assert!(trace.normalize_to_trigger(5.0).is_none());
+
+    // Only rising crossings trigger
+    let falling = transient(&x, &x.iter().map(|t| 1.0 - t).collect::<Vec<f64>>());
+    assert!(falling.trace("V(out)", 0).unwrap().normalize_to_trigger(0.5).is_none());
+}
+
+#[test]
+fn trimming_removes_the_points_before_a_time() {
+    let x: Vec<f64> = (0..=10).map(|index| index as f64 / 10.0).collect();
+    let mut simulation = transient(&x, &x);
+    simulation.trim_before(0.35).unwrap();
+    assert_eq!(reals(simulation.get("x", None).unwrap()), vec![0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]);
+    assert_eq!(simulation.get("V(out)", None), simulation.get("x", None));
+    simulation.trim_before(2.0).unwrap();
+    assert!(simulation.get("V(out)", None).unwrap().is_empty());
+}
+
+#[test]
+fn dc_is_the_average_over_time() {
+    // Trapezoids of 1 over [0, 1] and 4 over [1, 3]: an average of 5/3 with non-uniform steps
+    let simulation = transient(&[0.0, 1.0, 3.0], &[0.0, 2.0, 2.0]);
+    let removed = simulation.trace("V(out)", 0).unwrap().remove_dc();
+    let expected = [-5.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0];
+    for (value, expected) in removed.values().iter().zip(expected.iter()) {
+        assert!((value.real() - expected).abs() < 1e-12, "{:?}", value);
+    }
+    assert_eq!(removed.x(), simulation.get("x", None).unwrap());
+}
+
+#[test]
+fn peaks_are_normalized_to_one() {
+    let simulation = transient(&[0.0, 1.0, 2.0], &[1.0, -4.0, 2.0]);
+    let normalized = simulation.trace("V(out)", 0).unwrap().normalize_peak();
+    assert_eq!(reals(normalized.values()), vec![0.25, -1.0, 0.5]);
+
+    // A zero peak can't be scaled, the trace is returned unchanged
+    let silence = transient(&[0.0, 1.0, 2.0], &[0.0, 0.0, 0.0]);
+    assert_eq!(reals(silence.trace("V(out)", 0).unwrap().normalize_peak().values()), vec![0.0; 3]);
+}
+
+#[test]
+fn traces_are_subtracted_on_the_abscissa_of_the_first() {
+    // y = t minus y = 2 * t sampled twice as finely, the difference is -t
+    let coarse: Vec<f64> = (0..=3).map(|index| index as f64).collect();
+    let fine: Vec<f64> = (0..=6).map(|index| index as f64 / 2.0).collect();
+    let first = transient(&coarse, &coarse);
+    let second = transient(&fine, &fine.iter().map(|t| 2.0 * t).collect::<Vec<f64>>());
+    let difference = first.trace("V(out)", 0).unwrap().subtract(&second.trace("V(out)", 0).unwrap()).unwrap();
+    assert_eq!(difference.name(), "V(out)-V(out)");
+    assert_eq!(reals(difference.x()), coarse);
+    assert_eq!(reals(difference.values()), vec![0.0, -1.0, -2.0, -3.0]);
+
+    // The other trace must cover the whole abscissa
+    let short = transient(&fine[..4], &fine[..4]);
+    assert!(first.trace("V(out)", 0).unwrap().subtract(&short.trace("V(out)", 0).unwrap()).is_none());
+}
+
+#[test]
+fn non_finite_samples_are_found_and_repaired() {
+    let x = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+    let simulation = transient(&x, &[f64::NAN, 1.0, f64::INFINITY, f64::NAN, 4.0, f64::NEG_INFINITY]);
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    assert!(trace.has_non_finite());
+    assert_eq!(trace.non_finite_indices(), vec![0, 2, 3, 5]);
+
+    // Interior samples are interpolated over the abscissa, the ends hold the nearest finite one
+    let interpolated = trace.repair(Repair::Interpolate);
+    assert_eq!(reals(interpolated.values()), vec![1.0, 1.0, 2.0, 3.0, 4.0, 4.0]);
+    assert_eq!(reals(interpolated.x()), x);
+    assert!(!interpolated.as_trace().has_non_finite());
+
+    let dropped = trace.repair(Repair::Drop);
+    assert_eq!(reals(dropped.x()), vec![1.0, 4.0]);
+    assert_eq!(reals(dropped.values()), vec![1.0, 4.0]);
+
+    // Without any finite sample there is nothing to repair from
+    let diverged = transient(&x[..3], &[f64::NAN; 3]);
+    let trace = diverged.trace("V(out)", 0).unwrap();
+    assert_eq!(trace.non_finite_indices(), vec![0, 1, 2]);
+    for strategy in [Repair::Interpolate, Repair::Drop] {
+        let repaired = trace.repair(strategy);
+        assert_eq!(repaired.x().len(), 3);
+        assert!(repaired.values().iter().all(|value| value.real().is_nan()));
+    }
+    assert!(!transient(&x[..2], &[0.0, 1.0]).trace("V(out)", 0).unwrap().has_non_finite());
+}
+
+#[test]
+fn repeated_abscissas_are_collapsed_before_resampling() {
+    // LTSpice writes the same time twice or more around breakpoints
+    let simulation = transient(&[0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0], &[0.0, 1.0, 3.0, 2.0, 4.0, 6.0, 3.0]);
+    let trace = simulation.trace("V(out)", 0).unwrap();
+    for (strategy, expected) in [
+        (Dedup::First, vec![0.0, 1.0, 2.0, 3.0]),
+        (Dedup::Last, vec![0.0, 3.0, 6.0, 3.0]),
+        (Dedup::Average, vec![0.0, 2.0, 4.0, 3.0]),
+    ] {
+        let deduplicated = trace.dedup_x(strategy);
+        assert_eq!(reals(deduplicated.x()), vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(reals(deduplicated.values()), expected, "{:?}", strategy);
+    }
+
+    // Interpolated between the averages of the duplicates, skipping the abscissas outside the trace
+    let resampled = trace.resample(&[0.5, 1.5, 2.5, 5.0], Some(Dedup::Average));
+    assert_eq!(reals(resampled.x()), vec![0.5, 1.5, 2.5]);
+    assert_eq!(reals(resampled.values()), vec![1.0, 3.0, 3.5]);
+    // Without deduplication, the interpolation is between the last and first duplicates
+    let raw = trace.resample(&[1.5], None);
+    assert_eq!(reals(raw.values()), vec![2.5]);
+    assert_eq!(reals(trace.resample(&[1.0], None).values()), vec![1.0]);
+}
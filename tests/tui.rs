@@ -0,0 +1,94 @@
+/*
+ * This file contains the tests of the terminal waveform viewer, rendered off-screen
+ */
+
+#![cfg(feature = "tui")]
+
+use crossterm::event::KeyCode;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::tui::render;
+use ltspice::SteppedSimulation;
+
+/* #### Functions #### */
+
+// Loads the generated transient (two variables over 1 ms) with three steps.
+fn simulation() -> SteppedSimulation {
+    return SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(3))).unwrap();
+}
+
+fn screen(keys: &[KeyCode]) -> String {
+    return render(&simulation(), keys, 100, 20).unwrap();
+}
+
+// Returns the cursor readout line of a rendered screen.
+fn readout(screen: &str) -> String {
+    let line = screen.lines().find(|line| line.contains("x = ")).unwrap();
+    return line.trim_matches(|c: char| c == '│' || c.is_whitespace()).to_string();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn initial_view_shows_every_step_of_the_first_variable() {
+    let screen = screen(&[]);
+    assert_eq!(screen.lines().count(), 20);
+    assert!(screen.lines().next().unwrap().contains("V(n001) [V]"), "{}", screen);
+    assert!(screen.contains("0.00 s") && screen.contains("1.00 ms"), "{}", screen);
+
+    let readout = readout(&screen);
+    assert!(readout.starts_with("x = 500.000 µs"), "{}", readout);
+    for step in 0..3 {
+        assert!(readout.contains(&format!("step {}: ", step)), "{}", readout);
+    }
+    assert!(screen.contains("q: quit"));
+}
+
+#[test]
+fn variables_cycle_in_both_directions() {
+    assert!(screen(&[KeyCode::Down]).lines().next().unwrap().contains("V(n002) [V]"));
+    assert!(screen(&[KeyCode::Up]).lines().next().unwrap().contains("V(n002) [V]"));
+    assert!(screen(&[KeyCode::Down, KeyCode::Down]).lines().next().unwrap().contains("V(n001) [V]"));
+}
+
+#[test]
+fn zoom_and_pan_stay_within_the_simulation() {
+    // Zooming in halves the window around the cursor, zooming out never exceeds the span
+    let zoomed = screen(&[KeyCode::Char('+')]);
+    assert!(zoomed.contains("250 µs") && zoomed.contains("750 µs"), "{}", zoomed);
+    let restored = screen(&[KeyCode::Char('+'), KeyCode::Char('-'), KeyCode::Char('-')]);
+    assert!(restored.contains("0.00 s") && restored.contains("1.00 ms"), "{}", restored);
+
+    let panned = screen(&[KeyCode::Char('+'), KeyCode::Right]);
+    assert!(panned.contains("300 µs") && panned.contains("800 µs"), "{}", panned);
+    let clamped = screen(&[KeyCode::Left]);
+    assert!(clamped.contains("0.00 s") && clamped.contains("1.00 ms"), "{}", clamped);
+}
+
+#[test]
+fn cursor_moves_by_a_fraction_of_the_window() {
+    assert!(readout(&screen(&[KeyCode::Char(']')])).starts_with("x = 520.000 µs"));
+    assert!(readout(&screen(&[KeyCode::Char('['), KeyCode::Char('[')])).starts_with("x = 460.000 µs"));
+}
+
+#[test]
+fn steps_can_be_toggled_and_reset() {
+    let toggled = readout(&screen(&[KeyCode::Char('1')]));
+    assert!(toggled.contains("step 0: ") && !toggled.contains("step 1: ") && toggled.contains("step 2: "));
+
+    // 'a' disables every step when all are enabled, and enables them all otherwise
+    let none = readout(&screen(&[KeyCode::Char('a')]));
+    assert!(!none.contains("step"), "{}", none);
+    let all = readout(&screen(&[KeyCode::Char('1'), KeyCode::Char('a')]));
+    assert!((0..3).all(|step| all.contains(&format!("step {}: ", step))), "{}", all);
+
+    // Steps that do not exist are ignored, 'r' restores the initial view
+    assert_eq!(screen(&[KeyCode::Char('7')]), screen(&[]));
+    let keys = [KeyCode::Char('+'), KeyCode::Right, KeyCode::Char(']'), KeyCode::Char('0'), KeyCode::Char('r')];
+    assert_eq!(screen(&keys), screen(&[]));
+}
+
+#[test]
+fn quitting_stops_the_replay() {
+    assert_eq!(screen(&[KeyCode::Char('q'), KeyCode::Down]), screen(&[]));
+    assert_eq!(screen(&[KeyCode::Esc, KeyCode::Char('+')]), screen(&[]));
+}
@@ -0,0 +1,84 @@
+/*
+ * This file contains the tests of the parsing of raw files from untrusted sources
+ */
+
+use ltspice::options::LoadOptions;
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::untrusted::Limits;
+use ltspice::{Encoding, SteppedSimulation};
+
+/* #### Functions #### */
+
+fn parse(bytes: &[u8], limits: Limits) -> Result<SteppedSimulation, String> {
+    return SteppedSimulation::parse_untrusted_with_limits(bytes, &limits, LoadOptions::new()).map_err(|e| e.to_string());
+}
+
+// Replaces the first occurrence of `from` in the header of a UTF8 file.
+fn patch(bytes: &[u8], from: &str, to: &str) -> Vec<u8> {
+    let start = bytes.windows(from.len()).position(|window| window == from.as_bytes()).unwrap();
+    return [&bytes[..start], to.as_bytes(), &bytes[start + from.len()..]].concat();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn input_limits() {
+    let bytes = generate_raw(&RawSpec::new().points(100).variables(4));
+    assert_eq!(SteppedSimulation::parse_untrusted(&bytes).unwrap().get_variables().len(), 4);
+
+    assert!(parse(&bytes, Limits::new().max_bytes(bytes.len())).is_ok());
+    assert!(parse(&bytes, Limits::new().max_bytes(bytes.len() - 1)).unwrap_err().contains("larger than"));
+    assert!(parse(&bytes, Limits::new().max_header_bytes(64)).unwrap_err().contains("No binary header"));
+    assert!(parse(&bytes, Limits::new().max_variables(4)).is_ok());
+    assert!(parse(&bytes, Limits::new().max_variables(3)).unwrap_err().contains("more than 3 variables"));
+
+    // Compressed input is refused whatever its size
+    let gzip = [&[0x1f, 0x8b, 0x08, 0x00][..], &bytes].concat();
+    assert!(parse(&gzip, Limits::new()).unwrap_err().contains("Compressed"));
+}
+
+#[test]
+fn step_limit() {
+    let bytes = generate_raw(&RawSpec::new().steps(4).points(50).variables(2));
+    assert_eq!(parse(&bytes, Limits::new().max_steps(4)).unwrap().get_step_count(), 4);
+    assert!(parse(&bytes, Limits::new().max_steps(3)).unwrap_err().contains("more than 3 steps"));
+}
+
+#[test]
+fn decoded_size_limit() {
+    // 100 points of the x axis and two variables, of 16 bytes each once decoded
+    let bytes = generate_raw(&RawSpec::new().points(100).variables(2));
+    assert!(parse(&bytes, Limits::new().max_decoded_bytes(100 * 3 * 16)).is_ok());
+    assert!(parse(&bytes, Limits::new().max_decoded_bytes(100 * 3 * 16 - 1)).unwrap_err().contains("decodes to more than"));
+}
+
+#[test]
+fn truncated_headers_and_data() {
+    let bytes = generate_raw(&RawSpec::new().encoding(Encoding::UTF8).points(100).variables(2));
+    let binary = bytes.windows(8).position(|window| window == b"Binary:\n").unwrap();
+
+    // A header cut before the binary marker is refused, at any length
+    for length in [0, 1, binary / 2, binary + 4] {
+        assert!(parse(&bytes[..length], Limits::new()).is_err());
+    }
+
+    // Data cut mid-point only loads the complete points of 16 bytes, unless strict
+    let cut = &bytes[..bytes.len() - 10];
+    assert_eq!(parse(cut, Limits::new()).unwrap().get("x", None).unwrap().len(), 99);
+    let strict = SteppedSimulation::parse_untrusted_with_limits(cut, &Limits::new(), LoadOptions::new().strict(true));
+    assert!(strict.is_err());
+}
+
+#[test]
+fn overflowing_headers() {
+    let bytes = generate_raw(&RawSpec::new().encoding(Encoding::UTF8).points(100).variables(2));
+
+    // Counts beyond their integer type fail to parse
+    assert!(parse(&patch(&bytes, "No. Points: 100", "No. Points: 99999999999999999999"), Limits::new()).is_err());
+    assert!(parse(&patch(&bytes, "No. Variables: 3", "No. Variables: 99999999999999999999"), Limits::new()).is_err());
+
+    // A huge declared point count is bounded by the data, and never allocated
+    let huge = patch(&bytes, "No. Points: 100", "No. Points: 4294967295");
+    let simulation = parse(&huge, Limits::new().max_decoded_bytes(100 * 3 * 16)).unwrap();
+    assert_eq!(simulation.get("x", None).unwrap().len(), 100);
+}
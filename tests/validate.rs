@@ -0,0 +1,61 @@
+/*
+ * This file contains the tests of the validation report
+ */
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{Encoding, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Builds a UTF8 binary DC sweep of V1 with a single variable V(out).
+fn dc_sweep(x: &[f64], y: &[f64]) -> SteppedSimulation {
+    let mut bytes = format!(
+        "Title: * dc.asc\nDate: Thu Jan  1 00:00:00 1970\nPlotname: DC transfer characteristic\nFlags: real forward double\n\
+         No. Variables: 2\nNo. Points: {}\nOffset:   0.0000000000000000e+000\nCommand: Test\n\
+         Variables:\n\t0\tv1\tvoltage\n\t1\tV(out)\tvoltage\nBinary:\n",
+        x.len()
+    )
+    .into_bytes();
+    for (x, y) in x.iter().zip(y.iter()) {
+        bytes.extend_from_slice(&x.to_le_bytes());
+        bytes.extend_from_slice(&y.to_le_bytes());
+    }
+    return SteppedSimulation::from_bytes(&bytes).unwrap();
+}
+
+/* #### Tests #### */
+
+#[test]
+fn complete_files_are_valid() {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().steps(3).points(20).variables(2))).unwrap();
+    let report = simulation.validate();
+    assert!(report.is_valid(), "{:?}", report);
+    assert_eq!((report.declared_points, report.loaded_points), (60, 60));
+    assert!(!report.suspected_compression);
+}
+
+#[test]
+fn truncated_files_miss_points() {
+    let bytes = generate_raw(&RawSpec::new().points(20).variables(1).encoding(Encoding::UTF8));
+    // Each point is 8 bytes of time and 4 of V(n001), the last one and a half are cut
+    let simulation = SteppedSimulation::from_bytes(&bytes[..bytes.len() - 18]).unwrap();
+    let report = simulation.validate();
+    assert!(!report.is_valid());
+    assert_eq!((report.declared_points, report.loaded_points), (20, 18));
+}
+
+#[test]
+fn non_finite_samples_and_reversals_are_reported() {
+    let simulation = dc_sweep(&[0.0, 1.0, 2.0, 1.5, 3.0], &[0.0, f64::NAN, 1.0, f64::INFINITY, 2.0]);
+    let report = simulation.validate();
+    assert!(!report.is_valid());
+    assert_eq!(report.non_finite, vec![("V(out)".to_string(), 2)]);
+    assert_eq!(report.non_monotonic_steps, vec![0]);
+    assert!(report.hysteresis_steps.is_empty());
+
+    // Sweeping down is fine for a DC sweep, and so is sweeping up and back down
+    assert!(dc_sweep(&[3.0, 2.0, 1.0], &[0.0; 3]).validate().is_valid());
+    let report = dc_sweep(&[0.0, 1.0, 2.0, 3.0, 2.0, 1.0, 0.0], &[0.0; 7]).validate();
+    assert!(report.is_valid(), "{:?}", report);
+    assert_eq!(report.hysteresis_steps, vec![0]);
+}
@@ -0,0 +1,110 @@
+/*
+ * This file contains the tests of the variable list of the header
+ */
+
+use ltspice::{SteppedSimulation, VariableClass};
+
+/* #### Constants #### */
+
+// Variables of a mixed-signal transient, as LTSpice lists them
+const VARIABLES: [(&str, &str); 10] = [
+    ("V(out)", "voltage"),
+    ("V(u1:#n003)", "voltage"),
+    ("V(x1:+vin)", "voltage"),
+    ("V(n001#branch)", "voltage"),
+    ("Ix(U1:3)", "device_current"),
+    ("Ix(U1:+V)", "device_current"),
+    ("Id(M1)", "device_current"),
+    ("Ib(Q2)", "device_current"),
+    ("Ic(Q2)", "device_current"),
+    ("I(Vmeas)", "device_current"),
+];
+
+const POINTS: usize = 25;
+
+/* #### Functions #### */
+
+// Builds a binary transient with the variables of `VARIABLES`, whose samples are `index * 1000 + point`.
+fn mixed_signal(utf16: bool) -> Vec<u8> {
+    let mut header = format!(
+        "Title: * mixed.asc\nDate: Thu Jan  1 00:00:00 1970\nPlotname: Transient Analysis\nFlags: real forward\n\
+         No. Variables: {}\nNo. Points: {}\nOffset:   0.0000000000000000e+000\n\
+         Command: Linear Technology Corporation LTspice XVII\nVariables:\n\t0\ttime\ttime\n",
+        VARIABLES.len() + 1,
+        POINTS
+    );
+    for (index, (name, class)) in VARIABLES.iter().enumerate() {
+        header.push_str(&format!("\t{}\t{}\t{}\n", index + 1, name, class));
+    }
+    header.push_str("Binary:\n");
+
+    let mut bytes = match utf16 {
+        true => header.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect(),
+        false => header.into_bytes(),
+    };
+    for point in 0..POINTS {
+        bytes.extend_from_slice(&(point as f64 * 1e-6).to_le_bytes());
+        for index in 0..VARIABLES.len() {
+            bytes.extend_from_slice(&((index * 1000 + point) as f32).to_le_bytes());
+        }
+    }
+    return bytes;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn every_declared_variable_is_kept() {
+    for utf16 in [true, false] {
+        let simulation = SteppedSimulation::from_bytes(&mixed_signal(utf16)).unwrap();
+        let names: Vec<&str> = simulation.get_variables().iter().map(|variable| variable.name()).collect();
+        let expected: Vec<&str> = VARIABLES.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, expected);
+        assert!(simulation.get_warnings().is_empty(), "{:?}", simulation.get_warnings());
+    }
+}
+
+#[test]
+fn device_currents_are_currents() {
+    let simulation = SteppedSimulation::from_bytes(&mixed_signal(true)).unwrap();
+    for (variable, (_, class)) in simulation.get_variables().iter().zip(VARIABLES.iter()) {
+        let expected = match *class {
+            "voltage" => VariableClass::Voltage,
+            _ => VariableClass::Current,
+        };
+        assert_eq!(variable.class(), &expected, "{}", variable.name());
+    }
+}
+
+#[test]
+fn data_length_matches_the_declared_points() {
+    for utf16 in [true, false] {
+        let simulation = SteppedSimulation::from_bytes(&mixed_signal(utf16)).unwrap();
+        assert_eq!(simulation.get("x", Some(0)).unwrap().len(), POINTS);
+        for (index, (name, _)) in VARIABLES.iter().enumerate() {
+            let values = simulation.get(name, Some(0)).unwrap();
+            assert_eq!(values.len(), POINTS, "{}", name);
+            // Samples are decoded at the offset of their declared index
+            assert_eq!(values[POINTS - 1].real(), (index * 1000 + POINTS - 1) as f64, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn variables_are_found_by_header_index() {
+    let mut simulation = SteppedSimulation::from_bytes(&mixed_signal(false)).unwrap();
+    assert_eq!(simulation.get_by_index(0, None), simulation.get_x());
+    for (index, (name, _)) in VARIABLES.iter().enumerate() {
+        let variable = &simulation.get_variables()[index];
+        assert_eq!(variable.index(), Some(index as u32 + 1));
+        assert_eq!(simulation.get_by_index(index as u32 + 1, Some(0)), simulation.get(name, None), "{}", name);
+    }
+    assert!(simulation.get_by_index(VARIABLES.len() as u32 + 1, None).is_none());
+    assert!(simulation.get_by_index(1, Some(1)).is_none());
+
+    // Derived signals have no header index
+    let x = simulation.get_x().unwrap().to_vec();
+    simulation.insert_derived("V(d)", vec![x]).unwrap();
+    assert_eq!(simulation.get_variables().last().unwrap().index(), None);
+    assert!(simulation.get_by_index(VARIABLES.len() as u32 + 1, None).is_none());
+}
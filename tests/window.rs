@@ -0,0 +1,58 @@
+/*
+ * This file contains the tests of the moving-window statistics
+ */
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::window::Statistic;
+use ltspice::{SteppedSimulation, Value};
+
+/* #### Functions #### */
+
+// Loads the 1 ms of the generated transient, sampled every µs, with a derived signal.
+fn simulation(signal: fn(f64) -> f64) -> SteppedSimulation {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(1001))).unwrap();
+    let values = simulation.get("x", Some(0)).unwrap().iter().map(|t| Value::from(signal(t.real()))).collect();
+    simulation.insert_derived("I(L1)", vec![values]).unwrap();
+    return simulation;
+}
+
+/* #### Tests #### */
+
+#[test]
+fn sine_statistics_over_whole_periods() {
+    // 10 kHz sine of amplitude 2, offset by 1: half a sample more than a period keeps the
+    // samples spanning exactly one period, whatever the rounding of the abscissa
+    let simulation = simulation(|t| 1.0 + 2.0 * (2.0 * std::f64::consts::PI * 1e4 * t).sin());
+    let trace = simulation.trace("I(L1)", 0).unwrap();
+
+    let window = 100.5e-6;
+    let mean = trace.windowed(Statistic::Mean, window);
+    let rms = trace.windowed(Statistic::Rms, window);
+    let min = trace.windowed(Statistic::Min, window);
+    let max = trace.windowed(Statistic::Max, window);
+    assert_eq!(mean.name(), "mean(I(L1))");
+    assert_eq!(rms.values().len(), trace.len());
+
+    // Past the first period: mean 1, RMS √(1 + 2²/2) = √3, extremes -1 and 3
+    for index in 200..trace.len() {
+        assert!((mean.values()[index].real() - 1.0).abs() < 1e-3, "{:?}", mean.values()[index]);
+        assert!((rms.values()[index].real() - 3f64.sqrt()).abs() < 1e-3, "{:?}", rms.values()[index]);
+        assert!((min.values()[index].real() + 1.0).abs() < 1e-2);
+        assert!((max.values()[index].real() - 3.0).abs() < 1e-2);
+    }
+}
+
+#[test]
+fn partial_windows_cover_the_samples_since_the_start() {
+    // A ramp of 1 per ms: the mean of [0, t] is t / 2, the minimum stays at 0
+    let simulation = simulation(|t| t * 1e3);
+    let trace = simulation.trace("I(L1)", 0).unwrap();
+    let mean = trace.windowed(Statistic::Mean, 1.0);
+    let min = trace.windowed(Statistic::Min, 1.0);
+    let max = trace.windowed(Statistic::Max, 0.1e-3);
+
+    assert_eq!(mean.values()[0].real(), 0.0);
+    assert!((mean.values()[500].real() - 0.25).abs() < 1e-9);
+    assert!(min.values().iter().all(|value| value.real() == 0.0));
+    assert_eq!(max.values()[1000].real(), trace.values()[1000].real());
+}
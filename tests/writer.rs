@@ -0,0 +1,62 @@
+/*
+ * This file contains the round-trip tests of the raw writer
+ */
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::{DataType, Encoding, Mode, SteppedSimulation};
+
+/* #### Functions #### */
+
+// Checks that two simulations hold the same variables and samples, step by step.
+fn assert_same(expected: &SteppedSimulation, actual: &SteppedSimulation, context: &str) {
+    assert_eq!(actual.get_mode(), expected.get_mode(), "{}", context);
+    assert_eq!(actual.get_step_count(), expected.get_step_count(), "{}", context);
+    let names = |simulation: &SteppedSimulation| -> Vec<String> {
+        return simulation.get_variables().iter().map(|variable| variable.name().to_string()).collect();
+    };
+    assert_eq!(names(actual), names(expected), "{}", context);
+    for step in 0..expected.get_step_count() as u16 {
+        for name in std::iter::once("x".to_string()).chain(names(expected)) {
+            assert_eq!(actual.get(&name, Some(step)), expected.get(&name, Some(step)), "{} {} step {}", context, name, step);
+        }
+    }
+}
+
+// Returns the samples of a written file, which follow the "Binary:" line of its UTF8 header.
+fn samples(bytes: &[u8]) -> &[u8] {
+    let marker = b"Binary:\n";
+    let start = bytes.windows(marker.len()).position(|window| window == marker).unwrap() + marker.len();
+    return &bytes[start..];
+}
+
+/* #### Tests #### */
+
+#[test]
+fn written_files_read_back_identically() {
+    for mode in [Mode::Transient, Mode::AC, Mode::FFT, Mode::Noise, Mode::DC] {
+        for steps in [1, 3] {
+            for precision in [DataType::Float32, DataType::Float64] {
+                let context = format!("{:?} {} steps {:?}", mode, steps, precision);
+                let spec = RawSpec::new().mode(mode.clone()).steps(steps).points(40).variables(3).precision(precision);
+                let original = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+                let written = original.to_raw_bytes().unwrap();
+                let read = SteppedSimulation::from_bytes(&written).unwrap();
+                assert_same(&original, &read, &context);
+
+                // Writing again gives the same samples, only the provenance of the header differs
+                assert_eq!(samples(&read.to_raw_bytes().unwrap()), samples(&written), "{}", context);
+            }
+        }
+    }
+}
+
+#[test]
+fn written_files_keep_their_precision() {
+    for precision in [DataType::Float32, DataType::Float64] {
+        let spec = RawSpec::new().precision(precision).encoding(Encoding::UTF8);
+        let original = SteppedSimulation::from_bytes(&generate_raw(&spec)).unwrap();
+        let read = SteppedSimulation::from_bytes(&original.to_raw_bytes().unwrap()).unwrap();
+        let data_types: Vec<Option<DataType>> = read.get_variables().iter().map(|variable| variable.data_type()).collect();
+        assert!(data_types.iter().all(|data_type| *data_type == Some(precision)), "{:?}", data_types);
+    }
+}
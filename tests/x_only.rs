@@ -0,0 +1,33 @@
+/*
+ * This file contains the tests of the loading of the x axis only
+ */
+
+use std::fs;
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::SteppedSimulation;
+
+/* #### Tests #### */
+
+#[test]
+fn x_only_loads_the_steps_without_the_variables() {
+    let bytes = generate_raw(&RawSpec::new().steps(3).points(200).variables(2));
+    let path = std::env::temp_dir().join(format!("ltspice-x-only-{}.raw", std::process::id()));
+    fs::write(&path, &bytes).unwrap();
+    let full = SteppedSimulation::from_bytes(&bytes).unwrap();
+
+    let mut simulation = SteppedSimulation::new(path.clone());
+    simulation.load_x_only().unwrap();
+    assert_eq!(simulation.get_step_count(), 3);
+    for step in 0..3 {
+        assert_eq!(simulation.get("x", Some(step)), full.get("x", Some(step)));
+    }
+    // The variables are listed without data
+    assert_eq!(simulation.get_variables().len(), full.get_variables().len());
+    assert!(simulation.get("V(n001)", Some(0)).is_none());
+
+    // The options are restored, a reload decodes everything
+    simulation.reload().unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(simulation.get("V(n002)", Some(2)), full.get("V(n002)", Some(2)));
+}
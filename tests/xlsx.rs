@@ -0,0 +1,50 @@
+/*
+ * This file contains the tests of the Excel exporter
+ */
+#![cfg(feature = "xlsx")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use ltspice::testing::{generate_raw, RawSpec};
+use ltspice::xlsx::{XlsxLayout, XlsxOptions};
+use ltspice::SteppedSimulation;
+
+/* #### Functions #### */
+
+fn temporary(name: &str) -> PathBuf {
+    return std::env::temp_dir().join(format!("ltspice-{}-{}.xlsx", name, std::process::id()));
+}
+
+/* #### Tests #### */
+
+#[test]
+fn colliding_sheet_names_are_suffixed() {
+    let mut simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(10).variables(3))).unwrap();
+    simulation.rename("V(n001)", "V(a/b)").unwrap();
+    simulation.rename("V(n002)", "V(a:b)").unwrap();
+    simulation.rename("V(n003)", "v(a?b)").unwrap();
+
+    // Excel refuses duplicate sheet names, so saving succeeds only if they were de-duplicated
+    let path = temporary("names");
+    let options = XlsxOptions {
+        layout: XlsxLayout::PerVariable,
+        decimation: 1,
+    };
+    let result = simulation.to_xlsx(&path, &options);
+    let _ = fs::remove_file(&path);
+    result.unwrap();
+}
+
+#[test]
+fn too_many_steps_are_rejected() {
+    let simulation = SteppedSimulation::from_bytes(&generate_raw(&RawSpec::new().points(2).steps(8193).variables(1))).unwrap();
+    assert_eq!(simulation.get_step_count(), 8193);
+    let path = temporary("steps");
+    let options = XlsxOptions {
+        layout: XlsxLayout::PerVariable,
+        decimation: 1,
+    };
+    assert!(simulation.to_xlsx(&path, &options).is_err());
+    assert!(!path.exists());
+}